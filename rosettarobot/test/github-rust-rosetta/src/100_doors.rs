@@ -1,17 +1,20 @@
 // Implements http://rosettacode.org/wiki/100_doors
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 use std::iter::{Map, RangeInclusive, range_inclusive};
 
-type DoorIter<'a> = Map<'a, f32, DoorState, RangeInclusive<f32>>;
+pub type DoorIter<'a> = Map<'a, f32, DoorState, RangeInclusive<f32>>;
 
 #[deriving(Show, PartialEq)]
-enum DoorState {
+pub enum DoorState {
     Open,
     Closed
 }
 
 // This is an example of returning an iterator, this allows the caller to
 // choose if they want to allocate or just process as a stream.
-fn calculate_doors<'a>() -> DoorIter<'a> {
+pub fn calculate_doors<'a>() -> DoorIter<'a> {
     range_inclusive(1.0f32, 100.0).map(|f| {
         let x = f.sqrt();
         if x == x.round() {Open} else {Closed}
@@ -19,11 +22,17 @@ fn calculate_doors<'a>() -> DoorIter<'a> {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the state of every door to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let doors = calculate_doors();
     for (i, x) in doors.enumerate() {
-        println!("Door {} is {}", i + 1, x);
+        try!(writeln!(out, "Door {} is {}", i + 1, x));
     }
+    Ok(())
 }
 
 #[test]
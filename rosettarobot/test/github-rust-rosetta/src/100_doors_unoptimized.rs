@@ -1,11 +1,19 @@
 // Implements http://rosettacode.org/wiki/100_doors
 // this is the unoptimized version that performs all 100
 // passes, as per the original description of the problem
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 use std::iter::range_inclusive;
 use std::iter::range_step_inclusive;
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes whether every door is open to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     // states for the 100 doors
     // uses a vector of booleans,
     // where state==false means the door is closed
@@ -13,15 +21,15 @@ fn main() {
     solve(&mut doors);
 
     for (idx, door) in doors.iter().enumerate() {
-        println!("door {} open: {}", idx+1, door);
+        try!(writeln!(out, "door {} open: {}", idx+1, door));
     }
-
+    Ok(())
 }
 
 // unoptimized solution for the 100 Doors problem,
 // performs all 100 passes and mutates the vector with
 // the states in place
-fn solve(doors: &mut [bool, ..100])  {
+pub fn solve(doors: &mut [bool, ..100])  {
     for pass in range_inclusive(1u, 100u) {
         for door in range_step_inclusive(pass, 100u, pass) {
             // flip the state of the door
@@ -5,15 +5,34 @@
 // It follows operator precedence (i.e. 2 + 3 * 3 = 11),
 // understands negation (-5 + 6 = 1), ignores whitespace
 // and allows the use of parentheses
+//
+// The parser builds an expression tree (every node knows which part of the
+// input it was parsed from), which is evaluated with exact rationals so that
+// answers like 8 / (3 - 8 / 3) really are 24, and can be printed back with
+// as few parentheses as possible
+//
+// Answers can also be written in reverse Polish notation: infix_to_rpn and
+// rpn_to_infix convert between the two notations, and can show every step
+// of the conversion. The parser and the conversions are in 24_game/notation.rs,
+// which 24_game_rpn.rs includes too
 
 // We use a glob import in our test module. Seperating tests into a seperate
 // module enforces visibility restrictions so the test module can only access
 // publically exported code, the same as any user of the code.
 #![feature(globs)]
-use std::char;
+extern crate num;
+
+pub use self::notation::{Expr, Node, Num, Unary, Binary, Span};
+pub use self::notation::{Operator, Neg, Add, Sub, Mul, Div, Sentinel};
+pub use self::notation::{RpnItem, Operand, Op, Step};
+pub use self::notation::{infix_to_rpn, rpn_to_infix, rpn_to_string, parse_any};
+
+#[path = "24_game/notation.rs"]
+mod notation;
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    use num::rational::Ratio;
     use std::{rand, io};
 
     let mut rng = rand::task_rng();
@@ -23,306 +42,43 @@ fn main() {
         let mut sample = rand::sample(&mut rng, range(1u, 10), 4);
 
         println!("make 24 by combining the following 4 numbers with + - * / or (q)uit");
+        println!("(either as in 8 / (3 - 8 / 3) or as in 8 3 8 3 / - /)");
         println!("{}", sample);
 
         let line = input.read_line().unwrap();
         match line.as_slice().trim() {
             "q" => break,
-            input => {
-                if check_values(sample.as_mut_slice(), input) {
-                    match Parser::new(input).parse() {
-                        Ok(i) if i == 24. => println!("you made it!"),
-                        Ok(i) => println!("you entered {}, try again!", i),
-                        Err(s)  => println!("{}", s)
-                    };
-                } else {
-                    println!("unrecognized input, try again")
-                }
+            input => match parse_any(input) {
+                Ok(ref expr) if !check_values(sample.as_mut_slice(), expr) => {
+                    println!("you must use each of the numbers once, try again")
+                },
+                Ok(expr) => match expr.eval() {
+                    Ok(ref i) if *i == Ratio::from_integer(24) => println!("you made it!"),
+                    Ok(i) => println!("{} is {}, try again!", expr, i),
+                    Err(s) => println!("{}", s)
+                },
+                Err(s) => println!("{}", s)
             }
         }
     }
 }
 
-// Returns true if the entered expression uses the values contained in sample
-pub fn check_values(sample:&mut [uint], input:&str) -> bool {
-    let lex = Lexer::new(input);
-
-    let mut numbers_used = lex.filter_map(|a| {
-        match a {
-            Int(i) => Some(i),
-            _ => None
-        }
-    }).collect::<Vec<uint>>();
+// Returns true if the expression uses the values contained in sample
+pub fn check_values(sample: &mut [uint], expr: &Expr) -> bool {
+    let mut numbers_used = expr.numbers();
 
     numbers_used.sort();
     sample.sort();
     numbers_used.as_slice() == sample
 }
 
-// the tokens that our parser is going to recognize
-#[deriving(PartialEq,Eq,Show)]
-pub enum Token {
-    LParen,
-    RParen,
-    Plus,
-    Minus,
-    Slash,
-    Star,
-    Int(uint)
-}
-
-impl Token {
-   // are tokens associated to a binary operation?
-   fn is_binary(&self) -> bool {
-        match *self {
-            Plus | Minus | Slash | Star => true,
-            _ => false
-        }
-    }
-}
-
-trait Tokenable { fn as_token(&self) -> Option<Token>; }
-
-// map a character to its corresponding token
-impl Tokenable for char {
-    #[inline]
-    fn as_token(&self) -> Option<Token> {
-        let tok = match *self {
-            '(' => LParen,
-            ')' => RParen,
-            '+' => Plus,
-            '-' => Minus,
-            '/' => Slash,
-            '*' => Star,
-            _ => return None
-        };
-
-        Some(tok)
-    }
-}
-
-// Lexer reads an expression like (a + b) / c * d
-// as an iterator on the tokens that compose it
-// Int(a), LParen, Plus, Int(b), RParen...
-pub struct Lexer<'a> {
-    input: &'a str,
-    offset: uint
-}
-
-impl <'a> Lexer<'a> {
-    pub fn new(input: &str) -> Lexer {
-        Lexer { input: input, offset: 0u }
-    }
-
-    fn expect(&mut self, expected:&[Token]) -> Result<Token, String> {
-        let n = self.offset;
-        match self.next() {
-            Some(a) if expected.contains(&a)  => Ok(a),
-            other  => Err(format!("Parsing error: {} was unexpected at offset {}",
-                                  other,
-                                  n))
-        }
-    }
-}
-
-impl <'a> Iterator<Token> for Lexer<'a> {
-    fn next(&mut self) -> Option<Token> {
-        // slice the original string starting from the current offset
-        let mut remaining = self.input.slice_from(self.offset)
-                                      // keep track of the original indice
-                                      .char_indices()
-                                      // advance to the next non-whitespace char
-                                      .skip_while(|&(_, ch)| ch.is_whitespace());
-
-        let (tok, cur_offset) = match remaining.next() {
-            // Found a digit. if there are others, transform them to `uint`
-            Some((mut offset, ch)) if ch.is_digit() => {
-                let mut val = char::to_digit(ch, 10).unwrap();
-
-                for (idx, ch) in remaining {
-                    if ch.is_digit() {
-                        let digit = char::to_digit(ch, 10).unwrap();
-                        val = val * 10 + digit;
-                    } else {
-                        offset = idx;
-                        break;
-                    }
-                }
-
-                (Some(Int(val)), offset)
-            },
-            // found non-digit, try transforming it to the corresponding token
-            Some((o, ch)) => (ch.as_token(), o + 1),
-            _   => (None, 0u)
-        };
-
-        // update the offset for the next iteration
-        self.offset += cur_offset;
-        tok
-    }
-}
-
-// Operators are a "higher level" concept than tokens as they define the
-// semantics of the expression language e.g. token "Minus" can correspond to
-// the unary Neg Operator (-a) or to the binary Sub operator (a - b)
-#[deriving(PartialEq, Eq)]
-pub enum Operator {
-    Neg,
-    Add,
-    Sub,
-    Mul,
-    Div,
-    Sentinel
-}
-
-impl Operator {
-     fn precedence(&self) -> uint  {
-        match *self {
-            Sentinel => 0u,
-            Add | Sub => 1u,
-            Neg => 2u,
-            Mul | Div => 3u
-        }
-    }
-}
-
-// Operator precedence for binary operators:
-// * if x has higher precedence than y precedence for x > precedence for y
-// * if x and y have equal precedence the first one has precedence
-// e.g. in expression (4 / 2 * 2) operators * and / have the same precedence,
-// but the operations must be performed in the order they appear
-// (division first, multiplication second) otherwise results are different
-impl PartialOrd for Operator {
-    fn partial_cmp(&self, other: &Operator) -> Option<Ordering> {
-        match (self.precedence(), other.precedence()) {
-            (a, b) if a == b => Some(Greater),
-            (a, b) => a.partial_cmp(&b)
-        }
-    }
-}
-
-// recursive descent parser
-// with the shunting yard algorithm as explained on
-// http://www.engr.mun.ca/~theo/Misc/exp_parsing.htm
-// I followed the names of the methods as closely as possible vs the pseudo-code
-// that illustrates the algorithm
-pub struct Parser<'a> {
-    operators: Vec<Operator>,
-    operands: Vec<f32>,
-    lexer: Lexer<'a>
-}
-
-impl <'a> Parser<'a> {
-    pub fn new(input: &str) -> Parser {
-        Parser {
-            operators: vec![],
-            operands: vec![],
-            lexer: Lexer::new(input)
-        }
-    }
-
-    pub fn parse(&mut self) -> Result<f32, String> {
-        self.operators.push(Sentinel);
-        try!(self.e());
-        return match self.operands.last() {
-            Some(r) => Ok(*r),
-            None => Err("something went wrong, got no result".to_string())
-        }
-    }
-
-    fn e(&mut self) -> Result<(), String> {
-        try!(self.p());
-
-        loop {
-            match self.lexer.peekable().peek() {
-                Some(&x) if x.is_binary() => {
-                    let op = match x {
-                        Plus => Add,
-                        Minus => Sub,
-                        Star => Mul,
-                        Slash => Div,
-                        // there are no other binary operators
-                        _ => unreachable!()
-                    };
-
-                    self.push_operator(op);
-
-                    // Consume the peeked value
-                    self.lexer.next();
-                    try!(self.p());
-                }
-                _ => break
-            }
-        }
-
-        loop {
-            match self.operators.last() {
-                Some(&op) if op != Sentinel => self.pop_operator(),
-                _ => return Ok(())
-            }
-        }
-    }
-
-    fn p(&mut self) -> Result<(), String> {
-        match self.lexer.next() {
-            Some(Int(n)) => self.operands.push(n as f32),
-            Some(LParen) => {
-                self.operators.push(Sentinel);
-                try!(self.e());
-                try!(self.lexer.expect(&[RParen]));
-                self.operators.pop();
-            },
-            Some(Minus) => {
-                self.push_operator(Neg);
-                try!(self.p());
-            },
-            Some(e) => return Err(format!("unexpected token {}", e)),
-            _ => return Err("unexpected end of command".to_string())
-        }
-        Ok(())
-    }
-
-    fn pop_operator(&mut self) {
-        match self.operators.pop() {
-            Some(Add) => self.binary_op(|t1, t2| t1 + t2),
-            Some(Sub) => self.binary_op(|t1, t2| t1 - t2),
-            Some(Mul) => self.binary_op(|t1, t2| t1 * t2),
-            Some(Div) => self.binary_op(|t1, t2| t1 / t2),
-            Some(Neg) => self.unary_op(|t1| -t1),
-            _ => unreachable!()
-        }
-    }
-
-    fn push_operator(&mut self, op: Operator) {
-        match self.operators.last() {
-            Some(&last_op) if last_op > op => self.pop_operator(),
-            _ => {}
-        }
-        self.operators.push(op);
-    }
-
-    #[inline]
-    fn binary_op(&mut self, op: |f32, f32| -> f32) {
-        match (self.operands.pop(), self.operands.pop()) {
-            (Some(t1), Some(t2)) => self.operands.push(op(t2, t1)),
-            _ => unreachable!()
-        }
-    }
-
-    #[inline]
-    fn unary_op(&mut self, op: |f32| -> f32) {
-        match self.operands.pop() {
-            Some(t1) => self.operands.push(op(t1)),
-            _ => unreachable!()
-        }
-    }
-}
-
-
 #[cfg(test)]
 mod test {
-    use super::{Token, Lexer, Parser, Add, Sub, Mul, Div};
-    use super::{check_values, LParen, RParen, Plus, Slash, Star, Int};
+    use num::rational::Ratio;
+    use super::notation::{Token, Lexer, Parser, LParen, RParen, Plus, Slash, Star, Int};
+    use super::{check_values, Add, Sub, Mul, Div};
+    use super::{Expr, Num, Binary, Span};
+    use super::{infix_to_rpn, rpn_to_infix, rpn_to_string, parse_any};
 
     #[test]
     fn test_precedence() {
@@ -350,6 +106,8 @@ mod test {
         t(tok, Plus, 6);
         let read = tok.expect(&[LParen,Int(4),RParen]);
         assert_eq!(read, Ok(Int(4)));
+        assert_eq!(tok.offset, 8);
+        assert_eq!(tok.next(), None);
 
         let mut tok = Lexer::new("");
         assert_eq!(tok.next(), None);
@@ -367,30 +125,173 @@ mod test {
         t(tok, Slash, 9);
         t(tok, Int(2), 10);
         t(tok, RParen, 11);
+
+        let tok = &mut Lexer::new(" 12*3");
+        assert_eq!(tok.next_spanned(), Some((Int(12), Span { start: 1, end: 3 })));
+        assert_eq!(tok.next_spanned(), Some((Star, Span { start: 3, end: 4 })));
+        assert_eq!(tok.next_spanned(), Some((Int(3), Span { start: 4, end: 5 })));
+        assert_eq!(tok.next_spanned(), None);
     }
 
     #[test]
     fn parse() {
-        fn t(input: &str, expected: Result<f32, String>) {
-            assert_eq!(Parser::new(input).parse(), expected)
+        fn t(input: &str, expected: Result<int, String>) {
+            let result = Parser::new(input).parse().and_then(|e| e.eval());
+            assert_eq!(result, expected.map(|i| Ratio::from_integer(i)))
         }
 
-        t("2+2", Ok(4.));
-        t("2+3*4", Ok(14.));
-        t("4*(3+2)", Ok(20.));
-        t("5/(3+2)*3", Ok(3.));
+        t("2+2", Ok(4));
+        t("2+3*4", Ok(14));
+        t("4*(3+2)", Ok(20));
+        t("5/(3+2)*3", Ok(3));
         t("2++12", Err("unexpected token Plus".to_string()));
-        t("-2+12", Ok(10.));
-        t("-2*(2+3)", Ok(-10.));
+        t("-2+12", Ok(10));
+        t("-2*(2+3)", Ok(-10));
+        t("2*-3", Ok(-6));
+        t("1-2*3-4", Ok(-9));
+        t("2+2)", Err("unexpected token RParen".to_string()));
+        t("2+2 x", Err("unexpected character at offset 3".to_string()));
+        t("1/(2-2)", Err("division by zero at offset 2".to_string()));
+
+        // Exact, unlike with floats
+        t("8/(3-8/3)", Ok(24));
+        assert_eq!(Parser::new("1/3").parse().and_then(|e| e.eval()), Ok(Ratio::new(1, 3)));
 
         // Testing precedence
-        t("4 / 2 * 2", Ok(4.));
-        t("2 * 2 / 4", Ok(1.));
+        t("4 / 2 * 2", Ok(4));
+        t("2 * 2 / 4", Ok(1));
+    }
+
+    #[test]
+    fn spans() {
+        let expr = Parser::new("(1+2) * 34").parse().unwrap();
+        let one_plus_two = Binary(Add, box Expr { node: Num(1), span: Span { start: 1, end: 2 } },
+                                       box Expr { node: Num(2), span: Span { start: 3, end: 4 } });
+        let expected = Expr {
+            node: Binary(Mul, box Expr { node: one_plus_two, span: Span { start: 0, end: 5 } },
+                              box Expr { node: Num(34), span: Span { start: 8, end: 10 } }),
+            span: Span { start: 0, end: 10 }
+        };
+        assert_eq!(expr, expected);
+        assert_eq!(Parser::new(" -(1)").parse().unwrap().span, Span { start: 1, end: 5 });
+    }
+
+    #[test]
+    fn minimal_parentheses() {
+        fn t(input: &str, expected: &str) {
+            let shown = format!("{}", Parser::new(input).parse().unwrap());
+            assert_eq!(shown.as_slice(), expected);
+        }
+
+        t("((1 + 2)) * 3", "(1 + 2) * 3");
+        t("(1 * 2) + 3", "1 * 2 + 3");
+        t("1 - (2 - 3)", "1 - (2 - 3)");
+        t("(1 - 2) - 3", "1 - 2 - 3");
+        t("8/(3-8/3)", "8 / (3 - 8 / 3)");
+        t("(8/3)/2", "8 / 3 / 2");
+        t("8/(3/2)", "8 / (3 / 2)");
+        t("(-2) * 3", "(-2) * 3");
+        t("-(2*3)", "-2 * 3");
+        t("-(2+3)", "-(2 + 3)");
+        t("2 * -3", "2 * (-3)");
+        t("2 - -3", "2 - -3");
+        t("-(-2)", "--2");
+    }
+
+    #[test]
+    fn to_rpn() {
+        fn t(input: &str, expected: &str) {
+            let rpn = infix_to_rpn(input, |_| {}).unwrap();
+            assert_eq!(rpn_to_string(rpn.as_slice()).as_slice(), expected);
+        }
+
+        t("3 + 4 * 2 / (1 - 5)", "3 4 2 * 1 5 - / +");
+        t("1 - 2 - 3", "1 2 - 3 -");
+        t("1 - (2 - 3)", "1 2 3 - -");
+        t("-2 * 3", "2 3 * neg");
+        t("2 * -3", "2 3 neg *");
+        t("-(2 + 3) * 4", "2 3 + 4 * neg");
+
+        assert_eq!(infix_to_rpn("(1 + 2", |_| {}), Err("unmatched ( at offset 0".to_string()));
+        assert_eq!(infix_to_rpn("1 + 2)", |_| {}), Err("unmatched ) at offset 5".to_string()));
+        assert_eq!(infix_to_rpn("1 2", |_| {}),
+                   Err("unexpected token Int(2) at offset 2".to_string()));
+        assert_eq!(infix_to_rpn("1 +", |_| {}), Err("unexpected end of command".to_string()));
+    }
+
+    #[test]
+    fn to_rpn_steps() {
+        let mut steps = vec![];
+        infix_to_rpn("1 + 2 * 3", |step| steps.push(step)).unwrap();
+
+        let actions: Vec<&str> = steps.iter().map(|s| s.action.as_slice()).collect();
+        assert_eq!(actions, vec!["output 1", "push +", "output 2", "push *", "output 3",
+                                 "pop *", "pop +"]);
+        let fourth = &steps[3];
+        assert_eq!(fourth.token.as_slice(), "*");
+        assert_eq!(fourth.stack, vec!["+".to_string(), "*".to_string()]);
+        assert_eq!(fourth.output, vec!["1".to_string(), "2".to_string()]);
+        assert!(steps.last().unwrap().stack.is_empty());
+    }
+
+    #[test]
+    fn from_rpn() {
+        fn t(input: &str, expected: &str) {
+            let expr = rpn_to_infix(input, |_| {}).unwrap();
+            assert_eq!(expr.to_string().as_slice(), expected);
+        }
+
+        t("3 4 2 * 1 5 - / +", "3 + 4 * 2 / (1 - 5)");
+        t("1 2 3 - -", "1 - (2 - 3)");
+        t("2 3 + 4 * neg", "-(2 + 3) * 4");
+        t("8 3 8 3 / - /", "8 / (3 - 8 / 3)");
+        assert_eq!(rpn_to_infix(" 1 2 +", |_| {}).unwrap().span, Span { start: 1, end: 6 });
+
+        let t = |input: &str, expected: &str| {
+            assert_eq!(rpn_to_infix(input, |_| {}), Err(expected.to_string()));
+        };
+        t("3 +", "missing operand for + at offset 2");
+        t("neg", "missing operand for neg at offset 0");
+        t("3 x +", "invalid input x at offset 2");
+        t("3 4", "2 values left instead of one, an operator is missing");
+        t("", "unexpected end of command");
+    }
+
+    #[test]
+    fn from_rpn_steps() {
+        let mut steps = vec![];
+        rpn_to_infix("1 2 + neg", |step| steps.push(step)).unwrap();
+
+        let actions: Vec<&str> = steps.iter().map(|s| s.action.as_slice()).collect();
+        assert_eq!(actions, vec!["push 1", "push 2", "apply + to 1 and 2", "apply neg to 1 + 2"]);
+        assert_eq!(steps[1].stack, vec!["1".to_string(), "2".to_string()]);
+        assert_eq!(steps[3].stack, vec!["-(1 + 2)".to_string()]);
+    }
+
+    #[test]
+    fn round_trip() {
+        for input in ["8/(3-8/3)", "-(1+2)*3", "1-(2-3)", "2*-3", "(4/2)*2"].iter() {
+            let expr = Parser::new(*input).parse().unwrap();
+            let rpn = rpn_to_string(infix_to_rpn(*input, |_| {}).unwrap().as_slice());
+            let back = rpn_to_infix(rpn.as_slice(), |_| {}).unwrap();
+            assert_eq!(back.to_string(), expr.to_string());
+        }
+    }
+
+    #[test]
+    fn any_notation() {
+        let expr = parse_any("8 3 8 3 / - /").unwrap();
+        assert_eq!(expr.eval(), Ok(Ratio::from_integer(24)));
+        assert_eq!(parse_any("8 / (3 - 8 / 3)").unwrap().to_string(), expr.to_string());
+        assert_eq!(parse_any("1 +"), Err("unexpected end of command".to_string()));
     }
 
     #[test]
     fn try_check_values() {
         let m = &mut [1, 2, 3, 4];
-        assert!(check_values(m, "1+3 -(4/2)"));
+        let expr = Parser::new("1+3 -(4/2)").parse().unwrap();
+        assert!(check_values(m, &expr));
+        let expr = Parser::new("1+3 -(4/4)").parse().unwrap();
+        assert!(!check_values(m, &expr));
     }
 }
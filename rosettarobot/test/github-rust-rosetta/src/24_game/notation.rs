@@ -0,0 +1,629 @@
+// The expressions of the 24 game: a lexer and a recursive descent parser for infix
+// expressions, and the conversions between infix and reverse Polish notation.
+// Both 24_game.rs and 24_game_rpn.rs include this module privately; its items are public
+// so that the tests of 24_game.rs can check the lexer and the parser
+use num::rational::{Ratio, Rational};
+use std::char;
+use std::fmt;
+
+// the tokens that our parser is going to recognize
+#[deriving(PartialEq,Eq,Show)]
+pub enum Token {
+    LParen,
+    RParen,
+    Plus,
+    Minus,
+    Slash,
+    Star,
+    Int(uint)
+}
+
+impl Token {
+   // are tokens associated to a binary operation?
+   fn is_binary(&self) -> bool {
+        match *self {
+            Plus | Minus | Slash | Star => true,
+            _ => false
+        }
+    }
+}
+
+trait Tokenable { fn as_token(&self) -> Option<Token>; }
+
+// map a character to its corresponding token
+impl Tokenable for char {
+    #[inline]
+    fn as_token(&self) -> Option<Token> {
+        let tok = match *self {
+            '(' => LParen,
+            ')' => RParen,
+            '+' => Plus,
+            '-' => Minus,
+            '/' => Slash,
+            '*' => Star,
+            _ => return None
+        };
+
+        Some(tok)
+    }
+}
+
+// The part of the input a token or an expression comes from,
+// as byte offsets (the end is excluded)
+#[deriving(PartialEq,Eq,Show)]
+pub struct Span {
+    pub start: uint,
+    pub end: uint
+}
+
+// Lexer reads an expression like (a + b) / c * d
+// as an iterator on the tokens that compose it
+// Int(a), LParen, Plus, Int(b), RParen...
+pub struct Lexer<'a> {
+    input: &'a str,
+    pub offset: uint
+}
+
+impl <'a> Lexer<'a> {
+    pub fn new(input: &str) -> Lexer {
+        Lexer { input: input, offset: 0u }
+    }
+
+    pub fn expect(&mut self, expected:&[Token]) -> Result<Token, String> {
+        let n = self.offset;
+        match self.next() {
+            Some(a) if expected.contains(&a)  => Ok(a),
+            other  => Err(format!("Parsing error: {} was unexpected at offset {}",
+                                  other,
+                                  n))
+        }
+    }
+
+    // true if only whitespace is left
+    pub fn is_empty(&self) -> bool {
+        self.input.slice_from(self.offset).trim().is_empty()
+    }
+
+    // read the next token, along with the span it was read from
+    pub fn next_spanned(&mut self) -> Option<(Token, Span)> {
+        // slice the original string starting from the current offset
+        let mut remaining = self.input.slice_from(self.offset)
+                                      // keep track of the original indice
+                                      .char_indices()
+                                      // advance to the next non-whitespace char
+                                      .skip_while(|&(_, ch)| ch.is_whitespace());
+
+        let (tok, start, end) = match remaining.next() {
+            // Found a digit. if there are others, transform them to `uint`
+            Some((start, ch)) if ch.is_digit() => {
+                let mut val = char::to_digit(ch, 10).unwrap();
+                // the number may go on until the end of the input
+                let mut end = self.input.len() - self.offset;
+
+                for (idx, ch) in remaining {
+                    if ch.is_digit() {
+                        let digit = char::to_digit(ch, 10).unwrap();
+                        val = val * 10 + digit;
+                    } else {
+                        end = idx;
+                        break;
+                    }
+                }
+
+                (Int(val), start, end)
+            },
+            // found non-digit, try transforming it to the corresponding token
+            Some((start, ch)) => match ch.as_token() {
+                Some(tok) => (tok, start, start + 1),
+                None => return None
+            },
+            None => return None
+        };
+
+        // update the offset for the next iteration
+        let span = Span { start: self.offset + start, end: self.offset + end };
+        self.offset = span.end;
+        Some((tok, span))
+    }
+}
+
+impl <'a> Iterator<Token> for Lexer<'a> {
+    fn next(&mut self) -> Option<Token> {
+        self.next_spanned().map(|(tok, _)| tok)
+    }
+}
+
+// Operators are a "higher level" concept than tokens as they define the
+// semantics of the expression language e.g. token "Minus" can correspond to
+// the unary Neg Operator (-a) or to the binary Sub operator (a - b)
+#[deriving(PartialEq, Eq, Show)]
+pub enum Operator {
+    Neg,
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Sentinel
+}
+
+impl Operator {
+     fn precedence(&self) -> uint  {
+        match *self {
+            Sentinel => 0u,
+            Add | Sub => 1u,
+            Neg => 2u,
+            Mul | Div => 3u
+        }
+    }
+
+    fn symbol(&self) -> &'static str {
+        match *self {
+            Neg | Sub => "-",
+            Add => "+",
+            Mul => "*",
+            Div => "/",
+            Sentinel => ""
+        }
+    }
+
+    // RPN needs another symbol for negation, and Sentinel stands for (
+    // on the operator stack of infix_to_rpn
+    fn rpn_symbol(&self) -> &'static str {
+        match *self {
+            Neg => "neg",
+            Sentinel => "(",
+            _ => self.symbol()
+        }
+    }
+}
+
+// Operator precedence for binary operators:
+// * if x has higher precedence than y precedence for x > precedence for y
+// * if x and y have equal precedence the first one has precedence
+// e.g. in expression (4 / 2 * 2) operators * and / have the same precedence,
+// but the operations must be performed in the order they appear
+// (division first, multiplication second) otherwise results are different
+impl PartialOrd for Operator {
+    fn partial_cmp(&self, other: &Operator) -> Option<Ordering> {
+        match (self.precedence(), other.precedence()) {
+            (a, b) if a == b => Some(Greater),
+            (a, b) => a.partial_cmp(&b)
+        }
+    }
+}
+
+// A node of the expression tree
+#[deriving(PartialEq)]
+pub enum Node {
+    Num(uint),
+    Unary(Operator, Box<Expr>),
+    Binary(Operator, Box<Expr>, Box<Expr>)
+}
+
+// An expression, with the span of the input it was parsed from
+// (parentheses included). It is shown as infix with minimal parentheses
+#[deriving(PartialEq)]
+pub struct Expr {
+    pub node: Node,
+    pub span: Span
+}
+
+impl Expr {
+    // evaluate the expression with exact rational arithmetic
+    pub fn eval(&self) -> Result<Rational, String> {
+        match self.node {
+            Num(n) => Ok(Ratio::from_integer(n as int)),
+            Unary(_, ref operand) => operand.eval().map(|v| -v),
+            Binary(op, ref left, ref right) => {
+                let (l, r) = (try!(left.eval()), try!(right.eval()));
+                match op {
+                    Add => Ok(l + r),
+                    Sub => Ok(l - r),
+                    Mul => Ok(l * r),
+                    Div if r == Ratio::from_integer(0) => {
+                        Err(format!("division by zero at offset {}", right.span.start))
+                    },
+                    Div => Ok(l / r),
+                    _ => unreachable!()
+                }
+            }
+        }
+    }
+
+    // the numbers used in the expression, from left to right
+    pub fn numbers(&self) -> Vec<uint> {
+        match self.node {
+            Num(n) => vec![n],
+            Unary(_, ref operand) => operand.numbers(),
+            Binary(_, ref left, ref right) => {
+                let mut numbers = left.numbers();
+                numbers.push_all(right.numbers().as_slice());
+                numbers
+            }
+        }
+    }
+
+    // numbers never need parentheses
+    fn precedence(&self) -> uint {
+        match self.node {
+            Num(_) => ::std::uint::MAX,
+            Unary(op, _) | Binary(op, _, _) => op.precedence()
+        }
+    }
+
+    fn fmt_operand(&self, f: &mut fmt::Formatter, parens: bool) -> fmt::Result {
+        if parens { write!(f, "({})", self) } else { write!(f, "{}", self) }
+    }
+}
+
+// An operand needs parentheses if it binds less tightly than its operator,
+// or as tightly if it's on the right of - or / (a - (b - c)).
+// A negation binds less tightly than * and /, so that -2 * 3 is -(2 * 3)
+impl fmt::Show for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.node {
+            Num(n) => write!(f, "{}", n),
+            Unary(op, ref operand) => {
+                try!(write!(f, "{}", op.symbol()));
+                operand.fmt_operand(f, operand.precedence() < op.precedence())
+            },
+            Binary(op, ref left, ref right) => {
+                let right_parens = right.precedence() < op.precedence() ||
+                    (right.precedence() == op.precedence() && (op == Sub || op == Div));
+                try!(left.fmt_operand(f, left.precedence() < op.precedence()));
+                try!(write!(f, " {} ", op.symbol()));
+                right.fmt_operand(f, right_parens)
+            }
+        }
+    }
+}
+
+// recursive descent parser
+// with the shunting yard algorithm as explained on
+// http://www.engr.mun.ca/~theo/Misc/exp_parsing.htm
+// I followed the names of the methods as closely as possible vs the pseudo-code
+// that illustrates the algorithm
+pub struct Parser<'a> {
+    // operators waiting for their operands, with the offset of their token
+    operators: Vec<(Operator, uint)>,
+    operands: Vec<Expr>,
+    lexer: Lexer<'a>
+}
+
+impl <'a> Parser<'a> {
+    pub fn new(input: &str) -> Parser {
+        Parser {
+            operators: vec![],
+            operands: vec![],
+            lexer: Lexer::new(input)
+        }
+    }
+
+    pub fn parse(&mut self) -> Result<Expr, String> {
+        self.operators.push((Sentinel, 0));
+        try!(self.e());
+        match self.lexer.next() {
+            Some(e) => return Err(format!("unexpected token {}", e)),
+            None if !self.lexer.is_empty() => {
+                return Err(format!("unexpected character at offset {}", self.lexer.offset))
+            },
+            None => {}
+        }
+        return match self.operands.pop() {
+            Some(r) => Ok(r),
+            None => Err("something went wrong, got no result".to_string())
+        }
+    }
+
+    fn e(&mut self) -> Result<(), String> {
+        try!(self.p());
+
+        loop {
+            match self.lexer.peekable().peek() {
+                Some(&x) if x.is_binary() => {
+                    let op = match x {
+                        Plus => Add,
+                        Minus => Sub,
+                        Star => Mul,
+                        Slash => Div,
+                        // there are no other binary operators
+                        _ => unreachable!()
+                    };
+
+                    // Consume the peeked value
+                    match self.lexer.next_spanned() {
+                        Some((_, span)) => self.push_operator(op, span.start),
+                        None => unreachable!()
+                    }
+                    try!(self.p());
+                }
+                _ => break
+            }
+        }
+
+        loop {
+            match self.operators.last() {
+                Some(&(op, _)) if op != Sentinel => self.pop_operator(),
+                _ => return Ok(())
+            }
+        }
+    }
+
+    fn p(&mut self) -> Result<(), String> {
+        match self.lexer.next_spanned() {
+            Some((Int(n), span)) => self.operands.push(Expr { node: Num(n), span: span }),
+            Some((LParen, span)) => {
+                self.operators.push((Sentinel, span.start));
+                try!(self.e());
+                try!(self.lexer.expect(&[RParen]));
+                self.operators.pop();
+                // the expression between parentheses spans them too
+                let end = self.lexer.offset;
+                match self.operands.last_mut() {
+                    Some(expr) => expr.span = Span { start: span.start, end: end },
+                    None => unreachable!()
+                }
+            },
+            Some((Minus, span)) => {
+                // unary operators apply to what follows them,
+                // so they don't pop anything
+                self.operators.push((Neg, span.start));
+                try!(self.p());
+            },
+            Some((e, _)) => return Err(format!("unexpected token {}", e)),
+            _ => return Err("unexpected end of command".to_string())
+        }
+        Ok(())
+    }
+
+    fn pop_operator(&mut self) {
+        match self.operators.pop() {
+            Some((Neg, start)) => match self.operands.pop() {
+                Some(operand) => {
+                    let span = Span { start: start, end: operand.span.end };
+                    self.operands.push(Expr { node: Unary(Neg, box operand), span: span })
+                },
+                None => unreachable!()
+            },
+            Some((Sentinel, _)) | None => unreachable!(),
+            Some((op, _)) => match (self.operands.pop(), self.operands.pop()) {
+                (Some(right), Some(left)) => {
+                    let span = Span { start: left.span.start, end: right.span.end };
+                    self.operands.push(Expr { node: Binary(op, box left, box right), span: span })
+                },
+                _ => unreachable!()
+            }
+        }
+    }
+
+    fn push_operator(&mut self, op: Operator, offset: uint) {
+        loop {
+            match self.operators.last() {
+                Some(&(last_op, _)) if last_op > op => self.pop_operator(),
+                _ => break
+            }
+        }
+        self.operators.push((op, offset));
+    }
+}
+
+
+// An item of an expression in reverse Polish notation
+#[deriving(PartialEq,Eq,Show)]
+pub enum RpnItem {
+    Operand(uint),
+    Op(Operator)
+}
+
+// A step of a conversion between notations, to show how it works:
+// the token read, what was done with it, then the stack and the output.
+// When converting to RPN, the stack holds operators and the output is
+// the RPN written so far; when converting to infix, the stack holds
+// the subexpressions built so far and there is no output
+#[deriving(PartialEq,Eq,Show)]
+pub struct Step {
+    pub token: String,
+    pub action: String,
+    pub stack: Vec<String>,
+    pub output: Vec<String>
+}
+
+// writes items the way they are read by rpn_to_infix, e.g. "3 4 + neg"
+pub fn rpn_to_string(items: &[RpnItem]) -> String {
+    let words: Vec<String> = items.iter().map(|item| {
+        match *item {
+            Operand(n) => n.to_string(),
+            Op(op) => op.rpn_symbol().to_string()
+        }
+    }).collect();
+    words.connect(" ")
+}
+
+// converts an infix expression to RPN with the shunting yard algorithm,
+// calling trace after each step
+pub fn infix_to_rpn(input: &str, trace: |Step|) -> Result<Vec<RpnItem>, String> {
+    // the stack uses Sentinel for left parentheses: as it has the lowest
+    // precedence, operators are never popped past it
+    let mut stack: Vec<(Operator, uint)> = vec![];
+    let mut output: Vec<RpnItem> = vec![];
+    let mut expect_operand = true;
+    let mut lexer = Lexer::new(input);
+
+    let step = |token: &str, action: String, stack: &[(Operator, uint)], output: &[RpnItem]| {
+        trace(Step {
+            token: token.to_string(),
+            action: action,
+            stack: stack.iter().map(|&(op, _)| op.rpn_symbol().to_string()).collect(),
+            output: output.iter().map(|item| rpn_to_string(&[*item])).collect()
+        })
+    };
+
+    loop {
+        let (tok, span) = match lexer.next_spanned() {
+            Some(next) => next,
+            None => break
+        };
+        let token = input.slice(span.start, span.end);
+        match tok {
+            Int(n) if expect_operand => {
+                output.push(Operand(n));
+                expect_operand = false;
+                step(token, format!("output {}", n), stack.as_slice(), output.as_slice());
+            },
+            LParen if expect_operand => {
+                stack.push((Sentinel, span.start));
+                step(token, "push (".to_string(), stack.as_slice(), output.as_slice());
+            },
+            // a minus where an operand is expected is a negation, which
+            // applies to what follows it and so doesn't pop anything
+            Minus if expect_operand => {
+                stack.push((Neg, span.start));
+                step(token, "push neg".to_string(), stack.as_slice(), output.as_slice());
+            },
+            Plus | Minus | Star | Slash if !expect_operand => {
+                let op = match tok {
+                    Plus => Add,
+                    Minus => Sub,
+                    Star => Mul,
+                    _ => Div
+                };
+                loop {
+                    match stack.last() {
+                        Some(&(top, _)) if top > op => {
+                            stack.pop();
+                            output.push(Op(top));
+                            step(token, format!("pop {}", top.rpn_symbol()),
+                                 stack.as_slice(), output.as_slice());
+                        },
+                        _ => break
+                    }
+                }
+                stack.push((op, span.start));
+                expect_operand = true;
+                step(token, format!("push {}", op.rpn_symbol()),
+                     stack.as_slice(), output.as_slice());
+            },
+            RParen if !expect_operand => {
+                loop {
+                    match stack.pop() {
+                        Some((Sentinel, _)) => break,
+                        Some((top, _)) => {
+                            output.push(Op(top));
+                            step(token, format!("pop {}", top.rpn_symbol()),
+                                 stack.as_slice(), output.as_slice());
+                        },
+                        None => return Err(format!("unmatched ) at offset {}", span.start))
+                    }
+                }
+                step(token, "discard (".to_string(), stack.as_slice(), output.as_slice());
+            },
+            _ => return Err(format!("unexpected token {} at offset {}", tok, span.start))
+        }
+    }
+
+    if !lexer.is_empty() {
+        return Err(format!("unexpected character at offset {}", lexer.offset));
+    }
+    if expect_operand {
+        return Err("unexpected end of command".to_string());
+    }
+    loop {
+        match stack.pop() {
+            Some((Sentinel, start)) => return Err(format!("unmatched ( at offset {}", start)),
+            Some((top, _)) => {
+                output.push(Op(top));
+                step("", format!("pop {}", top.rpn_symbol()), stack.as_slice(), output.as_slice());
+            },
+            None => return Ok(output)
+        }
+    }
+}
+
+// the whitespace-separated words of input, with their spans
+fn words_spanned(input: &str) -> Vec<(&str, Span)> {
+    let mut words = vec![];
+    let mut start = None;
+    for (i, ch) in input.char_indices() {
+        match (ch.is_whitespace(), start) {
+            (true, Some(s)) => {
+                words.push((input.slice(s, i), Span { start: s, end: i }));
+                start = None;
+            },
+            (false, None) => start = Some(i),
+            _ => {}
+        }
+    }
+    match start {
+        Some(s) => words.push((input.slice_from(s), Span { start: s, end: input.len() })),
+        None => {}
+    }
+    words
+}
+
+// converts an RPN expression (such as "3 4 + neg") to an expression tree,
+// whose spans are in the RPN input, calling trace after each step.
+// The tree is shown as infix with minimal parentheses
+pub fn rpn_to_infix(input: &str, trace: |Step|) -> Result<Expr, String> {
+    let mut stack: Vec<Expr> = vec![];
+
+    for (word, span) in words_spanned(input).into_iter() {
+        let op = match word {
+            "+" => Some(Add),
+            "-" => Some(Sub),
+            "*" => Some(Mul),
+            "/" => Some(Div),
+            "neg" => Some(Neg),
+            _ => None
+        };
+        let action = match op {
+            Some(Neg) => match stack.pop() {
+                Some(operand) => {
+                    let action = format!("apply neg to {}", operand);
+                    let span = Span { start: operand.span.start, end: span.end };
+                    stack.push(Expr { node: Unary(Neg, box operand), span: span });
+                    action
+                },
+                None => return Err(format!("missing operand for neg at offset {}", span.start))
+            },
+            Some(op) => match (stack.pop(), stack.pop()) {
+                (Some(right), Some(left)) => {
+                    let action = format!("apply {} to {} and {}", op.symbol(), left, right);
+                    let span = Span { start: left.span.start, end: span.end };
+                    stack.push(Expr { node: Binary(op, box left, box right), span: span });
+                    action
+                },
+                _ => return Err(format!("missing operand for {} at offset {}", word, span.start))
+            },
+            None => match from_str::<uint>(word) {
+                Some(n) => {
+                    stack.push(Expr { node: Num(n), span: span });
+                    format!("push {}", n)
+                },
+                None => return Err(format!("invalid input {} at offset {}", word, span.start))
+            }
+        };
+        trace(Step {
+            token: word.to_string(),
+            action: action,
+            stack: stack.iter().map(|e| e.to_string()).collect(),
+            output: vec![]
+        });
+    }
+
+    match stack.len() {
+        0 => Err("unexpected end of command".to_string()),
+        1 => Ok(stack.pop().unwrap()),
+        n => Err(format!("{} values left instead of one, an operator is missing", n))
+    }
+}
+
+// parses an expression written either in infix notation or in RPN
+pub fn parse_any(input: &str) -> Result<Expr, String> {
+    match Parser::new(input).parse() {
+        Ok(expr) => Ok(expr),
+        Err(infix_error) => match rpn_to_infix(input, |_| {}) {
+            Ok(expr) => Ok(expr),
+            Err(_) => Err(infix_error)
+        }
+    }
+}
@@ -1,8 +1,17 @@
 // Implements http://rosettacode.org/wiki/24_game
 // Uses RPN expression
+// (answers in infix notation are converted to RPN, by the converter of 24_game.rs)
+extern crate num;
+
+use num::rational::{Ratio, Rational};
+
+// Only the converter is included, not the whole 24_game task, whose tests would run twice
+#[allow(dead_code)]
+#[path = "24_game/notation.rs"]
+mod notation;
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
     use std::rand::{task_rng, Rng};
     use std::io;
 
@@ -27,13 +36,23 @@ fn main() {
     }
 }
 
-fn check_input(expr: &str, choices: &Vec<uint>) -> Result<(), String> {
-    let mut stack: Vec<uint> = Vec::new();
-    for token in expr.words() {
-        if is_operator(token) {
+pub fn check_input(expr: &str, choices: &Vec<uint>) -> Result<(), String> {
+    let rpn = match notation::infix_to_rpn(expr, |_| {}) {
+        Ok(items) => notation::rpn_to_string(items.as_slice()),
+        Err(_) => expr.to_string()
+    };
+    // values can be negative, as in 2 8 - 4 * neg, or fractions, as in 8 3 8 3 / - /
+    let mut stack: Vec<Rational> = Vec::new();
+    for token in rpn.as_slice().words() {
+        if token == "neg" {
+            match stack.pop() {
+                Some(x) => stack.push(-x),
+                None => return Err("Not a valid RPN expression!".to_string())
+            }
+        } else if is_operator(token) {
             let (a, b) = (stack.pop(), stack.pop());
             match (a, b) {
-                (Some(x), Some(y)) => stack.push(evaluate(y, x, token)),
+                (Some(x), Some(y)) => stack.push(try!(evaluate(y, x, token))),
                 (_, _) => return Err("Not a valid RPN expression!".to_string())
             }
         } else {
@@ -44,7 +63,7 @@ fn check_input(expr: &str, choices: &Vec<uint>) -> Result<(), String> {
                     if !choices.contains(&n) {
                         return Err(format!("Cannot use {}", n));
                     }
-                    stack.push(n)
+                    stack.push(Ratio::from_integer(n as int))
                 },
                 None => return Err(format!("Invalid input: {}", token))
             }
@@ -57,24 +76,25 @@ fn check_input(expr: &str, choices: &Vec<uint>) -> Result<(), String> {
     }
     match ans {
         Some(x) => {
-            if x == 24 { return Ok(()); }
+            if x == Ratio::from_integer(24) { return Ok(()); }
             return Err(format!("Wrong answer. Result: {}", x));
         }
         None => return Err("Error encountered!".to_string()),
     }
 }
 
-fn evaluate(a: uint, b: uint, op: &str) -> uint {
+pub fn evaluate(a: Rational, b: Rational, op: &str) -> Result<Rational, String> {
     match op {
-        "+" => a + b,
-        "-" => a - b,
-        "*" => a * b,
-        "/" => a / b,
+        "+" => Ok(a + b),
+        "-" => Ok(a - b),
+        "*" => Ok(a * b),
+        "/" if b == Ratio::from_integer(0) => Err("Division by zero!".to_string()),
+        "/" => Ok(a / b),
         _   => unreachable!()
     }
 }
 
-fn is_operator(op: &str) -> bool {
+pub fn is_operator(op: &str) -> bool {
     ["*", "-", "+", "/"].contains(&op)
 }
 
@@ -96,4 +116,22 @@ fn test_check_input() {
 
     // invalid RPN expression
     assert_eq!(check_input("4 3 + 6 2 *", &v1), Err("Not a valid RPN expression!".to_string()));
+
+    // infix input
+    assert_eq!(check_input("4 * 3 + 6 * 2", &v1), Ok(()));
+    assert_eq!(check_input("(4 + 3) * 2", &v1), Err("Wrong answer. Result: 14".to_string()));
+
+    // negation, in both notations
+    let v2: Vec<uint> = vec![2, 8, 4, 1];
+    assert_eq!(check_input("-(2 - 8) * 4", &v2), Ok(()));
+    assert_eq!(check_input("2 8 - 4 * neg", &v2), Ok(()));
+    assert_eq!(check_input("1 2 - 8 * neg", &v2), Err("Wrong answer. Result: 8".to_string()));
+    assert_eq!(check_input("neg 2", &v2), Err("Not a valid RPN expression!".to_string()));
+
+    // division is exact
+    let v3: Vec<uint> = vec![8, 3, 8, 3];
+    assert_eq!(check_input("8 / (3 - 8 / 3)", &v3), Ok(()));
+    assert_eq!(check_input("8 3 / 3 *", &v3), Err("Wrong answer. Result: 8".to_string()));
+    assert_eq!(check_input("8 / 3", &v3), Err("Wrong answer. Result: 8/3".to_string()));
+    assert_eq!(check_input("8 / (3 - 3)", &v3), Err("Division by zero!".to_string()));
 }
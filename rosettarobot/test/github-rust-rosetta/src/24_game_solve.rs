@@ -5,6 +5,11 @@
 #![feature(macro_rules)]
 extern crate num;
 use num::rational::{Ratio, Rational};
+use std::collections::TreeMap;
+use std::fmt;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 use std::num::Zero;
 // convenience macro to create a fixed-sized vector
 // of rationals by writing:
@@ -14,14 +19,26 @@ macro_rules! rationals(
     ($($e:expr),+) => ([$(Ratio::<int>::from_integer($e)),+])
 )
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the solutions of two puzzles to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let mut r = rationals![1i, 3, 7, 9];
     let sol = solve(r.as_mut_slice(), 24).unwrap_or("no solution found".to_string());
-    println!("{}", sol);
+    try!(writeln!(out, "{}", sol));
+
+    // solve() only tries right-nested expressions, which can't solve this one
+    let r = rationals![4i, 4, 10, 10];
+    for expr in solutions(r.as_slice(), Ratio::from_integer(24)).iter() {
+        try!(writeln!(out, "{}", expr));
+    }
+    Ok(())
 }
 // for a vector of rationals r, find the combination of arithmentic
 // operations that yield target_val as a result (if such combination exists)
-fn solve(r: &mut[Rational], target_val: int) -> Option<String> {
+pub fn solve(r: &mut[Rational], target_val: int) -> Option<String> {
     //need to sort because next_permutation()
     // returns permutations in lexicographic order
     r.sort();
@@ -38,7 +55,7 @@ fn solve(r: &mut[Rational], target_val: int) -> Option<String> {
 // with the result and the expression in String form
 // returns all (result, expression in string form)
 // results in a vector
-fn compute_all_operations(l: &[Rational]) -> Vec<(Rational, String)> {
+pub fn compute_all_operations(l: &[Rational]) -> Vec<(Rational, String)> {
     match l {
         []         => vec![],
         [x]  => vec![(x, (format!("{}", x)))],
@@ -56,6 +73,124 @@ fn compute_all_operations(l: &[Rational]) -> Vec<(Rational, String)> {
         }
     }
 }
+// A solution in normal form: nested sums and products are flattened and
+// their operands sorted, so that solutions which only differ by commutativity
+// or associativity (a - (b - c) is a - b + c) have the same normal form
+#[deriving(Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Expr {
+    Number(Rational),
+    // the terms which are added, and the terms which are subtracted
+    Sum(Vec<Expr>, Vec<Expr>),
+    // the factors which are multiplied, and the factors which divide
+    Product(Vec<Expr>, Vec<Expr>)
+}
+
+// splits a sum (or a product) into its two lists of operands,
+// anything else is a sum (or product) of a single term
+fn operands(e: Expr, product: bool) -> (Vec<Expr>, Vec<Expr>) {
+    match (e, product) {
+        (Sum(direct, inverse), false) | (Product(direct, inverse), true) => (direct, inverse),
+        (e, _) => (vec![e], vec![])
+    }
+}
+
+// builds a + b, a - b, a * b or a / b in normal form
+fn combine(a: Expr, b: Expr, product: bool, inverse: bool) -> Expr {
+    let (mut direct, mut inverses) = operands(a, product);
+    let (b_direct, b_inverses) = operands(b, product);
+    if inverse {
+        direct.extend(b_inverses.into_iter());
+        inverses.extend(b_direct.into_iter());
+    } else {
+        direct.extend(b_direct.into_iter());
+        inverses.extend(b_inverses.into_iter());
+    }
+    direct.sort();
+    inverses.sort();
+    if product { Product(direct, inverses) } else { Sum(direct, inverses) }
+}
+
+// sums are the only operands which need parentheses, in products
+fn fmt_operand(f: &mut fmt::Formatter, e: &Expr, in_product: bool) -> fmt::Result {
+    match *e {
+        Sum(..) if in_product => write!(f, "({})", e),
+        _ => write!(f, "{}", e)
+    }
+}
+
+impl fmt::Show for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (direct, inverses, op, inverse_op, in_product) = match *self {
+            Number(n) if n < Zero::zero() => return write!(f, "({})", n),
+            Number(n) => return write!(f, "{}", n),
+            Sum(ref direct, ref inverses) => (direct, inverses, " + ", " - ", false),
+            Product(ref direct, ref inverses) => (direct, inverses, " * ", " / ", true)
+        };
+        for (i, e) in direct.iter().enumerate() {
+            if i > 0 { try!(write!(f, "{}", op)); }
+            try!(fmt_operand(f, e, in_product));
+        }
+        for e in inverses.iter() {
+            try!(write!(f, "{}", inverse_op));
+            try!(fmt_operand(f, e, in_product));
+        }
+        Ok(())
+    }
+}
+
+// finds every distinct way of combining all the numbers with + - * / into
+// target, whatever the shape of the expression, sorted by their infix form
+pub fn solutions(numbers: &[Rational], target: Rational) -> Vec<Expr> {
+    let items: Vec<(Rational, Expr)> = numbers.iter().map(|&n| (n, Number(n))).collect();
+    let mut found = TreeMap::new();
+    search(items.as_slice(), target, &mut found);
+    found.into_iter().map(|(_, expr)| expr).collect()
+}
+
+// the number of distinct solutions, which tells how hard a puzzle is
+pub fn count_solutions(numbers: &[Rational], target: Rational) -> uint {
+    solutions(numbers, target).len()
+}
+
+// replaces any two items by any combination of them, until there's
+// only one item left. Its infix form identifies it in found
+fn search(items: &[(Rational, Expr)], target: Rational, found: &mut TreeMap<String, Expr>) {
+    if items.len() == 1 {
+        let (ref value, ref expr) = items[0];
+        if *value == target {
+            found.insert(expr.to_string(), expr.clone());
+        }
+        return;
+    }
+    for i in range(0, items.len()) {
+        for j in range(i + 1, items.len()) {
+            let (ref a, ref x) = items[i];
+            let (ref b, ref y) = items[j];
+            let mut combinations = vec![
+                (*a + *b, combine(x.clone(), y.clone(), false, false)),
+                (*a - *b, combine(x.clone(), y.clone(), false, true)),
+                (*b - *a, combine(y.clone(), x.clone(), false, true)),
+                (*a * *b, combine(x.clone(), y.clone(), true, false))];
+            if !b.is_zero() {
+                combinations.push((*a / *b, combine(x.clone(), y.clone(), true, true)));
+            }
+            if !a.is_zero() {
+                combinations.push((*b / *a, combine(y.clone(), x.clone(), true, true)));
+            }
+
+            let mut rest: Vec<(Rational, Expr)> = items.iter().enumerate()
+                                                       .filter(|&(k, _)| k != i && k != j)
+                                                       .map(|(_, item)| item.clone())
+                                                       .collect();
+            for combination in combinations.into_iter() {
+                rest.push(combination);
+                search(rest.as_slice(), target, found);
+                rest.pop();
+            }
+        }
+    }
+}
+
 #[test]
 fn test_rationals_macro() {
     assert_eq!(
@@ -74,4 +209,39 @@ fn test_solve() {
     assert_eq!(
         solve(r.as_mut_slice(), 24),
         Some("(9 / (3 / (1 + 7)))".to_string()));
-}
+}
+
+#[test]
+fn test_all_shapes() {
+    // (10 * 10 - 4) / 4 isn't right-nested
+    let mut r = rationals![4i, 4, 10, 10];
+    assert_eq!(solve(r.as_mut_slice(), 24), None);
+    let found: Vec<String> = solutions(r.as_slice(), Ratio::from_integer(24))
+                                 .iter().map(|e| e.to_string()).collect();
+    assert_eq!(found, vec!["(10 * 10 - 4) / 4".to_string()]);
+}
+
+#[test]
+fn test_distinct_solutions() {
+    fn t(r: &[Rational], target: int, expected: &[&str]) {
+        let found: Vec<String> = solutions(r, Ratio::from_integer(target))
+                                     .iter().map(|e| e.to_string()).collect();
+        assert_eq!(found.iter().map(|s| s.as_slice()).collect::<Vec<&str>>().as_slice(), expected);
+    }
+    // 9 / (3 / (1 + 7)) and (1 + 7) * (9 / 3) are the same solution
+    t(&rationals![1i, 3, 7, 9], 24, &["9 * (1 + 7) / 3"]);
+    t(&rationals![3i, 3, 8, 8], 24, &["8 / (3 - 8 / 3)"]);
+    t(&rationals![1i, 2, 3, 4], 24,
+      &["(1 + 3) * (2 + 4)", "1 * 2 * 3 * 4", "2 * 3 * 4 / 1", "4 * (1 + 2 + 3)"]);
+    t(&rationals![1i, 1, 1, 1], 4, &["(1 + 1) * (1 + 1)", "1 + 1 + 1 + 1"]);
+}
+
+#[test]
+fn test_any_count_any_target() {
+    let found = solutions(&rationals![3i, 4], Ratio::new(3, 4));
+    assert_eq!(found.iter().map(|e| e.to_string()).collect::<Vec<String>>(),
+               vec!["3 / 4".to_string()]);
+    assert_eq!(count_solutions(&rationals![5i], Ratio::from_integer(5)), 1);
+    assert_eq!(count_solutions(&rationals![2i, 3], Ratio::from_integer(7)), 0);
+    assert_eq!(count_solutions(&rationals![1i, 2, 3, 4, 5], Ratio::from_integer(24)), 93);
+}
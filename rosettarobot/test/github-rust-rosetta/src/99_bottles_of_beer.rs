@@ -1,18 +1,27 @@
 // Implements http://rosettacode.org/wiki/99_Bottles_of_Beer
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 use std::string::String;
 
 #[cfg(not(test))]
-fn main() {
-    for num_bottles in std::iter::range_inclusive(1u, 99).rev() {
-        println!("{}", bottles_line(num_bottles, true));
-        println!("{}", bottles_line(num_bottles, false));
-        println!("Take one down, pass it around...");
-        println!("{}", bottles_line(num_bottles - 1, true));
-        println!("-----------------------------------");
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the whole song to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
+    for num_bottles in ::std::iter::range_inclusive(1u, 99).rev() {
+        try!(writeln!(out, "{}", bottles_line(num_bottles, true)));
+        try!(writeln!(out, "{}", bottles_line(num_bottles, false)));
+        try!(writeln!(out, "Take one down, pass it around..."));
+        try!(writeln!(out, "{}", bottles_line(num_bottles - 1, true)));
+        try!(writeln!(out, "-----------------------------------"));
     }
+    Ok(())
 }
 
-fn bottles_line(num_bottles: uint, on_the_wall: bool) -> String {
+pub fn bottles_line(num_bottles: uint, on_the_wall: bool) -> String {
     let tail = match on_the_wall {
         true => "of beer on the wall!\n",
         false => "of beer\n"
@@ -4,6 +4,9 @@ extern crate num;
 
 use num::bigint::BigUint;
 use std::cmp::min;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 use std::iter::range_inclusive;
 use std::num::{Zero, One};
 
@@ -36,7 +39,7 @@ impl Solver {
         self.cumulative(idx).last().unwrap()
     }
 
-    fn cumulative(&mut self, idx: uint) -> &[BigUint] {
+    pub fn cumulative(&mut self, idx: uint) -> &[BigUint] {
         for l in range_inclusive(self.cache.len(), idx) {
             let mut r : Vec<BigUint> = vec![Zero::zero()];
 
@@ -56,18 +59,24 @@ impl Solver {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the first rows of the triangle and some of its sums to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let mut solver = Solver::new();
 
-    println!("rows");
+    try!(writeln!(out, "rows"));
     for n in range(1u, 11) {
-        println!("{}: {}", n, solver.row_string(n));
+        try!(writeln!(out, "{}: {}", n, solver.row_string(n)));
     }
 
-    println!("sums");
+    try!(writeln!(out, "sums"));
     for &y in [23u, 123, 1234, 12345].iter() {
-        println!("{}: {}", y, solver.row_sum(y));
+        try!(writeln!(out, "{}: {}", y, solver.row_sum(y)));
     }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -2,7 +2,7 @@
 
 use std::io::stdio;
 
-fn main() {
+pub fn main() {
     let input = stdio::stdin().read_line().unwrap();
     let words = input.as_slice().words().take(2)
                             .map(from_str::<int>)
@@ -1,32 +1,41 @@
 // http://rosettacode.org/wiki/ABC_Problem
 use std::collections::HashSet;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
-const  WORDS: &'static [&'static str] = &["A", "BARK", "BOOK", "TREAT",
+pub const  WORDS: &'static [&'static str] = &["A", "BARK", "BOOK", "TREAT",
                                          "COMMON", "SQUAD", "CONFUSE"];
 
-const  BLOCKS: &'static [&'static str] = &["BO", "XK", "DQ", "CP", "NA",
+pub const  BLOCKS: &'static [&'static str] = &["BO", "XK", "DQ", "CP", "NA",
                                            "GT", "RE", "TG", "QD", "FS",
                                            "JW", "HU", "VI", "AN", "OB",
                                            "ER", "FS", "LY", "PC", "ZM"];
 
 #[cfg(not(test))]
-fn main() {
-    println!("******\nmethod 1\n******");
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes whether each word can be built, with both methods, to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
+    try!(writeln!(out, "******\nmethod 1\n******"));
     for word in WORDS.iter() {
-        println!("can {} be built? {}", word, can_be_built_input_first(*word))
+        try!(writeln!(out, "can {} be built? {}", word, can_be_built_input_first(*word)))
     }
 
-    println!("\n******\nmethod 2\n******");
+    try!(writeln!(out, "\n******\nmethod 2\n******"));
     for word in WORDS.iter() {
-        println!("can {} be built? {}", word, can_be_built_blocks_first(*word))
+        try!(writeln!(out, "can {} be built? {}", word, can_be_built_blocks_first(*word)))
     }
+    Ok(())
 }
 
 /// First method (common solution among other languages)
 ///
 /// Iterates through the letters of the input and checks if there is a suitable
 /// *unused* block for each letter
-fn can_be_built_input_first(input: &str) -> bool {
+pub fn can_be_built_input_first(input: &str) -> bool {
     let mut used = HashSet::new();
 
     for chr in input.as_slice().chars() {
@@ -49,7 +58,7 @@ fn can_be_built_input_first(input: &str) -> bool {
 /// of the characters in the input that hasn't already been fulfilled.
 /// It should be more efficient than the first method whenever there are more
 /// blocks than characters in the input.
-fn can_be_built_blocks_first(input: &str) -> bool {
+pub fn can_be_built_blocks_first(input: &str) -> bool {
     let mut matched = HashSet::new();
 
     BLOCKS.iter().any(|block| {
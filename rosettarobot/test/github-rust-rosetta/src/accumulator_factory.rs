@@ -1,5 +1,8 @@
 // Implements http://rosettacode.org/wiki/Accumulator_factory
 #![feature(overloaded_calls)]
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
 pub struct G<T, U> {
     n: T,
@@ -18,7 +21,12 @@ pub fn accum<T: Add<T, U> + Clone, U>(n: T) -> G<T, U> {
 
 #[cfg(not(test))]
 pub fn main() {
-    println!("{}", accumulate());
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the value of the accumulator to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
+    writeln!(out, "{}", accumulate())
 }
 
 #[test]
@@ -26,7 +34,7 @@ pub fn test() {
     assert_eq!(8.3, accumulate());
 }
 
-fn accumulate() -> f32 {
+pub fn accumulate() -> f32 {
     // Deviation: works with all types implementing addition, but not a mixture
     // of types (it is possible to handle mixed types, but would require type
     // switching at the moment).
@@ -1,6 +1,9 @@
 // Implements http://rosettacode.org/wiki/Ackermann_function
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
-fn ack(m: int, n: int) -> int {
+pub fn ack(m: int, n: int) -> int {
     if m == 0 {
         n + 1
     } else if n == 0 {
@@ -18,7 +21,12 @@ fn test_ack() {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes A(3, 4) to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let a = ack(3, 4);
-    println!("{}", a);
+    writeln!(out, "{}", a)
 }
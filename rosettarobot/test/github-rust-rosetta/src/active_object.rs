@@ -128,7 +128,7 @@ impl<S: Mul<f64, T> + Zero,
 // This function is fairly straightforward.  We create the integrator, set its input function k(t)
 // to 2pi * f * t, and then wait as described in the Rosetta stone problem.
 #[cfg(not(test))]
-fn integrate() -> f64 {
+pub fn integrate() -> f64 {
     let object = Integrator::new(Duration::milliseconds(10));
     let mut timer = Timer::new().unwrap();
     object.input(|t| {
@@ -142,7 +142,7 @@ fn integrate() -> f64 {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
     println!("{}", integrate());
 }
 
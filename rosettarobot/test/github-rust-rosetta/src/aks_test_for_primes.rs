@@ -1,4 +1,7 @@
 // http://rosettacode.org/wiki/AKS_test_for_primes
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
 pub fn is_prime(p: uint) -> bool {
     if p<2 {
@@ -16,20 +19,27 @@ pub fn is_prime(p: uint) -> bool {
 }
 
 // need to allow dead code because this is used as a library
-// by pernicious numbers
+// by pernicious numbers, here and on `run`
 #[allow(dead_code)]
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the coefficients of the first polynomials and the primes up to 50 to `out`
+#[allow(dead_code)]
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     for p in range(0u, 8) {
-        println!("{}: {}", p, coefficients(p));
+        try!(writeln!(out, "{}: {}", p, coefficients(p)));
     }
 
     for p in range(1u, 51).filter(|&x| is_prime(x)) {
-        print!("{} ", p);
+        try!(write!(out, "{} ", p));
     }
+    Ok(())
 }
 
-fn coefficients(p: uint) -> Vec<i64> {
+pub fn coefficients(p: uint) -> Vec<i64> {
     if p==0 {
         vec![1]
     } else {
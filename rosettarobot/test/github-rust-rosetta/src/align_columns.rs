@@ -1,19 +1,24 @@
 // Implements http://rosettacode.org/wiki/Align_columns
 
-const TEST_STR: &'static str =
-    "Given$a$text$file$of$many$lines,$where$fields$within$a$line$\nare$delineated\
-    $by$a$single$'dollar'$character,$write$a$program\nthat$aligns$each$column$of\
-    $fields$by$ensuring$that$words$in$each$\ncolumn$are$separated$by$at$least$one\
-    $space.\nFurther,$allow$for$each$word$in$a$column$to$be$either$left$\n\
-    justified,$right$justified,$or$center$justified$within$its$column.\n";
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
+
+// The text of the task, one line per row with `$` between the fields
+pub const TEST_STR: &'static str = include_str!("resources/align_columns_input.txt");
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes `TEST_STR` to `out`, aligned to the left, to the right and centered
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let (chunks, max_lengths) = align_columns(TEST_STR);
-    print_aligned_columns(&chunks, &max_lengths);
+    print_aligned_columns(out, &chunks, &max_lengths)
 }
 
-fn align_columns(text: &str) -> (Vec<Vec<String>>, Vec<uint>) {
+pub fn align_columns(text: &str) -> (Vec<Vec<String>>, Vec<uint>) {
     let mut lines: Vec<String> = text.split('\n').map(|s| s.to_string()).collect();
     let mut max_lengths: Vec<uint> = Vec::new();
     let mut chunks: Vec<Vec<String>> = Vec::new();
@@ -26,7 +31,7 @@ fn align_columns(text: &str) -> (Vec<Vec<String>>, Vec<uint>) {
 
         for i in range(0u, v.len()) {
             if i < max_lengths.len() {
-                *max_lengths.get_mut(i) = std::cmp::max(max_lengths[i], v[i]);
+                *max_lengths.get_mut(i) = ::std::cmp::max(max_lengths[i], v[i]);
             } else {
                 max_lengths.push(v[i]);
             }
@@ -36,38 +41,40 @@ fn align_columns(text: &str) -> (Vec<Vec<String>>, Vec<uint>) {
     (chunks, max_lengths)
 }
 
-fn print_aligned_columns(chunks: &Vec<Vec<String>>, max_lengths: &Vec<uint>) {
+pub fn print_aligned_columns<W: Writer>(out: &mut W, chunks: &Vec<Vec<String>>,
+                                       max_lengths: &Vec<uint>) -> IoResult<()> {
     // left aligned
     for i in range(0u, chunks.len()) {
         for j in range(0u, chunks[i].len()) {
-            print!("{0:<1$}", chunks[i][j], 1 + max_lengths[j]);
+            try!(write!(out, "{0:<1$}", chunks[i][j], 1 + max_lengths[j]));
         }
-        println!("");
+        try!(writeln!(out, ""));
     }
-    println!("");
+    try!(writeln!(out, ""));
     // right aligned
     for i in range(0u, chunks.len()) {
         for j in range(0u, chunks[i].len()) {
-            print!("{0:>1$}", chunks[i][j], 1 + max_lengths[j]);
+            try!(write!(out, "{0:>1$}", chunks[i][j], 1 + max_lengths[j]));
         }
-        println!("");
+        try!(writeln!(out, ""));
     }
-    println!("");
+    try!(writeln!(out, ""));
     // center aligned
     for i in range(0u, chunks.len()) {
         for j in range(0u, chunks[i].len()) {
             let ref string: String = chunks[i][j];
             let spaces: uint = 1 + max_lengths[j] - string.len();
             for _ in range(0u, spaces>>1) {
-                print!(" ");
+                try!(write!(out, " "));
             }
-            print!("{}", string);
+            try!(write!(out, "{}", string));
             for _ in range(0u, spaces - (spaces>>1)) {
-                print!(" ");
+                try!(write!(out, " "));
             }
         }
-        println!("");
+        try!(writeln!(out, ""));
     }
+    Ok(())
 }
 
 #[test]
@@ -81,5 +88,5 @@ fn test_result() {
             assert!(chunkset[j].len() <= max_lengths[j]);
         }
     }
-    print_aligned_columns(&chunks, &max_lengths);
+    print_aligned_columns(&mut ::std::io::util::NullWriter, &chunks, &max_lengths).unwrap();
 }
@@ -1,9 +1,12 @@
 // Implements http://rosettacode.org/wiki/Almost_prime
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
 #[allow(unused_imports)]
 use std::iter::{count, range_inclusive};
 
-fn is_kprime(mut n: uint, k: uint) -> bool {
+pub fn is_kprime(mut n: uint, k: uint) -> bool {
     let mut p = 2;
     let mut f = 0;
 
@@ -18,17 +21,23 @@ fn is_kprime(mut n: uint, k: uint) -> bool {
     f + (n > 1) as uint == k
 }
 
-fn get_kprimes(k: uint, amount: uint) -> Vec<uint> {
+pub fn get_kprimes(k: uint, amount: uint) -> Vec<uint> {
     count(2u, 1).filter(|&x| is_kprime(x, k))
                 .take(amount)
                 .collect()
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the first 10 k-almost-primes, for k up to 5, to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     for k in range_inclusive(1u, 5) {
-        println!("k = {}: {}", k, get_kprimes(k, 10));
+        try!(writeln!(out, "k = {}: {}", k, get_kprimes(k, 10)));
     }
+    Ok(())
 }
 
 #[test]
@@ -4,7 +4,7 @@ use std::io::{File, BufferedReader};
 use std::collections::{HashMap, HashSet};
 use std::collections::hashmap::{Occupied, Vacant};
 
-fn sorted_characters(string: &str) -> String {
+pub fn sorted_characters(string: &str) -> String {
     let mut chars = string.chars().collect::<Vec<char>>();
     chars.sort();
     String::from_chars(chars.as_slice())
@@ -12,7 +12,7 @@ fn sorted_characters(string: &str) -> String {
 
 /// Returns groups of anagrams where each group consists of a set
 /// containing the words
-fn anagrams<T: Iterator<String>>(mut lines: T) -> HashMap<String, HashSet<String>> {
+pub fn anagrams<T: Iterator<String>>(mut lines: T) -> HashMap<String, HashSet<String>> {
     let mut groups = HashMap::new();
 
     // Make groups of words according to the letters they contain
@@ -30,7 +30,7 @@ fn anagrams<T: Iterator<String>>(mut lines: T) -> HashMap<String, HashSet<String
 }
 
 /// Returns the groups of anagrams that contain the most words in them
-fn largest_groups(groups: &HashMap<String, HashSet<String>>)
+pub fn largest_groups(groups: &HashMap<String, HashSet<String>>)
                       -> HashMap<String, HashSet<String>> {
     let max_length = groups.iter().map(|(_, group)| group.len())
                                   .max().unwrap();
@@ -44,7 +44,7 @@ fn largest_groups(groups: &HashMap<String, HashSet<String>>)
 }
 
 #[cfg(not(test))]
-fn main () {
+pub fn main () {
     let path = Path::new("src/resources/unixdict.txt");
     let mut reader = BufferedReader::new(File::open(&path));
     let lines = reader.lines().map(|l| l.unwrap());
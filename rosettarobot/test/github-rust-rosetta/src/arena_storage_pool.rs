@@ -2,9 +2,17 @@
 extern crate arena;
 
 use arena::TypedArena;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the sum of two values allocated in an arena to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     // Memory is allocated using the default allocator (currently jemalloc).  The memory is
     // allocated in chunks, and when one chunk is full another is allocated.  This ensures that
     // references to an arena don't become invalid when the original chunk runs out of space.  The
@@ -20,7 +28,8 @@ fn main() {
     // modifiable after it is allocated.  This is to prevent the reference from being dropped
     // accidentally before the arena is deallocated.
     let v2 = arena.alloc(3);
-    println!("{}", v1 + *v2);
+    try!(writeln!(out, "{}", v1 + *v2));
     // The arena's destructor is called as it goes out of scope, at which point it deallocates
     // everything stored within it at once.
+    Ok(())
 }
@@ -1,7 +1,7 @@
 // Implements http://rosettacode.org/wiki/Arithmetic/Integer
 use std::io::stdin;
 
-fn main() {
+pub fn main() {
     let input = stdin().read_line().unwrap();
     let words = input.as_slice().words().take(2)
                                         .map(from_str)
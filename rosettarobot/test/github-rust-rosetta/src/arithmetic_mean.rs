@@ -1,8 +1,11 @@
 // Implements http://rosettacode.org/wiki/Averages/Arithmetic_mean
 use std::iter::AdditiveIterator;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
 // The mean is not defined for an empty list, so we must return an Option
-fn mean(list: &[f64]) -> Option<f64> {
+pub fn mean(list: &[f64]) -> Option<f64> {
     match list.len() {
         0 => None,
         n => {
@@ -13,12 +16,17 @@ fn mean(list: &[f64]) -> Option<f64> {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the mean of a few numbers to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let input = [3.0, 1.0, 4.0, 1.0, 5.0, 9.0];
 
     // This should be 3.833333
     let mean = mean(input).unwrap();
-    println!("{}", mean);
+    writeln!(out, "{}", mean)
 }
 
 #[test]
@@ -1,21 +1,30 @@
 // http://rosettacode.org/wiki/Arithmetic/Rational
 use std::num;
 use std::fmt;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the perfect numbers below 2^19 to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     for p in perfect_numbers(1 << 19).iter() {
-        println!("{} is perfect", p);
+        try!(writeln!(out, "{} is perfect", p));
     }
+    Ok(())
 }
 
-fn perfect_numbers(max: i64) -> Vec<i64> {
+pub fn perfect_numbers(max: i64) -> Vec<i64> {
     let mut ret=Vec::new();
     for candidate in range(2, max) {
         let mut sum=Frac::secure_new(1, candidate).unwrap();
         let max2=((candidate as f64).sqrt().floor()) as i64;
 
-        for factor in std::iter::range_inclusive(2, max2) {
+        for factor in ::std::iter::range_inclusive(2, max2) {
             if candidate % factor == 0 {
                 sum = sum + Frac::new(1, factor) + Frac::new(factor, candidate);
             }
@@ -25,26 +34,26 @@ fn perfect_numbers(max: i64) -> Vec<i64> {
     ret
 }
 
-struct Frac {
+pub struct Frac {
     num: i64,
     den: i64
 }
 
-fn gcd(m: i64, n:i64) -> i64 {
+pub fn gcd(m: i64, n:i64) -> i64 {
     let mut t: i64;
     let (mut m, mut n)=(num::abs(m), num::abs(n));
     while n>0 { t = n; n = m % n; m = t; }
     m
 }
 
-fn lcm(m: i64, n:i64) -> i64 {
+pub fn lcm(m: i64, n:i64) -> i64 {
     num::abs(m) / gcd(m, n) * num::abs(n)
 }
 
 
 impl Frac {
     // fails on den=0
-    fn new(num: i64, den:i64) -> Frac {
+    pub fn new(num: i64, den:i64) -> Frac {
         let (n, d) = match (num, den) {
             (0, _)          => (0, 0),
             (n, d) if d<0   => (-n, -d),
@@ -55,7 +64,7 @@ impl Frac {
     }
 
     // does not fail (returns Err on den=0)
-    fn secure_new(num: i64, den:i64) -> Result<Frac, String> {
+    pub fn secure_new(num: i64, den:i64) -> Result<Frac, String> {
         if den==0 {
             Err("Error: Division by zero".to_string())
         } else {
@@ -64,12 +73,12 @@ impl Frac {
     }
 
     // fails on den=0, returns frac already in its reduced form
-    fn new_reduced(num: i64, den:i64) -> Frac {
+    pub fn new_reduced(num: i64, den:i64) -> Frac {
         Frac::new(num, den).reduce()
     }
 
     // reduces the fraction to lowest terms
-    fn reduce(mut self) -> Frac {
+    pub fn reduce(mut self) -> Frac {
         match self {
             z @ Frac{num:0, den:0} => z,
             _         => {
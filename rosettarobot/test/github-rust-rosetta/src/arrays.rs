@@ -1,7 +1,7 @@
 // Implements http://rosettacode.org/wiki/Arrays
 
 #[cfg(not(test))]
-fn main() {}
+pub fn main() {}
 
 #[test]
 fn create_array() {
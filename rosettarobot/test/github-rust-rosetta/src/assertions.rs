@@ -1,6 +1,6 @@
 // Implements http://rosettacode.org/wiki/Assertions
 
-fn main() {
+pub fn main() {
     let my_number = 42i;
     assert!(my_number == 42);
     assert_eq!(my_number, 42);
@@ -168,7 +168,7 @@ mod buckets {
 }
 
 // Convenience method to create a distribution of buckets summing to initial_sum.
-fn make_buckets(initial_sum: uint) -> buckets::Buckets {
+pub fn make_buckets(initial_sum: uint) -> buckets::Buckets {
     let mut buckets = [0, .. buckets::N_BUCKETS];
     let mut dist = initial_sum;
     for (i, b) in buckets.as_mut_slice().iter_mut().enumerate() {
@@ -180,7 +180,7 @@ fn make_buckets(initial_sum: uint) -> buckets::Buckets {
 }
 
 // The equalize task--it chooses two random buckets and tries to make their values the same.
-fn equalize(bl: &buckets::Buckets, running: &AtomicBool, worker: uint) {
+pub fn equalize(bl: &buckets::Buckets, running: &AtomicBool, worker: uint) {
     // We preallocate the Range for improved performance.
     let between = Range::new(0, buckets::N_BUCKETS);
     // We use the weak random number generator for improved performance.
@@ -201,7 +201,7 @@ fn equalize(bl: &buckets::Buckets, running: &AtomicBool, worker: uint) {
 }
 
 // The randomize task--it chooses two random buckets and randomly redistributes their values.
-fn randomize(bl: &buckets::Buckets, running: &AtomicBool, worker: uint) {
+pub fn randomize(bl: &buckets::Buckets, running: &AtomicBool, worker: uint) {
     // We preallocate the Range for improved performance.
     let between = Range::new(0, buckets::N_BUCKETS);
     // We use the weak random number generator for improved performance.
@@ -218,7 +218,7 @@ fn randomize(bl: &buckets::Buckets, running: &AtomicBool, worker: uint) {
 // The display task--for a total time of `duration`, it displays information about the update
 // process and checks to make sure that the invariant (that the total remains constant) is
 // preserved.  It prints an update `nticks` times, evenly spaced.
-fn display(bl: &buckets::Buckets, running: &AtomicBool, original_total: uint, duration: Duration, nticks: i32) {
+pub fn display(bl: &buckets::Buckets, running: &AtomicBool, original_total: uint, duration: Duration, nticks: i32) {
     println!("transfers, N. transfers, buckets, buckets sum:");
 
     let mut timer = Timer::new().unwrap();
@@ -242,7 +242,7 @@ fn display(bl: &buckets::Buckets, running: &AtomicBool, original_total: uint, du
 }
 
 // Putting together all three tasks.
-fn perform_atomic_updates(duration: Duration, original_total: uint, num_ticks: i32)
+pub fn perform_atomic_updates(duration: Duration, original_total: uint, num_ticks: i32)
 {
     // Worker IDs for the two updater tasks.
     const ID_EQUALIZE: uint = 0;
@@ -266,11 +266,11 @@ fn perform_atomic_updates(duration: Duration, original_total: uint, num_ticks: i
     display(bl, running, original_total, duration, num_ticks);
 }
 
-const ORIGINAL_TOTAL: uint = 1000;
-const NUM_TICKS: i32 = 10;
+pub const ORIGINAL_TOTAL: uint = 1000;
+pub const NUM_TICKS: i32 = 10;
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
     // Run for 10 seconds
     perform_atomic_updates(Duration::seconds(10), ORIGINAL_TOTAL, NUM_TICKS);
 }
@@ -1,8 +1,11 @@
 // Implements http://rosettacode.org/wiki/Averages/Mean_angle
 
 use std::num::Float;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
-fn mean_angle(angles: &[f64]) -> f64 {
+pub fn mean_angle(angles: &[f64]) -> f64 {
     let (sum_cos, sum_sin) = angles.iter()
                                    .map(|&x| x * Float::pi() / 180.)
                                    // We map into a tuple of sin and cos so we
@@ -18,14 +21,19 @@ fn mean_angle(angles: &[f64]) -> f64 {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the mean angles of three sets to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let set1 = [350., 10.];
     let set2 = [90., 180., 270., 360.];
     let set3 = [10., 20., 30.];
 
-    println!("Mean angle of first set is {} degrees", mean_angle(set1));
-    println!("Mean angle of second set is {} degrees", mean_angle(set2));
-    println!("Mean angle of third set is {} degrees", mean_angle(set3));
+    try!(writeln!(out, "Mean angle of first set is {} degrees", mean_angle(set1)));
+    try!(writeln!(out, "Mean angle of second set is {} degrees", mean_angle(set2)));
+    writeln!(out, "Mean angle of third set is {} degrees", mean_angle(set3))
 }
 
 #[test]
@@ -1,6 +1,6 @@
 // Implements http://rosettacode.org/wiki/Balanced_brackets
 
-trait Balanced {
+pub trait Balanced {
     /// Returns true if the brackets are balanced
     fn is_balanced(&self) -> bool;
 }
@@ -31,14 +31,14 @@ impl Balanced for String {
 
 /// Generates random brackets
 #[cfg(not(test))]
-fn generate_brackets(num: uint) -> String {
+pub fn generate_brackets(num: uint) -> String {
     use std::rand::random;
 
     range(0, num).map(|_| if random() { '[' } else { ']' }).collect()
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
     for i in range (0u, 10) {
         let brackets = generate_brackets(i);
 
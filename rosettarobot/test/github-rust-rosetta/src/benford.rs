@@ -6,14 +6,14 @@
 use std::io::{File, BufferedReader};
 
 // Calculate the expected frequency of a digit according to Benford's Law
-fn benford_freq(d: u64) -> f32 {
+pub fn benford_freq(d: u64) -> f32 {
     assert!(d >= 1 && d <= 9);
 
     (1.0 + 1.0/(d as f32)).log10()
 }
 
 // Returns the leading digit of any number
-fn first_digit_of(n: u64) -> uint {
+pub fn first_digit_of(n: u64) -> uint {
     let mut d = n;
     while d > 9 {
         d = d / 10;
@@ -22,7 +22,7 @@ fn first_digit_of(n: u64) -> uint {
 }
 
 // Count frequency table using the first digit of each number in a vector
-fn benford_distrib(numbers: &Vec<u64>) -> Vec<f32> {
+pub fn benford_distrib(numbers: &Vec<u64>) -> Vec<f32> {
 
     // Counts
 
@@ -44,9 +44,9 @@ fn benford_distrib(numbers: &Vec<u64>) -> Vec<f32> {
     freq
 }
 
-const N: uint = 100;
+pub const N: uint = 100;
 
-fn main() {
+pub fn main() {
 
     // Calculate expected frequencies of all digits according to Benford's Law
 
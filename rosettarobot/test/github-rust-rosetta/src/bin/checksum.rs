@@ -0,0 +1,219 @@
+// Prints or checks the checksums of files, like md5sum, sha1sum and sha256sum of GNU coreutils,
+// with the implementations of `rosetta::digest`.
+//
+// Usage:
+//     checksum <algorithm> [-b] [file...]     prints the checksum of each file
+//     checksum <algorithm> -c [file...]       checks the checksums listed in each file
+//
+// <algorithm> is one of md5, sha1, sha256 and crc32.  Without files, or for the file `-`, the
+// standard input is read.  `-b` marks the files as binary in the output, which only matters on
+// Windows.  With `-c`, every listed file is reported as OK or FAILED, and the exit status is 1 if
+// a checksum doesn't match or a file can't be read.
+extern crate rosetta;
+
+use rosetta::digest;
+use rosetta::digest::Digest;
+use std::io::{mod, File, IoResult};
+#[cfg(not(test))]
+use std::io::LineBufferedWriter;
+#[cfg(not(test))]
+use std::io::stdio::StdWriter;
+#[cfg(not(test))]
+use std::os;
+use std::str;
+
+#[cfg(not(test))]
+fn main() {
+    let args = os::args();
+    let mut stderr = io::stderr();
+
+    let digest = if args.len() >= 2 { digest::from_name(args[1][]) } else { None };
+    let mut digest = match digest {
+        Some(digest) => digest,
+        None => return usage(&mut stderr)
+    };
+    let (mut check, mut binary) = (false, false);
+    let mut files = Vec::new();
+    for arg in args.slice_from(2).iter() {
+        match arg[] {
+            "-c" | "--check" => check = true,
+            "-b" | "--binary" => binary = true,
+            "-" => files.push(arg[]),
+            option if option.starts_with("-") => return usage(&mut stderr),
+            file => files.push(file)
+        }
+    }
+    if files.is_empty() {
+        files.push("-");
+    }
+
+    let mut ok = true;
+    for &file in files.iter() {
+        let file_ok = if check {
+            check_file(&mut *digest, file, &mut stderr)
+        } else {
+            match hash_file(&mut *digest, file) {
+                Ok(hash) => {
+                    println!("{}", digest::checksum_line(hash[], file, binary));
+                    true
+                }
+                Err(e) => {
+                    let _ = writeln!(&mut stderr, "checksum: {}: {}", file, e);
+                    false
+                }
+            }
+        };
+        ok = ok && file_ok;
+    }
+    if !ok {
+        os::set_exit_status(1);
+    }
+}
+
+#[cfg(not(test))]
+fn usage(stderr: &mut LineBufferedWriter<StdWriter>) {
+    let _ = writeln!(stderr, "Usage:\n    checksum <algorithm> [-b] [file...]\n    \
+                              checksum <algorithm> -c [file...]\n\
+                              <algorithm> is one of: {}", digest::NAMES.connect(", "));
+    os::set_exit_status(1);
+}
+
+// Hashes a file, or the standard input for `-`
+fn hash_file(digest: &mut Digest, file: &str) -> IoResult<Vec<u8>> {
+    if file == "-" {
+        digest::hash_reader(digest, &mut io::stdin())
+    } else {
+        digest::hash_reader(digest, &mut try!(File::open(&Path::new(file))))
+    }
+}
+
+// Checks the files listed in the file `list`, or in the standard input for `-`, returns true if
+// they all match
+#[cfg(not(test))]
+fn check_file(digest: &mut Digest, list: &str,
+              stderr: &mut LineBufferedWriter<StdWriter>) -> bool {
+    let mut stdout = io::stdout();
+    if list == "-" {
+        return check_list(digest, list, &mut io::stdin(), &mut stdout, stderr);
+    }
+    match File::open(&Path::new(list)) {
+        Ok(file) => {
+            check_list(digest, list, &mut io::BufferedReader::new(file), &mut stdout, stderr)
+        },
+        Err(e) => {
+            let _ = writeln!(stderr, "checksum: {}: {}", list, e);
+            false
+        }
+    }
+}
+
+// Checks the files listed in `reader`, whose name is `list`, and reports each of them to `out`.
+// Returns true if they all match.  The lines are read as bytes, so that one which isn't UTF-8 is
+// only an improperly formatted line.
+fn check_list<R: Buffer, W: Writer, E: Writer>(digest: &mut Digest, list: &str, reader: &mut R,
+                                               out: &mut W, stderr: &mut E) -> bool {
+    let (mut checked, mut malformed, mut unreadable, mut failed) = (0u, 0u, 0u, 0u);
+    loop {
+        let line = match reader.read_until(b'\n') {
+            Ok(line) => line,
+            Err(ref e) if e.kind == io::EndOfFile => break,
+            Err(e) => {
+                let _ = writeln!(stderr, "checksum: {}: {}", list, e);
+                return false;
+            }
+        };
+        // Like lines_any(), without the "\n" or "\r\n"
+        let mut line = line[];
+        if line.ends_with(b"\n") {
+            line = line[..line.len() - 1];
+            if line.ends_with(b"\r") {
+                line = line[..line.len() - 1];
+            }
+        }
+        let size = digest.output_size();
+        let entry = match str::from_utf8(line).and_then(|l| digest::parse_checksum_line(l, size)) {
+            Some(entry) => entry,
+            None => {
+                malformed += 1;
+                continue;
+            }
+        };
+        checked += 1;
+        match hash_file(digest, entry.filename[]) {
+            Ok(hash) => if hash == entry.hash {
+                let _ = writeln!(out, "{}: OK", entry.filename);
+            } else {
+                failed += 1;
+                let _ = writeln!(out, "{}: FAILED", entry.filename);
+            },
+            Err(e) => {
+                unreadable += 1;
+                let _ = writeln!(stderr, "checksum: {}: {}", entry.filename, e);
+                let _ = writeln!(out, "{}: FAILED open or read", entry.filename);
+            }
+        }
+    }
+
+    if checked == 0 {
+        let _ = writeln!(stderr, "checksum: {}: no properly formatted checksum lines found", list);
+        return false;
+    }
+    let warnings = [
+        (malformed, "line is improperly formatted", "lines are improperly formatted"),
+        (unreadable, "listed file could not be read", "listed files could not be read"),
+        (failed, "computed checksum did NOT match", "computed checksums did NOT match")];
+    for &(count, singular, plural) in warnings.iter() {
+        if count > 0 {
+            let _ = writeln!(stderr, "checksum: WARNING: {} {}", count,
+                             if count == 1 { singular } else { plural });
+        }
+    }
+    unreadable == 0 && failed == 0
+}
+
+#[cfg(test)]
+mod test {
+    use super::check_list;
+    use rosetta::digest;
+    use std::io::{File, MemReader, MemWriter, TempDir};
+
+    // Checks `list`, returns the result and what was written to the output and to the error output
+    fn check(list: &[u8]) -> (bool, String, String) {
+        let mut digest = digest::from_name("md5").unwrap();
+        let (mut out, mut err) = (MemWriter::new(), MemWriter::new());
+        let ok = check_list(&mut *digest, "list", &mut MemReader::new(list.to_vec()), &mut out,
+                            &mut err);
+        (ok, String::from_utf8(out.unwrap()).unwrap(), String::from_utf8(err.unwrap()).unwrap())
+    }
+
+    #[test]
+    fn check_mode() {
+        let dir = TempDir::new("checksum").unwrap();
+        let path = dir.path().join("abc");
+        File::create(&path).write_str("abc").unwrap();
+        let (abc, missing) = (path.as_str().unwrap(), dir.path().join("missing"));
+        let missing = missing.as_str().unwrap();
+
+        let list = format!("900150983cd24fb0d6963f7d28e17f72  {}\n", abc);
+        assert_eq!(check(list.as_bytes()), (true, format!("{}: OK\n", abc), String::new()));
+
+        // A line which isn't UTF-8 doesn't stop the others from being checked
+        let mut list = format!("900150983cd24fb0d6963f7d28e17f72  {}\r\n\
+                                00000000000000000000000000000000 *{}\n\
+                                900150983cd24fb0d6963f7d28e17f72  {}\n", abc, abc, missing)
+                           .into_bytes();
+        list.push_all(b"\xff\xfe\n");
+        let (ok, out, err) = check(list[]);
+        assert!(!ok);
+        assert_eq!(out, format!("{}: OK\n{}: FAILED\n{}: FAILED open or read\n",
+                                abc, abc, missing));
+        let warnings: Vec<&str> = err[].lines().skip(1).collect();
+        assert_eq!(warnings, vec!["checksum: WARNING: 1 line is improperly formatted",
+                                  "checksum: WARNING: 1 listed file could not be read",
+                                  "checksum: WARNING: 1 computed checksum did NOT match"]);
+
+        assert_eq!(check(b"\xff\n"),
+                   (false, String::new(),
+                    "checksum: list: no properly formatted checksum lines found\n".to_string()));
+    }
+}
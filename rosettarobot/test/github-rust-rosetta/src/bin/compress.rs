@@ -0,0 +1,120 @@
+// Compresses or decompresses a file with the codecs of `rosetta::codec`, or compares the codecs.
+//
+// Usage:
+//     compress [--codec <codec>] [-d] [file]           writes the compressed file to stdout
+//     compress --bench [--codec <codec>] [file...]     compares the codecs
+//
+// <codec> is one of rle, rle-escape, packbits, lzw, lzw-gif, lzw-z and huffman, lzw by default.
+// `-d` decompresses the file instead.  Without a file, or for the file `-`, the standard input is
+// read.  `--bench` compresses and decompresses every file (the files of src/resources by default,
+// from the root of the repository) with the codec, or with all of them, and reports the
+// compression ratio and the throughput.
+extern crate rosetta;
+
+#[cfg(not(test))]
+use rosetta::codec;
+#[cfg(not(test))]
+use rosetta::codec::Codec;
+#[cfg(not(test))]
+use std::io::{mod, fs, File, IoResult, LineBufferedWriter};
+#[cfg(not(test))]
+use std::io::stdio::StdWriter;
+#[cfg(not(test))]
+use std::os;
+
+#[cfg(not(test))]
+fn main() {
+    let args = os::args();
+    let mut stderr = io::stderr();
+
+    let (mut codec_name, mut decompress, mut bench) = (None, false, false);
+    let mut files = Vec::new();
+    let mut args = args.iter().skip(1);
+    loop {
+        match args.next().map(|arg| arg[]) {
+            Some("--codec") => match args.next() {
+                Some(name) => codec_name = Some(name[]),
+                None => return usage(&mut stderr)
+            },
+            Some("-d") | Some("--decompress") => decompress = true,
+            Some("--bench") => bench = true,
+            Some("-") => files.push("-"),
+            Some(option) if option.starts_with("-") => return usage(&mut stderr),
+            Some(file) => files.push(file),
+            None => break
+        }
+    }
+    let names = match codec_name {
+        Some(name) if codec::from_name(name).is_none() => return usage(&mut stderr),
+        Some(name) => vec![name],
+        None if bench => codec::NAMES.to_vec(),
+        None => vec!["lzw"]
+    };
+
+    let result = if bench {
+        benchmark(names[], files[])
+    } else if files.len() > 1 {
+        return usage(&mut stderr);
+    } else {
+        let codec = codec::from_name(names[0]).unwrap();
+        run(&*codec, files.head().map(|f| *f).unwrap_or("-"), decompress)
+    };
+    match result {
+        Ok(()) => (),
+        Err(e) => {
+            let _ = writeln!(&mut stderr, "compress: {}", e);
+            os::set_exit_status(1);
+        }
+    }
+}
+
+#[cfg(not(test))]
+fn usage(stderr: &mut LineBufferedWriter<StdWriter>) {
+    let _ = writeln!(stderr, "Usage:\n    compress [--codec <codec>] [-d] [file]\n    \
+                              compress --bench [--codec <codec>] [file...]\n\
+                              <codec> is one of: {}", codec::NAMES.connect(", "));
+    os::set_exit_status(1);
+}
+
+// Compresses or decompresses a file, or the standard input for `-`, to the standard output
+#[cfg(not(test))]
+fn run(codec: &Codec, file: &str, decompress: bool) -> IoResult<()> {
+    let mut stdout = io::stdout();
+    let mut input: Box<Reader> = if file == "-" {
+        box io::stdin()
+    } else {
+        box try!(File::open(&Path::new(file)))
+    };
+    if decompress {
+        codec.decompress(&mut *input, &mut stdout)
+    } else {
+        codec.compress(&mut *input, &mut stdout)
+    }
+}
+
+// Prints a line for every file and codec
+#[cfg(not(test))]
+fn benchmark(names: &[&str], files: &[&str]) -> IoResult<()> {
+    let paths = if files.is_empty() {
+        let mut paths = try!(fs::readdir(&Path::new("src/resources")));
+        paths.sort_by(|a, b| a.filename().cmp(&b.filename()));
+        paths
+    } else {
+        files.iter().map(|f| Path::new(*f)).collect()
+    };
+
+    println!("{:<28} {:<10} {:>9} {:>10} {:>6} {:>11} {:>11}",
+             "file", "codec", "size", "compressed", "ratio", "comp MB/s", "decomp MB/s");
+    for path in paths.iter() {
+        let data = try!(File::open(path).read_to_end());
+        let filename = path.filename_display().to_string();
+        for &name in names.iter() {
+            let codec = codec::from_name(name).unwrap();
+            let result = try!(codec::benchmark(&*codec, data[]));
+            println!("{:<28} {:<10} {:>9} {:>10} {:>6.3} {:>11.2} {:>11.2}",
+                     filename, name, result.size, result.compressed_size, result.ratio(),
+                     result.compress_speed(), result.decompress_speed());
+        }
+    }
+    Ok(())
+}
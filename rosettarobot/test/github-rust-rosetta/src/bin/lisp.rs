@@ -0,0 +1,69 @@
+// A read-eval-print loop for the Lisp of `rosetta::lisp`.
+//
+// Usage:
+//     lisp            reads expressions from the standard input and prints their values
+//     lisp <file>     evaluates every expression of <file> and prints the value of the last one
+//
+// An expression can span several lines: the loop keeps reading until its parentheses are closed.
+// Errors are printed on the standard error.
+extern crate rosetta;
+
+#[cfg(not(test))]
+use rosetta::lisp::{Interpreter, ParseError};
+#[cfg(not(test))]
+use rosetta::s_expressions::UnexpectedEOF;
+#[cfg(not(test))]
+use std::io::{mod, File};
+#[cfg(not(test))]
+use std::os;
+
+#[cfg(not(test))]
+fn main() {
+    let args = os::args();
+    let mut interpreter = Interpreter::new();
+    let mut stderr = io::stderr();
+
+    if args.len() == 2 {
+        let source = match File::open(&Path::new(args[1][])).read_to_string() {
+            Ok(source) => source,
+            Err(e) => {
+                let _ = writeln!(&mut stderr, "Can't read {}: {}", args[1], e);
+                os::set_exit_status(1);
+                return;
+            }
+        };
+        match interpreter.eval_program(source[]) {
+            Ok(value) => println!("{}", value),
+            Err(e) => {
+                let _ = writeln!(&mut stderr, "error: {}", e);
+                os::set_exit_status(1);
+            }
+        }
+        return;
+    }
+
+    let mut stdin = io::stdin();
+    let mut input = String::new();
+    loop {
+        print!("{}", if input.is_empty() { "> " } else { "  " });
+        io::stdio::flush();
+        match stdin.read_line() {
+            Ok(line) => input.push_str(line[]),
+            Err(_) => break // End of input
+        }
+        if input[].trim().is_empty() {
+            input.clear();
+            continue;
+        }
+        match interpreter.eval_str(input[]) {
+            // The expression isn't finished yet, keep reading
+            Err(ParseError(UnexpectedEOF)) => continue,
+            Ok(value) => println!("{}", value),
+            Err(e) => {
+                let _ = writeln!(&mut stderr, "error: {}", e);
+            }
+        }
+        input.clear();
+    }
+    println!("");
+}
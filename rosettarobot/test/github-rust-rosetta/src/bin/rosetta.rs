@@ -0,0 +1,44 @@
+// Lists the tasks of rust-rosetta and runs any of them by name.
+//
+// Usage:
+//     rosetta list
+//     rosetta run <task>
+//     rosetta json
+//
+// Task names are the ones used in `Cargo.toml`, e.g. `rosetta run hailstone`.  `rosetta json`
+// prints the task registry (URL, source path, documentation and tests of every task) as JSON.
+extern crate rosetta;
+
+#[cfg(not(test))]
+use std::io;
+#[cfg(not(test))]
+use std::os;
+
+#[cfg(not(test))]
+static USAGE: &'static str = "Usage:\n    rosetta list\n    rosetta run <task>\n    rosetta json";
+
+#[cfg(not(test))]
+fn main() {
+    let args = os::args();
+    let mut stderr = io::stderr();
+
+    if args.len() == 2 && args[1].as_slice() == "list" {
+        for name in rosetta::TASKS.iter() {
+            println!("{}", name);
+        }
+    } else if args.len() == 2 && args[1].as_slice() == "json" {
+        println!("{}", rosetta::registry::to_json());
+    } else if args.len() == 3 && args[1].as_slice() == "run" {
+        match rosetta::find_task(args[2].as_slice()) {
+            Some(task) => task(),
+            None => {
+                let _ = writeln!(&mut stderr, "Unknown task: {}. Use `rosetta list` to see all tasks.",
+                                 args[2]);
+                os::set_exit_status(1);
+            }
+        }
+    } else {
+        let _ = writeln!(&mut stderr, "{}", USAGE);
+        os::set_exit_status(1);
+    }
+}
@@ -1,7 +1,10 @@
 // Implements http://rosettacode.org/wiki/Binary_digits
 use std::iter::range_inclusive;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
-trait BinaryString {
+pub trait BinaryString {
     fn to_binary_string(&self) -> String;
 }
 
@@ -12,10 +15,16 @@ impl BinaryString for uint {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the numbers up to 16 in binary to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     for s in range_inclusive(0, 16u) {
-        println!("{}", s.to_binary_string());
+        try!(writeln!(out, "{}", s.to_binary_string()));
     }
+    Ok(())
 }
 
 #[test]
@@ -1,12 +1,21 @@
 // http://rosettacode.org/wiki/Binary_search
 #[cfg(not(test))]
-fn main() {
-    println!("{}", binary_search(&[1u,2,3,4,5,6], 4));
-    println!("{}", binary_search_rec(&[1u,2,3,4,5,6], 4));
+use std::io;
+use std::io::IoResult;
+
+#[cfg(not(test))]
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the index found by both searches to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
+    try!(writeln!(out, "{}", binary_search(&[1u,2,3,4,5,6], 4)));
+    writeln!(out, "{}", binary_search_rec(&[1u,2,3,4,5,6], 4))
 }
 
 // iterative version
-fn binary_search<T: Ord>(haystack: &[T], needle: T) -> Option<uint> {
+pub fn binary_search<T: Ord>(haystack: &[T], needle: T) -> Option<uint> {
     let mut low  = 0u;
     let mut high = haystack.len() - 1;
 
@@ -24,7 +33,7 @@ fn binary_search<T: Ord>(haystack: &[T], needle: T) -> Option<uint> {
 }
 
 // recursive version
-fn binary_search_rec<T: Ord>(haystack: &[T], needle: T) -> Option<uint> {
+pub fn binary_search_rec<T: Ord>(haystack: &[T], needle: T) -> Option<uint> {
     fn recurse<T: Ord>(low: uint, high: uint, haystack: &[T], needle: T) -> Option<uint> {
         match (low + high) / 2 {
             _ if high < low => None,
@@ -4,8 +4,11 @@ extern crate num;
 extern crate core;
 use num::bigint::BigUint;
 use core::num::One;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
-fn binomial(n: uint, mut k: uint) -> BigUint {
+pub fn binomial(n: uint, mut k: uint) -> BigUint {
     // Since binomial(n, k) = binomial(n, n - k), we might as well use
     // the smaller k to optimize
     if n - k < k {
@@ -23,8 +26,13 @@ fn binomial(n: uint, mut k: uint) -> BigUint {
 }
 
 #[cfg(not(test))]
-fn main() {
-    println!("{}", binomial(5, 3));
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the binomial coefficient of 5 and 3 to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
+    writeln!(out, "{}", binomial(5, 3))
 }
 
 #[test]
@@ -1,5 +1,11 @@
 // Implements http://rosettacode.org/wiki/Basic_bitmap_storage
+//
+// This is also the image module of the other bitmap tasks: besides color images, it has 8-bit
+// grayscale images, the conversions between both (as in the Grayscale image task), and reads and
+// writes all the Netpbm formats, PBM, PGM and PPM, in ASCII and binary.
+use std::cmp;
 use std::default::Default;
+use std::io;
 use std::io::{File, BufferedWriter, IoResult};
 
 #[deriving(Clone, Default, PartialEq, Show)]
@@ -30,32 +36,536 @@ impl Image {
         }
     }
 
-    pub fn write_ppm(&self, filename: &str) -> IoResult<()> {
-        let file = File::create(&Path::new(filename));
-        let mut writer = BufferedWriter::new(file);
-        try!(writer.write_line("P6"));
-        try!(write!(&mut writer, "{} {} {}\n", self.width, self.height, 255u));
-        for color in self.data.iter() {
-            for channel in [color.red, color.green, color.blue].iter() {
-                try!(writer.write_u8(*channel));
+    // The color at (x, y), or None outside of the image
+    pub fn get(&self, x: int, y: int) -> Option<Color> {
+        if x < 0 || y < 0 || x as uint >= self.width || y as uint >= self.height {
+            None
+        } else {
+            Some(self.data[x as uint + y as uint * self.width])
+        }
+    }
+
+    // Sets the color at (x, y), and does nothing outside of the image.  Shapes are drawn with it,
+    // so that they are clipped at the edges.
+    pub fn set(&mut self, x: int, y: int, color: Color) {
+        if x >= 0 && y >= 0 && (x as uint) < self.width && (y as uint) < self.height {
+            self.data[x as uint + y as uint * self.width] = color;
+        }
+    }
+
+    // Draws the image with a `#` for every black pixel and a `.` for the others, a line per row
+    pub fn to_ascii(&self) -> String {
+        let mut ascii = String::new();
+        for row in self.data.chunks(cmp::max(self.width, 1)) {
+            for color in row.iter() {
+                ascii.push(if *color == Default::default() { '#' } else { '.' });
             }
+            ascii.push('\n');
         }
-        Ok(())
+        ascii
+    }
+
+    // The pixel at (x, y), or the pixel of the image which stands for it outside of the image
+    pub fn get_extended(&self, x: int, y: int, edges: Edges) -> Color {
+        self.data[edges.index(x, self.width) + edges.index(y, self.height) * self.width]
+    }
+
+    // Converts to grayscale with the luminance of BT.709
+    pub fn to_gray(&self) -> ImageGray8 {
+        self.to_gray_with(Bt709)
+    }
+
+    pub fn to_gray_with(&self, luma: Luma) -> ImageGray8 {
+        let (red, green, blue) = luma.weights();
+        ImageGray8 {
+            width: self.width,
+            height: self.height,
+            data: self.data.iter().map(|c| {
+                let value = red * c.red as f64 + green * c.green as f64 + blue * c.blue as f64;
+                value.round().max(0.0).min(255.0) as u8
+            }).collect()
+        }
+    }
+
+    // A binary (P6) or ASCII (P3) pixmap
+    pub fn to_pnm(&self, binary: bool) -> Pnm {
+        let mut samples = Vec::with_capacity(self.data.len() * 3);
+        for color in self.data.iter() {
+            samples.push_all(&[color.red as u16, color.green as u16, color.blue as u16]);
+        }
+        Pnm { kind: Ppm, binary: binary, width: self.width, height: self.height, maxval: 255,
+              samples: samples }
+    }
+
+    pub fn write_ppm(&self, filename: &str) -> IoResult<()> {
+        self.to_pnm(true).save(&Path::new(filename))
     }
 }
 
 impl Index<(uint, uint), Color> for Image {
     fn index<'a>(&'a self, &(x, y): &(uint, uint)) -> &'a Color {
+        assert!(x < self.width && y < self.height, "({}, {}) is outside of the image", x, y);
         &self.data[x + y*self.width]
     }
 }
 
 impl IndexMut<(uint, uint), Color> for Image {
     fn index_mut<'a>(&'a mut self, &(x, y): &(uint, uint)) -> &'a mut Color {
+        assert!(x < self.width && y < self.height, "({}, {}) is outside of the image", x, y);
         self.data.get_mut(x + y*self.width)
     }
 }
 
+// Clips the segment from `from` to `to` to the rectangle from `min` to `max`, with the
+// Cohen-Sutherland algorithm, or returns None if the segment is outside of it.  The line tasks only
+// draw the part of a line which can be in the image.
+pub fn clip_line(from: (f64, f64), to: (f64, f64), (min_x, min_y): (f64, f64),
+                 (max_x, max_y): (f64, f64)) -> Option<((f64, f64), (f64, f64))> {
+    // The sides of the rectangle a point is beyond
+    let (left, right, top, bottom) = (1u, 2u, 4u, 8u);
+    let outcode = |(x, y): (f64, f64)| {
+        (if x < min_x { left } else if x > max_x { right } else { 0 }) |
+        (if y < min_y { top } else if y > max_y { bottom } else { 0 })
+    };
+
+    let (mut from, mut to) = (from, to);
+    let (mut from_code, mut to_code) = (outcode(from), outcode(to));
+    loop {
+        if from_code | to_code == 0 {
+            return Some((from, to));
+        }
+        if from_code & to_code != 0 {
+            return None;
+        }
+        // Move an end which is outside to the side it is beyond
+        let code = if from_code != 0 { from_code } else { to_code };
+        let ((x0, y0), (x1, y1)) = (from, to);
+        let point = if code & top != 0 {
+            (x0 + (x1 - x0) * (min_y - y0) / (y1 - y0), min_y)
+        } else if code & bottom != 0 {
+            (x0 + (x1 - x0) * (max_y - y0) / (y1 - y0), max_y)
+        } else if code & left != 0 {
+            (min_x, y0 + (y1 - y0) * (min_x - x0) / (x1 - x0))
+        } else {
+            (max_x, y0 + (y1 - y0) * (max_x - x0) / (x1 - x0))
+        };
+        if from_code != 0 {
+            from = point;
+            from_code = outcode(from);
+        } else {
+            to = point;
+            to_code = outcode(to);
+        }
+    }
+}
+
+// The weights of the red, green and blue channels in the luminance of a color
+#[deriving(Clone, PartialEq, Show)]
+pub enum Luma {
+    Bt601, // Of standard definition television, and of JPEG
+    Bt709, // Of high definition television, and of sRGB
+    Average,
+    Weights(f64, f64, f64)
+}
+
+impl Luma {
+    pub fn weights(&self) -> (f64, f64, f64) {
+        match *self {
+            Bt601 => (0.299, 0.587, 0.114),
+            Bt709 => (0.2126, 0.7152, 0.0722),
+            Average => (1.0 / 3.0, 1.0 / 3.0, 1.0 / 3.0),
+            Weights(red, green, blue) => (red, green, blue)
+        }
+    }
+}
+
+// What is outside of an image, for the filters which read the neighbors of pixels
+#[deriving(Clone, PartialEq, Show)]
+pub enum Edges {
+    Extend, // The closest pixel of the border
+    Wrap, // The pixel of the opposite side, as if the image was repeated
+    Mirror // The image reflected by its border, without repeating the border
+}
+
+impl Edges {
+    // The coordinate in `0..len` which stands for `i`
+    pub fn index(&self, i: int, len: uint) -> uint {
+        let len = len as int;
+        assert!(len > 0, "an empty image has no pixels");
+        let i = match *self {
+            Extend => cmp::min(cmp::max(i, 0), len - 1),
+            Wrap => (i % len + len) % len,
+            Mirror if len == 1 => 0,
+            Mirror => {
+                let period = 2 * (len - 1);
+                let i = (i % period + period) % period;
+                if i < len { i } else { period - i }
+            }
+        };
+        i as uint
+    }
+}
+
+// An 8-bit grayscale image, from black (0) to white (255)
+pub struct ImageGray8 {
+    pub width: uint,
+    pub height: uint,
+    pub data: Vec<u8>
+}
+
+impl ImageGray8 {
+    pub fn new(width: uint, height: uint) -> ImageGray8 {
+        ImageGray8 { width: width, height: height, data: Vec::from_elem(width * height, 0) }
+    }
+
+    // The value at (x, y), or None outside of the image
+    pub fn get(&self, x: int, y: int) -> Option<u8> {
+        if x < 0 || y < 0 || x as uint >= self.width || y as uint >= self.height {
+            None
+        } else {
+            Some(self.data[x as uint + y as uint * self.width])
+        }
+    }
+
+    // Sets the value at (x, y), and does nothing outside of the image
+    pub fn set(&mut self, x: int, y: int, value: u8) {
+        if x >= 0 && y >= 0 && (x as uint) < self.width && (y as uint) < self.height {
+            self.data[x as uint + y as uint * self.width] = value;
+        }
+    }
+
+    pub fn to_color(&self) -> Image {
+        Image {
+            width: self.width,
+            height: self.height,
+            data: self.data.iter().map(|&v| Color { red: v, green: v, blue: v }).collect()
+        }
+    }
+
+    // A binary (P5) or ASCII (P2) graymap
+    pub fn to_pnm(&self, binary: bool) -> Pnm {
+        Pnm { kind: Pgm, binary: binary, width: self.width, height: self.height, maxval: 255,
+              samples: self.data.iter().map(|&v| v as u16).collect() }
+    }
+}
+
+// The Netpbm formats: bitmaps (black and white), graymaps and pixmaps (color)
+#[deriving(Clone, PartialEq, Show)]
+pub enum PnmKind {
+    Pbm,
+    Pgm,
+    Ppm
+}
+
+impl PnmKind {
+    // The number of samples of a pixel
+    pub fn channels(&self) -> uint {
+        if *self == Ppm { 3 } else { 1 }
+    }
+}
+
+// A Netpbm image as it is stored: the samples of every pixel, row by row, from 0 to `maxval`.  In
+// a bitmap, the maxval is 1, which is black.
+#[deriving(Clone, PartialEq, Show)]
+pub struct Pnm {
+    pub kind: PnmKind,
+    pub binary: bool,
+    pub width: uint,
+    pub height: uint,
+    pub maxval: u16,
+    pub samples: Vec<u16>
+}
+
+#[deriving(PartialEq, Show)]
+pub enum PnmError {
+    NotPnm, // The data doesn't start with a magic number from P1 to P6
+    MalformedHeader(String),
+    MalformedRaster(String),
+    Truncated, // The data stops before the last sample
+    IoError(io::IoError)
+}
+
+impl Pnm {
+    pub fn open(path: &Path) -> Result<Pnm, PnmError> {
+        match File::open(path).read_to_end() {
+            Ok(data) => Pnm::parse(data.as_slice()),
+            Err(e) => Err(IoError(e))
+        }
+    }
+
+    pub fn parse(data: &[u8]) -> Result<Pnm, PnmError> {
+        if data.len() < 2 || data[0] != b'P' {
+            return Err(NotPnm);
+        }
+        let (kind, binary) = match data[1] {
+            b'1' => (Pbm, false),
+            b'2' => (Pgm, false),
+            b'3' => (Ppm, false),
+            b'4' => (Pbm, true),
+            b'5' => (Pgm, true),
+            b'6' => (Ppm, true),
+            _ => return Err(NotPnm)
+        };
+        let mut parser = PnmParser { data: data, position: 2 };
+        let width = try!(parser.header_number("width"));
+        let height = try!(parser.header_number("height"));
+        let maxval = if kind == Pbm { 1 } else { try!(parser.header_number("maxval")) };
+        if maxval == 0 || maxval > 65535 {
+            return Err(MalformedHeader(format!("the maxval must be between 1 and 65535, not {}",
+                                               maxval)));
+        }
+        if width == 0 || height == 0 {
+            return Err(MalformedHeader(format!("{}x{} has no pixels", width, height)));
+        }
+        let len = match width.checked_mul(&height).and_then(|n| n.checked_mul(&kind.channels())) {
+            Some(len) => len,
+            None => return Err(MalformedHeader(format!("{}x{} is too large", width, height)))
+        };
+
+        let samples = if binary {
+            // A single whitespace character separates the header from the raster
+            match data.get(parser.position) {
+                Some(&c) if is_space(c) => parser.position += 1,
+                Some(_) => return Err(MalformedHeader("expected a whitespace character before \
+                                                       the raster".to_string())),
+                None => return Err(Truncated)
+            }
+            try!(parse_binary_raster(data.slice_from(parser.position), kind.clone(), width,
+                                     len, maxval as u16))
+        } else {
+            let mut samples = Vec::new();
+            for i in range(0, len) {
+                let sample = try!(if kind == Pbm { parser.bit() } else { parser.sample() });
+                if sample > maxval {
+                    return Err(MalformedRaster(format!("sample {} is {}, more than the maxval",
+                                                       i, sample)));
+                }
+                samples.push(sample as u16);
+            }
+            samples
+        };
+        Ok(Pnm { kind: kind, binary: binary, width: width, height: height,
+                 maxval: maxval as u16, samples: samples })
+    }
+
+    pub fn save(&self, path: &Path) -> IoResult<()> {
+        try!(self.check_pixels());
+        let mut writer = BufferedWriter::new(try!(File::create(path)));
+        self.write(&mut writer)
+    }
+
+    pub fn write<W: Writer>(&self, writer: &mut W) -> IoResult<()> {
+        assert_eq!(self.samples.len(), self.width * self.height * self.kind.channels());
+        try!(self.check_pixels());
+        let magic = match self.kind { Pbm => 1u, Pgm => 2, Ppm => 3 };
+        let magic = if self.binary { magic + 3 } else { magic };
+        try!(write!(writer, "P{}\n{} {}", magic, self.width, self.height));
+        if self.kind != Pbm {
+            try!(write!(writer, " {}", self.maxval));
+        }
+        try!(writer.write_u8(b'\n'));
+
+        let row_len = self.width * self.kind.channels();
+        match (self.kind.clone(), self.binary) {
+            (Pbm, true) => for row in self.samples.chunks(row_len) {
+                // 8 pixels per byte, the first one in the most significant bit
+                for pixels in row.chunks(8) {
+                    let byte = pixels.iter().enumerate()
+                                     .fold(0u8, |byte, (i, &p)| byte | (p as u8 & 1) << (7 - i));
+                    try!(writer.write_u8(byte));
+                }
+            },
+            (_, true) => for &sample in self.samples.iter() {
+                if self.maxval > 255 {
+                    try!(writer.write_be_u16(sample));
+                } else {
+                    try!(writer.write_u8(sample as u8));
+                }
+            },
+            (_, false) => for row in self.samples.chunks(row_len) {
+                // A line per row, but no longer than 70 characters
+                let mut line_len = 0;
+                for &sample in row.iter() {
+                    let sample = sample.to_string();
+                    if line_len > 0 && line_len + 1 + sample.len() > 70 {
+                        try!(writer.write_u8(b'\n'));
+                        line_len = 0;
+                    } else if line_len > 0 {
+                        try!(writer.write_u8(b' '));
+                        line_len += 1;
+                    }
+                    try!(writer.write_str(sample.as_slice()));
+                    line_len += sample.len();
+                }
+                try!(writer.write_u8(b'\n'));
+            }
+        }
+        Ok(())
+    }
+
+    // An image without pixels isn't written, since it couldn't be parsed back
+    fn check_pixels(&self) -> IoResult<()> {
+        if self.width > 0 && self.height > 0 {
+            return Ok(());
+        }
+        Err(io::IoError {
+            kind: io::InvalidInput,
+            desc: "an image without pixels can't be written",
+            detail: Some(format!("{}x{} has no pixels", self.width, self.height))
+        })
+    }
+
+    // Scales a sample to 8 bits
+    fn scaled(&self, sample: u16) -> u8 {
+        let maxval = self.maxval as u32;
+        ((sample as u32 * 255 + maxval / 2) / maxval) as u8
+    }
+
+    // Converts to a color image, with 8-bit channels
+    pub fn to_image(&self) -> Image {
+        let data = match self.kind {
+            Pbm => self.samples.iter().map(|&s| {
+                let v = if s == 1 { 0 } else { 255 };
+                Color { red: v, green: v, blue: v }
+            }).collect(),
+            Pgm => self.samples.iter().map(|&s| {
+                let v = self.scaled(s);
+                Color { red: v, green: v, blue: v }
+            }).collect(),
+            Ppm => self.samples.chunks(3).map(|c| {
+                Color { red: self.scaled(c[0]), green: self.scaled(c[1]), blue: self.scaled(c[2]) }
+            }).collect()
+        };
+        Image { width: self.width, height: self.height, data: data }
+    }
+
+    // Converts to a grayscale image, with the luminance of the colors of a pixmap
+    pub fn to_gray(&self) -> ImageGray8 {
+        match self.kind {
+            Pgm => ImageGray8 {
+                width: self.width,
+                height: self.height,
+                data: self.samples.iter().map(|&s| self.scaled(s)).collect()
+            },
+            _ => self.to_image().to_gray()
+        }
+    }
+}
+
+// Whitespace, as defined by the Netpbm formats
+fn is_space(c: u8) -> bool {
+    c == b' ' || c == b'\t' || c == b'\n' || c == b'\r' || c == 0x0b || c == 0x0c
+}
+
+fn is_digit(c: u8) -> bool {
+    c >= b'0' && c <= b'9'
+}
+
+// Reads the numbers of the header, and of the raster of the ASCII formats
+struct PnmParser<'a> {
+    data: &'a [u8],
+    position: uint
+}
+
+impl<'a> PnmParser<'a> {
+    // Skips whitespace and comments, which go from `#` to the end of the line
+    fn skip_space(&mut self) {
+        let mut comment = false;
+        while self.position < self.data.len() {
+            match self.data[self.position] {
+                b'#' => comment = true,
+                b'\n' | b'\r' => comment = false,
+                c if !comment && !is_space(c) => return,
+                _ => ()
+            }
+            self.position += 1;
+        }
+    }
+
+    // Reads a decimal number, or returns None if there isn't one
+    fn number(&mut self) -> Option<uint> {
+        self.skip_space();
+        let mut number = None;
+        while self.position < self.data.len() && is_digit(self.data[self.position]) {
+            let digit = (self.data[self.position] - b'0') as uint;
+            // Saturates, the numbers that large are invalid anyway
+            let n = number.unwrap_or(0u).checked_mul(&10).and_then(|n| n.checked_add(&digit));
+            number = Some(n.unwrap_or(::std::uint::MAX));
+            self.position += 1;
+        }
+        number
+    }
+
+    fn header_number(&mut self, what: &str) -> Result<uint, PnmError> {
+        let number = self.number();
+        match (number, self.data.get(self.position)) {
+            (Some(number), None) => Ok(number),
+            (Some(number), Some(&c)) if is_space(c) || c == b'#' => Ok(number),
+            (None, None) => Err(Truncated),
+            _ => Err(MalformedHeader(format!("expected the {} at byte {}", what, self.position)))
+        }
+    }
+
+    // A sample of an ASCII graymap or pixmap
+    fn sample(&mut self) -> Result<uint, PnmError> {
+        let number = self.number();
+        match (number, self.data.get(self.position)) {
+            (Some(number), None) => Ok(number),
+            (Some(number), Some(&c)) if is_space(c) || c == b'#' => Ok(number),
+            (None, None) => Err(Truncated),
+            _ => Err(MalformedRaster(format!("expected a sample at byte {}", self.position)))
+        }
+    }
+
+    // A pixel of an ASCII bitmap, where the pixels don't need to be separated
+    fn bit(&mut self) -> Result<uint, PnmError> {
+        self.skip_space();
+        match self.data.get(self.position) {
+            Some(&c) if c == b'0' || c == b'1' => {
+                self.position += 1;
+                Ok((c - b'0') as uint)
+            }
+            Some(_) => Err(MalformedRaster(format!("expected 0 or 1 at byte {}", self.position))),
+            None => Err(Truncated)
+        }
+    }
+}
+
+// Reads `len` samples of a binary raster
+fn parse_binary_raster(raster: &[u8], kind: PnmKind, width: uint, len: uint, maxval: u16)
+                       -> Result<Vec<u16>, PnmError> {
+    if kind == Pbm {
+        // Every row starts with a new byte
+        let row_bytes = (width + 7) / 8;
+        let height = if width == 0 { 0 } else { len / width };
+        if raster.len() < row_bytes * height {
+            return Err(Truncated);
+        }
+        let mut samples = Vec::with_capacity(len);
+        for row in raster.chunks(row_bytes).take(height) {
+            for x in range(0, width) {
+                samples.push((row[x / 8] >> (7 - x % 8)) as u16 & 1);
+            }
+        }
+        return Ok(samples);
+    }
+
+    let sample_bytes = if maxval > 255 { 2 } else { 1 };
+    if raster.len() / sample_bytes < len {
+        return Err(Truncated);
+    }
+    let mut samples = Vec::with_capacity(len);
+    for (i, bytes) in raster.chunks(sample_bytes).take(len).enumerate() {
+        let sample = bytes.iter().fold(0u16, |sample, &b| sample << 8 | b as u16);
+        if sample > maxval {
+            return Err(MalformedRaster(format!("sample {} is {}, more than the maxval", i,
+                                               sample)));
+        }
+        samples.push(sample);
+    }
+    Ok(samples)
+}
+
 #[cfg(not(test))]
 #[allow(dead_code)]
 pub fn main() {
@@ -67,22 +577,16 @@ pub fn main() {
         }
     }
 
-    for y in range(0u, 10) {
-        for x in range(0u, 10) {
-            if image[(x,y)].red + image[(x,y)].green + image[(x,y)].blue == 0 {
-                print!("#");
-            } else {
-                print!(".");
-            }
-        }
-        println!("");
-    }
+    print!("{}", image.to_ascii());
 }
 
 #[cfg(test)]
 mod test {
-    use super::{Color, Image};
+    use super::{Color, Edges, Image, ImageGray8, Pnm, Pbm, Pgm, Ppm};
+    use super::{Average, Bt601, Weights, Extend, Mirror, Wrap};
+    use super::{NotPnm, MalformedHeader, MalformedRaster, Truncated};
     use std::default::Default;
+    use std::io::MemWriter;
 
     #[test]
     #[should_fail]
@@ -93,6 +597,27 @@ mod test {
         assert!(false);
     }
 
+    #[test]
+    #[should_fail]
+    fn out_of_row() {
+        let mut image = Image::new(10, 10);
+        image[(10, 0)] = Color { red: 1, green: 1, blue: 1 };
+    }
+
+    #[test]
+    fn clipping() {
+        let mut image = Image::new(3, 2);
+        let white = Color { red: 255, green: 255, blue: 255 };
+        for &(x, y) in [(-1i, 0i), (0, -1), (3, 0), (0, 2), (2, 1)].iter() {
+            image.set(x, y, white);
+        }
+        assert_eq!(image.get(2, 1), Some(white));
+        assert_eq!(image.get(3, 0), None);
+        assert_eq!(image.get(-1, 1), None);
+        assert_eq!(image.to_ascii().as_slice(), "###\n##.\n");
+        assert_eq!(Image::new(0, 2).to_ascii().as_slice(), "");
+    }
+
     #[test]
     fn getting() {
         let image = Image::new(3, 4);
@@ -121,4 +646,166 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn gray_conversions() {
+        let mut image = Image::new(2, 1);
+        image[(0, 0)] = Color { red: 255, green: 0, blue: 0 };
+        image[(1, 0)] = Color { red: 10, green: 200, blue: 30 };
+        let gray = image.to_gray();
+        assert_eq!(gray.data, vec![54, 147]);
+
+        let color = gray.to_color();
+        assert_eq!(color[(1, 0)], Color { red: 147, green: 147, blue: 147 });
+        assert_eq!(color.to_gray().data, gray.data);
+    }
+
+    #[test]
+    fn luma() {
+        let mut image = Image::new(2, 1);
+        image[(0, 0)] = Color { red: 10, green: 200, blue: 30 };
+        image[(1, 0)] = Color { red: 255, green: 255, blue: 255 };
+        assert_eq!(image.to_gray_with(Bt601).data, vec![124, 255]);
+        assert_eq!(image.to_gray_with(Average).data, vec![80, 255]);
+        assert_eq!(image.to_gray_with(Weights(0.0, 0.0, 1.0)).data, vec![30, 255]);
+        assert_eq!(image.to_gray_with(Weights(2.0, 2.0, 2.0)).data, vec![255, 255]);
+    }
+
+    #[test]
+    fn edges() {
+        let indices = |edges: Edges, len: uint| -> Vec<uint> {
+            range(-4i, 7).map(|i| edges.index(i, len)).collect()
+        };
+        assert_eq!(indices(Extend, 3), vec![0u, 0, 0, 0, 0, 1, 2, 2, 2, 2, 2]);
+        assert_eq!(indices(Wrap, 3), vec![2u, 0, 1, 2, 0, 1, 2, 0, 1, 2, 0]);
+        assert_eq!(indices(Mirror, 3), vec![0u, 1, 2, 1, 0, 1, 2, 1, 0, 1, 2]);
+        assert_eq!(indices(Mirror, 1), vec![0u, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+
+        let mut image = Image::new(2, 1);
+        image[(1, 0)] = Color { red: 1, green: 2, blue: 3 };
+        assert_eq!(image.get_extended(5, -3, Extend), image[(1, 0)]);
+        assert_eq!(image.get_extended(-2, 0, Wrap), image[(0, 0)]);
+    }
+
+    #[test]
+    fn gray_clipping() {
+        let mut image = ImageGray8::new(2, 2);
+        image.set(1, 0, 7);
+        image.set(2, 0, 9);
+        assert_eq!(image.get(1, 0), Some(7));
+        assert_eq!(image.get(0, 2), None);
+        assert_eq!(image.data, vec![0, 7, 0, 0]);
+    }
+
+    #[test]
+    fn parsing_ascii() {
+        let pbm = Pnm::parse(b"P1\n# a comment\n3 2\n010\n1 1 0\n").unwrap();
+        assert_eq!((pbm.kind, pbm.binary, pbm.width, pbm.height, pbm.maxval),
+                   (Pbm, false, 3, 2, 1));
+        assert_eq!(pbm.samples, vec![0, 1, 0, 1, 1, 0]);
+        assert_eq!(pbm.to_image().to_ascii().as_slice(), ".#.\n##.\n");
+
+        let pgm = Pnm::parse(b"P2 2 1 #comment\n 15\n0 15").unwrap();
+        assert_eq!((pgm.kind, pgm.maxval), (Pgm, 15));
+        assert_eq!(pgm.to_gray().data, vec![0, 255]);
+
+        let ppm = Pnm::parse(b"P3\n1 1\n1000\n1000 500 0\n").unwrap();
+        assert_eq!(ppm.samples, vec![1000, 500, 0]);
+        assert_eq!(ppm.to_image()[(0, 0)], Color { red: 255, green: 128, blue: 0 });
+    }
+
+    #[test]
+    fn parsing_binary() {
+        let pbm = Pnm::parse(b"P4\n10 2\n\xc0\x40\x01\x80").unwrap();
+        assert_eq!((pbm.kind, pbm.binary), (Pbm, true));
+        assert_eq!(pbm.samples, vec![1, 1, 0, 0, 0, 0, 0, 0, 0, 1,
+                                     0, 0, 0, 0, 0, 0, 0, 1, 1, 0]);
+
+        let pgm = Pnm::parse(b"P5 3 1 255\n\x00\x80\xff").unwrap();
+        assert_eq!(pgm.to_gray().data, vec![0, 128, 255]);
+
+        let ppm = Pnm::parse(b"P6\n# 16 bits\n1 1 65535\n\xff\xff\x80\x00\x00\x00").unwrap();
+        assert_eq!(ppm.samples, vec![65535, 32768, 0]);
+        assert_eq!(ppm.to_image()[(0, 0)], Color { red: 255, green: 128, blue: 0 });
+
+        let pentagon = Pnm::parse(include_bytes!("resources/Pentagon.pgm")).unwrap();
+        assert_eq!((pentagon.kind, pentagon.width, pentagon.height), (Pgm, 320, 240));
+    }
+
+    #[test]
+    fn writing() {
+        let samples = vec![0, 1, 0, 1, 1, 1, 0, 0, 1];
+        let pbm = Pnm { kind: Pbm, binary: true, width: 9, height: 1, maxval: 1, samples: samples };
+        let mut writer = MemWriter::new();
+        pbm.write(&mut writer).unwrap();
+        assert_eq!(writer.get_ref(), b"P4\n9 1\n\x5c\x80");
+
+        let mut gray = ImageGray8::new(2, 1);
+        gray.data = vec![3, 250];
+        let mut writer = MemWriter::new();
+        gray.to_pnm(false).write(&mut writer).unwrap();
+        assert_eq!(writer.get_ref(), b"P2\n2 1 255\n3 250\n");
+
+        // Long rows are split in lines of at most 70 characters
+        let wide = ImageGray8::new(40, 1).to_pnm(false);
+        let mut writer = MemWriter::new();
+        wide.write(&mut writer).unwrap();
+        let lines: Vec<uint> = writer.get_ref().split(|&c| c == b'\n').map(|l| l.len()).collect();
+        assert_eq!(lines, vec![2, 8, 69, 9, 0]);
+
+        // The parser would reject an image without pixels
+        let mut writer = MemWriter::new();
+        assert!(Image::new(0, 2).to_pnm(true).write(&mut writer).is_err());
+        assert!(writer.get_ref().is_empty());
+    }
+
+    #[test]
+    fn round_trips() {
+        let samples = vec![0, 300, 65535, 1, 2, 3];
+        for &kind in [Pbm, Pgm, Ppm].iter() {
+            for &binary in [false, true].iter() {
+                let (width, maxval) = if kind == Ppm { (1, 65535) } else { (3, 1) };
+                let samples = samples.iter().map(|&s| if kind == Ppm { s } else { s & 1 })
+                                     .collect();
+                let pnm = Pnm { kind: kind, binary: binary, width: width, height: 2,
+                                maxval: maxval, samples: samples };
+                let mut writer = MemWriter::new();
+                pnm.write(&mut writer).unwrap();
+                assert_eq!(Pnm::parse(writer.get_ref()).unwrap(), pnm);
+            }
+        }
+    }
+
+    #[test]
+    fn parsing_errors() {
+        assert_eq!(Pnm::parse(b"GIF89a"), Err(NotPnm));
+        assert_eq!(Pnm::parse(b"P7\n"), Err(NotPnm));
+        assert_eq!(Pnm::parse(b"P2\n2"), Err(Truncated));
+        assert_eq!(Pnm::parse(b"P2\n2 x 255\n"),
+                   Err(MalformedHeader("expected the height at byte 5".to_string())));
+        assert_eq!(Pnm::parse(b"P2\n2 2 0\n"),
+                   Err(MalformedHeader("the maxval must be between 1 and 65535, not 0"
+                                       .to_string())));
+        assert_eq!(Pnm::parse(b"P5 1 1 70000\n\x00\x00"),
+                   Err(MalformedHeader("the maxval must be between 1 and 65535, not 70000"
+                                       .to_string())));
+        assert_eq!(Pnm::parse(b"P4\n0 5\n"), Err(MalformedHeader("0x5 has no pixels".to_string())));
+        assert_eq!(Pnm::parse(b"P2 3 0 255\n"),
+                   Err(MalformedHeader("3x0 has no pixels".to_string())));
+        assert!(match Pnm::parse(b"P5 99999999999 99999999999 255\n") {
+            Err(MalformedHeader(_)) => true,
+            _ => false
+        });
+        assert_eq!(Pnm::parse(b"P5 1 1 255#\n\x00"),
+                   Err(MalformedHeader("expected a whitespace character before the raster"
+                                       .to_string())));
+        assert_eq!(Pnm::parse(b"P5 2 2 255\n\x00\x00\x00"), Err(Truncated));
+        assert_eq!(Pnm::parse(b"P5 1 1 7\n\x08"),
+                   Err(MalformedRaster("sample 0 is 8, more than the maxval".to_string())));
+        assert_eq!(Pnm::parse(b"P1 2 1\n02"),
+                   Err(MalformedRaster("expected 0 or 1 at byte 8".to_string())));
+        assert_eq!(Pnm::parse(b"P3 1 1 255\n1 2 x"),
+                   Err(MalformedRaster("expected a sample at byte 15".to_string())));
+        assert_eq!(Pnm::parse(b"P3 1 1 255\n1 2"), Err(Truncated));
+    }
 }
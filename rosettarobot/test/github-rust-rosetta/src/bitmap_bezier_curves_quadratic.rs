@@ -0,0 +1,95 @@
+// Implements http://rosettacode.org/wiki/Bitmap/B%C3%A9zier_curves/Quadratic
+//
+// The curve is approximated by straight lines, drawn with Bresenham's line algorithm.  There is a
+// line for every 4 pixels of the control polygon, so that long curves stay smooth.
+use std::cmp;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
+use bitmap::{Color, Image};
+use bitmap_bresenhams_line_algorithm::DrawLine;
+mod bitmap;
+// With the path of the file, so that its own `mod bitmap` is found next to it
+#[allow(dead_code)]
+#[path = "bitmap_bresenhams_line_algorithm.rs"]
+mod bitmap_bresenhams_line_algorithm;
+
+pub trait DrawQuadraticBezier {
+    // Draws the curve from `p0` to `p2`, with the control point `p1`
+    fn draw_quadratic_bezier(&mut self, p0: (int, int), p1: (int, int), p2: (int, int),
+                             color: Color);
+}
+
+fn distance((x0, y0): (int, int), (x1, y1): (int, int)) -> f64 {
+    (((x1 - x0) * (x1 - x0) + (y1 - y0) * (y1 - y0)) as f64).sqrt()
+}
+
+impl DrawQuadraticBezier for Image {
+    fn draw_quadratic_bezier(&mut self, p0: (int, int), p1: (int, int), p2: (int, int),
+                             color: Color) {
+        let length = distance(p0, p1) + distance(p1, p2);
+        let segments = cmp::max(1, (length / 4.0).ceil() as uint);
+        let ((x0, y0), (x1, y1), (x2, y2)) = (p0, p1, p2);
+
+        let mut previous = p0;
+        for i in range(1, segments + 1) {
+            let t = i as f64 / segments as f64;
+            let (a, b, c) = ((1.0 - t) * (1.0 - t), 2.0 * (1.0 - t) * t, t * t);
+            let coordinate = |c0: int, c1: int, c2: int| {
+                (a * c0 as f64 + b * c1 as f64 + c * c2 as f64 + 0.5).floor() as int
+            };
+            let point = (coordinate(x0, x1, x2), coordinate(y0, y1, y2));
+            self.draw_line(previous, point, color);
+            previous = point;
+        }
+    }
+}
+
+#[cfg(not(test))]
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes a curve drawn on an image to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
+    let mut image = Image::new(32, 16);
+    image.fill(Color { red: 255, green: 255, blue: 255 });
+    let black = Color { red: 0, green: 0, blue: 0 };
+    image.draw_quadratic_bezier((1, 14), (12, -12), (30, 10), black);
+    write!(out, "{}", image.to_ascii())
+}
+
+#[cfg(test)]
+mod test {
+    use bitmap::{Color, Image};
+    use super::DrawQuadraticBezier;
+
+    #[test]
+    fn curves() {
+        let white = Color { red: 255, green: 255, blue: 255 };
+        let mut image = Image::new(9, 9);
+        image.draw_quadratic_bezier((0, 0), (4, 8), (8, 0), white);
+        // The ends, and the middle of the curve
+        for &(x, y) in [(0, 0), (8, 0), (4, 4)].iter() {
+            assert_eq!(image.get(x, y), Some(white));
+        }
+        // The curve is connected, from one side to the other
+        for x in range(0i, 9) {
+            assert!(range(0i, 9).any(|y| image.get(x, y) == Some(white)));
+        }
+
+        // Aligned control points give a straight line
+        let mut image = Image::new(10, 2);
+        image.draw_quadratic_bezier((0, 1), (5, 1), (9, 1), white);
+        assert_eq!(image.to_ascii().as_slice(), "##########\n..........\n");
+    }
+
+    #[test]
+    fn clipping() {
+        // Only the part of the curve inside the image is drawn
+        let mut image = Image::new(4, 4);
+        let white = Color { red: 255, green: 255, blue: 255 };
+        image.draw_quadratic_bezier((-8, 3), (2, -3), (12, 3), white);
+        assert_eq!(image.to_ascii().as_slice(), "....\n####\n####\n####\n");
+    }
+}
@@ -0,0 +1,116 @@
+// Implements http://rosettacode.org/wiki/Bitmap/Bresenham's_line_algorithm
+//
+// The line can start and end outside of the image, only the pixels inside it are drawn.  The
+// steps of the algorithm outside of the image are skipped, so a long line is as quick to draw
+// as its part inside the image.
+use std::cmp;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
+use std::iter::range_inclusive;
+use bitmap::{clip_line, Color, Image};
+mod bitmap;
+
+pub trait DrawLine {
+    // Draws the line from `from` to `to`, both ends included
+    fn draw_line(&mut self, from: (int, int), to: (int, int), color: Color);
+}
+
+impl DrawLine for Image {
+    fn draw_line(&mut self, (x0, y0): (int, int), (x1, y1): (int, int), color: Color) {
+        let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+        let (step_x, step_y) = (if x0 < x1 { 1 } else { -1 }, if y0 < y1 { 1 } else { -1 });
+        // Every step moves along the longer axis, and along the other one when the line is more
+        // than half a pixel away
+        let (long, short) = (cmp::max(dx, -dy), cmp::min(dx, -dy));
+
+        // Only the steps around the part of the line inside the image are walked: the pixels of
+        // the line are less than a pixel away from it
+        let clipped = clip_line((x0 as f64, y0 as f64), (x1 as f64, y1 as f64), (-1.0, -1.0),
+                                (self.width as f64, self.height as f64));
+        let (enter, leave) = match clipped {
+            Some(ends) => ends,
+            None => return
+        };
+        let steps_to = |(x, y): (f64, f64)| {
+            if dx >= -dy { (x - x0 as f64).abs() } else { (y - y0 as f64).abs() }
+        };
+        let first = cmp::max(steps_to(enter).floor() as int - 1, 0);
+        let last = cmp::min(steps_to(leave).ceil() as int + 1, long);
+
+        // The pixel after the first steps, and its error
+        let moved = if long == 0 { 0 } else { (2 * first * short + long) / (2 * long) };
+        let (moved_x, moved_y) = if dx >= -dy { (first, moved) } else { (moved, first) };
+        let (mut x, mut y) = (x0 + moved_x * step_x, y0 + moved_y * step_y);
+        // The error of the next pixel, for both directions at once
+        let mut error = dx + dy + moved_x * dy + moved_y * dx;
+        for _ in range_inclusive(first, last) {
+            self.set(x, y, color);
+            let error2 = 2 * error;
+            if error2 >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if error2 <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+}
+
+#[cfg(not(test))]
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes a triangle drawn on an image to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
+    let mut image = Image::new(16, 12);
+    image.fill(Color { red: 255, green: 255, blue: 255 });
+    let black = Color { red: 0, green: 0, blue: 0 };
+    image.draw_line((1, 1), (14, 5), black);
+    image.draw_line((14, 5), (3, 10), black);
+    image.draw_line((3, 10), (1, 1), black);
+    write!(out, "{}", image.to_ascii())
+}
+
+#[cfg(test)]
+mod test {
+    use bitmap::{Color, Image};
+    use super::DrawLine;
+
+    fn drawn(from: (int, int), to: (int, int)) -> Vec<(int, int)> {
+        let mut image = Image::new(6, 4);
+        image.draw_line(from, to, Color { red: 1, green: 1, blue: 1 });
+        let mut pixels = Vec::new();
+        for y in range(0i, 4) {
+            for x in range(0i, 6) {
+                if image.get(x, y).unwrap().red == 1 {
+                    pixels.push((x, y));
+                }
+            }
+        }
+        pixels
+    }
+
+    #[test]
+    fn octants() {
+        assert_eq!(drawn((0, 0), (5, 2)), vec![(0, 0), (1, 0), (2, 1), (3, 1), (4, 2), (5, 2)]);
+        // The same pixels in the other direction
+        assert_eq!(drawn((5, 2), (0, 0)), drawn((0, 0), (5, 2)));
+        assert_eq!(drawn((1, 3), (1, 0)), vec![(1, 0), (1, 1), (1, 2), (1, 3)]);
+        assert_eq!(drawn((0, 3), (3, 0)), vec![(3, 0), (2, 1), (1, 2), (0, 3)]);
+        assert_eq!(drawn((2, 2), (2, 2)), vec![(2, 2)]);
+    }
+
+    #[test]
+    fn clipping() {
+        assert_eq!(drawn((-2, 1), (8, 1)), vec![(0, 1), (1, 1), (2, 1), (3, 1), (4, 1), (5, 1)]);
+        assert_eq!(drawn((-10, -10), (-1, 20)), vec![]);
+        // Only the steps near the image are walked
+        assert_eq!(drawn((-1000000000, -1000000000), (1000000000, 1000000000)),
+                   vec![(0, 0), (1, 1), (2, 2), (3, 3)]);
+        assert_eq!(drawn((-1000000000, 2), (1000000000, 1)), drawn((-2, 1), (8, 1)));
+    }
+}
@@ -0,0 +1,134 @@
+// Implements http://rosettacode.org/wiki/Bitmap/Flood_fill
+//
+// The area is filled a row at a time, with an explicit stack rather than recursion, so that large
+// areas don't overflow the stack.
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
+use bitmap::{Color, Image};
+mod bitmap;
+
+pub trait FloodFill {
+    // Replaces the color of (x, y), and of all the pixels of the same color connected to it
+    // horizontally or vertically.  Does nothing if (x, y) is outside of the image.
+    fn flood_fill(&mut self, x: int, y: int, color: Color);
+}
+
+impl FloodFill for Image {
+    fn flood_fill(&mut self, x: int, y: int, color: Color) {
+        let target = match self.get(x, y) {
+            Some(target) if target != color => target,
+            _ => return
+        };
+
+        let mut stack = vec![(x, y)];
+        loop {
+            let (x, y) = match stack.pop() {
+                Some(point) => point,
+                None => break
+            };
+            if self.get(x, y) != Some(target) {
+                continue;
+            }
+            // The whole run of the row, then the runs above and below it
+            let (mut left, mut right) = (x, x);
+            while self.get(left - 1, y) == Some(target) {
+                left -= 1;
+            }
+            while self.get(right + 1, y) == Some(target) {
+                right += 1;
+            }
+            for x in range(left, right + 1) {
+                self.set(x, y, color);
+            }
+            for &row in [y - 1, y + 1].iter() {
+                for x in range(left, right + 1) {
+                    // Only one pixel of every run needs to be pushed
+                    if self.get(x, row) == Some(target) &&
+                       (x == left || self.get(x - 1, row) != Some(target)) {
+                        stack.push((x, row));
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(test))]
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes a ring before and after filling it to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
+    use std::default::Default;
+
+    // Fill the inside of a ring
+    let mut image = Image::new(12, 9);
+    let white = Color { red: 255, green: 255, blue: 255 };
+    image.fill(white);
+    for y in range(1i, 8) {
+        for x in range(1i, 11) {
+            let (dx, dy) = (x - 6, y - 4);
+            if dx * dx + 2 * dy * dy >= 14 && dx * dx + 2 * dy * dy <= 26 {
+                image.set(x, y, Default::default());
+            }
+        }
+    }
+    try!(write!(out, "{}", image.to_ascii()));
+    try!(writeln!(out, ""));
+    image.flood_fill(6, 4, Default::default());
+    write!(out, "{}", image.to_ascii())
+}
+
+#[cfg(test)]
+mod test {
+    use bitmap::{Color, Image};
+    use std::default::Default;
+    use super::FloodFill;
+
+    fn image_from_ascii(ascii: &str) -> Image {
+        let rows: Vec<&str> = ascii.lines().collect();
+        let mut image = Image::new(rows[0].len(), rows.len());
+        image.fill(Color { red: 255, green: 255, blue: 255 });
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.chars().enumerate() {
+                if c == '#' {
+                    image.set(x as int, y as int, Default::default());
+                }
+            }
+        }
+        image
+    }
+
+    #[test]
+    fn fill_area() {
+        let mut image = image_from_ascii("..#...\n\
+                                          .##.#.\n\
+                                          ...#..\n\
+                                          ##.#.#\n\
+                                          ...#..");
+        image.flood_fill(0, 0, Default::default());
+        assert_eq!(image.to_ascii().as_slice(), "###...\n\
+                                                 ###.#.\n\
+                                                 ####..\n\
+                                                 ####.#\n\
+                                                 ####..\n");
+
+        // Filling with the same color changes nothing
+        image.flood_fill(0, 0, Default::default());
+        image.flood_fill(-1, 0, Color { red: 1, green: 1, blue: 1 });
+        assert_eq!(image.to_ascii().as_slice(), "###...\n\
+                                                 ###.#.\n\
+                                                 ####..\n\
+                                                 ####.#\n\
+                                                 ####..\n");
+    }
+
+    #[test]
+    fn large_area() {
+        let mut image = Image::new(500, 500);
+        image.flood_fill(250, 250, Color { red: 1, green: 1, blue: 1 });
+        assert!(image.data.iter().all(|c| c.red == 1));
+    }
+}
@@ -0,0 +1,172 @@
+// Implements http://rosettacode.org/wiki/Bitmap/Histogram
+//
+// The image is converted to black and white at a threshold computed from the histogram of its
+// luminance: the median of the task, the mean, or the threshold of Otsu's method, which best
+// separates the dark pixels from the light ones.
+use bitmap::{Color, Image, ImageGray8, Luma};
+mod bitmap;
+
+// The number of pixels of each luminance
+#[deriving(Clone, PartialEq, Show)]
+pub struct Histogram {
+    pub counts: Vec<uint>
+}
+
+impl Histogram {
+    pub fn new(image: &ImageGray8) -> Histogram {
+        let mut counts = Vec::from_elem(256, 0u);
+        for &value in image.data.iter() {
+            counts[value as uint] += 1;
+        }
+        Histogram { counts: counts }
+    }
+
+    pub fn total(&self) -> uint {
+        self.counts.iter().fold(0, |total, &count| total + count)
+    }
+
+    // The mean luminance, rounded, or 0 without pixels
+    pub fn mean(&self) -> u8 {
+        let total = self.total();
+        if total == 0 {
+            return 0;
+        }
+        let sum = self.counts.iter().enumerate()
+                             .fold(0, |sum, (value, &count)| sum + value * count);
+        (sum as f64 / total as f64).round() as u8
+    }
+
+    // The smallest luminance of at least half of the pixels, or 0 without pixels
+    pub fn median(&self) -> u8 {
+        let total = self.total();
+        let mut below = 0;
+        for (value, &count) in self.counts.iter().enumerate() {
+            below += count;
+            if 2 * below >= total {
+                return value as u8;
+            }
+        }
+        0
+    }
+
+    // The threshold which maximizes the variance between the pixels below it and the others.  It
+    // is 0 if the pixels all have the same luminance.
+    pub fn otsu(&self) -> u8 {
+        let total = self.total();
+        let sum = self.counts.iter().enumerate()
+                             .fold(0.0, |sum, (value, &count)| sum + (value * count) as f64);
+        let (mut best, mut best_variance) = (0, -1.0);
+        let (mut count_below, mut sum_below) = (0, 0.0);
+        for threshold in range(1u, 256) {
+            count_below += self.counts[threshold - 1];
+            sum_below += ((threshold - 1) * self.counts[threshold - 1]) as f64;
+            let count_above = total - count_below;
+            if count_below == 0 || count_above == 0 {
+                continue;
+            }
+            let mean_below = sum_below / count_below as f64;
+            let mean_above = (sum - sum_below) / count_above as f64;
+            let variance = count_below as f64 * count_above as f64 *
+                           (mean_below - mean_above) * (mean_below - mean_above);
+            if variance > best_variance {
+                best = threshold;
+                best_variance = variance;
+            }
+        }
+        best as u8
+    }
+}
+
+// How to choose the threshold between black and white
+#[deriving(Clone, PartialEq, Show)]
+pub enum Threshold {
+    Median,
+    Mean,
+    Otsu,
+    Fixed(u8)
+}
+
+pub trait BlackAndWhite {
+    // The pixels with a luminance below the threshold become black, and the others white
+    fn to_black_and_white(&self, luma: Luma, threshold: Threshold) -> Image;
+}
+
+impl BlackAndWhite for Image {
+    fn to_black_and_white(&self, luma: Luma, threshold: Threshold) -> Image {
+        let gray = self.to_gray_with(luma);
+        let histogram = Histogram::new(&gray);
+        let threshold = match threshold {
+            Median => histogram.median(),
+            Mean => histogram.mean(),
+            Otsu => histogram.otsu(),
+            Fixed(threshold) => threshold
+        };
+        let (black, white) = (Color { red: 0, green: 0, blue: 0 },
+                              Color { red: 255, green: 255, blue: 255 });
+        Image {
+            width: self.width,
+            height: self.height,
+            data: gray.data.iter().map(|&v| if v < threshold { black } else { white }).collect()
+        }
+    }
+}
+
+#[cfg(not(test))]
+pub fn main() {
+    use bitmap::{Bt709, Pnm};
+
+    let path = Path::new("../src/resources/filters_input.ppm");
+    let image = match Pnm::open(&path) {
+        Ok(pnm) => pnm.to_image(),
+        Err(e) => fail!("failed to read {}: {}", path.display(), e)
+    };
+    let histogram = Histogram::new(&image.to_gray_with(Bt709));
+    println!("Median: {}, mean: {}, Otsu: {}",
+             histogram.median(), histogram.mean(), histogram.otsu());
+    print!("{}", image.to_black_and_white(Bt709, Median).to_ascii());
+}
+
+#[cfg(test)]
+mod test {
+    use bitmap::{Bt601, Bt709, ImageGray8, Pnm};
+    use super::{BlackAndWhite, Fixed, Histogram, Mean, Median, Otsu, Threshold};
+
+    #[test]
+    fn histogram() {
+        let image = ImageGray8 { width: 3, height: 2, data: vec![0, 10, 10, 200, 255, 10] };
+        let histogram = Histogram::new(&image);
+        assert_eq!((histogram.counts[10], histogram.counts[255], histogram.total()), (3, 1, 6));
+        assert_eq!(histogram.median(), 10);
+        assert_eq!(histogram.mean(), 81);
+        assert_eq!(histogram.otsu(), 11);
+
+        let empty = Histogram::new(&ImageGray8::new(0, 0));
+        assert_eq!((empty.median(), empty.mean(), empty.otsu()), (0, 0, 0));
+        let uniform = Histogram::new(&ImageGray8 { width: 2, height: 1, data: vec![7, 7] });
+        assert_eq!((uniform.median(), uniform.mean(), uniform.otsu()), (7, 7, 0));
+    }
+
+    #[test]
+    fn golden_files() {
+        let image = Pnm::parse(include_bytes!("resources/filters_input.ppm")).unwrap().to_image();
+        let gray = Pnm::parse(include_bytes!("resources/filters_gray.pgm")).unwrap();
+        assert_eq!(image.to_gray_with(Bt601).data, gray.to_gray().data);
+
+        let histogram = Histogram::new(&image.to_gray_with(Bt601));
+        assert_eq!((histogram.median(), histogram.mean(), histogram.otsu()), (148, 140, 128));
+        let otsu = Pnm::parse(include_bytes!("resources/filters_otsu.pbm")).unwrap();
+        assert_eq!(image.to_black_and_white(Bt601, Otsu).data, otsu.to_image().data);
+        assert_eq!(image.to_black_and_white(Bt601, Fixed(128)).data, otsu.to_image().data);
+    }
+
+    #[test]
+    fn thresholds() {
+        let image = ImageGray8 { width: 4, height: 1, data: vec![0, 100, 150, 250] }.to_color();
+        let ascii = |threshold: Threshold| image.to_black_and_white(Bt709, threshold).to_ascii();
+        assert_eq!(ascii(Median).as_slice(), "#...\n");
+        assert_eq!(ascii(Mean).as_slice(), "##..\n");
+        assert_eq!(ascii(Otsu).as_slice(), "##..\n");
+        assert_eq!(ascii(Fixed(0)).as_slice(), "....\n");
+        assert_eq!(ascii(Fixed(255)).as_slice(), "####\n");
+    }
+}
@@ -0,0 +1,93 @@
+// Implements http://rosettacode.org/wiki/Bitmap/Midpoint_circle_algorithm
+//
+// The circle can be partly or completely outside of the image, only the pixels inside it are
+// drawn.
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
+use bitmap::{Color, Image};
+mod bitmap;
+
+pub trait DrawCircle {
+    fn draw_circle(&mut self, center: (int, int), radius: uint, color: Color);
+}
+
+impl DrawCircle for Image {
+    fn draw_circle(&mut self, (x0, y0): (int, int), radius: uint, color: Color) {
+        let radius = radius as int;
+        // Going around the first octant from the top, `f` tells whether the midpoint between the
+        // next two candidate pixels is outside of the circle
+        let (mut x, mut y) = (0i, radius);
+        let mut f = 1 - radius;
+        let (mut ddf_x, mut ddf_y) = (1i, -2 * radius);
+
+        self.set(x0, y0 + radius, color);
+        self.set(x0, y0 - radius, color);
+        self.set(x0 + radius, y0, color);
+        self.set(x0 - radius, y0, color);
+        while x < y {
+            if f >= 0 {
+                y -= 1;
+                ddf_y += 2;
+                f += ddf_y;
+            }
+            x += 1;
+            ddf_x += 2;
+            f += ddf_x;
+            for &(dx, dy) in [(x, y), (y, x)].iter() {
+                self.set(x0 + dx, y0 + dy, color);
+                self.set(x0 - dx, y0 + dy, color);
+                self.set(x0 + dx, y0 - dy, color);
+                self.set(x0 - dx, y0 - dy, color);
+            }
+        }
+    }
+}
+
+#[cfg(not(test))]
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes a circle drawn on an image to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
+    let mut image = Image::new(25, 25);
+    image.fill(Color { red: 255, green: 255, blue: 255 });
+    image.draw_circle((12, 12), 10, Color { red: 0, green: 0, blue: 0 });
+    write!(out, "{}", image.to_ascii())
+}
+
+#[cfg(test)]
+mod test {
+    use bitmap::{Color, Image};
+    use std::default::Default;
+    use super::DrawCircle;
+
+    #[test]
+    fn circles() {
+        let white = Color { red: 255, green: 255, blue: 255 };
+        let mut image = Image::new(7, 7);
+        image.fill(white);
+        image.draw_circle((3, 3), 3, Default::default());
+        assert_eq!(image.to_ascii().as_slice(), "..###..\n\
+                                                 .#...#.\n\
+                                                 #.....#\n\
+                                                 #.....#\n\
+                                                 #.....#\n\
+                                                 .#...#.\n\
+                                                 ..###..\n");
+
+        image.fill(white);
+        image.draw_circle((3, 3), 0, Default::default());
+        assert_eq!(image.to_ascii().as_slice(), ".......\n.......\n.......\n...#...\n\
+                                                 .......\n.......\n.......\n");
+    }
+
+    #[test]
+    fn clipping() {
+        let mut image = Image::new(4, 3);
+        image.draw_circle((0, 0), 2, Color { red: 1, green: 1, blue: 1 });
+        image.draw_circle((20, -20), 5, Color { red: 1, green: 1, blue: 1 });
+        assert_eq!(image.to_ascii().as_slice(), "##.#\n##.#\n..##\n");
+    }
+}
@@ -1,13 +1,20 @@
 // http://rosettacode.org/wiki/Bitwise_operations
-fn main() {
+use std::io::{mod, IoResult};
+
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the results of the bitwise operations on two bytes to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let a: u8 = 105;
     let b: u8 = 91;
-    println!("a      = {:0>8t}", a);
-    println!("b      = {:0>8t}", b);
-    println!("a | b  = {:0>8t}", a | b);
-    println!("a & b  = {:0>8t}", a & b);
-    println!("a ^ b  = {:0>8t}", a ^ b);
-    println!("!a     = {:0>8t}", !a);
-    println!("a << 3 = {:0>8t}", a >> 3);
-    println!("a >> 3 = {:0>8t}", a << 3);
+    try!(writeln!(out, "a      = {:0>8t}", a));
+    try!(writeln!(out, "b      = {:0>8t}", b));
+    try!(writeln!(out, "a | b  = {:0>8t}", a | b));
+    try!(writeln!(out, "a & b  = {:0>8t}", a & b));
+    try!(writeln!(out, "a ^ b  = {:0>8t}", a ^ b));
+    try!(writeln!(out, "!a     = {:0>8t}", !a));
+    try!(writeln!(out, "a << 3 = {:0>8t}", a >> 3));
+    writeln!(out, "a >> 3 = {:0>8t}", a << 3)
 }
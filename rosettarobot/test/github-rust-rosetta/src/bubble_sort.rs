@@ -2,7 +2,7 @@
 use std::iter::range_inclusive;
 
 /// Progress through the slice and 'bubble' elements up until they are in order.
-fn bubble_sort<T: PartialOrd>(v: &mut [T]) {
+pub fn bubble_sort<T: PartialOrd>(v: &mut [T]) {
     range_inclusive(1, v.len()).rev().all(|length| {
         let mut changes = 0u;
 
@@ -19,7 +19,7 @@ fn bubble_sort<T: PartialOrd>(v: &mut [T]) {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
     let mut numbers = [4i, 65, 2, -31, 0, 99, 2, 83, 782, 1];
     bubble_sort(numbers);
 }
@@ -4,13 +4,19 @@ extern crate libc;
 
 use libc::c_char;
 use std::c_str::CString;
+use std::io::{mod, IoResult};
 
 extern "C" {
     // C functions are declared in an `extern "C"` block.
     fn strdup(s: *const c_char) -> *const c_char;
 }
 
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes a string duplicated by the C library to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     // Create a Rust static string. No allocations.
     let rust_str = "Hello World!";
     // Call strdup. C functions are considered possibly unsafe to call, so
@@ -37,6 +43,7 @@ fn main() {
     // to `CString`. This does not allocate memory.
     let dup_rust_str = wrap_dup_c_str.as_str().unwrap();
     // Now you can easily print the result
-    println!("{}", dup_rust_str);
+    try!(writeln!(out, "{}", dup_rust_str));
     // The block ends here, and `CString` frees the C string we created.
+    Ok(())
 }
@@ -1,18 +1,24 @@
 // Implements http://rosettacode.org/wiki/Apply_a_callback_to_an_array
+use std::io::{mod, IoResult};
 
-fn main () {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes an array before and after the callback to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let array = [1,2,3,4,5];
-    println!("{}", array.as_slice());
+    try!(writeln!(out, "{}", array.as_slice()));
 
-    println!("{}", array.iter()
-                        // The map does not modify the original array.
-                        // It just returns a 'lazy' iterator.
-                        .map(callback)
-                        // To get a result, we 'consume' the iterator by
-                        // collecting it into a `Vec`.
-                        .collect::<Vec<int>>());
+    writeln!(out, "{}", array.iter()
+                             // The map does not modify the original array.
+                             // It just returns a 'lazy' iterator.
+                             .map(callback)
+                             // To get a result, we 'consume' the iterator by
+                             // collecting it into a `Vec`.
+                             .collect::<Vec<int>>())
 }
 
-fn callback(val: &int) -> int {
+pub fn callback(val: &int) -> int {
     val + 1
 }
@@ -2,7 +2,7 @@
 
 use std::io::fs::PathExtensions;
 
-fn main() {
+pub fn main() {
     let paths = ["input.txt", "docs"];
     for path in paths.iter().map(|&x| Path::new(x)) {
         let msg = match path.exists() {
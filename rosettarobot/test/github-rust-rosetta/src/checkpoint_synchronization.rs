@@ -68,7 +68,7 @@ pub fn checkpoint() {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
     checkpoint();
 }
 
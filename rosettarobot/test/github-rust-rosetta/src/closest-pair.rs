@@ -11,13 +11,16 @@ extern crate num;
 
 use std::collections::TreeSet;
 use std::cmp::{PartialOrd, Ordering};
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 use num::complex::Complex;
 
-type Point = Complex<f32>;
+pub type Point = Complex<f32>;
 
 // Wrapper around Point (i.e. Complex<f32>) so that we can use a TreeSet
 #[deriving(PartialEq)]
-struct YSortedPoint {
+pub struct YSortedPoint {
     point: Point
 }
 
@@ -35,7 +38,7 @@ impl Ord for YSortedPoint {
 
 impl Eq for YSortedPoint {}
 
-fn closest_pair(points: &mut [Point]) -> Option<(Point, Point)> {
+pub fn closest_pair(points: &mut [Point]) -> Option<(Point, Point)> {
     if points.len() < 2 {
         return None
     }
@@ -70,7 +73,7 @@ fn closest_pair(points: &mut [Point]) -> Option<(Point, Point)> {
         // Compare to points in bounding box
         {
             let mut strip_iter = strip.upper_bound(&YSortedPoint {
-                point: Point { re: std::num::Float::infinity(), im: point.im - closest_distance }
+                point: Point { re: ::std::num::Float::infinity(), im: point.im - closest_distance }
             });
             loop {
                 let point2 = match strip_iter.next() {
@@ -99,6 +102,11 @@ fn closest_pair(points: &mut [Point]) -> Option<(Point, Point)> {
 
 #[cfg(not(test))]
 pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the closest pair of some points, and their distance, to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let mut test_data = [
         Complex::new(0.654682, 0.925557),
         Complex::new(0.409382, 0.619391),
@@ -112,8 +120,8 @@ pub fn main() {
         Complex::new(0.839186, 0.728260)
     ];
     let (p1, p2) = closest_pair(test_data.as_mut_slice()).unwrap();
-    println!("Closest pair: {} and {}", p1, p2);
-    println!("Distance: {}", (p1 - p2).norm_sqr().sqrt());
+    try!(writeln!(out, "Closest pair: {} and {}", p1, p2));
+    writeln!(out, "Distance: {}", (p1 - p2).norm_sqr().sqrt())
 }
 
 #[cfg(test)]
@@ -3,15 +3,31 @@
 #![feature(overloaded_calls, unboxed_closures)]
 
 use std::iter::count;
+use std::io::{mod, IoResult};
 
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the values of the first closures to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     // An infinite iterator that generates closures
     let closures = count(0u, 1).map(|x| move |:| (x as f64).powi(2));
 
     // Take the first 9 closures from the iterator and call them
     for c in closures.take(9) {
-        println!("{}", c())
+        try!(writeln!(out, "{}", c()))
     }
+    Ok(())
+}
+
+#[test]
+fn test_squares() {
+    let squares: Vec<f64> = count(0u, 1).map(|x| move |:| (x as f64).powi(2))
+                                        .take(4)
+                                        .map(|c| c())
+                                        .collect();
+    assert_eq!(squares, vec![0.0, 1.0, 4.0, 9.0]);
 }
 
 // FIXME: add a function that returns an iterator over unboxed closures
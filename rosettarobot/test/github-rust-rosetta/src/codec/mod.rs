@@ -0,0 +1,201 @@
+// A common interface to the compression of the `run_length_encoding`, `lzw` and
+// `huffman_coding` tasks, for the `compress` binary, with a benchmark to compare them.
+//
+// Run-length encoding is streamed; LZW and Huffman coding need the whole input in memory.
+
+use std::io::{InvalidInput, IoError, IoResult, MemReader, MemWriter};
+use time;
+
+use huffman_coding;
+use lzw;
+use run_length_encoding;
+use run_length_encoding::{Escape, PackBits, Text};
+
+// A compression algorithm, which compresses and decompresses everything a reader has to read
+pub trait Codec {
+    fn compress(&self, input: &mut Reader, output: &mut Writer) -> IoResult<()>;
+
+    // Invalid compressed data gives an `InvalidInput` error
+    fn decompress(&self, input: &mut Reader, output: &mut Writer) -> IoResult<()>;
+}
+
+// The error of invalid compressed data
+fn invalid_data(detail: String) -> IoError {
+    IoError { kind: InvalidInput, desc: "invalid compressed data", detail: Some(detail) }
+}
+
+// Run-length encoding, in one of the formats of the task
+pub struct RunLength(pub run_length_encoding::Format);
+
+impl Codec for RunLength {
+    fn compress(&self, mut input: &mut Reader, mut output: &mut Writer) -> IoResult<()> {
+        let RunLength(ref format) = *self;
+        run_length_encoding::encode_stream(&mut input, &mut output, format.clone())
+    }
+
+    fn decompress(&self, mut input: &mut Reader, mut output: &mut Writer) -> IoResult<()> {
+        let RunLength(ref format) = *self;
+        run_length_encoding::decode_stream(&mut input, &mut output, format.clone())
+    }
+}
+
+// LZW with variable-width codes
+pub struct Lzw(pub lzw::Config);
+
+impl Codec for Lzw {
+    fn compress(&self, input: &mut Reader, output: &mut Writer) -> IoResult<()> {
+        let Lzw(ref config) = *self;
+        let data = try!(input.read_to_end());
+        output.write(lzw::compress_bytes(data.as_slice(), config).as_slice())
+    }
+
+    fn decompress(&self, input: &mut Reader, output: &mut Writer) -> IoResult<()> {
+        let Lzw(ref config) = *self;
+        let data = try!(input.read_to_end());
+        match lzw::decompress_bytes(data.as_slice(), config) {
+            Ok(data) => output.write(data.as_slice()),
+            Err(e) => Err(invalid_data(e))
+        }
+    }
+}
+
+// Canonical Huffman codes
+pub struct Huffman;
+
+impl Codec for Huffman {
+    fn compress(&self, input: &mut Reader, output: &mut Writer) -> IoResult<()> {
+        let data = try!(input.read_to_end());
+        output.write(huffman_coding::compress(data.as_slice()).as_slice())
+    }
+
+    fn decompress(&self, input: &mut Reader, output: &mut Writer) -> IoResult<()> {
+        let data = try!(input.read_to_end());
+        match huffman_coding::decompress(data.as_slice()) {
+            Ok(data) => output.write(data.as_slice()),
+            Err(e) => Err(invalid_data(e))
+        }
+    }
+}
+
+// The names `from_name` knows
+pub static NAMES: &'static [&'static str] =
+    &["rle", "rle-escape", "packbits", "lzw", "lzw-gif", "lzw-z", "huffman"];
+
+// Returns the codec called `name`, which is one of `NAMES`.  "rle" is the textual format of the
+// task, "rle-escape" uses 0xff as the escape byte, and "lzw-z" writes the .Z files of `compress`.
+pub fn from_name(name: &str) -> Option<Box<Codec>> {
+    match name {
+        "rle" => Some(box RunLength(Text) as Box<Codec>),
+        "rle-escape" => Some(box RunLength(Escape(0xff)) as Box<Codec>),
+        "packbits" => Some(box RunLength(PackBits) as Box<Codec>),
+        "lzw" => Some(box Lzw(lzw::Config::new(lzw::Msb)) as Box<Codec>),
+        "lzw-gif" => Some(box Lzw(lzw::Config::new(lzw::Gif)) as Box<Codec>),
+        "lzw-z" => Some(box Lzw(lzw::Config::new(lzw::UnixCompress)) as Box<Codec>),
+        "huffman" => Some(box Huffman as Box<Codec>),
+        _ => None
+    }
+}
+
+// Compresses bytes in memory
+pub fn compress(codec: &Codec, data: &[u8]) -> IoResult<Vec<u8>> {
+    let mut output = MemWriter::new();
+    try!(codec.compress(&mut MemReader::new(data.to_vec()), &mut output));
+    Ok(output.unwrap())
+}
+
+// Decompresses bytes in memory
+pub fn decompress(codec: &Codec, data: &[u8]) -> IoResult<Vec<u8>> {
+    let mut output = MemWriter::new();
+    try!(codec.decompress(&mut MemReader::new(data.to_vec()), &mut output));
+    Ok(output.unwrap())
+}
+
+// The result of compressing and decompressing some data in memory
+#[deriving(Clone, PartialEq, Show)]
+pub struct Benchmark {
+    pub size: uint,
+    pub compressed_size: uint,
+    pub compress_ns: u64,
+    pub decompress_ns: u64
+}
+
+impl Benchmark {
+    // The compressed size relative to the original size, 0 for no data
+    pub fn ratio(&self) -> f64 {
+        if self.size == 0 { 0.0 } else { self.compressed_size as f64 / self.size as f64 }
+    }
+
+    // In megabytes of original data per second
+    pub fn compress_speed(&self) -> f64 {
+        megabytes_per_second(self.size, self.compress_ns)
+    }
+
+    // In megabytes of original data per second
+    pub fn decompress_speed(&self) -> f64 {
+        megabytes_per_second(self.size, self.decompress_ns)
+    }
+}
+
+fn megabytes_per_second(size: uint, ns: u64) -> f64 {
+    size as f64 / 1e6 / (ns as f64 / 1e9).max(1e-9)
+}
+
+// Compresses and decompresses `data`, and checks that it comes back unchanged
+pub fn benchmark(codec: &Codec, data: &[u8]) -> IoResult<Benchmark> {
+    let start = time::precise_time_ns();
+    let compressed = try!(compress(codec, data));
+    let middle = time::precise_time_ns();
+    let decompressed = try!(decompress(codec, compressed.as_slice()));
+    let end = time::precise_time_ns();
+
+    if decompressed.as_slice() != data {
+        return Err(invalid_data("the decompressed data differs from the original".to_string()));
+    }
+    Ok(Benchmark { size: data.len(), compressed_size: compressed.len(),
+                   compress_ns: middle - start, decompress_ns: end - middle })
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::InvalidInput;
+
+    use super::{Benchmark, Codec, Huffman, NAMES, benchmark, compress, decompress, from_name};
+
+    #[test]
+    fn test_round_trips() {
+        let files = [include_bytes!("../resources/test_data.fasta"),
+                     include_bytes!("../resources/Pentagon.pgm"), b""];
+        for name in NAMES.iter() {
+            let codec = from_name(*name).unwrap();
+            for &data in files.iter() {
+                let compressed = compress(&*codec, data).unwrap();
+                assert_eq!(decompress(&*codec, compressed.as_slice()).unwrap().as_slice(), data);
+            }
+        }
+        assert!(from_name("zip").is_none());
+    }
+
+    #[test]
+    fn test_invalid_data() {
+        // Data which stops in the middle, for the formats which mark their end
+        for name in ["rle", "packbits", "lzw", "lzw-gif", "huffman"].iter() {
+            let codec = from_name(*name).unwrap();
+            let compressed = compress(&*codec, b"abracadabra").unwrap();
+            let truncated = compressed.slice_to(compressed.len() - 1);
+            let error = decompress(&*codec, truncated).unwrap_err();
+            assert_eq!(error.kind, InvalidInput);
+        }
+    }
+
+    #[test]
+    fn test_benchmark() {
+        let data = include_bytes!("../resources/unixdict.txt");
+        let result = benchmark(&Huffman as &Codec, data).unwrap();
+        assert_eq!(result.size, data.len());
+        assert!(result.ratio() < 0.6);
+        assert!(result.compress_speed() > 0.0 && result.decompress_speed() > 0.0);
+
+        let empty = Benchmark { size: 0, compressed_size: 8, compress_ns: 0, decompress_ns: 0 };
+        assert_eq!(empty.ratio(), 0.0);
+    }
+}
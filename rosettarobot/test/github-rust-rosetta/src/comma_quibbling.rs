@@ -1,5 +1,9 @@
 // http://rosettacode.org/wiki/Comma_quibbling
-fn quibble(seq: &[&str]) -> String {
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
+
+pub fn quibble(seq: &[&str]) -> String {
     match seq {
         [] => "{}".to_string(),
         [word] => format!("{{{}}}", word ),
@@ -8,11 +12,16 @@ fn quibble(seq: &[&str]) -> String {
 }
 
 #[cfg(not(test))]
-fn main() {
-    println!("{}", quibble([]));
-    println!("{}", quibble(["ABC"]));
-    println!("{}", quibble(["ABC", "DEF"]));
-    println!("{}", quibble(["ABC", "DEF", "G", "H"]));
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the quibbles of a few lists to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
+    try!(writeln!(out, "{}", quibble([])));
+    try!(writeln!(out, "{}", quibble(["ABC"])));
+    try!(writeln!(out, "{}", quibble(["ABC", "DEF"])));
+    writeln!(out, "{}", quibble(["ABC", "DEF", "G", "H"]))
 }
 
 #[test]
@@ -1,4 +1,7 @@
 // http://rosettacode.org/wiki/Compile-time_calculation
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
 // syntax extension are not yet stable, so we need to opt-in
 // explicitly to the phase feature gate
@@ -8,10 +11,15 @@
 // a syntax extension. The plugin's code is in src/factorial_plugin.rs
 #[phase(plugin)] extern crate factorial_plugin;
 
-#[cfg(not(test))] 
-fn main() {
+#[cfg(not(test))]
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the factorial computed at compile time to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     // we can invoke factorial_10! as a regular macro
-    println!("{}", factorial!(10u));
+    writeln!(out, "{}", factorial!(10u))
 }
 
 #[test]
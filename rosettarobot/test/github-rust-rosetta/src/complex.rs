@@ -2,17 +2,23 @@
 
 extern crate num;
 
+use std::io::{mod, IoResult};
 use num::complex::Complex;
 
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the results of the operations on two complex numbers to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let a = Complex::new(-4.0f32, 5.0);
     let b = Complex::new(1.0f32, 1.0);
 
-    println!("a = {}", a);
-    println!("b = {}", b);
-    println!("a + b = {}", a + b);
-    println!("a * b = {}", a * b);
-    println!("1 / a = {}", Complex::new(1.0f32, 0.0) / a);
-    println!("-a = {}", -a);
-    println!("conj a = {}", a.conj());
+    try!(writeln!(out, "a = {}", a));
+    try!(writeln!(out, "b = {}", b));
+    try!(writeln!(out, "a + b = {}", a + b));
+    try!(writeln!(out, "a * b = {}", a * b));
+    try!(writeln!(out, "1 / a = {}", Complex::new(1.0f32, 0.0) / a));
+    try!(writeln!(out, "-a = {}", -a));
+    writeln!(out, "conj a = {}", a.conj())
 }
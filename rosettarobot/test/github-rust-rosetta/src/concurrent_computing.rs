@@ -3,7 +3,7 @@ use std::io::timer::sleep;
 use std::rand::random;
 use std::time::duration::Duration;
 
-fn main() {
+pub fn main() {
     let strings = vec!["Enjoy", "Rosetta", "Code"];
 
     for s in strings.into_iter(){
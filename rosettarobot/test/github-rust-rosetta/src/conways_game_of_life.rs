@@ -0,0 +1,271 @@
+// Implements http://rosettacode.org/wiki/Conway%27s_Game_of_Life
+//
+// Any Life-like rule can be used, in the B/S notation: `B3/S23` is Conway's rule, where a dead cell
+// with 3 live neighbors is born and a live cell with 2 or 3 live neighbors survives.  Past its
+// edges, the grid has the same boundaries as the elementary cellular automata.
+use std::from_str::FromStr;
+use bitmap::{Color, Image};
+use elementary_cellular_automaton::{Boundary, Cyclic};
+mod bitmap;
+// With the path of the file, so that its own `mod bitmap` is found next to it
+#[allow(dead_code)]
+#[path = "elementary_cellular_automaton.rs"]
+mod elementary_cellular_automaton;
+
+// The numbers of live neighbors for which a cell is born, and for which it survives, as masks
+// where bit n stands for n live neighbors
+#[deriving(Clone, PartialEq, Show)]
+pub struct Rule {
+    birth: u16,
+    survival: u16
+}
+
+impl Rule {
+    pub fn conway() -> Rule {
+        from_str("B3/S23").unwrap()
+    }
+
+    pub fn next(&self, alive: bool, neighbors: uint) -> bool {
+        let mask = if alive { self.survival } else { self.birth };
+        (mask >> neighbors) & 1 == 1
+    }
+}
+
+// Parses the B/S notation, e.g. `B36/S23` for HighLife or `B2/S` for Seeds
+impl FromStr for Rule {
+    fn from_str(s: &str) -> Option<Rule> {
+        let parts: Vec<&str> = s.split('/').collect();
+        if parts.len() != 2 {
+            return None;
+        }
+        let mask = |part: &str, prefix: char| {
+            let mut mask = 0u16;
+            let mut chars = part.chars();
+            if chars.next().map(|c| c.to_uppercase()) != Some(prefix) {
+                return None;
+            }
+            for c in chars {
+                match c.to_digit(9) {
+                    Some(n) => mask |= 1 << n,
+                    None => return None
+                }
+            }
+            Some(mask)
+        };
+        match (mask(parts[0], 'B'), mask(parts[1], 'S')) {
+            (Some(birth), Some(survival)) => Some(Rule { birth: birth, survival: survival }),
+            _ => None
+        }
+    }
+}
+
+#[deriving(Clone, PartialEq, Show)]
+pub struct Life {
+    rule: Rule,
+    boundary: Boundary,
+    pub width: uint,
+    pub height: uint,
+    cells: Vec<bool>
+}
+
+impl Life {
+    // A grid of dead cells
+    pub fn new(rule: Rule, boundary: Boundary, width: uint, height: uint) -> Life {
+        Life { rule: rule, boundary: boundary, width: width, height: height,
+               cells: Vec::from_elem(width * height, false) }
+    }
+
+    // The lines of `pattern` are the rows of the grid, where `#` is a live cell and any other
+    // character a dead one.  The grid is as wide as the longest line.
+    pub fn from_pattern(rule: Rule, boundary: Boundary, pattern: &str) -> Life {
+        let lines: Vec<&str> = pattern.lines().collect();
+        let width = lines.iter().map(|line| line.char_len()).max().unwrap_or(0);
+        let mut life = Life::new(rule, boundary, width, lines.len());
+        for (y, line) in lines.iter().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                life.set(x, y, c == '#');
+            }
+        }
+        life
+    }
+
+    // The cell at (x, y), or the boundary outside of the grid
+    pub fn get(&self, x: int, y: int) -> bool {
+        let (width, height) = (self.width, self.height);
+        self.boundary.get(y, height, |y| {
+            self.boundary.get(x, width, |x| self.cells[y * width + x])
+        })
+    }
+
+    pub fn set(&mut self, x: uint, y: uint, alive: bool) {
+        self.cells[y * self.width + x] = alive;
+    }
+
+    // The number of live cells
+    pub fn population(&self) -> uint {
+        self.cells.iter().filter(|&&cell| cell).count()
+    }
+
+    // Computes the next generation
+    pub fn step(&mut self) {
+        let mut next = Vec::with_capacity(self.cells.len());
+        for y in range(0, self.height as int) {
+            for x in range(0, self.width as int) {
+                let mut neighbors = 0;
+                for dy in range(-1i, 2) {
+                    for dx in range(-1i, 2) {
+                        if (dx != 0 || dy != 0) && self.get(x + dx, y + dy) {
+                            neighbors += 1;
+                        }
+                    }
+                }
+                next.push(self.rule.next(self.get(x, y), neighbors));
+            }
+        }
+        self.cells = next;
+    }
+
+    // A line of `alive` and `dead` characters per row, even an empty one
+    pub fn to_text(&self, alive: char, dead: char) -> String {
+        let mut text = String::new();
+        for y in range(0, self.height) {
+            for &cell in self.cells.slice(y * self.width, (y + 1) * self.width).iter() {
+                text.push(if cell { alive } else { dead });
+            }
+            text.push('\n');
+        }
+        text
+    }
+
+    // A square of `scale` x `scale` pixels per cell
+    pub fn to_image(&self, alive: Color, dead: Color, scale: uint) -> Image {
+        let mut image = Image::new(self.width * scale, self.height * scale);
+        for y in range(0, image.height) {
+            for x in range(0, image.width) {
+                let cell = self.cells[y / scale * self.width + x / scale];
+                image[(x, y)] = if cell { alive } else { dead };
+            }
+        }
+        image
+    }
+}
+
+#[cfg(not(test))]
+pub fn main() {
+    let glider = ".#......\n..#.....\n###.....\n........\n........\n........";
+    let mut life = Life::from_pattern(Rule::conway(), Cyclic, glider);
+    for generation in range(0u, 4) {
+        println!("Generation {}:\n{}", generation, life.to_text('#', '.'));
+        life.step();
+    }
+
+    // The R-pentomino, which keeps changing for a long time
+    let mut life = Life::new(Rule::conway(), Cyclic, 160, 120);
+    for &(x, y) in [(80, 59), (81, 59), (79, 60), (80, 60), (80, 61)].iter() {
+        life.set(x, y, true);
+    }
+    for _ in range(0u, 200) {
+        life.step();
+    }
+    println!("Population after 200 generations: {}", life.population());
+    let (black, white) = (Color { red: 0, green: 0, blue: 0 },
+                          Color { red: 255, green: 255, blue: 255 });
+    match life.to_image(black, white, 4).write_ppm("life.ppm") {
+        Ok(()) => println!("Wrote life.ppm"),
+        Err(e) => println!("Failed to write life.ppm: {}", e)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitmap::Color;
+    use elementary_cellular_automaton::{Alive, Cyclic, Dead};
+    use super::{Life, Rule};
+
+    static GLIDER: &'static str = ".#....\n..#...\n###...\n......\n......\n......";
+
+    #[test]
+    fn rules() {
+        let conway = Rule::conway();
+        assert_eq!((conway.next(false, 3), conway.next(false, 2)), (true, false));
+        assert_eq!((conway.next(true, 2), conway.next(true, 3), conway.next(true, 4)),
+                   (true, true, false));
+        assert_eq!(from_str::<Rule>("b3/s23"), Some(conway));
+        assert!(from_str::<Rule>("B2/S").is_some());
+        assert_eq!(from_str::<Rule>("B3"), None);
+        assert_eq!(from_str::<Rule>("S23/B3"), None);
+        assert_eq!(from_str::<Rule>("B9/S23"), None);
+
+        let pattern = "###\n#.#\n#..";
+        let highlife = from_str("B36/S23").unwrap();
+        let mut life = Life::from_pattern(highlife, Dead, pattern);
+        life.step();
+        assert_eq!(life.to_text('#', '.').as_slice(), "#.#\n###\n.#.\n");
+        let mut life = Life::from_pattern(Rule::conway(), Dead, pattern);
+        life.step();
+        assert_eq!(life.to_text('#', '.').as_slice(), "#.#\n#.#\n.#.\n");
+
+        let mut seeds = Life::from_pattern(from_str("B2/S").unwrap(), Dead, "....\n.##.\n....");
+        seeds.step();
+        assert_eq!(seeds.to_text('#', '.').as_slice(), ".##.\n....\n.##.\n");
+    }
+
+    #[test]
+    fn blinker() {
+        let pattern = ".....\n..#..\n..#..\n..#..\n.....";
+        let mut life = Life::from_pattern(Rule::conway(), Dead, pattern);
+        life.step();
+        assert_eq!(life.to_text('#', '.').as_slice(), ".....\n.....\n.###.\n.....\n.....\n");
+        life.step();
+        assert_eq!(life.to_text('#', '.').as_slice(), ".....\n..#..\n..#..\n..#..\n.....\n");
+    }
+
+    #[test]
+    fn empty_grids() {
+        let life = Life::from_pattern(Rule::conway(), Dead, "\n\n");
+        assert_eq!((life.width, life.height), (0, 2));
+        assert_eq!(life.to_text('#', '.').as_slice(), "\n\n");
+        assert_eq!(Life::new(Rule::conway(), Dead, 3, 0).to_text('#', '.').as_slice(), "");
+    }
+
+    #[test]
+    fn boundaries() {
+        // The glider moves by one cell diagonally every 4 generations, and wraps around the
+        // cyclic grid, while it becomes a block in the corner of the dead one
+        let mut life = Life::from_pattern(Rule::conway(), Cyclic, GLIDER);
+        for _ in range(0u, 4) {
+            life.step();
+        }
+        assert_eq!(life.to_text('#', '.').as_slice(),
+                   "......\n..#...\n...#..\n.###..\n......\n......\n");
+        for _ in range(0u, 12) {
+            life.step();
+        }
+        assert_eq!(life.to_text('#', '.').as_slice(),
+                   "#...##\n......\n......\n......\n.....#\n#.....\n");
+
+        let mut life = Life::from_pattern(Rule::conway(), Dead, GLIDER);
+        for _ in range(0u, 16) {
+            life.step();
+        }
+        assert_eq!(life.to_text('#', '.').as_slice(),
+                   "......\n......\n......\n......\n....##\n....##\n");
+        assert_eq!(life.population(), 4);
+
+        // Outside of the grid, 3 live cells are next to the middle of each edge
+        let mut life = Life::new(Rule::conway(), Alive, 3, 3);
+        assert_eq!((life.get(-1, 1), life.get(1, 3), life.get(1, 1)), (true, true, false));
+        life.step();
+        assert_eq!(life.to_text('#', '.').as_slice(), ".#.\n#.#\n.#.\n");
+    }
+
+    #[test]
+    fn image() {
+        let life = Life::from_pattern(Rule::conway(), Dead, "#.\n.#\n#.");
+        let (black, white) = (Color { red: 0, green: 0, blue: 0 },
+                              Color { red: 255, green: 255, blue: 255 });
+        let image = life.to_image(black, white, 2);
+        assert_eq!((image.width, image.height), (4, 6));
+        assert_eq!(image.to_ascii().as_slice(), "##..\n##..\n..##\n..##\n##..\n##..\n");
+    }
+}
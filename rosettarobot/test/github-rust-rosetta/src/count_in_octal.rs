@@ -2,10 +2,17 @@
 
 use std::u8;
 use std::iter::range_inclusive;
+use std::io::{mod, IoResult};
 
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the numbers from 0 to 255 in octal to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     // We count from 0 to 255 (377 in octal)
-      for i in range_inclusive(0, u8::MAX) {
-            println!("{:o}", i);
-      }
+    for i in range_inclusive(0, u8::MAX) {
+        try!(writeln!(out, "{:o}", i));
+    }
+    Ok(())
 }
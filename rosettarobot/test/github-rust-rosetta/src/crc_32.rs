@@ -1,31 +1,77 @@
 // http://rosettacode.org/wiki/CRC-32
 
 #[cfg(not(test))]
-fn main() {
+use std::io;
+use std::io::IoResult;
+
+// The size of a CRC-32 checksum in bytes
+pub const SIZE: uint = 4;
+
+// The message is processed byte by byte
+pub const CHUNK: uint = 1;
+
+#[cfg(not(test))]
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the checksum of the example string to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let s = "The quick brown fox jumps over the lazy dog";
-    println!("{:X}", crc(s.as_bytes()));
+    writeln!(out, "{:X}", crc(s.as_bytes()))
+}
+
+pub fn crc(bytes: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(bytes);
+    crc.value()
+}
+
+// The partial evaluation of a checksum, the message can be given in several parts
+pub struct Crc32 {
+    table: [u32, ..256],
+    crc: u32
 }
 
-fn crc(bytes: &[u8]) -> u32 {
-    // Store the CRC of all possible 256 one byte values in table
-    let mut table: [u32, ..256] = [0,..256];
-    for i in range(0, table.len()) {
-        let mut word = i as u32;
-        for _ in range(0u, 8) {
-            if word & 1 == 1 {
-                word = (word >> 1) ^ 0xedb88320
-            } else {
-                word = word >> 1;
+impl Crc32 {
+    pub fn new() -> Crc32 {
+        // Store the CRC of all possible 256 one byte values in table
+        let mut table: [u32, ..256] = [0,..256];
+        for i in range(0, table.len()) {
+            let mut word = i as u32;
+            for _ in range(0u, 8) {
+                if word & 1 == 1 {
+                    word = (word >> 1) ^ 0xedb88320
+                } else {
+                    word = word >> 1;
+                }
             }
+            table[i] = word;
         }
-        table[i] = word;
+        Crc32 { table: table, crc: 0xffffffff }
     }
 
-    let mut crc: u32 = 0xffffffff;
-    for byte in bytes.iter() {
-        crc = table[(crc as u8 ^ *byte) as uint] ^ (crc >> 8);
+    pub fn reset(&mut self) {
+        self.crc = 0xffffffff;
+    }
+
+    pub fn update(&mut self, bytes: &[u8]) {
+        for byte in bytes.iter() {
+            self.crc = self.table[(self.crc as u8 ^ *byte) as uint] ^ (self.crc >> 8);
+        }
+    }
+
+    // The checksum of the message given so far
+    pub fn value(&self) -> u32 {
+        self.crc ^ 0xffffffff
+    }
+
+    // Returns the checksum as big-endian bytes, and starts a new one
+    pub fn finalize(&mut self) -> [u8, ..SIZE] {
+        let value = self.value();
+        self.reset();
+        [(value >> 24) as u8, (value >> 16) as u8, (value >> 8) as u8, value as u8]
     }
-    crc ^ 0xffffffff
 }
 
 #[test]
@@ -33,3 +79,12 @@ fn test() {
     let s = "The quick brown fox jumps over the lazy dog";
     assert_eq!(crc(s.as_bytes()), 0x414FA339);
 }
+
+#[test]
+fn test_incremental() {
+    let mut crc = Crc32::new();
+    crc.update(b"The quick brown fox ");
+    crc.update(b"jumps over the lazy dog");
+    assert_eq!(crc.finalize().as_slice(), [0x41u8, 0x4F, 0xA3, 0x39].as_slice());
+    assert_eq!(crc.value(), 0);
+}
@@ -5,7 +5,7 @@ extern crate libc;
 use std::io::{mod, File, fs};
 
 #[cfg(not(test))]
-fn main () {
+pub fn main () {
     // Create a new file.  We get a Result object from
     // File::create. We could check if there is an error by using
     // .is_error() or by using pattern matching. We choose here to
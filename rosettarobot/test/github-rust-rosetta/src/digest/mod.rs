@@ -0,0 +1,417 @@
+// A common interface to the hash functions of the `md5_implementation`, `sha1` and `sha256`
+// tasks (and to the checksum of the `crc_32` task), with HMAC (RFC 2104) and PBKDF2 (RFC 2898)
+// built on top of it.  It also reads and writes the checksum lines of GNU md5sum & co, for the
+// `checksum` binary.
+//
+// Each task has `update`, `finalize` and `reset` methods of its own, which are exposed here
+// through the `Digest` trait.  Every hash can be computed incrementally, and `finalize` starts a
+// new one.
+
+use serialize::hex::{FromHex, ToHex};
+use std::cmp;
+use std::io::{mod, IoResult};
+
+use crc_32;
+use crc_32::Crc32;
+use md5_implementation;
+use md5_implementation::Md5;
+use sha1;
+use sha1::Sha1;
+use sha256;
+use sha256::Sha256;
+
+// A hash function which is fed the message in several parts
+pub trait Digest {
+    // Adds `data` to the message
+    fn update(&mut self, data: &[u8]);
+
+    // Returns the hash of the message, and starts a new one
+    fn finalize(&mut self) -> Vec<u8>;
+
+    // Forgets the message given so far
+    fn reset(&mut self);
+
+    // The size of the hashes, in bytes
+    fn output_size(&self) -> uint;
+
+    // The size of the blocks the message is processed in, in bytes
+    fn block_size(&self) -> uint;
+}
+
+// Implements `Digest` with the inherent methods of a task's hash, which take precedence over the
+// trait's
+macro_rules! native_digest(
+    ($hash:ident in $module:ident) => (
+        impl Digest for $hash {
+            fn update(&mut self, data: &[u8]) {
+                self.update(data)
+            }
+
+            fn finalize(&mut self) -> Vec<u8> {
+                self.finalize().as_slice().to_vec()
+            }
+
+            fn reset(&mut self) {
+                self.reset()
+            }
+
+            fn output_size(&self) -> uint {
+                $module::SIZE
+            }
+
+            fn block_size(&self) -> uint {
+                $module::CHUNK
+            }
+        }
+    )
+)
+
+native_digest!(Crc32 in crc_32)
+native_digest!(Md5 in md5_implementation)
+native_digest!(Sha1 in sha1)
+native_digest!(Sha256 in sha256)
+
+// The names `from_name` knows
+pub static NAMES: &'static [&'static str] = &["md5", "sha1", "sha256", "crc32"];
+
+// Returns the digest called `name`, which is one of `NAMES`
+pub fn from_name(name: &str) -> Option<Box<Digest>> {
+    match name {
+        "md5" => Some(box Md5::new() as Box<Digest>),
+        "sha1" => Some(box Sha1::new() as Box<Digest>),
+        "sha256" => Some(box Sha256::new() as Box<Digest>),
+        "crc32" => Some(box Crc32::new() as Box<Digest>),
+        _ => None
+    }
+}
+
+// Hashes a whole message at once
+pub fn hash<D: Digest>(digest: &mut D, message: &[u8]) -> Vec<u8> {
+    digest.reset();
+    digest.update(message);
+    digest.finalize()
+}
+
+// Hashes everything `reader` reads, a buffer at a time
+pub fn hash_reader<R: Reader>(digest: &mut Digest, reader: &mut R) -> IoResult<Vec<u8>> {
+    let mut buffer = [0u8, ..8192];
+    digest.reset();
+    loop {
+        match reader.read(&mut buffer) {
+            Ok(n) => digest.update(buffer.slice_to(n)),
+            Err(ref e) if e.kind == io::EndOfFile => break,
+            Err(e) => return Err(e)
+        }
+    }
+    Ok(digest.finalize())
+}
+
+// An entry of a checksum file
+#[deriving(PartialEq, Show)]
+pub struct ChecksumLine {
+    pub hash: Vec<u8>,
+    pub filename: String,
+    pub binary: bool // Whether the file was read in binary mode, which only matters on Windows
+}
+
+// Formats a checksum the way md5sum does: the hash in hexadecimal, a space, then `*` for a binary
+// file or a space for a text file, then the filename.  A filename with a backslash or a newline
+// is escaped, and the line then starts with a backslash.
+pub fn checksum_line(hash: &[u8], filename: &str, binary: bool) -> String {
+    let escaped = filename.contains_char('\\') || filename.contains_char('\n');
+    let filename = if escaped {
+        filename.replace("\\", "\\\\").replace("\n", "\\n")
+    } else {
+        filename.to_string()
+    };
+    format!("{}{} {}{}", if escaped { "\\" } else { "" }, hash.to_hex(),
+            if binary { '*' } else { ' ' }, filename)
+}
+
+// Parses a line written by `checksum_line`, for hashes of `size` bytes
+pub fn parse_checksum_line(line: &str, size: uint) -> Option<ChecksumLine> {
+    let (escaped, line) = if line.starts_with("\\") {
+        (true, line.slice_from(1))
+    } else {
+        (false, line)
+    };
+    let hex_len = 2 * size;
+    if line.len() <= hex_len + 2 || !line.is_char_boundary(hex_len) {
+        return None;
+    }
+    // from_hex skips whitespace, so the hash can still be too short
+    let hash = match line.slice_to(hex_len).from_hex() {
+        Ok(ref hash) if hash.len() == size => hash.clone(),
+        _ => return None
+    };
+    let binary = match line.slice(hex_len, hex_len + 2) {
+        "  " => false,
+        " *" => true,
+        _ => return None
+    };
+
+    let filename = line.slice_from(hex_len + 2);
+    if !escaped {
+        return Some(ChecksumLine { hash: hash, filename: filename.to_string(), binary: binary });
+    }
+    let mut unescaped = String::with_capacity(filename.len());
+    let mut chars = filename.chars();
+    loop {
+        match chars.next() {
+            Some('\\') => match chars.next() {
+                Some('\\') => unescaped.push('\\'),
+                Some('n') => unescaped.push('\n'),
+                _ => return None
+            },
+            Some(c) => unescaped.push(c),
+            None => break
+        }
+    }
+    Some(ChecksumLine { hash: hash, filename: unescaped, binary: binary })
+}
+
+// A keyed hash: HMAC is itself a `Digest`, whose hashes depend on the key
+pub struct Hmac<D> {
+    digest: D,
+    inner_pad: Vec<u8>,
+    outer_pad: Vec<u8>
+}
+
+impl<D: Digest> Hmac<D> {
+    pub fn new(mut digest: D, key: &[u8]) -> Hmac<D> {
+        let block_size = digest.block_size();
+
+        // Keys longer than a block are hashed first, shorter ones are padded with zeros
+        let mut key = if key.len() > block_size {
+            hash(&mut digest, key)
+        } else {
+            key.to_vec()
+        };
+        key.grow(block_size - key.len(), 0);
+
+        let inner_pad: Vec<u8> = key.iter().map(|b| *b ^ 0x36).collect();
+        let outer_pad: Vec<u8> = key.iter().map(|b| *b ^ 0x5c).collect();
+        digest.reset();
+        digest.update(inner_pad.as_slice());
+        Hmac { digest: digest, inner_pad: inner_pad, outer_pad: outer_pad }
+    }
+}
+
+impl<D: Digest> Digest for Hmac<D> {
+    fn update(&mut self, data: &[u8]) {
+        self.digest.update(data);
+    }
+
+    fn finalize(&mut self) -> Vec<u8> {
+        let inner = self.digest.finalize();
+        self.digest.update(self.outer_pad.as_slice());
+        self.digest.update(inner.as_slice());
+        let outer = self.digest.finalize();
+        self.digest.update(self.inner_pad.as_slice());
+        outer
+    }
+
+    fn reset(&mut self) {
+        self.digest.reset();
+        self.digest.update(self.inner_pad.as_slice());
+    }
+
+    fn output_size(&self) -> uint {
+        self.digest.output_size()
+    }
+
+    fn block_size(&self) -> uint {
+        self.digest.block_size()
+    }
+}
+
+// Derives a key of `length` bytes from a password, with PBKDF2.  `prf` is the pseudorandom
+// function keyed with the password, usually an `Hmac`.
+pub fn pbkdf2<D: Digest>(prf: &mut D, salt: &[u8], iterations: uint, length: uint) -> Vec<u8> {
+    assert!(iterations > 0);
+    prf.reset();
+    let mut key = Vec::with_capacity(length);
+    let mut index = 1u32;
+    while key.len() < length {
+        // The block is the xor of the chained hashes u1 = prf(salt || index), u2 = prf(u1), ...
+        prf.update(salt);
+        prf.update(&[(index >> 24) as u8, (index >> 16) as u8, (index >> 8) as u8, index as u8]);
+        let mut u = prf.finalize();
+        let mut block = u.clone();
+        for _ in range(1, iterations) {
+            prf.update(u.as_slice());
+            u = prf.finalize();
+            for (b, x) in block.iter_mut().zip(u.iter()) {
+                *b ^= *x;
+            }
+        }
+
+        let needed = cmp::min(block.len(), length - key.len());
+        key.push_all(block.slice_to(needed));
+        index += 1;
+    }
+    key
+}
+
+#[cfg(test)]
+mod test {
+    use serialize::hex::ToHex;
+    use std::io::MemReader;
+
+    use md5_implementation::Md5;
+    use sha1::Sha1;
+    use sha256::Sha256;
+    use super::{ChecksumLine, Digest, Hmac, NAMES, checksum_line, from_name, hash, hash_reader};
+    use super::{parse_checksum_line, pbkdf2};
+
+    fn hmac_hex<D: Digest>(digest: D, key: &[u8], message: &[u8]) -> String {
+        let mut hmac = Hmac::new(digest, key);
+        hmac.update(message);
+        hmac.finalize().as_slice().to_hex()
+    }
+
+    #[test]
+    fn test_native_digests() {
+        let mut md5 = Md5::new();
+        assert_eq!(md5.output_size(), 16);
+        assert_eq!(hash(&mut md5, b"abc").as_slice().to_hex().as_slice(),
+                   "900150983cd24fb0d6963f7d28e17f72");
+        let mut sha1 = Sha1::new();
+        assert_eq!(sha1.output_size(), 20);
+        assert_eq!(hash(&mut sha1, b"abc").as_slice().to_hex().as_slice(),
+                   "a9993e364706816aba3e25717850c26c9cd0d89d");
+        let mut sha256 = Sha256::new();
+        assert_eq!(sha256.output_size(), 32);
+        assert_eq!(hash(&mut sha256, b"abc").as_slice().to_hex().as_slice(),
+                   "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+
+        // Through a trait object
+        let mut digests = vec![box Md5::new() as Box<Digest>, box Sha1::new() as Box<Digest>,
+                               box Sha256::new() as Box<Digest>];
+        for digest in digests.iter_mut() {
+            digest.update(b"a");
+            digest.update(b"bc");
+            assert_eq!(digest.finalize().len(), digest.output_size());
+        }
+    }
+
+    // RFC 2202, test cases 1, 2 and 6
+    #[test]
+    fn test_hmac_md5() {
+        assert_eq!(hmac_hex(Md5::new(), &[0x0bu8, ..16], b"Hi There").as_slice(),
+                   "9294727a3638bb1c13f48ef8158bfc9d");
+        assert_eq!(hmac_hex(Md5::new(), b"Jefe", b"what do ya want for nothing?").as_slice(),
+                   "750c783e6ab0b503eaa86e310a5db738");
+        assert_eq!(hmac_hex(Md5::new(), &[0xaau8, ..80],
+                            b"Test Using Larger Than Block-Size Key - Hash Key First").as_slice(),
+                   "6b1ab7fe4bd7bf8f0b62e6ce61b9d0cd");
+    }
+
+    // RFC 2202, test cases 1, 2 and 6
+    #[test]
+    fn test_hmac_sha1() {
+        assert_eq!(hmac_hex(Sha1::new(), &[0x0bu8, ..20], b"Hi There").as_slice(),
+                   "b617318655057264e28bc0b6fb378c8ef146be00");
+        assert_eq!(hmac_hex(Sha1::new(), b"Jefe", b"what do ya want for nothing?").as_slice(),
+                   "effcdf6ae5eb2fa2d27416d5f184df9c259a7c79");
+        assert_eq!(hmac_hex(Sha1::new(), &[0xaau8, ..80],
+                            b"Test Using Larger Than Block-Size Key - Hash Key First").as_slice(),
+                   "aa4ae5e15272d00e95705637ce8a3b55ed402112");
+    }
+
+    // RFC 4231, test cases 1, 2 and 6
+    #[test]
+    fn test_hmac_sha256() {
+        assert_eq!(hmac_hex(Sha256::new(), &[0x0bu8, ..20], b"Hi There").as_slice(),
+                   "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7");
+        assert_eq!(hmac_hex(Sha256::new(), b"Jefe", b"what do ya want for nothing?").as_slice(),
+                   "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843");
+        assert_eq!(hmac_hex(Sha256::new(), &[0xaau8, ..131],
+                            b"Test Using Larger Than Block-Size Key - Hash Key First").as_slice(),
+                   "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54");
+    }
+
+    #[test]
+    fn test_hmac_reuse() {
+        let mut hmac = Hmac::new(Sha1::new(), b"Jefe");
+        hmac.update(b"what do ya ");
+        hmac.update(b"want for nothing?");
+        let first = hmac.finalize();
+        hmac.update(b"something else");
+        hmac.reset();
+        hmac.update(b"what do ya want for nothing?");
+        assert_eq!(hmac.finalize(), first);
+    }
+
+    // RFC 6070
+    #[test]
+    fn test_pbkdf2_hmac_sha1() {
+        let vectors = [
+            (b"password", b"salt", 1u, 20u, "0c60c80f961f0e71f3a9b524af6012062fe037a6"),
+            (b"password", b"salt", 2, 20, "ea6c014dc72d6f8ccd1ed92ace1d41f0d8de8957"),
+            (b"password", b"salt", 4096, 20, "4b007901b765489abead49d926f721d065a429c1"),
+            (b"passwordPASSWORDpassword", b"saltSALTsaltSALTsaltSALTsaltSALTsalt", 4096, 25,
+             "3d2eec4fe41c849b80c8d83662c0e44a8b291a964cf2f07038"),
+            (b"pass\0word", b"sa\0lt", 4096, 16, "56fa6aa75548099dcc37d7f03425e0c3")];
+        for &(password, salt, iterations, length, expected) in vectors.iter() {
+            let mut prf = Hmac::new(Sha1::new(), password);
+            let key = pbkdf2(&mut prf, salt, iterations, length);
+            assert_eq!(key.as_slice().to_hex().as_slice(), expected);
+        }
+    }
+
+    #[test]
+    fn test_pbkdf2_hmac_sha256() {
+        let mut prf = Hmac::new(Sha256::new(), b"password");
+        assert_eq!(pbkdf2(&mut prf, b"salt", 4096, 32).as_slice().to_hex().as_slice(),
+                   "c5e478d59288c841aa530db6845c4c8d962893a001ce4e11a4963873aa98134a");
+    }
+
+    #[test]
+    fn test_from_name() {
+        let sizes: Vec<uint> = NAMES.iter().map(|name| from_name(*name).unwrap().output_size())
+                                    .collect();
+        assert_eq!(sizes, vec![16, 20, 32, 4]);
+        assert!(from_name("sha512").is_none());
+
+        let mut crc = from_name("crc32").unwrap();
+        crc.update(b"The quick brown fox jumps over the lazy dog");
+        assert_eq!(crc.finalize().as_slice().to_hex().as_slice(), "414fa339");
+    }
+
+    #[test]
+    fn test_hash_reader() {
+        // Longer than the buffer
+        let data = Vec::from_elem(20000, b'a');
+        let mut sha1 = from_name("sha1").unwrap();
+        let expected = hash(&mut Sha1::new(), data.as_slice());
+        assert_eq!(hash_reader(&mut *sha1, &mut MemReader::new(data)).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_checksum_lines() {
+        let hash = [0x41u8, 0x4f, 0xa3, 0x39];
+        let line = checksum_line(&hash, "some file", false);
+        assert_eq!(line.as_slice(), "414fa339  some file");
+        assert_eq!(checksum_line(&hash, "a.out", true).as_slice(), "414fa339 *a.out");
+        assert_eq!(parse_checksum_line(line.as_slice(), 4), Some(ChecksumLine {
+            hash: hash.to_vec(),
+            filename: "some file".to_string(),
+            binary: false
+        }));
+        assert!(parse_checksum_line("414fa339 *a.out", 4).unwrap().binary);
+
+        // Escaped filenames
+        let line = checksum_line(&hash, "back\\slash\nnewline", false);
+        assert_eq!(line.as_slice(), "\\414fa339  back\\\\slash\\nnewline");
+        assert_eq!(parse_checksum_line(line.as_slice(), 4).unwrap().filename.as_slice(),
+                   "back\\slash\nnewline");
+
+        // Malformed lines
+        for line in ["", "414fa339", "414fa339  ", "414fa33  file", "414fa3390  file",
+                     "414fa33g  file", "414f a339  file", "414fa339 file", "414fa339\t file",
+                     "\\414fa339  a\\b", "é14fa339  file"].iter() {
+            assert_eq!(parse_checksum_line(*line, 4), None);
+        }
+    }
+}
@@ -3,15 +3,18 @@
 use std::collections::{HashMap, PriorityQueue, DList, Deque};
 use std::collections::hashmap::{Occupied, Vacant};
 use std::uint;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
-type Node = uint;
-type Cost = uint;
-type Edge = (Node, Node);
+pub type Node = uint;
+pub type Cost = uint;
+pub type Edge = (Node, Node);
 
 
 /// The DistPair struct is for the Priority Queue.
 #[deriving(Eq, PartialEq, PartialOrd)]
-struct DistPair(Node, Cost);
+pub struct DistPair(Node, Cost);
 impl Ord for DistPair {
     fn cmp(&self, other:&DistPair) -> Ordering {
         let DistPair(_, dist_a) = *self;
@@ -21,14 +24,14 @@ impl Ord for DistPair {
 }
 
 /// Graph structure, represented as an Adjancency List.
-struct Graph<'a> {
+pub struct Graph<'a> {
     vertices: Vec<&'a str>,
     adj_list: Vec<Vec<Node>>,
     costs: HashMap<Edge, Cost>,
 }
 
 impl<'a> Graph<'a> {
-    fn new() -> Graph<'a> {
+    pub fn new() -> Graph<'a> {
         let vertices:Vec<&str> = Vec::new();
         let adj_list:Vec<Vec<Node>> = Vec::new();
         let costs:HashMap<Edge, Cost> = HashMap::new();
@@ -38,7 +41,7 @@ impl<'a> Graph<'a> {
 
     /// Returns the index of the vertex, or None if vertex 
     /// not found.
-    fn vertex_index(&self, vertex: &str) -> Option<Node> {
+    pub fn vertex_index(&self, vertex: &str) -> Option<Node> {
         for (idx, &v) in self.vertices.iter().enumerate(){
             if v == vertex{return Some(idx)}
         }
@@ -47,7 +50,7 @@ impl<'a> Graph<'a> {
 
     /// Returns the index of the vertex. If vertex is not found, inserts
     /// the vertex.
-    fn get_or_insert_vertex(&mut self, vertex: &'a str) -> Node {
+    pub fn get_or_insert_vertex(&mut self, vertex: &'a str) -> Node {
         self.vertex_index(vertex)
             .unwrap_or_else( || {
                 self.adj_list.push(Vec::new());
@@ -55,9 +58,9 @@ impl<'a> Graph<'a> {
                 self.vertices.len()-1
             })
     }
-    
+
     /// Adds the given edge to the graph.
-    fn add_edge(&mut self, from: &'a str, to: &'a str, cost: uint) {
+    pub fn add_edge(&mut self, from: &'a str, to: &'a str, cost: uint) {
         let from_idx = self.get_or_insert_vertex(from);
         let to_idx = self.get_or_insert_vertex(to);
 
@@ -78,7 +81,7 @@ impl<'a> Graph<'a> {
     ///
     /// Returns vector of vertices representing the path, or an empty vector
     /// if there's no path, or if the source or target is not in the graph.
-    fn dijkstra(&self, source: &str, target: &str) -> Vec<&str> {
+    pub fn dijkstra(&self, source: &str, target: &str) -> Vec<&str> {
         let num_vert = self.vertices.len();
         let mut dist:Vec<uint> = Vec::from_elem(num_vert, uint::MAX); //Close enough to infinity
         let mut prev:HashMap<Node, Node> = HashMap::new();
@@ -156,7 +159,12 @@ fn test_dijkstras() {
 }
 
 #[cfg(not(test))]
-fn main(){
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the shortest path from a to e to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let mut graph = Graph::new();
     graph.add_edge("a", "b", 7);
     graph.add_edge("a", "c", 9);
@@ -167,8 +175,8 @@ fn main(){
     graph.add_edge("c", "f", 2);
     graph.add_edge("d", "e", 6);
     graph.add_edge("e", "f", 9);
-    
+
     let path = graph.dijkstra("a", "e");
-    println!("Path is: {}", path);
+    writeln!(out, "Path is: {}", path)
 }
 
@@ -2,8 +2,11 @@
 
 use std::ops::{Add, Mul};
 use std::num::Zero;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
-fn dotp<T:Add<T, T> + Mul<T, T> + Zero + Copy>(this: &[T], other: &[T]) -> T {
+pub fn dotp<T:Add<T, T> + Mul<T, T> + Zero + Copy>(this: &[T], other: &[T]) -> T {
   assert!(this.len() == other.len(), "The dimensions must be equal");
 
   let zero : T = Zero::zero();
@@ -13,10 +16,15 @@ fn dotp<T:Add<T, T> + Mul<T, T> + Zero + Copy>(this: &[T], other: &[T]) -> T {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the dot product of two vectors to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let a = &[1.0f32, 3.0, -5.0];
     let b = &[4.0f32, -2.0, -1.0];
-    println!("{}", dotp(a, b));
+    writeln!(out, "{}", dotp(a, b))
 }
 
 #[test]
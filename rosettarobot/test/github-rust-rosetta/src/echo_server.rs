@@ -6,7 +6,7 @@ use std::io::net::tcp::{TcpListener, TcpStream};
 use std::time::Duration;
 
 // The actual echo server
-fn echo_server(host: &'static str, port: u16, timeout: Option<Duration>) -> IoResult<()> {
+pub fn echo_server(host: &'static str, port: u16, timeout: Option<Duration>) -> IoResult<()> {
     // Create a new TCP listener at host:port.
     let mut listener = try!(TcpListener::bind(host, port));
     println!("Starting echo server on {}", listener.socket_name());
@@ -44,7 +44,7 @@ fn echo_server(host: &'static str, port: u16, timeout: Option<Duration>) -> IoRe
 }
 
 // Each connection gets its own session.
-fn echo_session(mut stream: TcpStream) -> IoResult<()> {
+pub fn echo_session(mut stream: TcpStream) -> IoResult<()> {
     let name = try!(stream.peer_name());
     let ref mut writer = stream.clone();
     let mut reader = BufferedReader::new(stream);
@@ -57,8 +57,8 @@ fn echo_session(mut stream: TcpStream) -> IoResult<()> {
     Ok(())
 }
 
-const HOST: &'static str = "127.0.0.1";
-const PORT: u16 = 12321;
+pub const HOST: &'static str = "127.0.0.1";
+pub const PORT: u16 = 12321;
 
 pub fn run_server(duration: Option<Duration>) -> IoResult<()> {
     echo_server(HOST, PORT, duration)
@@ -0,0 +1,190 @@
+// Implements http://rosettacode.org/wiki/Elementary_cellular_automaton
+//
+// The rule is given by its Wolfram code: bit n of the rule is the next state of a cell whose left
+// neighbor, itself and right neighbor read as the binary number n.  The automaton is an iterator
+// over its generations, which can be rendered as text or as an image.
+use bitmap::{Color, Image};
+mod bitmap;
+
+// What lies past the ends of a row of cells
+#[deriving(Clone, PartialEq, Show)]
+pub enum Boundary {
+    // Dead cells
+    Dead,
+    // Live cells
+    Alive,
+    // The cells of the other end
+    Cyclic
+}
+
+impl Boundary {
+    // The cell at `i` of a row of `len` cells, which is `cell(i)` inside the row
+    pub fn get(&self, i: int, len: uint, cell: |uint| -> bool) -> bool {
+        if 0 <= i && i < len as int {
+            return cell(i as uint);
+        }
+        match *self {
+            Dead => false,
+            Alive => true,
+            Cyclic => cell((i % len as int + len as int) as uint % len)
+        }
+    }
+}
+
+#[deriving(Clone, PartialEq, Show)]
+pub struct Automaton {
+    rule: u8,
+    boundary: Boundary,
+    cells: Vec<bool>
+}
+
+impl Automaton {
+    pub fn new(rule: u8, boundary: Boundary, cells: Vec<bool>) -> Automaton {
+        Automaton { rule: rule, boundary: boundary, cells: cells }
+    }
+
+    // A live cell in the middle of `width` cells
+    pub fn single_cell(rule: u8, boundary: Boundary, width: uint) -> Automaton {
+        Automaton::new(rule, boundary, Vec::from_fn(width, |i| i == width / 2))
+    }
+
+    // The cells of `pattern`, where `#` is a live cell and any other character a dead one
+    pub fn from_pattern(rule: u8, boundary: Boundary, pattern: &str) -> Automaton {
+        Automaton::new(rule, boundary, pattern.chars().map(|c| c == '#').collect())
+    }
+
+    pub fn cells(&self) -> &[bool] {
+        self.cells.as_slice()
+    }
+
+    // Computes the next generation
+    pub fn step(&mut self) {
+        let len = self.cells.len();
+        let next = {
+            let cells = self.cells.as_slice();
+            Vec::from_fn(len, |i| {
+                let left = self.boundary.get(i as int - 1, len, |j| cells[j]);
+                let right = self.boundary.get(i as int + 1, len, |j| cells[j]);
+                let n = ((left as uint) << 2) | ((cells[i] as uint) << 1) | (right as uint);
+                (self.rule >> n) & 1 == 1
+            })
+        };
+        self.cells = next;
+    }
+}
+
+// The current generation, then the following ones
+impl Iterator<Vec<bool>> for Automaton {
+    fn next(&mut self) -> Option<Vec<bool>> {
+        let cells = self.cells.clone();
+        self.step();
+        Some(cells)
+    }
+}
+
+// A line of `alive` and `dead` characters per generation
+pub fn to_text(generations: &[Vec<bool>], alive: char, dead: char) -> String {
+    let mut text = String::new();
+    for cells in generations.iter() {
+        for &cell in cells.iter() {
+            text.push(if cell { alive } else { dead });
+        }
+        text.push('\n');
+    }
+    text
+}
+
+// A row of pixels per generation, as wide as the widest one
+pub fn to_image(generations: &[Vec<bool>], alive: Color, dead: Color) -> Image {
+    let width = generations.iter().map(|cells| cells.len()).max().unwrap_or(0);
+    let mut image = Image::new(width, generations.len());
+    image.fill(dead);
+    for (y, cells) in generations.iter().enumerate() {
+        for (x, &cell) in cells.iter().enumerate() {
+            if cell {
+                image[(x, y)] = alive;
+            }
+        }
+    }
+    image
+}
+
+#[cfg(not(test))]
+pub fn main() {
+    for &rule in [90u8, 30, 110].iter() {
+        let automaton = Automaton::single_cell(rule, Cyclic, 64);
+        let generations: Vec<Vec<bool>> = automaton.take(16).collect();
+        println!("Rule {}:\n{}", rule, to_text(generations.as_slice(), '#', '.'));
+    }
+
+    let generations: Vec<Vec<bool>> = Automaton::single_cell(30, Dead, 512).take(256).collect();
+    let (black, white) = (Color { red: 0, green: 0, blue: 0 },
+                          Color { red: 255, green: 255, blue: 255 });
+    match to_image(generations.as_slice(), black, white).write_ppm("rule_30.ppm") {
+        Ok(()) => println!("Wrote rule_30.ppm"),
+        Err(e) => println!("Failed to write rule_30.ppm: {}", e)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitmap::Color;
+    use super::{Alive, Automaton, Boundary, Cyclic, Dead, to_image, to_text};
+
+    fn text(automaton: Automaton, generations: uint) -> String {
+        let generations: Vec<Vec<bool>> = automaton.take(generations).collect();
+        to_text(generations.as_slice(), '#', '.')
+    }
+
+    #[test]
+    fn rules() {
+        assert_eq!(text(Automaton::single_cell(90, Dead, 7), 4).as_slice(),
+                   "...#...\n..#.#..\n.#...#.\n#.#.#.#\n");
+        assert_eq!(text(Automaton::single_cell(30, Dead, 9), 4).as_slice(),
+                   "....#....\n...###...\n..##..#..\n.##.####.\n");
+        assert_eq!(text(Automaton::single_cell(110, Dead, 8), 4).as_slice(),
+                   "....#...\n...##...\n..###...\n.##.#...\n");
+        // Rule 204 keeps every cell
+        let pattern = "#..##.#";
+        assert_eq!(text(Automaton::from_pattern(204, Cyclic, pattern), 3).as_slice(),
+                   "#..##.#\n#..##.#\n#..##.#\n");
+    }
+
+    #[test]
+    fn boundaries() {
+        let check = |boundary: Boundary, rule_30: &str, rule_1: &str| {
+            assert_eq!(text(Automaton::from_pattern(30, boundary.clone(), "#...#."), 3).as_slice(),
+                       rule_30);
+            assert_eq!(text(Automaton::from_pattern(1, boundary, "......"), 2).as_slice(), rule_1);
+        };
+        check(Dead, "#...#.\n##.###\n#..#..\n", "......\n######\n");
+        check(Alive, "#...#.\n.#.##.\n.#.#..\n", "......\n.####.\n");
+        check(Cyclic, "#...#.\n##.##.\n#..#..\n", "......\n######\n");
+
+        assert_eq!(Cyclic.get(-1, 3, |i| i == 2), true);
+        assert_eq!(Cyclic.get(-4, 3, |i| i == 2), true);
+        assert_eq!(Cyclic.get(4, 3, |i| i == 1), true);
+    }
+
+    #[test]
+    fn iteration() {
+        let mut automaton = Automaton::single_cell(90, Dead, 5);
+        assert_eq!(automaton.next(), Some(vec![false, false, true, false, false]));
+        assert_eq!(automaton.cells(), [false, true, false, true, false].as_slice());
+        automaton.step();
+        assert_eq!(automaton.cells(), [true, false, false, false, true].as_slice());
+    }
+
+    #[test]
+    fn image() {
+        let generations: Vec<Vec<bool>> = Automaton::single_cell(90, Dead, 5).take(3).collect();
+        let (black, white) = (Color { red: 0, green: 0, blue: 0 },
+                              Color { red: 255, green: 255, blue: 255 });
+        let image = to_image(generations.as_slice(), black, white);
+        assert_eq!((image.width, image.height), (5, 3));
+        assert_eq!(image.to_ascii().as_slice(), "..#..\n.#.#.\n#...#\n");
+        assert_eq!(to_image(generations.as_slice(), white, black).to_ascii().as_slice(),
+                   "##.##\n#.#.#\n.###.\n");
+        assert_eq!(to_image([].as_slice(), black, white).data, vec![]);
+    }
+}
@@ -3,6 +3,9 @@
 use std::str::StrSlice;
 use std::collections::HashMap;
 use std::collections::hashmap::{Occupied, Vacant};
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
 pub fn shannon_entropy(s: &str) -> f64 {
     let mut map = HashMap::new();
@@ -24,11 +27,17 @@ pub fn shannon_entropy(s: &str) -> f64 {
 }
 
 // Needed so fibonacci_word compiles cleanly, because fibonacci_word
-// uses this code as a library
+// uses this code as a library, here and on `run`
 #[allow(dead_code)]
 #[cfg(not(test))]
-fn main() {
-    println!("{:f}", shannon_entropy("1223334444"));
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the entropy of the example string to `out`
+#[allow(dead_code)]
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
+    writeln!(out, "{:f}", shannon_entropy("1223334444"))
 }
 
 #[test]
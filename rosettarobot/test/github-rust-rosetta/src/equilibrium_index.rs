@@ -2,8 +2,11 @@
 use std::iter::AdditiveIterator;
 use std::num::Zero;
 use std::ops::Add;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
-fn equilibrium_indices<T: Add<T, T> + Sub<T, T> + Eq + Zero + Copy>(v: &[T]) -> Vec<uint> {
+pub fn equilibrium_indices<T: Add<T, T> + Sub<T, T> + Eq + Zero + Copy>(v: &[T]) -> Vec<uint> {
     let mut right = v.iter().map(|&x| x).sum();
     let mut left: T = Zero::zero();
 
@@ -20,10 +23,15 @@ fn equilibrium_indices<T: Add<T, T> + Sub<T, T> + Eq + Zero + Copy>(v: &[T]) ->
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the equilibrium indices of the example sequence to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let v = [-7i, 1, 5, 2, -4, 3, 0];
     let indices = equilibrium_indices(v);
-    println!("Equilibrium indices for {} are: {}", v.as_slice(), indices);
+    writeln!(out, "Equilibrium indices for {} are: {}", v.as_slice(), indices)
 }
 
 #[test]
@@ -18,7 +18,7 @@ use sync::raw::Mutex;
 
 // Given a duration to wait before sending an event from one process to another, returns the
 // elapsed time before the event was actually sent.
-fn handle_event(duration: Duration) -> Duration {
+pub fn handle_event(duration: Duration) -> Duration {
     // Create a Mutex.  By default a Mutex is created with a single condition variable (condvar_id
     // 0) but it can be created with an arbitrary number using Mutex::new_with_condvars();
     let mutex = Arc::new(Mutex::new());
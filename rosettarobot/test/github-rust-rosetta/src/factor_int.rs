@@ -1,31 +1,41 @@
 // Implements http://rosettacode.org/wiki/Factors_of_an_integer
 
 #[cfg(not(test))]
-fn main() {
+use std::io;
+use std::io::IoResult;
+
+#[cfg(not(test))]
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the factors of 78 to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let target = 78i;
-    println!("Factors of integer {}:", target);
+    try!(writeln!(out, "Factors of integer {}:", target));
     let factors = factor_int(target);
-    
+
     for f in factors.iter() {
-        println!("{}", f);
+        try!(writeln!(out, "{}", f));
     }
+    Ok(())
 }
 
 // Compute the factors of an integer
 // This method uses a simple check on each value between 1 and sqrt(x) to find
 // pairs of factors
-fn factor_int(x: int) -> Vec<int> {
+pub fn factor_int(x: int) -> Vec<int> {
     let mut factors: Vec<int> = Vec::new();
-    
+
     let bound: int = (x as f64).sqrt().floor() as int;
-    
+
     for i in range(1i, bound) {
         if x % i == 0 {
             factors.push(i);
             factors.push(x/i);
         }
     }
-    
+
     factors
 }
 
@@ -1,9 +1,12 @@
 // Implements http://rosettacode.org/wiki/Factorial
 
 use std::iter::range_inclusive;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
 // Calculate the factorial using recursion
-fn factorial_recursive (n: uint) -> uint {
+pub fn factorial_recursive (n: uint) -> uint {
     match n {
         0 => 1,
         _ => n * factorial_recursive(n-1)
@@ -11,12 +14,12 @@ fn factorial_recursive (n: uint) -> uint {
 }
 
 // Calculate the factorial using a fold
-fn factorial_iterative(n: uint) -> uint {
+pub fn factorial_iterative(n: uint) -> uint {
     range_inclusive(1, n).fold(1, |p, t| p * t)
 }
 
 // Calculate the factorial using a for loop
-fn factorial_loop(n: uint) -> uint {
+pub fn factorial_loop(n: uint) -> uint {
     let mut fac = 1;
     for x in range_inclusive(1, n) {
         fac *= x;
@@ -25,16 +28,22 @@ fn factorial_loop(n: uint) -> uint {
 }
 
 #[cfg(not(test))]
-fn main () {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the factorials of 1 to 9 with each implementation to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let fs = vec![("Recursive", factorial_recursive),
                   ("Iterative", factorial_iterative),
                   ("Looooooop", factorial_loop)];
     for (name, f) in fs.into_iter() {
-        println!("---------\n{}", name)
+        try!(writeln!(out, "---------\n{}", name));
         for i in range(1u, 10) {
-            println!("{}", f(i))
+            try!(writeln!(out, "{}", f(i)));
         }
     }
+    Ok(())
 }
 
 // Some tests and benchmarks
@@ -2,9 +2,12 @@
 
 extern crate num;
 
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 use num::complex::Complex;
 
-fn fft(arr: &[Complex<f32>]) -> Vec<Complex<f32>> {
+pub fn fft(arr: &[Complex<f32>]) -> Vec<Complex<f32>> {
     if arr.len() <= 1 {
         return arr.to_vec()
     }
@@ -34,7 +37,12 @@ fn fft(arr: &[Complex<f32>]) -> Vec<Complex<f32>> {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the transform of a square wave to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let test = [
         Complex::new(1f32, 0f32),
         Complex::new(1f32, 0f32),
@@ -47,7 +55,7 @@ fn main() {
     ];
 
     let test_fft = fft(test.as_slice());
-    println!("{}", test_fft);
+    writeln!(out, "{}", test_fft)
 }
 
 #[cfg(test)]
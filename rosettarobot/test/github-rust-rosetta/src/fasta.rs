@@ -6,7 +6,7 @@ use std::io::fs::File;
 use std::io::BufferedReader;
 
 // We use a type parameter bound `<T: Buffer>` to accept all kinds of buffers
-fn format_fasta<T: Buffer>(reader: &mut T) -> String {
+pub fn format_fasta<T: Buffer>(reader: &mut T) -> String {
     reader.lines().map(|l| l.unwrap()).fold(String::new(), |mut out, line| {
         // We need to trim new lines
         let ln = line.as_slice().trim();
@@ -29,13 +29,13 @@ fn format_fasta<T: Buffer>(reader: &mut T) -> String {
     })
 }
 
-fn read_file() -> String {
+pub fn read_file() -> String {
     let file = File::open(&Path::new("src/resources/test_data.fasta"));
     format_fasta(&mut BufferedReader::new(file))
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
     let s = read_file();
     println!("{}", s);
 }
@@ -1,19 +1,29 @@
 // Implements http://rosettacode.org/wiki/Fibonacci_sequence
 #[cfg(not(test))]
-fn main() {
+use std::io;
+use std::io::IoResult;
+
+#[cfg(not(test))]
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the first ten numbers with each implementation to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let fns = vec![(fib_recursive, "recursive"),
                    (fib_tail_recursive, "tail recursive"),
                    (fib_iterative, "iterative")];
 
     for (f, desc) in fns.into_iter() {
         let r = range(0u64, 10).map(|i| f(i)).collect::<Vec<u64>>();
-        println!("{} implementation:\n{}\n", desc, r);
+        try!(writeln!(out, "{} implementation:\n{}\n", desc, r));
     }
+    Ok(())
 }
 
 // Fibonacci "classic" recursive version
 // not tail recursive (it's going to blow the stack for n too high)
-fn fib_recursive(n: u64) -> u64 {
+pub fn fib_recursive(n: u64) -> u64 {
     match n {
         0 | 1 => n,
         n => fib_recursive(n - 1) + fib_recursive(n - 2)
@@ -21,7 +31,7 @@ fn fib_recursive(n: u64) -> u64 {
 }
 
 // tail recursive version
-fn fib_tail_recursive(n: u64) -> u64 {
+pub fn fib_tail_recursive(n: u64) -> u64 {
     fn in_fib(n : u64, current : u64, next : u64) -> u64 {
         match n {
             0 => current,
@@ -33,7 +43,7 @@ fn fib_tail_recursive(n: u64) -> u64 {
 }
 
 // iterative version
-fn fib_iterative(n: u64) -> u64 {
+pub fn fib_iterative(n: u64) -> u64 {
     let (mut cur, mut next) = (0u64, 1u64);
 
     for _ in range(0u64, n) {
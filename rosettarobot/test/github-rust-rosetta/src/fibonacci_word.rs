@@ -2,12 +2,15 @@
 
 use entropy::shannon_entropy;
 use std::iter::range_inclusive;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 mod entropy;
 
 // Returns "amount" fibonacci words as a vector of tuples
 // The first value of the tuple is the length of the word
 // and the second one its entropy
-fn fib_words(amount: uint) -> Vec<(uint, f64)> {
+pub fn fib_words(amount: uint) -> Vec<(uint, f64)> {
     let mut data = Vec::with_capacity(amount);
     let mut previous = String::from_str("1");
     let mut next = String::from_str("0");
@@ -28,17 +31,23 @@ fn fib_words(amount: uint) -> Vec<(uint, f64)> {
     data
 }
 #[cfg(not(test))]
-fn main() {
-    println!("Calculating... This may take a couple of minutes...\n");
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the length and entropy of the first 18 words to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
+    try!(writeln!(out, "Calculating... This may take a couple of minutes...\n"));
 
     let words = fib_words(18);
     let mut i = 1i;
 
-    println!("{:>2}:{:>10} {}", "N", "length", "entropy");
+    try!(writeln!(out, "{:>2}:{:>10} {}", "N", "length", "entropy"));
     for &(length, entropy) in words.iter() {
-        println!("{:>2i}:{:>10u} {:.15f}", i, length, entropy);
+        try!(writeln!(out, "{:>2i}:{:>10u} {:.15f}", i, length, entropy));
         i += 1;
     }
+    Ok(())
 }
 #[test]
 fn test_fibonacii_words() {
@@ -2,7 +2,7 @@
 
 use std::io::fs::PathExtensions;
 
-fn main() {
+pub fn main() {
     let path_wd = Path::new("input.txt");
     println!("{}", path_wd.stat().unwrap().size);
 
@@ -1,30 +1,33 @@
 // Implements http://rosettacode.org/wiki/Four_bit_adder
 use std::{fmt, num};
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
 // primitive gates
-fn not(a: bool) -> bool { !a }
-fn or(a: bool, b: bool) -> bool { a || b }
-fn and(a: bool, b: bool) -> bool { a && b }
+pub fn not(a: bool) -> bool { !a }
+pub fn or(a: bool, b: bool) -> bool { a || b }
+pub fn and(a: bool, b: bool) -> bool { a && b }
 
 // xor gate [2x not, 2x and, 1x or]
 // (A & !B) | (B & !A)
-fn xor(a: bool, b: bool) -> bool { or(and(a, not(b)), and(b, not(a))) }
+pub fn xor(a: bool, b: bool) -> bool { or(and(a, not(b)), and(b, not(a))) }
 
 // half adder [1x xor, 1x and]
 // S = A ^ B, C = A & B
-fn half_adder(a: bool, b: bool) -> (bool, bool) { (xor(a, b), and(a, b)) }
+pub fn half_adder(a: bool, b: bool) -> (bool, bool) { (xor(a, b), and(a, b)) }
 
 // full adder [2x half_adder, 1x or]
 // t = (C0 + A), t2 = t.S + B
 // S = t2.S, C = t.C | t2.C
-fn full_adder(a: bool, b: bool, carry: bool) -> (bool, bool) {
+pub fn full_adder(a: bool, b: bool, carry: bool) -> (bool, bool) {
   let (s0, c0) = half_adder(carry, a);
   let (s1, c1) = half_adder(s0, b);
 
   (s1, or(c0, c1))
 }
 
-struct Nibble([bool,.. 4]);
+pub struct Nibble([bool,.. 4]);
 impl Nibble {
   fn new(arr: [u8,.. 4]) -> Nibble {
     Nibble([arr[0] != 0, arr[1] != 0, arr[2] != 0, arr[3] != 0])
@@ -59,7 +62,7 @@ impl<'a> Deref<[bool,.. 4]> for Nibble {
 
 // 4bit adder [4x full_adder]
 // calculate each bit of the sum, propogate the carry
-fn four_bit_adder(a: Nibble, b: Nibble, carry: bool) -> (Nibble, bool) {
+pub fn four_bit_adder(a: Nibble, b: Nibble, carry: bool) -> (Nibble, bool) {
   let (s0, carry) = full_adder(a[3], b[3], carry);
   let (s1, carry) = full_adder(a[2], b[2], carry);
   let (s2, carry) = full_adder(a[1], b[1], carry);
@@ -69,14 +72,19 @@ fn four_bit_adder(a: Nibble, b: Nibble, carry: bool) -> (Nibble, bool) {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+  run(&mut io::stdout()).unwrap();
+}
+
+// Writes the sum of 11 and 6 to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
   let nib_a = Nibble::new([1u8, 0, 1, 1]);
   let a = nib_a.to_u8(false);
   let b = 6;
   let nib_b = Nibble::from_u8(b);
   let (result, carry) = four_bit_adder(nib_a, nib_b, false);
-  println!("{} + {} = {} | {} + {} = {} | overflow: {}",
-            a, b, result.to_u8(carry), nib_a, nib_b, result, carry)
+  writeln!(out, "{} + {} = {} | {} + {} = {} | overflow: {}",
+           a, b, result.to_u8(carry), nib_a, nib_b, result, carry)
 }
 
 #[test]
@@ -123,7 +131,7 @@ fn test_full_add() {
 
 #[test]
 fn test_four_bit_adder() {
-  for (a, b) in range(0, std::u8::MAX).map(|n| (n >> 4, n & 15)) {
+  for (a, b) in range(0, ::std::u8::MAX).map(|n| (n >> 4, n & 15)) {
     let nib_a = Nibble::from_u8(a);
     let nib_b = Nibble::from_u8(b);
 
@@ -1,9 +1,16 @@
 // http://rosettacode.org/wiki/Function_composition
-#[cfg(not(test))]
 use std::f32::consts;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes PI composed through two conversions to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     fn f(x: uint) -> String { x.to_string() }
     fn g(x: f32) -> uint { x as uint }
 
@@ -12,13 +19,13 @@ fn main() {
     // the uint to a String
     // via function composition
     let comp = compose(f, g);
-    println!("{}", comp(consts::PI));
+    writeln!(out, "{}", comp(consts::PI))
 }
 
 // the future unboxed closures should
 // allow to compose closures, not just bare functions (so that environment can be captured)
 // Also returning a proc has the limitation that the composed function can only be called once
-fn compose<A, B, C>(f: fn(A) -> B, g: fn(C) -> A) -> proc(C): 'static -> B {
+pub fn compose<A, B, C>(f: fn(A) -> B, g: fn(C) -> A) -> proc(C): 'static -> B {
     proc(x: C) {f(g(x))}
 }
 
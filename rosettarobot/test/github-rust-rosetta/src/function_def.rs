@@ -1,8 +1,12 @@
 //http://rosettacode.org/wiki/Function_definition
 
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
+
 // Function taking 2 ints, multply them and return the value
 
-fn multiply(x: int, y: int) -> int
+pub fn multiply(x: int, y: int) -> int
 {
   // In Rust a statement is a expression. An expression at the end of a
   // function without semicolon is a return expression
@@ -10,7 +14,7 @@ fn multiply(x: int, y: int) -> int
 }
 
 //generic version of multiply
-fn multiply_gen<T: Mul<T, T>>(x: T, y: T) -> T
+pub fn multiply_gen<T: Mul<T, T>>(x: T, y: T) -> T
 {
   x * y
 }
@@ -28,9 +32,15 @@ fn test_multiply()
 }
 
 #[cfg(not(test))]
-fn main()
+pub fn main()
+{
+  run(&mut io::stdout()).unwrap();
+}
+
+// Writes a few products to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()>
 {
-  println!("2 multiply 4 = {}", multiply(2i,4));
-  println!("2.0 multiply 4.0 = {}", multiply_gen(2.0f32, 4.0));
-  println!("5.0 multiply 7.0 is {}", multiply_gen(5.0 as f32, 7.0 as f32));
+  try!(writeln!(out, "2 multiply 4 = {}", multiply(2i,4)));
+  try!(writeln!(out, "2.0 multiply 4.0 = {}", multiply_gen(2.0f32, 4.0)));
+  writeln!(out, "5.0 multiply 7.0 is {}", multiply_gen(5.0 as f32, 7.0 as f32))
 }
@@ -1,12 +1,16 @@
 // Implements http://rosettacode.org/wiki/Gray_code
 
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
+
 // Encode an uint
-fn gray_encode(integer: uint) -> uint {
+pub fn gray_encode(integer: uint) -> uint {
     (integer >> 1) ^ integer
 }
 
 // Decode an uint
-fn gray_decode(integer: uint) -> uint {
+pub fn gray_decode(integer: uint) -> uint {
     match integer {
         0 => 0,
         _ => integer ^ gray_decode(integer >> 1)
@@ -14,11 +18,17 @@ fn gray_decode(integer: uint) -> uint {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the 5-bit Gray codes to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     for i in range(0u,32u) {
-        println!("{:2} {:0>5t} {:0>5t} {:2}", i, i, gray_encode(i),
-            gray_decode(i));
+        try!(writeln!(out, "{:2} {:0>5t} {:0>5t} {:2}", i, i, gray_encode(i),
+            gray_decode(i)));
     }
+    Ok(())
 }
 
 #[test]
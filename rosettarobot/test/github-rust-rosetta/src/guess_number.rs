@@ -2,7 +2,7 @@
 use std::rand::{task_rng, Rng};
 use std::io::stdio::stdin;
 
-fn main() {
+pub fn main() {
     let mystery_number = task_rng().gen_range(0i, 10) + 1;
     println!("Guess my number between 1 and 10");
 
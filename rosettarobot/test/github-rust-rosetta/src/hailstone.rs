@@ -1,14 +1,18 @@
 // Implements http://rosettacode.org/wiki/Hailstone_sequence
 
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
+
 // Define a struct which stores the state for the iterator.
-struct Hailstone {
+pub struct Hailstone {
     next: uint, // Accessible only to the current module.
     pub start: uint  // Publically accessible.
 }
 
 impl Hailstone {
   // Define a constructor for the struct.
-    fn new(n: uint) -> Hailstone {
+    pub fn new(n: uint) -> Hailstone {
         Hailstone { next: n, start: n }
     }
 }
@@ -39,26 +43,31 @@ impl Iterator<uint> for Hailstone {
 }
 
 /// Returns the start number and length of the longest hailstone sequence up to `limit`
-fn biggest_hailstone(limit: uint) -> (uint, uint) {
+pub fn biggest_hailstone(limit: uint) -> (uint, uint) {
     range(0u, limit).map(|n| (n, Hailstone::new(n).count()))
                     .max_by(|&(_, count)| count)
                     .unwrap()
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the sequence of 27 and the longest sequence below 100000 to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     // Find the hailstone for 27.
     let two_seven = Hailstone::new(27).collect::<Vec<uint>>();
     let ts_len = two_seven.len();
-    println!("Testing: {}, Length: {}, Values: {}...{}",
-            two_seven[0],
-            ts_len,
-            two_seven.slice(0, 4),
-            two_seven.slice(ts_len - 4, ts_len));
+    try!(writeln!(out, "Testing: {}, Length: {}, Values: {}...{}",
+                       two_seven[0],
+                       ts_len,
+                       two_seven.slice(0, 4),
+                       two_seven.slice(ts_len - 4, ts_len)));
 
     // Find the longest.
     let (biggest, length) = biggest_hailstone(100000);
-    println!("Largest: {}, Size: {}", biggest, length);
+    writeln!(out, "Largest: {}, Size: {}", biggest, length)
 }
 
 #[test]
@@ -8,7 +8,7 @@ use std::collections::{RingBuf, Deque};
 // needed because hamming_numbers_alt uses this as a library
 #[allow(dead_code)]
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
     // capacity of the queue currently needs to be a power of 2 because of a bug with RingBuf
     let hamming : Hamming<BigUint> = Hamming::new(128);
 
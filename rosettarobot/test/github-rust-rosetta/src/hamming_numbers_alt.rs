@@ -6,22 +6,33 @@ extern crate num;
 
 use hamming_numbers::{Hamming, HammingNumber};
 use std::num::{One, pow};
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 use num::bigint::{BigUint, ToBigUint};
 
 mod hamming_numbers;
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the first 20, the 1691st and the millionth Hamming numbers to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     // capacity of the queue currently needs to be a power of 2 because of a bug with RingBuf
     let hamming : Hamming<HammingTriple> = Hamming::new(128);
 
     for (idx, h) in hamming.enumerate().take(1_000_000) {
         match idx + 1 {
-            1...20 => print!("{} ", h.to_biguint().unwrap()),
-            i @ 1691 | i @ 1_000_000 => println!("\n{}th number: {}", i, h.to_biguint().unwrap()),
+            1...20 => try!(write!(out, "{} ", h.to_biguint().unwrap())),
+            i @ 1691 | i @ 1_000_000 => {
+                try!(writeln!(out, "\n{}th number: {}", i, h.to_biguint().unwrap()))
+            }
             _ =>  continue
         }
     }
+    Ok(())
 }
 
 // we store these to calculate the ln of a hamming number
@@ -79,7 +90,7 @@ impl ToBigUint for HammingTriple {
 }
 
 impl HammingTriple {
-    fn new(pow_2: uint, pow_3: uint, pow_5: uint) -> HammingTriple {
+    pub fn new(pow_2: uint, pow_3: uint, pow_5: uint) -> HammingTriple {
         HammingTriple {
             pow_2: pow_2,
             pow_3: pow_3,
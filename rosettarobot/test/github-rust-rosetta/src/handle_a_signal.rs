@@ -6,7 +6,7 @@ extern crate libc;
 extern crate time;
 
 #[cfg(all(unix, not(test)))]
-fn main()
+pub fn main()
 {
     use libc::consts::os::posix88::SIGINT;
     use libc::funcs::posix01::signal;
@@ -55,7 +55,7 @@ fn main()
 }
 
 #[cfg(not(unix))]
-fn main()
+pub fn main()
 {
     println!("Not supported on this platform");
 }
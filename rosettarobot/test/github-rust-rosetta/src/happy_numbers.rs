@@ -1,10 +1,12 @@
 // Implements http://rosettacode.org/wiki/Happy_numbers
 
 use std::collections::treemap::TreeSet;
-#[cfg(not(test))]
 use std::iter::count;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
-fn digits(mut n: uint) -> Vec<uint> {
+pub fn digits(mut n: uint) -> Vec<uint> {
     let mut ds = vec![];
     if n == 0 {
         return vec![0];
@@ -17,7 +19,7 @@ fn digits(mut n: uint) -> Vec<uint> {
     ds
 }
 
-fn is_happy(mut x: uint) -> bool {
+pub fn is_happy(mut x: uint) -> bool {
     let mut past = TreeSet::new();
     while x != 1 {
         // Take the sum of the squares of the digits of x
@@ -34,13 +36,18 @@ fn is_happy(mut x: uint) -> bool {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the first 8 happy numbers to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     // Print the first 8 happy numbers
     let v: Vec<uint> = count(1u, 1)
         .filter(|x| is_happy(*x))
         .take(8)
         .collect();
-    println!("{}", v)
+    writeln!(out, "{}", v)
 }
 
 #[test]
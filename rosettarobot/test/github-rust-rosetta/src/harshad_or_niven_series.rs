@@ -1,10 +1,17 @@
 // http://rosettacode.org/wiki/Harshad_or_Niven_series
+use std::io::{mod, IoResult};
 use std::uint;
-fn main() {
+
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the first 20 Harshad numbers, and the first one greater than 1000, to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let digit_sum = |i: uint| i.to_string().as_slice().chars()
         .fold(0u, |d, c| d + c.to_digit(10).unwrap());
     let mut harshads = range(1u, uint::MAX).filter(|&n| n % digit_sum(n) == 0);
 
-    for _ in range(0u, 20) { print!("{} ", harshads.next().unwrap()) }
-    println!("\n{}", harshads.skip_while(|&h| h <= 1000).next().unwrap());
+    for _ in range(0u, 20) { try!(write!(out, "{} ", harshads.next().unwrap())) }
+    writeln!(out, "\n{}", harshads.skip_while(|&h| h <= 1000).next().unwrap())
 }
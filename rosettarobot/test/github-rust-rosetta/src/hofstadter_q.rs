@@ -1,14 +1,18 @@
 // Implements an iterable version of http://rosettacode.org/wiki/Hofstadter_Q_sequence
 
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
+
 // Define a struct which stores the state for the iterator.
-struct HofstadterQ {
+pub struct HofstadterQ {
     next: uint,
     memoize_vec: Vec<uint>
 }
 
 impl HofstadterQ {
   // Define a constructor for the struct.
-    fn new() -> HofstadterQ {
+    pub fn new() -> HofstadterQ {
         HofstadterQ { next: 1, memoize_vec: vec![1] }
     }
 }
@@ -33,7 +37,12 @@ impl Iterator<uint> for HofstadterQ {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the first 1000 terms of the sequence to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     // Set up the iterable.
     let hof: HofstadterQ = HofstadterQ::new();
     // The number of terms we want from the iterator.
@@ -41,12 +50,13 @@ fn main() {
     // Create the iterator.
     let mut it = hof.take(upto - 2);
     // Print the base values.
-    println!("H(1) = 1");
-    println!("H(2) = 1");
+    try!(writeln!(out, "H(1) = 1"));
+    try!(writeln!(out, "H(2) = 1"));
     // Print the rest of the sequence.
     for i in range(3u, 1+upto) {
-        println!("H({}) = {}", i, it.next().unwrap());
+        try!(writeln!(out, "H({}) = {}", i, it.next().unwrap()));
     }
+    Ok(())
 }
 
 #[test]
@@ -1,12 +1,22 @@
+// Implements http://rosettacode.org/wiki/Horner%27s_rule_for_polynomial_evaluation
+
 use std::num::zero;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
-fn horner<T:Num>(cs:&[T], x:T) -> T {
+pub fn horner<T:Num>(cs:&[T], x:T) -> T {
     cs.iter().rev().fold(zero::<T>(), |acc, c| (acc*x) + (*c))
 }
 
-#[cfg(not(test))] 
-fn main() {
-    println!("{}", horner([-19i, 7, -4, 6], 3i)); // 128
+#[cfg(not(test))]
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the value of a polynomial at 3 to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
+    writeln!(out, "{}", horner([-19i, 7, -4, 6], 3i)) // 128
 }
 
 #[cfg(test)]
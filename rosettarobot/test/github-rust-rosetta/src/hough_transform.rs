@@ -2,86 +2,25 @@
 //
 // Contributed by Gavin Baker <gavinb@antonym.org>
 // Adapted from the Go version
+//
+// Besides the accumulator image of the task, `detect_lines` finds the lines of an image: it keeps
+// the edges found by the Sobel operator, accumulates their votes, and returns the peaks which are
+// the largest of their neighborhood.  A dark line has an edge on each side, so the distance of a
+// peak is the mean of the strong ones next to it, which puts it between both edges.
 
 #![allow(dead_code)]
 
-use std::io::{BufferedReader, BufferedWriter, File};
-
-// Simple 8-bit grayscale image
-
-struct ImageGray8 {
-    width: uint,
-    height: uint,
-    data: Vec<u8>,
-}
-
-fn load_pgm(filename: &str) -> ImageGray8 {
-
-    // Open file
-
-    let path = Path::new(filename);
-    let mut file = BufferedReader::new(File::open(&path));
-
-    // Read header
-
-    let magic_in = file.read_line().unwrap();
-    let width_in = file.read_line().unwrap();
-    let height_in = file.read_line().unwrap();
-    let maxval_in = file.read_line().unwrap();
-
-    assert!(magic_in.as_slice() == "P5\n");
-    assert!(maxval_in.as_slice() == "255\n");
-
-    // Parse header
-
-    let width: uint = from_str(width_in.as_slice().trim()).unwrap();
-    let height: uint = from_str(height_in.as_slice().trim()).unwrap();
-
-    println!("Reading pgm file {}: {} x {}", filename, width, height);
-
-    // Create image and allocate buffer
-
-    let mut img = ImageGray8 {
-        width: width,
-        height: height,
-        data: Vec::from_elem(width*height, 0),
-    };
-
-    // Read image data
-
-    match file.read_at_least(img.data.len(), img.data.as_mut_slice()) {
-        Ok(bytes_read) => println!("Read {} bytes", bytes_read),
-        Err(e) => println!("error reading: {}", e)
-    }
-
-    img
-}
-
-fn save_pgm(img: &ImageGray8, filename: &str) {
-
-    // Open file
-
-    let path = Path::new(filename);
-    let mut file = BufferedWriter::new(File::create(&path));
-
-    // Write header
-
-    match file.write_line(format!("P5\n{}\n{}\n255", img.width, img.height).as_slice()) {
-        Err(e) => println!("Failed to write header: {}", e),
-        _ => {},
-    }
-
-    println!("Writing pgm file {}: {} x {}", filename, img.width, img.height);
-
-    // Write binary image data
+use std::cmp;
+use std::f64::consts::PI;
+use bitmap::{Color, Image, ImageGray8, Pnm};
+use bitmap_bresenhams_line_algorithm::DrawLine;
+mod bitmap;
+// With the path of the file, so that its own `mod bitmap` is found next to it
+#[allow(dead_code)]
+#[path = "bitmap_bresenhams_line_algorithm.rs"]
+mod bitmap_bresenhams_line_algorithm;
 
-    match file.write(img.data.as_slice()) {
-        Err(e) => println!("Failed to image data: {}", e),
-        _ => {},
-    }
-}
-
-fn hough(image: &ImageGray8, out_width: uint, out_height: uint) -> ImageGray8 {
+pub fn hough(image: &ImageGray8, out_width: uint, out_height: uint) -> ImageGray8 {
 
     let in_width = image.width;
     let in_height = image.height;
@@ -99,7 +38,7 @@ fn hough(image: &ImageGray8, out_width: uint, out_height: uint) -> ImageGray8 {
 
     let rmax = (in_width as f64).hypot(in_height as f64);
     let dr = rmax / (out_height/2) as f64;
-    let dth = std::f64::consts::PI / out_width as f64;
+    let dth = ::std::f64::consts::PI / out_width as f64;
 
     // Process input image in raster order
 
@@ -129,12 +68,352 @@ fn hough(image: &ImageGray8, out_width: uint, out_height: uint) -> ImageGray8 {
     accum
 }
 
+// The parameters of `detect_lines`
+pub struct Config {
+    theta_steps: uint,
+    rho_step: f64,
+    edge_threshold: f64,
+    min_votes: uint,
+    suppression: uint,
+    max_lines: Option<uint>
+}
+
+impl Config {
+    // Angles by steps of 1 degree and distances by steps of 1 pixel, edges with a gradient of at
+    // least 128 and lines of at least 50 pixels, with only the strongest within 5 steps
+    pub fn new() -> Config {
+        Config { theta_steps: 180, rho_step: 1.0, edge_threshold: 128.0, min_votes: 50,
+                 suppression: 5, max_lines: None }
+    }
+
+    // Sets the number of angles, from 0 to pi (excluded)
+    pub fn theta_steps(self, theta_steps: uint) -> Config {
+        assert!(theta_steps > 0, "there must be at least one angle");
+        Config { theta_steps: theta_steps, ..self }
+    }
+
+    // Sets the resolution of the distances, in pixels
+    pub fn rho_step(self, rho_step: f64) -> Config {
+        assert!(rho_step > 0.0, "the distance step must be positive");
+        Config { rho_step: rho_step, ..self }
+    }
+
+    // Sets the smallest magnitude of the gradient of an edge.  The magnitude of the Sobel operator
+    // is up to 4 * sqrt(2) * 255, and 1020 between black and white.
+    pub fn edge_threshold(self, edge_threshold: f64) -> Config {
+        Config { edge_threshold: edge_threshold, ..self }
+    }
+
+    // Sets the smallest number of edge pixels of a line
+    pub fn min_votes(self, min_votes: uint) -> Config {
+        Config { min_votes: min_votes, ..self }
+    }
+
+    // Sets the radius of the non-maximum suppression, in steps of the accumulator: a line must
+    // have more votes than the others within that number of steps of angle and distance
+    pub fn suppression(self, suppression: uint) -> Config {
+        Config { suppression: suppression, ..self }
+    }
+
+    // Keeps only the lines with the most votes
+    pub fn max_lines(self, max_lines: uint) -> Config {
+        Config { max_lines: Some(max_lines), ..self }
+    }
+}
+
+// The line of the points (x, y) where x * cos(theta) + y * sin(theta) = rho, with theta from 0
+// (a vertical line) to pi, in radians, and y going down.  `votes` is its number of edge pixels.
+#[deriving(Clone, PartialEq, Show)]
+pub struct Line {
+    pub rho: f64,
+    pub theta: f64,
+    pub votes: uint
+}
+
+// Keeps the edges of an image, in black on white, where the magnitude of the gradient given by
+// the Sobel operator reaches `threshold`.  The pixels outside of the image are those of the border.
+pub fn detect_edges(image: &ImageGray8, threshold: f64) -> ImageGray8 {
+    let mut edges = ImageGray8::new(image.width, image.height);
+    let (width, height) = (image.width as int, image.height as int);
+    let pixel = |x: int, y: int| {
+        image.data[(cmp::min(cmp::max(x, 0), width - 1) +
+                    cmp::min(cmp::max(y, 0), height - 1) * width) as uint] as f64
+    };
+    for y in range(0, height) {
+        for x in range(0, width) {
+            let gx = pixel(x + 1, y - 1) + 2.0 * pixel(x + 1, y) + pixel(x + 1, y + 1)
+                     - pixel(x - 1, y - 1) - 2.0 * pixel(x - 1, y) - pixel(x - 1, y + 1);
+            let gy = pixel(x - 1, y + 1) + 2.0 * pixel(x, y + 1) + pixel(x + 1, y + 1)
+                     - pixel(x - 1, y - 1) - 2.0 * pixel(x, y - 1) - pixel(x + 1, y - 1);
+            edges.set(x, y, if gx.hypot(gy) >= threshold { 0 } else { 255 });
+        }
+    }
+    edges
+}
+
+// The votes of every black pixel for the lines going through it, by angle and then by distance
+pub struct Accumulator {
+    pub theta_steps: uint,
+    pub rho_steps: uint,
+    pub rho_step: f64,
+    pub votes: Vec<uint>
+}
+
+impl Accumulator {
+    pub fn new(image: &ImageGray8, theta_steps: uint, rho_step: f64) -> Accumulator {
+        // Distances from -rho_max to rho_max, with 0 in the middle
+        let rho_max = (image.width as f64).hypot(image.height as f64);
+        let rho_center = (rho_max / rho_step).ceil() as uint;
+        let rho_steps = 2 * rho_center + 1;
+        let mut accum = Accumulator { theta_steps: theta_steps, rho_steps: rho_steps,
+                                      rho_step: rho_step,
+                                      votes: Vec::from_elem(theta_steps * rho_steps, 0) };
+        let angles: Vec<(f64, f64)> = range(0, theta_steps).map(|t| {
+            let theta = accum.theta(t);
+            (theta.cos(), theta.sin())
+        }).collect();
+
+        for y in range(0, image.height) {
+            for x in range(0, image.width) {
+                if image.data[x + y * image.width] == 255 {
+                    continue;
+                }
+                for (t, &(cos, sin)) in angles.iter().enumerate() {
+                    let rho = x as f64 * cos + y as f64 * sin;
+                    let r = (rho / rho_step).round() as int + rho_center as int;
+                    accum.votes[t * accum.rho_steps + r as uint] += 1;
+                }
+            }
+        }
+        accum
+    }
+
+    pub fn theta(&self, t: uint) -> f64 {
+        PI * t as f64 / self.theta_steps as f64
+    }
+
+    pub fn rho(&self, r: uint) -> f64 {
+        (r as f64 - (self.rho_steps / 2) as f64) * self.rho_step
+    }
+
+    // The votes at a step of angle and distance.  The angles past pi are those from 0 with the
+    // opposite distance.
+    fn get(&self, t: int, r: int) -> Option<(uint, uint)> {
+        let (theta_steps, rho_steps) = (self.theta_steps as int, self.rho_steps as int);
+        let turns = if t >= 0 { t / theta_steps } else { (t + 1) / theta_steps - 1 };
+        let (t, r) = (t - turns * theta_steps, if turns % 2 == 0 { r } else { rho_steps - 1 - r });
+        if r < 0 || r >= rho_steps {
+            None
+        } else {
+            let index = t as uint * self.rho_steps + r as uint;
+            Some((index, self.votes[index]))
+        }
+    }
+
+    // The lines with at least `min_votes` votes and more votes than all the others within
+    // `radius` steps.  Between equal neighbors, the first one is kept.  The lines with the most
+    // votes come first, at the distance given by `mean_rho`.
+    pub fn peaks(&self, min_votes: uint, radius: uint) -> Vec<Line> {
+        let radius = radius as int;
+        let mut lines = Vec::new();
+        for t in range(0, self.theta_steps) {
+            for r in range(0, self.rho_steps) {
+                let index = t * self.rho_steps + r;
+                let votes = self.votes[index];
+                if votes == 0 || votes < min_votes {
+                    continue;
+                }
+                let mut peak = true;
+                for dt in range(-radius, radius + 1) {
+                    for dr in range(-radius, radius + 1) {
+                        match self.get(t as int + dt, r as int + dr) {
+                            Some((i, v)) if i < index && v >= votes => peak = false,
+                            Some((i, v)) if i > index && v > votes => peak = false,
+                            _ => ()
+                        }
+                    }
+                }
+                if peak {
+                    lines.push(Line { rho: self.mean_rho(t, r, radius), theta: self.theta(t),
+                                      votes: votes });
+                }
+            }
+        }
+        // Stable, so the lines with as many votes stay in order
+        lines.sort_by(|a, b| b.votes.cmp(&a.votes));
+        lines
+    }
+
+    // The mean of the distances within `radius` steps of `r`, at the angle `t`, which have at
+    // least half of the votes of `r`, weighted by their votes.  The edges on both sides of a line
+    // get as many votes, and the line is between them.
+    fn mean_rho(&self, t: uint, r: uint, radius: int) -> f64 {
+        let votes = self.votes[t * self.rho_steps + r];
+        let (mut sum, mut total) = (0.0, 0.0);
+        for r in range(r as int - radius, r as int + radius + 1) {
+            if r < 0 || r >= self.rho_steps as int {
+                continue;
+            }
+            let v = self.votes[t * self.rho_steps + r as uint];
+            if 2 * v >= votes {
+                sum += self.rho(r as uint) * v as f64;
+                total += v as f64;
+            }
+        }
+        sum / total
+    }
+
+    // The votes in shades of gray, from white for none to black for the most
+    pub fn to_image(&self) -> ImageGray8 {
+        let max = cmp::max(self.votes.iter().map(|&v| v).max().unwrap_or(0), 1);
+        let mut image = ImageGray8::new(self.theta_steps, self.rho_steps);
+        for t in range(0, self.theta_steps) {
+            for r in range(0, self.rho_steps) {
+                let votes = self.votes[t * self.rho_steps + r];
+                image.data[t + r * self.theta_steps] = (255 - votes * 255 / max) as u8;
+            }
+        }
+        image
+    }
+}
+
+// Finds the lines of an image
+pub fn detect_lines(image: &ImageGray8, config: &Config) -> Vec<Line> {
+    let edges = detect_edges(image, config.edge_threshold);
+    let accum = Accumulator::new(&edges, config.theta_steps, config.rho_step);
+    let mut lines = accum.peaks(config.min_votes, config.suppression);
+    match config.max_lines {
+        Some(max_lines) => lines.truncate(max_lines),
+        None => ()
+    }
+    lines
+}
+
+// Draws lines across a color copy of an image
+pub fn draw_lines(image: &ImageGray8, lines: &[Line], color: Color) -> Image {
+    let mut result = image.to_color();
+    // Long enough to cross the image from the closest point of the line to the origin
+    let length = 2.0 * (image.width as f64).hypot(image.height as f64);
+    for line in lines.iter() {
+        let (cos, sin) = (line.theta.cos(), line.theta.sin());
+        let (x, y) = (line.rho * cos, line.rho * sin);
+        let from = ((x + length * sin).round() as int, (y - length * cos).round() as int);
+        let to = ((x - length * sin).round() as int, (y + length * cos).round() as int);
+        result.draw_line(from, to, color);
+    }
+    result
+}
+
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
 
-    let image = load_pgm("../src/resources/Pentagon.pgm");
+    let path = Path::new("../src/resources/Pentagon.pgm");
+    let image = match Pnm::open(&path) {
+        Ok(pnm) => pnm.to_gray(),
+        Err(e) => fail!("failed to read {}: {}", path.display(), e)
+    };
+    println!("Read {}: {} x {}", path.display(), image.width, image.height);
 
     let accum = hough(&image, 460, 360);
 
-    save_pgm(&accum, "hough.pgm");
+    match accum.to_pnm(true).save(&Path::new("hough.pgm")) {
+        Ok(()) => println!("Wrote hough.pgm: {} x {}", accum.width, accum.height),
+        Err(e) => println!("Failed to write hough.pgm: {}", e)
+    }
+
+    let lines = detect_lines(&image, &Config::new());
+    for line in lines.iter() {
+        println!("Line at {:.1} pixels and {:.1} degrees: {} votes",
+                 line.rho, line.theta.to_degrees(), line.votes);
+    }
+    let red = Color { red: 255, green: 0, blue: 0 };
+    match draw_lines(&image, lines.as_slice(), red).write_ppm("hough_lines.ppm") {
+        Ok(()) => println!("Wrote hough_lines.ppm"),
+        Err(e) => println!("Failed to write hough_lines.ppm: {}", e)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::f64::consts::PI;
+    use bitmap::{Color, ImageGray8, Pnm};
+    use super::{Accumulator, Config, Line, detect_edges, detect_lines, draw_lines, hough};
+
+    #[test]
+    fn single_point() {
+        // A single black pixel votes exactly once for every angle
+        let mut image = ImageGray8 { width: 5, height: 5, data: Vec::from_elem(25, 255) };
+        *image.data.get_mut(2 * 5) = 0;
+
+        let accum = hough(&image, 8, 10);
+        assert_eq!((accum.width, accum.height), (8, 10));
+        for x in range(0, accum.width) {
+            let votes = range(0, accum.height).filter(|&y| accum.data[x + y * accum.width] < 255)
+                                              .count();
+            assert_eq!(votes, 1);
+        }
+    }
+
+    #[test]
+    fn edges() {
+        // A black band between two white ones, with an edge on each side
+        let image = ImageGray8 { width: 6, height: 4,
+                                 data: Vec::from_fn(24, |i| if i % 6 < 3 { 255 } else { 0 }) };
+        let edges = detect_edges(&image, 128.0);
+        let expected = Vec::from_fn(24, |i| if i % 6 == 2 || i % 6 == 3 { 0 } else { 255 });
+        assert_eq!(edges.data, expected);
+    }
+
+    #[test]
+    fn ruling_lines() {
+        // A horizontal line at y = 20 and a vertical one at x = 150, between their two edges
+        let mut image = ImageGray8 { width: 200, height: 60, data: Vec::from_elem(200 * 60, 255) };
+        for x in range(0, 200) {
+            image.set(x, 20, 0);
+        }
+        for y in range(0, 60) {
+            image.set(150, y, 0);
+        }
+        let lines = vec![Line { rho: 20.0, theta: PI / 2.0, votes: 200 },
+                         Line { rho: 150.0, theta: 0.0, votes: 60 }];
+        assert_eq!(detect_lines(&image, &Config::new().min_votes(40)), lines);
+        let fine = Config::new().min_votes(40).theta_steps(360).rho_step(0.5);
+        assert_eq!(detect_lines(&image, &fine), lines);
+        assert_eq!(detect_lines(&image, &Config::new().max_lines(1)), lines.slice_to(1).to_vec());
+        assert_eq!(detect_lines(&image, &Config::new().min_votes(201)), vec![]);
+    }
+
+    #[test]
+    fn pentagon() {
+        let image = Pnm::parse(include_bytes!("resources/Pentagon.pgm")).unwrap().to_gray();
+        let lines = detect_lines(&image, &Config::new());
+        let degrees: Vec<(f64, f64, uint)> =
+            lines.iter().map(|l| ((l.rho * 10.0).round() / 10.0,
+                                  (l.theta.to_degrees() * 1e6).round() / 1e6, l.votes))
+                 .collect();
+        assert_eq!(degrees, vec![(176.8, 85.0, 130), (-24.2, 112.0, 125), (94.0, 56.0, 117),
+                                 (13.1, 148.0, 109), (248.9, 16.0, 98)]);
+    }
+
+    #[test]
+    fn angles_past_pi() {
+        // The line at an angle of pi and a distance of rho is the one at 0 and -rho
+        let image = ImageGray8::new(3, 4);
+        let accum = Accumulator::new(&image, 4, 1.0);
+        assert_eq!((accum.rho_steps, accum.rho(0), accum.rho(10)), (11, -5.0, 5.0));
+        assert_eq!(accum.get(4, 7).map(|(i, _)| i), Some(3));
+        assert_eq!(accum.get(-1, 2).map(|(i, _)| i), Some(3 * 11 + 8));
+        assert_eq!(accum.get(8, 2).map(|(i, _)| i), Some(2));
+        assert_eq!(accum.get(0, 11), None);
+    }
+
+    #[test]
+    fn drawing() {
+        let image = ImageGray8 { width: 5, height: 4, data: Vec::from_elem(20, 255) };
+        let lines = [Line { rho: 2.0, theta: 0.0, votes: 1 },
+                     Line { rho: 1.0, theta: PI / 2.0, votes: 1 }];
+        let black = Color { red: 0, green: 0, blue: 0 };
+        let drawn = draw_lines(&image, lines.as_slice(), black);
+        assert_eq!(drawn.to_ascii().as_slice(), "..#..\n#####\n..#..\n..#..\n");
+    }
 }
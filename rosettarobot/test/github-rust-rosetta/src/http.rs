@@ -5,7 +5,7 @@ use std::io::IoResult;
 #[cfg(test)]
 mod webserver;
 
-fn get_index(target: &str, port: u16) -> IoResult<String> {
+pub fn get_index(target: &str, port: u16) -> IoResult<String> {
     // Create a socket. Mutable so we can write to it.
     let mut socket = try!(TcpStream::connect(target, port));
     // Write to the socket as bytes.
@@ -20,10 +20,10 @@ fn get_index(target: &str, port: u16) -> IoResult<String> {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
     const PORT: u16 = 80;
 
-    let target = std::os::args().pop().unwrap();
+    let target = ::std::os::args().pop().unwrap();
     println!("Making the request... This might take a minute.");
     match get_index(target.as_slice(), PORT) {
         Ok(out) => println!("{}", out),
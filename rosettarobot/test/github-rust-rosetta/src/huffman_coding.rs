@@ -1,7 +1,12 @@
 // Implement data structures for a Huffman encoding tree:
 //   http://rosettacode.org/wiki/Huffman_coding
+//
+// Besides the table of codes of a string, this compresses arbitrary bytes with canonical Huffman
+// codes: the compressed data starts with the code length of every byte which appears in the
+// input, which is all the decompressor needs to rebuild the codes.
 
 extern crate core;
+use std::cmp;
 use std::collections::HashMap;
 use std::collections::hashmap::{Occupied, Vacant};
 use std::collections::priority_queue::PriorityQueue;
@@ -9,17 +14,17 @@ use std::collections::priority_queue::PriorityQueue;
 // Each HNode has a weight, representing the sum of the frequencies for all its
 // children. It is either a leaf (containing a character), or a HTree
 // (containing two children)
-struct HNode {
+pub struct HNode {
     weight: uint,
     item: HTreeOrHLeaf,
 }
 
-enum HTreeOrHLeaf {
+pub enum HTreeOrHLeaf {
     HTree(HTreeData),
     HLeaf(char),
 }
 
-struct HTreeData {
+pub struct HTreeData {
     left: Box<HNode>,
     right: Box<HNode>,
 }
@@ -50,9 +55,8 @@ impl PartialEq for HNode {
     }
 }
 
-// Takes a non-empty string (function will fail if string is empty) and computes
-// the Huffman encoding tree for that string.
-fn huffman_tree(input: &str) -> HNode {
+// Computes the Huffman encoding tree for a string, or None if the string is empty.
+pub fn huffman_tree(input: &str) -> Option<HNode> {
     // 1. Loop through all the characters in that string, adding them to a HashMap
     //    of character to frequency.
     let mut freq = HashMap::new();
@@ -63,13 +67,16 @@ fn huffman_tree(input: &str) -> HNode {
         };
     }
 
-    // 2. For each (character, frequency) pair in the HashMap, add a Leaf to a
-    //    PriorityQueue
+    tree_from_frequencies(freq.into_iter())
+}
+
+fn tree_from_frequencies<I: Iterator<(char, uint)>>(frequencies: I) -> Option<HNode> {
+    // 2. For each (character, frequency) pair, add a Leaf to a PriorityQueue
     let mut queue = PriorityQueue::<HNode>::new();
-    for (ch, freq) in freq.iter() {
+    for (ch, freq) in frequencies {
         let new_node = HNode{
-            weight: *freq,
-            item: HLeaf(*ch),
+            weight: freq,
+            item: HLeaf(ch),
         };
         queue.push(new_node);
     }
@@ -90,12 +97,12 @@ fn huffman_tree(input: &str) -> HNode {
         };
         queue.push(new_node);
     }
-    queue.pop().unwrap()
+    queue.pop()
 }
 
 // Takes a Huffman Tree, traverse it and build a table with each character and
-// its encoding string.
-fn build_encoding_table(tree: &HNode,
+// its encoding string.  A tree with a single character encodes it as "0".
+pub fn build_encoding_table(tree: &HNode,
                       table: &mut HashMap<char,String>,
                       start_str: &str) {
     match tree.item {
@@ -105,10 +112,254 @@ fn build_encoding_table(tree: &HNode,
             build_encoding_table(&*data.right, table,
                                format!("{}1", start_str).as_slice());
         },
+        HLeaf(ch) if start_str.is_empty() => {table.insert(ch, "0".to_string());}
         HLeaf(ch)   => {table.insert(ch, start_str.to_string());}
     };
 }
 
+// Codes longer than this don't fit in a u64.  Getting one would take more than 10^13 bytes of
+// input, with frequencies following the Fibonacci sequence.
+pub const MAX_CODE_LENGTH: uint = 64;
+
+// Computes the length of the Huffman code of every byte, 0 for the bytes which don't appear in
+// `data`.  The tree is built over the chars with the same code points as the bytes.
+pub fn code_lengths(data: &[u8]) -> [u8, ..256] {
+    let mut frequencies = [0u, ..256];
+    for &byte in data.iter() {
+        frequencies[byte as uint] += 1;
+    }
+
+    fn leaf_depths(tree: &HNode, depth: u8, lengths: &mut [u8, ..256]) {
+        match tree.item {
+            HTree(ref data) => {
+                leaf_depths(&*data.left, depth + 1, lengths);
+                leaf_depths(&*data.right, depth + 1, lengths);
+            }
+            HLeaf(ch) => lengths[ch as uint] = depth
+        }
+    }
+
+    let mut lengths = [0u8, ..256];
+    let weights = range(0u, 256).filter(|&b| frequencies[b] > 0)
+                                .map(|b| (b as u8 as char, frequencies[b]));
+    match tree_from_frequencies(weights) {
+        None => (),
+        // A single byte still needs a code with one bit
+        Some(HNode { item: HLeaf(ch), .. }) => lengths[ch as uint] = 1,
+        Some(tree) => leaf_depths(&tree, 0, &mut lengths)
+    }
+    lengths
+}
+
+// Assigns canonical codes to symbols given their code lengths: the codes of the same length are
+// consecutive numbers, in the order of the symbols, and follow the prefixes of the shorter codes.
+// Returns the code of every symbol, whose `lengths[symbol]` lowest bits are the code.
+pub fn canonical_codes(lengths: &[u8]) -> Vec<u64> {
+    let mut codes = Vec::from_elem(lengths.len(), 0u64);
+    let mut code = 0u64;
+    for length in range(1, MAX_CODE_LENGTH + 1) {
+        for (symbol, _) in lengths.iter().enumerate().filter(|&(_, &l)| l as uint == length) {
+            codes[symbol] = code;
+            code += 1;
+        }
+        code <<= 1;
+    }
+    codes
+}
+
+// Decodes canonical codes one bit at a time, from the number of codes of each length
+struct Decoder {
+    counts: [uint, ..MAX_CODE_LENGTH + 1], // Indexed by length
+    symbols: Vec<u8>, // Sorted by code
+}
+
+impl Decoder {
+    fn new(lengths: &[u8, ..256]) -> Result<Decoder, String> {
+        let mut counts = [0u, ..MAX_CODE_LENGTH + 1];
+        let mut symbols = Vec::new();
+        for length in range(1, MAX_CODE_LENGTH + 1) {
+            for (symbol, _) in lengths.iter().enumerate().filter(|&(_, &l)| l as uint == length) {
+                counts[length] += 1;
+                symbols.push(symbol as u8);
+            }
+        }
+
+        // The number of codes still available at each length must never become negative.  It
+        // doesn't matter how many there are once they outnumber the symbols, which avoids
+        // overflows.
+        let mut left = 1u;
+        for &count in counts.iter().skip(1) {
+            left = cmp::min(left * 2, 512);
+            if count > left {
+                return Err("invalid code lengths, too many short codes".to_string());
+            }
+            left -= count;
+        }
+        Ok(Decoder { counts: counts, symbols: symbols })
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u8, String> {
+        // `code` is read bit by bit; the codes of each length start at `first`, and their
+        // symbols at `index`
+        let (mut code, mut first, mut index) = (0u64, 0u64, 0u);
+        for &count in self.counts.iter().skip(1) {
+            match reader.read_bit() {
+                Some(bit) => code |= bit as u64,
+                None => return Err("truncated data".to_string())
+            }
+            if code < first + count as u64 {
+                return Ok(self.symbols[index + (code - first) as uint]);
+            }
+            index += count;
+            if index == self.symbols.len() {
+                break; // There are no longer codes
+            }
+            first = (first + count as u64) << 1;
+            code <<= 1;
+        }
+        Err(format!("invalid code at bit {}", reader.position()))
+    }
+}
+
+// Compresses `data`.  The output starts with a header: the length of `data` as a big-endian u64,
+// then, unless `data` is empty, the number of different bytes minus one, and each of these bytes
+// followed by the length of its code.  The codes of the bytes follow, most significant bit first.
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let lengths = code_lengths(data);
+    assert!(lengths.iter().all(|&length| length as uint <= MAX_CODE_LENGTH));
+
+    let mut header = Vec::new();
+    for i in range(0u, 8).rev() {
+        header.push((data.len() as u64 >> (8 * i)) as u8);
+    }
+    let symbols: Vec<uint> = range(0u, 256).filter(|&symbol| lengths[symbol] > 0).collect();
+    if !symbols.is_empty() {
+        header.push((symbols.len() - 1) as u8);
+        for &symbol in symbols.iter() {
+            header.push(symbol as u8);
+            header.push(lengths[symbol]);
+        }
+    }
+
+    let codes = canonical_codes(&lengths);
+    let mut writer = BitWriter::new(header);
+    for &byte in data.iter() {
+        writer.write_bits(codes[byte as uint], lengths[byte as uint] as uint);
+    }
+    writer.into_bytes()
+}
+
+// Decompresses the output of `compress`
+pub fn decompress(compressed: &[u8]) -> Result<Vec<u8>, String> {
+    if compressed.len() < 8 {
+        return Err("truncated header".to_string());
+    }
+    let len = compressed.slice_to(8).iter().fold(0u64, |len, &byte| len << 8 | byte as u64);
+    let mut data = compressed.slice_from(8);
+
+    let mut lengths = [0u8, ..256];
+    if len > 0 {
+        let n_symbols = match data.head() {
+            Some(&n) => n as uint + 1,
+            None => return Err("truncated header".to_string())
+        };
+        if data.len() < 1 + 2 * n_symbols {
+            return Err("truncated header".to_string());
+        }
+        for entry in data.slice(1, 1 + 2 * n_symbols).chunks(2) {
+            let (symbol, length) = (entry[0] as uint, entry[1]);
+            if length == 0 || length as uint > MAX_CODE_LENGTH || lengths[symbol] != 0 {
+                return Err(format!("invalid code length for byte {}", symbol));
+            }
+            lengths[symbol] = length;
+        }
+        data = data.slice_from(1 + 2 * n_symbols);
+    }
+
+    let decoder = try!(Decoder::new(&lengths));
+    let mut reader = BitReader::new(data);
+    // Don't trust the length for the allocation, every byte takes at least one bit
+    let mut output = Vec::with_capacity(cmp::min(len, data.len() as u64 * 8) as uint);
+    while (output.len() as u64) < len {
+        output.push(try!(decoder.decode(&mut reader)));
+    }
+    Ok(output)
+}
+
+// Appends bits to bytes, most significant bit first
+pub struct BitWriter {
+    bytes: Vec<u8>,
+    used: uint // Number of bits used in the last byte, 0 if it is full
+}
+
+impl BitWriter {
+    // Bits are written after `bytes`
+    pub fn new(bytes: Vec<u8>) -> BitWriter {
+        BitWriter { bytes: bytes, used: 0 }
+    }
+
+    pub fn write_bit(&mut self, bit: bool) {
+        if self.used == 0 {
+            self.bytes.push(0);
+        }
+        if bit {
+            *self.bytes.last_mut().unwrap() |= 0x80 >> self.used;
+        }
+        self.used = (self.used + 1) % 8;
+    }
+
+    // Writes the `count` lowest bits of `bits`, the most significant first
+    pub fn write_bits(&mut self, bits: u64, count: uint) {
+        for i in range(0, count).rev() {
+            self.write_bit((bits >> i) & 1 == 1);
+        }
+    }
+
+    // Returns the bytes, the last one padded with zeros
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+// Reads bits from bytes, most significant bit first
+pub struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: uint // In bits
+}
+
+impl<'a> BitReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> BitReader<'a> {
+        BitReader { bytes: bytes, position: 0 }
+    }
+
+    // The number of bits read so far
+    pub fn position(&self) -> uint {
+        self.position
+    }
+
+    pub fn read_bit(&mut self) -> Option<bool> {
+        if self.position >= self.bytes.len() * 8 {
+            return None;
+        }
+        let byte = self.bytes[self.position / 8];
+        let bit = byte & (0x80 >> (self.position % 8)) != 0;
+        self.position += 1;
+        Some(bit)
+    }
+
+    // Reads `count` bits, the most significant first
+    pub fn read_bits(&mut self, count: uint) -> Option<u64> {
+        let mut bits = 0u64;
+        for _ in range(0, count) {
+            bits = bits << 1 | match self.read_bit() {
+                Some(bit) => bit as u64,
+                None => return None
+            };
+        }
+        Some(bits)
+    }
+}
+
 // Attempts to construct a tree, and test that the construction is successful
 //    7
 //   ----
@@ -120,7 +371,7 @@ fn build_encoding_table(tree: &HNode,
 #[test]
 fn test_tree_construction() {
     let to_encode = "4444221";
-    let tree = huffman_tree(to_encode);
+    let tree = huffman_tree(to_encode).unwrap();
     assert!(tree.weight == 7);
     let children = match tree.item {
         HTree(data) => data,
@@ -172,7 +423,7 @@ fn test_tree_construction() {
 // And tests that the table was correctly constructed
 fn test_table_construction() {
     let to_encode = "4444221";
-    let tree = huffman_tree(to_encode);
+    let tree = huffman_tree(to_encode).unwrap();
     let mut table = HashMap::<char,String>::new();
     build_encoding_table(&tree, &mut table, "");
     let one  = table['1'].as_slice();
@@ -183,14 +434,102 @@ fn test_table_construction() {
             (one == "00" && two == "01"));
 }
 
+#[test]
+fn test_single_character_table() {
+    let tree = huffman_tree("aaa").unwrap();
+    let mut table = HashMap::<char,String>::new();
+    build_encoding_table(&tree, &mut table, "");
+    assert_eq!(table['a'].as_slice(), "0");
+    assert!(huffman_tree("").is_none());
+}
+
+#[test]
+fn test_canonical_codes() {
+    // The example of RFC 1951: A, B, C and D have codes of lengths 2, 1, 3 and 3
+    assert_eq!(canonical_codes(&[2, 1, 3, 3]), vec![0b10, 0b0, 0b110, 0b111]);
+    assert_eq!(canonical_codes(&[0, 2, 0, 2, 2, 2]), vec![0, 0b00, 0, 0b01, 0b10, 0b11]);
+
+    // The lengths are those of a Huffman code
+    let lengths = code_lengths(b"4444221");
+    assert_eq!((lengths[b'4' as uint], lengths[b'2' as uint], lengths[b'1' as uint]), (1, 2, 2));
+    assert_eq!(lengths.iter().filter(|&&length| length > 0).count(), 3);
+}
+
+#[test]
+fn test_bits() {
+    let mut writer = BitWriter::new(vec![0xff]);
+    writer.write_bit(true);
+    writer.write_bits(0b0110, 4);
+    writer.write_bits(0x1ff, 9);
+    let bytes = writer.into_bytes();
+    assert_eq!(bytes, vec![0xff, 0b1011_0111, 0b1111_1100]);
+
+    let mut reader = BitReader::new(bytes.slice_from(1));
+    assert_eq!(reader.read_bit(), Some(true));
+    assert_eq!(reader.read_bits(4), Some(0b0110));
+    assert_eq!(reader.read_bits(9), Some(0x1ff));
+    assert_eq!(reader.position(), 14);
+    assert_eq!(reader.read_bits(2), Some(0));
+    assert_eq!(reader.read_bit(), None);
+}
+
+#[test]
+fn test_compress() {
+    let text = b"this is an example for huffman encoding";
+    let compressed = compress(text);
+    assert!(compressed.len() < text.len());
+    assert_eq!(decompress(compressed.as_slice()), Ok(text.to_vec()));
+
+    // Every byte, with different frequencies
+    let mut data = Vec::new();
+    for byte in range(0u, 256) {
+        data.grow(byte % 7 + 1, byte as u8);
+    }
+    let compressed = compress(data.as_slice());
+    assert_eq!(decompress(compressed.as_slice()), Ok(data));
+}
+
+#[test]
+fn test_compress_edge_cases() {
+    // Only the length in the header
+    assert_eq!(compress(b""), vec![0, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(decompress(compress(b"").as_slice()), Ok(Vec::new()));
+
+    // A single symbol has a one bit code
+    let compressed = compress(b"aaaaaaaaa");
+    assert_eq!(compressed, vec![0, 0, 0, 0, 0, 0, 0, 9, 0, b'a', 1, 0, 0]);
+    assert_eq!(decompress(compressed.as_slice()), Ok(b"aaaaaaaaa".to_vec()));
+}
+
+#[test]
+fn test_decompress_errors() {
+    let compressed = compress(b"abracadabra");
+    assert_eq!(decompress(compressed.slice_to(5)), Err("truncated header".to_string()));
+    assert_eq!(decompress(compressed.slice_to(10)), Err("truncated header".to_string()));
+    let truncated = compressed.slice_to(compressed.len() - 1);
+    assert_eq!(decompress(truncated), Err("truncated data".to_string()));
+
+    // Three codes of length 1
+    let invalid = [0, 0, 0, 0, 0, 0, 0, 1, 2, b'a', 1, b'b', 1, b'c', 1, 0];
+    assert_eq!(decompress(invalid.as_slice()),
+               Err("invalid code lengths, too many short codes".to_string()));
+    let invalid = [0, 0, 0, 0, 0, 0, 0, 1, 0, b'a', 0, 0];
+    assert_eq!(decompress(invalid.as_slice()), Err("invalid code length for byte 97".to_string()));
+
+    // The code 1 isn't used when there is a single symbol
+    let invalid = [0, 0, 0, 0, 0, 0, 0, 2, 0, b'a', 1, 0b0100_0000];
+    assert_eq!(decompress(invalid.as_slice()), Err("invalid code at bit 2".to_string()));
+}
+
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
     let to_encode = "this is an example for huffman encoding";
-    let tree = huffman_tree(to_encode);
+    let tree = huffman_tree(to_encode).unwrap();
     let mut table = HashMap::<char,String>::new();
     build_encoding_table(&tree, &mut table, "");
 
     for (ch, encoding) in table.iter() {
         println!("{}: {}", *ch, encoding);
     }
+    println!("{} bytes compressed to {}", to_encode.len(), compress(to_encode.as_bytes()).len());
 }
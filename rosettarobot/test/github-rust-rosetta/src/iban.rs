@@ -2,20 +2,28 @@
 
 extern crate num;
 
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 use num::bigint::{BigInt, ToBigInt};
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes whether an example IBAN is valid to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     if is_valid("GB82 WEST 1234 5698 7654 32") {
-        println!("IBAN correctly validated!");
+        writeln!(out, "IBAN correctly validated!")
     } else {
-        println!("Invalid IBAN!");
+        writeln!(out, "Invalid IBAN!")
     }
 }
 
 // Returns true if the IBAN is valid
 // See http://en.wikipedia.org/wiki/International_Bank_Account_Number#Validating_the_IBAN
-fn is_valid(iban: &str) -> bool {
+pub fn is_valid(iban: &str) -> bool {
     // Discard whitespace
     let mut iban_chars: Vec<char> = iban.chars().filter(|c| !c.is_whitespace()).collect();
 
@@ -46,7 +54,7 @@ fn is_valid(iban: &str) -> bool {
 }
 
 // Returns a BigInt made from the digits and letters of the IBAN
-fn parse_digits(chars: Vec<char>) -> Option<BigInt> {
+pub fn parse_digits(chars: Vec<char>) -> Option<BigInt> {
     let mut vec = Vec::with_capacity(chars.len() + 10);
 
     // Copy the digits to the vector and expand the letters to digits
@@ -61,7 +69,7 @@ fn parse_digits(chars: Vec<char>) -> Option<BigInt> {
     from_str(vec.into_string().as_slice())
 }
 
-fn country_length(country_code: &str) -> Option<uint> {
+pub fn country_length(country_code: &str) -> Option<uint> {
     let countries = [
         ("AL", 28),
         ("AD", 24),
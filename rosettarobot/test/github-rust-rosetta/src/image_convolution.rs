@@ -0,0 +1,143 @@
+// Implements http://rosettacode.org/wiki/Image_convolution
+//
+// As in most image editors, the kernel is not flipped: its top left weight is for the top left
+// neighbor of the pixel.  The channels are rounded and clamped to 0..255.
+use bitmap::{Color, Edges, Image};
+mod bitmap;
+
+// A square of weights, with an odd size so that it has a middle
+#[deriving(Clone, PartialEq, Show)]
+pub struct Kernel {
+    size: uint,
+    weights: Vec<f64>
+}
+
+impl Kernel {
+    // The weights are given row by row
+    pub fn new(size: uint, weights: Vec<f64>) -> Kernel {
+        assert!(size % 2 == 1, "the kernel must have a middle");
+        assert!(weights.len() == size * size, "the kernel must have {} weights", size * size);
+        Kernel { size: size, weights: weights }
+    }
+
+    // The mean of `size` x `size` pixels
+    pub fn box_blur(size: uint) -> Kernel {
+        Kernel::new(size, Vec::from_elem(size * size, 1.0 / (size * size) as f64))
+    }
+
+    pub fn gaussian_blur() -> Kernel {
+        let weights = [1.0, 2.0, 1.0, 2.0, 4.0, 2.0, 1.0, 2.0, 1.0];
+        Kernel::new(3, weights.iter().map(|&w| w / 16.0).collect())
+    }
+
+    pub fn sharpen() -> Kernel {
+        Kernel::new(3, vec![0.0, -1.0, 0.0, -1.0, 5.0, -1.0, 0.0, -1.0, 0.0])
+    }
+
+    // Lights the edges from the top left
+    pub fn emboss() -> Kernel {
+        Kernel::new(3, vec![-2.0, -1.0, 0.0, -1.0, 1.0, 1.0, 0.0, 1.0, 2.0])
+    }
+}
+
+pub trait Convolve {
+    fn convolve(&self, kernel: &Kernel, edges: Edges) -> Self;
+}
+
+impl Convolve for Image {
+    fn convolve(&self, kernel: &Kernel, edges: Edges) -> Image {
+        let radius = (kernel.size / 2) as int;
+        let channel = |value: f64| value.round().max(0.0).min(255.0) as u8;
+        let mut result = Image::new(self.width, self.height);
+        for y in range(0, self.height as int) {
+            for x in range(0, self.width as int) {
+                let (mut red, mut green, mut blue) = (0.0, 0.0, 0.0);
+                for (i, &weight) in kernel.weights.iter().enumerate() {
+                    let dx = (i % kernel.size) as int - radius;
+                    let dy = (i / kernel.size) as int - radius;
+                    let color = self.get_extended(x + dx, y + dy, edges.clone());
+                    red += weight * color.red as f64;
+                    green += weight * color.green as f64;
+                    blue += weight * color.blue as f64;
+                }
+                result.set(x, y, Color { red: channel(red), green: channel(green),
+                                         blue: channel(blue) });
+            }
+        }
+        result
+    }
+}
+
+#[cfg(not(test))]
+pub fn main() {
+    use bitmap::{Extend, Pnm};
+
+    let path = Path::new("../src/resources/filters_input.ppm");
+    let image = match Pnm::open(&path) {
+        Ok(pnm) => pnm.to_image(),
+        Err(e) => fail!("failed to read {}: {}", path.display(), e)
+    };
+    let kernels = [("blur.ppm", Kernel::gaussian_blur()), ("sharpen.ppm", Kernel::sharpen()),
+                   ("emboss.ppm", Kernel::emboss())];
+    for &(filename, ref kernel) in kernels.iter() {
+        match image.convolve(kernel, Extend).write_ppm(filename) {
+            Ok(()) => println!("Wrote {}", filename),
+            Err(e) => println!("Failed to write {}: {}", filename, e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitmap::{Color, Extend, Image, Mirror, Pnm, Wrap};
+    use super::{Convolve, Kernel};
+
+    #[test]
+    fn golden_files() {
+        let image = Pnm::parse(include_bytes!("resources/filters_input.ppm")).unwrap().to_image();
+        let blur = Pnm::parse(include_bytes!("resources/filters_blur.ppm")).unwrap();
+        assert_eq!(image.convolve(&Kernel::gaussian_blur(), Extend).data, blur.to_image().data);
+        let sharpen = Pnm::parse(include_bytes!("resources/filters_sharpen.ppm")).unwrap();
+        assert_eq!(image.convolve(&Kernel::sharpen(), Mirror).data, sharpen.to_image().data);
+        let emboss = Pnm::parse(include_bytes!("resources/filters_emboss.ppm")).unwrap();
+        assert_eq!(image.convolve(&Kernel::emboss(), Wrap).data, emboss.to_image().data);
+
+        let identity = Kernel::new(3, vec![0.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(image.convolve(&identity, Wrap).data, image.data);
+        let box_blur = image.convolve(&Kernel::box_blur(3), Wrap);
+        assert_eq!(box_blur.data.slice_to(4).to_vec(),
+                   vec![Color { red: 82, green: 91, blue: 114 },
+                        Color { red: 48, green: 111, blue: 104 },
+                        Color { red: 96, green: 131, blue: 94 },
+                        Color { red: 144, green: 157, blue: 99 }]);
+    }
+
+    #[test]
+    fn uniform() {
+        // The blurs keep a uniform image, whatever the edges, and the other kernels sum to 1
+        let mut image = Image::new(4, 3);
+        image.fill(Color { red: 10, green: 100, blue: 250 });
+        for kernel in [Kernel::box_blur(5), Kernel::gaussian_blur(), Kernel::sharpen(),
+                       Kernel::emboss()].iter() {
+            for edges in [Extend, Wrap, Mirror].iter() {
+                assert_eq!(image.convolve(kernel, edges.clone()).data, image.data);
+            }
+        }
+    }
+
+    #[test]
+    fn clamping() {
+        let mut image = Image::new(3, 1);
+        image.set(1, 0, Color { red: 200, green: 100, blue: 0 });
+        let sharpened = image.convolve(&Kernel::sharpen(), Extend);
+        assert_eq!(sharpened.data, vec![Color { red: 0, green: 0, blue: 0 },
+                                        Color { red: 255, green: 255, blue: 0 },
+                                        Color { red: 0, green: 0, blue: 0 }]);
+    }
+
+    #[test]
+    #[should_fail]
+    fn missing_weights() {
+        Kernel::new(3, vec![1.0, 2.0]);
+    }
+}
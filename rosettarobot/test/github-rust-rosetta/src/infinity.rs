@@ -1,6 +1,13 @@
 // Implements http://rosettacode.org/wiki/Infinity
 
-fn main() {
+use std::io::{mod, IoResult};
+
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the infinity of f32 to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let inf : f32 = Float::infinity();
-    println!("{}", inf);
+    writeln!(out, "{}", inf)
 }
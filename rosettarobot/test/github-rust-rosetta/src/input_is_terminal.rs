@@ -2,7 +2,7 @@
 
 extern crate libc;
 
-fn main() {
+pub fn main() {
     let istty = unsafe { libc::isatty(libc::STDIN_FILENO as i32) } != 0;
     if istty {
         println!("stdin is tty");
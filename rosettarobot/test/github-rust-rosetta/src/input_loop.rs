@@ -1,7 +1,7 @@
 // Implements http://rosettacode.org/wiki/Input_loop
 use std::io;
 
-fn main() {
+pub fn main() {
     for line in io::stdin().lines() {
         print!("{}", line.unwrap());
     }
@@ -5,7 +5,7 @@ extern crate num;
 use num::bigint::BigUint;
 use std::num::One;
 
-fn main() {
+pub fn main() {
     let one: BigUint = One::one();
     let mut i: BigUint = One::one();
 
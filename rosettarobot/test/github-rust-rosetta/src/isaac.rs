@@ -2,31 +2,41 @@
 // includes the XOR version of the encryption scheme
 #![feature(macro_rules)]
 use std::iter::range_step;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
-const MSG :&'static str = "a Top Secret secret";
-const KEY: &'static str = "this is my secret key";
+pub const MSG :&'static str = "a Top Secret secret";
+pub const KEY: &'static str = "this is my secret key";
 
 #[cfg(not(test))]
-fn main () {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the message encrypted with the key, and decrypted again, to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let mut isaac = Isaac::new();
     isaac.seed(KEY, true);
     let encr = isaac.vernam(MSG.as_bytes());
 
-    println!("msg: {}", MSG);
-    println!("key: {}", KEY);
-    print!("XOR: ");
+    try!(writeln!(out, "msg: {}", MSG));
+    try!(writeln!(out, "key: {}", KEY));
+    try!(write!(out, "XOR: "));
     for a in encr.iter() {
-        print!("{:02X}", *a);
+        try!(write!(out, "{:02X}", *a));
     }
 
     let mut isaac = Isaac::new();
     isaac.seed(KEY, true);
     let decr = isaac.vernam(encr.as_slice());
 
-    print!("\nXOR dcr: ");
+    try!(write!(out, "\nXOR dcr: "));
     for a in decr.iter() {
-        print!("{}", a.to_ascii());
+        try!(write!(out, "{}", a.to_ascii()));
     }
+    Ok(())
+}
 }
 
 macro_rules! mix_v(
@@ -41,7 +51,7 @@ macro_rules! mix_v(
    } );
 )
 
-struct Isaac {
+pub struct Isaac {
     mm: [u32,.. 256],
     aa: u32,
     bb: u32,
@@ -51,7 +61,7 @@ struct Isaac {
 }
 
 impl Isaac {
-    fn new() -> Isaac {
+    pub fn new() -> Isaac {
         Isaac {
             mm: [0u32, ..256],
             aa: 0,
@@ -62,7 +72,7 @@ impl Isaac {
         }
     }
 
-    fn isaac(&mut self) {
+    pub fn isaac(&mut self) {
         self.cc += 1;
         self.bb += self.cc;
 
@@ -85,7 +95,7 @@ impl Isaac {
         self.rand_cnt = 0;
     }
 
-    fn rand_init(&mut self, flag: bool)
+    pub fn rand_init(&mut self, flag: bool)
     {
         let mut a_v = [0x9e3779b9u32, ..8];
 
@@ -118,7 +128,7 @@ impl Isaac {
     }
 
     // Get a random 32-bit value
-    fn i_random(&mut self) -> u32 {
+    pub fn i_random(&mut self) -> u32 {
         let r = self.rand_rsl[self.rand_cnt as uint];
         self.rand_cnt += 1;
         if self.rand_cnt >255 {
@@ -129,7 +139,7 @@ impl Isaac {
     }
 
     // Seed ISAAC with a string
-    fn seed(&mut self, seed: &str, flag: bool) {
+    pub fn seed(&mut self, seed: &str, flag: bool) {
         for i in range (0u, 256) { self.mm[i] = 0; }
         for i in range (0u, 256) { self.rand_rsl[i] = 0; }
 
@@ -141,12 +151,12 @@ impl Isaac {
     }
 
     // Get a random character in printable ASCII range
-    fn i_rand_ascii(&mut self) -> u8 {
+    pub fn i_rand_ascii(&mut self) -> u8 {
         (self.i_random() % 95 + 32) as u8
     }
 
     /// XOR message
-    fn vernam(&mut self, msg :&[u8]) -> Vec<u8> {
+    pub fn vernam(&mut self, msg :&[u8]) -> Vec<u8> {
         msg.iter().map(|&b| (self.i_rand_ascii() ^ b))
             .collect::<Vec<u8>>()
     }
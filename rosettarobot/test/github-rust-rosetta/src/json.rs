@@ -2,6 +2,9 @@
 
 extern crate serialize;
 
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 use serialize::json;
 
 #[deriving(Show, Encodable, Decodable, PartialEq, Eq)]
@@ -11,16 +14,21 @@ pub struct Contact {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes a contact encoded to JSON, and another one decoded from it, to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     // Encode contact to json
     let c = Contact { name: "John".to_string(), city: "Paris".to_string() };
     let json = json::encode(&c);
-    println!("Encoded: {}", json.as_slice());
+    try!(writeln!(out, "Encoded: {}", json.as_slice()));
 
     // Decode json to contact
     let json_str = "{\"name\":\"Alan\", \"city\":\"Tokyo\"}";
     let contact: Contact = json::decode(json_str).unwrap();
-    println!("Decoded: {}", contact);
+    writeln!(out, "Decoded: {}", contact)
 }
 
 #[test]
@@ -1,55 +1,92 @@
 // Implements http://rosettacode.org/wiki/K-d_tree
+//
+// The tree is generic over the type of the coordinates and over the number of dimensions, which
+// is the same for all the points of a tree.  Besides the nearest neighbor, it finds the k nearest
+// neighbors and the points within a radius or a box, and points can be inserted and removed
+// after the tree is built.  A subtree which gets unbalanced by insertions is rebuilt (as in a
+// scapegoat tree), and removed points are only marked as such until the whole tree is rebuilt
+// once there are as many of them as there are points left.
 
 extern crate time;
 
+use std::cmp;
+use std::mem;
+use std::num::{Num, Zero};
 use std::rand::Rng;
 use std::cmp::Ordering;
 #[cfg(not(test))]
 use time::get_time;
 
+// Subtrees with fewer points aren't rebuilt, and neither is the tree until at least this many
+// points were removed
+static MIN_REBALANCE: uint = 32;
+
 #[deriving(Clone, PartialEq, Show)]
-struct Point {
-    pub coords: Vec<f32>
+pub struct Point<T> {
+    pub coords: Vec<T>
 }
 
-impl Sub<Point, Point> for Point {
-    fn sub(&self, rhs: &Point) -> Point {
+impl<T: Num + PartialOrd + Copy> Sub<Point<T>, Point<T>> for Point<T> {
+    fn sub(&self, rhs: &Point<T>) -> Point<T> {
         assert_eq!(self.coords.len(), rhs.coords.len());
-        Point {coords: self.coords.iter().zip(rhs.coords.iter()).map(|(x, &y)| x - y).collect()}
+        Point {coords: self.coords.iter().zip(rhs.coords.iter()).map(|(&x, &y)| x - y).collect()}
     }
 }
 
-impl Point {
-    fn norm_sq(&self) -> f32 {
-        self.coords.iter().fold(0f32, |a, &b| a + b*b)
+impl<T: Num + PartialOrd + Copy> Point<T> {
+    pub fn norm_sq(&self) -> T {
+        self.coords.iter().fold(Zero::zero(), |a: T, &b| a + b*b)
+    }
+
+    // Unlike (self - other).norm_sq(), this works for unsigned coordinates too
+    pub fn dist_sq(&self, other: &Point<T>) -> T {
+        self.coords.iter().zip(other.coords.iter()).fold(Zero::zero(), |a: T, (&x, &y)| {
+            let d = axis_dist(x, y);
+            a + d*d
+        })
+    }
+
+    // Whether every coordinate can be ordered, which NaN can't
+    pub fn is_ordered(&self) -> bool {
+        self.coords.iter().all(|x| x.partial_cmp(x).is_some())
     }
 }
 
-struct KDTreeNode {
-    point: Point,
+pub fn axis_dist<T: Num + PartialOrd>(a: T, b: T) -> T {
+    if a > b { a - b } else { b - a }
+}
+
+// Points whose coordinate along `dim` is lower than this node's are on the left, those whose
+// coordinate is higher are on the right, and equal ones can be on either side.
+pub struct KDTreeNode<T> {
+    point: Point<T>,
     dim: uint,
+    removed: bool,
+    size: uint, // Number of nodes in the subtree, removed ones included
     // Construction could become faster if we use an arena allocator,
     // but this is easier to use.
-    left: Option<Box<KDTreeNode>>,
-    right: Option<Box<KDTreeNode>>
+    left: Option<Box<KDTreeNode<T>>>,
+    right: Option<Box<KDTreeNode<T>>>
 }
 
-impl KDTreeNode {
+impl<T: Num + PartialOrd + Copy> KDTreeNode<T> {
     // Create a new KDTreeNode around the `dim`th dimension.
     // Alternatively, we could dynamically determine the dimension to
     // split on by using the longest dimension.
-    pub fn new(points: &mut [Point], dim: uint) -> KDTreeNode {
+    pub fn new(points: &mut [Point<T>], dim: uint) -> KDTreeNode<T> {
         let points_len = points.len();
         if points_len == 1 {
             return KDTreeNode {
                 point: points[0].clone(),
                 dim: dim,
+                removed: false,
+                size: 1,
                 left: None,
                 right: None
             };
         }
-    
-        // Split around the median
+
+        // Split around the median.  The trees reject NaN coordinates, so they can all be compared.
         let pivot = quickselect_by(points, points_len/2,
             |a, b| a.coords[dim].partial_cmp(&b.coords[dim]).unwrap());
 
@@ -65,91 +102,310 @@ impl KDTreeNode {
         KDTreeNode {
             point: pivot.clone(),
             dim: dim,
+            removed: false,
+            size: points_len,
             left: left,
             right: right
         }
     }
 
-    pub fn find_nearest_neighbor<'a>(&'a self, point: &Point) -> (&'a Point, uint) {
-        self.find_nearest_neighbor_helper(point, &self.point, (point - self.point).norm_sq(), 1)
+    fn leaf(point: Point<T>, dim: uint) -> KDTreeNode<T> {
+        KDTreeNode { point: point, dim: dim, removed: false, size: 1, left: None, right: None }
     }
 
-    fn find_nearest_neighbor_helper<'a>(&'a self, point: &Point, best: &'a Point,
-                                        best_dist_sq: f32, n_visited: uint) -> (&'a Point, uint) {
-        let mut my_best = best;
-        let mut my_best_dist_sq = best_dist_sq;
-        let mut my_n_visited = n_visited;
+    // A subtree is unbalanced when one side holds more than 3/4 of its nodes
+    fn is_unbalanced(&self) -> bool {
+        let largest = cmp::max(subtree_size(&self.left), subtree_size(&self.right));
+        self.size > MIN_REBALANCE && largest * 4 > self.size * 3
+    }
 
-        // We should always examine the near side
-        if self.point.coords[self.dim] < point.coords[self.dim] {
-            if self.right.is_some() {
-                let (a, b) = self.right.as_ref().unwrap().find_nearest_neighbor_helper(
-                    point,
-                    my_best,
-                    my_best_dist_sq,
-                    my_n_visited);
-                my_best = a;
-                my_n_visited = b;
+    // Inserts `point` below this node, and rebuilds the subtrees which got unbalanced.  Returns
+    // the number of removed points which the rebuilds dropped.
+    fn insert(&mut self, point: Point<T>) -> uint {
+        let dim = (self.dim + 1) % point.coords.len();
+        let dropped = {
+            let child = if point.coords[self.dim] < self.point.coords[self.dim] {
+                &mut self.left
+            } else {
+                &mut self.right
+            };
+            let mut dropped = 0;
+            if child.is_some() {
+                dropped = child.as_mut().unwrap().insert(point);
+            } else {
+                *child = Some(box KDTreeNode::leaf(point, dim));
             }
-        } else {
-            if self.left.is_some() {
-                let (a, b) = self.left.as_ref().unwrap().find_nearest_neighbor_helper(
-                    point,
-                    my_best,
-                    my_best_dist_sq,
-                    my_n_visited);
-                my_best = a;
-                my_n_visited = b;
+            if child.as_ref().unwrap().is_unbalanced() {
+                dropped += rebuild(child);
             }
+            dropped
+        };
+        self.size = self.size + 1 - dropped;
+        dropped
+    }
+
+    // Marks a point equal to `point` as removed, returns false if there is none
+    fn remove(&mut self, point: &Point<T>) -> bool {
+        if !self.removed && self.point == *point {
+            self.removed = true;
+            return true;
+        }
+        let (c, here) = (point.coords[self.dim], self.point.coords[self.dim]);
+        (c <= here && self.left.as_mut().map_or(false, |node| node.remove(point))) ||
+            (c >= here && self.right.as_mut().map_or(false, |node| node.remove(point)))
+    }
+
+    // Moves the points which weren't removed into `points`, leaving only empty nodes behind
+    fn drain_points(&mut self, points: &mut Vec<Point<T>>) {
+        if !self.removed {
+            points.push(mem::replace(&mut self.point, Point { coords: Vec::new() }));
         }
+        match self.left.take() {
+            Some(mut node) => node.drain_points(points),
+            None => ()
+        }
+        match self.right.take() {
+            Some(mut node) => node.drain_points(points),
+            None => ()
+        }
+    }
 
-        // distance along this node's axis
-        let axis_dist_sq = (self.point.coords[self.dim] - point.coords[self.dim]).powi(2);
-        if axis_dist_sq <= my_best_dist_sq {
-            // self can only be nearer than best if axis_dist_sq is less than
-            // best_dist_sq because axis_dist_sq is a lower bound for
-            // self_dist_sq
-            let self_dist_sq = (point - self.point).norm_sq();
-            if self_dist_sq < my_best_dist_sq {
-                my_best = &self.point;
-                my_best_dist_sq = self_dist_sq;
+    fn depth(&self) -> uint {
+        let left = self.left.as_ref().map_or(0, |node| node.depth());
+        let right = self.right.as_ref().map_or(0, |node| node.depth());
+        1 + cmp::max(left, right)
+    }
+
+    // Keeps the k nearest points found so far in `best`, nearest first
+    fn nearest<'a>(&'a self, target: &Point<T>, k: uint, best: &mut Vec<(&'a Point<T>, T)>,
+                   n_visited: &mut uint) {
+        *n_visited += 1;
+        if !self.removed {
+            let dist_sq = self.point.dist_sq(target);
+            let position = best.iter().position(|&(_, d)| dist_sq < d).unwrap_or(best.len());
+            if position < k {
+                best.insert(position, (&self.point, dist_sq));
+                best.truncate(k);
             }
+        }
 
-            // bookkeeping
-            my_n_visited += 1;
-
-            // same reasoning applies for the far side of the split
-            if self.point.coords[self.dim] < point.coords[self.dim] {
-                if self.left.is_some() {
-                    let (a, b) = self.left.as_ref().unwrap().find_nearest_neighbor_helper(
-                        point,
-                        my_best,
-                        my_best_dist_sq,
-                        my_n_visited);
-                    my_best = a;
-                    my_n_visited = b;
-                }
-            } else {
-                if self.right.is_some() {
-                    let (a, b) = self.right.as_ref().unwrap().find_nearest_neighbor_helper(
-                        point,
-                        my_best,
-                        my_best_dist_sq,
-                        my_n_visited);
-                    my_best = a;
-                    my_n_visited = b;
-                }
+        // We should always examine the near side
+        let (near, far) = if target.coords[self.dim] < self.point.coords[self.dim] {
+            (&self.left, &self.right)
+        } else {
+            (&self.right, &self.left)
+        };
+        match *near {
+            Some(ref node) => node.nearest(target, k, best, n_visited),
+            None => ()
+        }
+
+        // The distance along this node's axis is a lower bound for the distance of any point on
+        // the far side, so it is only worth examining if that could beat the worst point so far
+        let axis_dist = axis_dist(target.coords[self.dim], self.point.coords[self.dim]);
+        let axis_dist_sq = axis_dist * axis_dist;
+        let worth_it = match best.last() {
+            Some(&(_, worst)) => best.len() < k || axis_dist_sq <= worst,
+            None => true
+        };
+        match *far {
+            Some(ref node) if worth_it => node.nearest(target, k, best, n_visited),
+            _ => ()
+        }
+    }
+
+    fn within_radius<'a>(&'a self, target: &Point<T>, radius_sq: T,
+                         found: &mut Vec<&'a Point<T>>, n_visited: &mut uint) {
+        *n_visited += 1;
+        if !self.removed && self.point.dist_sq(target) <= radius_sq {
+            found.push(&self.point);
+        }
+        let (c, here) = (target.coords[self.dim], self.point.coords[self.dim]);
+        let axis_dist = axis_dist(c, here);
+        let axis_dist_sq = axis_dist * axis_dist;
+        match self.left {
+            Some(ref node) if c <= here || axis_dist_sq <= radius_sq => {
+                node.within_radius(target, radius_sq, found, n_visited)
+            },
+            _ => ()
+        }
+        match self.right {
+            Some(ref node) if c >= here || axis_dist_sq <= radius_sq => {
+                node.within_radius(target, radius_sq, found, n_visited)
+            },
+            _ => ()
+        }
+    }
+
+    fn in_box<'a>(&'a self, min: &Point<T>, max: &Point<T>, found: &mut Vec<&'a Point<T>>,
+                  n_visited: &mut uint) {
+        *n_visited += 1;
+        let inside = self.point.coords.iter().zip(min.coords.iter().zip(max.coords.iter()))
+                                     .all(|(c, (lo, hi))| lo <= c && c <= hi);
+        if !self.removed && inside {
+            found.push(&self.point);
+        }
+        let here = self.point.coords[self.dim];
+        match self.left {
+            Some(ref node) if min.coords[self.dim] <= here => {
+                node.in_box(min, max, found, n_visited)
+            },
+            _ => ()
+        }
+        match self.right {
+            Some(ref node) if max.coords[self.dim] >= here => {
+                node.in_box(min, max, found, n_visited)
+            },
+            _ => ()
+        }
+    }
+}
+
+fn subtree_size<T>(node: &Option<Box<KDTreeNode<T>>>) -> uint {
+    node.as_ref().map_or(0, |node| node.size)
+}
+
+// Rebuilds a subtree from the points which weren't removed, returns how many removed points
+// were dropped
+fn rebuild<T: Num + PartialOrd + Copy>(subtree: &mut Option<Box<KDTreeNode<T>>>) -> uint {
+    let mut node = match subtree.take() {
+        Some(node) => node,
+        None => return 0
+    };
+    let mut points = Vec::with_capacity(node.size);
+    node.drain_points(&mut points);
+    *subtree = if points.is_empty() {
+        None
+    } else {
+        Some(box KDTreeNode::new(points.as_mut_slice(), node.dim))
+    };
+    node.size - points.len()
+}
+
+// A k-d tree of points with `dims` coordinates.  The queries also return how many nodes they
+// visited.
+pub struct KDTree<T> {
+    root: Option<Box<KDTreeNode<T>>>,
+    dims: uint,
+    len: uint, // Number of points, not counting the removed ones
+    removed: uint, // Removed points which are still in the tree
+}
+
+impl<T: Num + PartialOrd + Copy> KDTree<T> {
+    pub fn new(dims: uint) -> KDTree<T> {
+        KDTree { root: None, dims: dims, len: 0, removed: 0 }
+    }
+
+    pub fn from_points(dims: uint, mut points: Vec<Point<T>>) -> KDTree<T> {
+        assert!(points.iter().all(|p| p.coords.len() == dims));
+        assert!(points.iter().all(|p| p.is_ordered()), "points can't have NaN coordinates");
+        let len = points.len();
+        KDTree {
+            root: if len == 0 { None } else { Some(box KDTreeNode::new(points.as_mut_slice(), 0)) },
+            dims: dims,
+            len: len,
+            removed: 0
+        }
+    }
+
+    pub fn len(&self) -> uint {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn depth(&self) -> uint {
+        self.root.as_ref().map_or(0, |node| node.depth())
+    }
+
+    pub fn insert(&mut self, point: Point<T>) {
+        assert_eq!(point.coords.len(), self.dims);
+        assert!(point.is_ordered(), "points can't have NaN coordinates");
+        let mut dropped = 0;
+        if self.root.is_some() {
+            dropped = self.root.as_mut().unwrap().insert(point);
+        } else {
+            self.root = Some(box KDTreeNode::leaf(point, 0));
+        }
+        if self.root.as_ref().unwrap().is_unbalanced() {
+            dropped += rebuild(&mut self.root);
+        }
+        self.len += 1;
+        self.removed -= dropped;
+    }
+
+    // Removes one point equal to `point`, returns false if there is none
+    pub fn remove(&mut self, point: &Point<T>) -> bool {
+        let found = match self.root {
+            Some(ref mut node) => node.remove(point),
+            None => false
+        };
+        if found {
+            self.len -= 1;
+            self.removed += 1;
+            if self.removed > cmp::max(self.len, MIN_REBALANCE) {
+                self.rebalance();
             }
         }
+        found
+    }
+
+    // Rebuilds the whole tree, dropping the removed points
+    pub fn rebalance(&mut self) {
+        rebuild(&mut self.root);
+        self.removed = 0;
+    }
 
-        (my_best, my_n_visited)
+    pub fn find_nearest_neighbor(&self, target: &Point<T>) -> Option<(&Point<T>, uint)> {
+        let (mut best, n_visited) = self.nearest(target, 1);
+        best.pop().map(|(point, _)| (point, n_visited))
+    }
+
+    // The k nearest points to `target` with their squared distance, nearest first
+    pub fn nearest(&self, target: &Point<T>, k: uint) -> (Vec<(&Point<T>, T)>, uint) {
+        assert_eq!(target.coords.len(), self.dims);
+        let mut best = Vec::with_capacity(k + 1);
+        let mut n_visited = 0;
+        match self.root {
+            Some(ref node) if k > 0 => node.nearest(target, k, &mut best, &mut n_visited),
+            _ => ()
+        }
+        (best, n_visited)
+    }
+
+    // The points at most `radius` away from `target`
+    pub fn within_radius(&self, target: &Point<T>, radius: T) -> (Vec<&Point<T>>, uint) {
+        assert_eq!(target.coords.len(), self.dims);
+        let mut found = Vec::new();
+        let mut n_visited = 0;
+        let radius_sq = radius * radius;
+        match self.root {
+            Some(ref node) => node.within_radius(target, radius_sq, &mut found, &mut n_visited),
+            None => ()
+        }
+        (found, n_visited)
+    }
+
+    // The points whose coordinates are all between those of `min` and `max` (included)
+    pub fn in_box(&self, min: &Point<T>, max: &Point<T>) -> (Vec<&Point<T>>, uint) {
+        assert_eq!(min.coords.len(), self.dims);
+        assert_eq!(max.coords.len(), self.dims);
+        let mut found = Vec::new();
+        let mut n_visited = 0;
+        match self.root {
+            Some(ref node) => node.in_box(min, max, &mut found, &mut n_visited),
+            None => ()
+        }
+        (found, n_visited)
     }
 }
 
 #[cfg(not(test))]
 pub fn main() {
     // wordpress
-    let mut wp_points: Vec<Point> = [
+    let wp_points: Vec<Point<f32>> = [
         [2f32, 3f32],
         [5f32, 4f32],
         [9f32, 6f32],
@@ -157,10 +413,10 @@ pub fn main() {
         [8f32, 1f32],
         [7f32, 2f32]
     ].iter().map(|x| Point { coords: x.to_vec() }).collect();
-    let wp_tree = KDTreeNode::new(wp_points.as_mut_slice(), 0);
+    let wp_tree = KDTree::from_points(2, wp_points);
 
     let wp_target = Point { coords: vec![9f32, 2f32] };
-    let (point, n_visited) = wp_tree.find_nearest_neighbor(&wp_target);
+    let (point, n_visited) = wp_tree.find_nearest_neighbor(&wp_target).unwrap();
     println!("Wikipedia example data:");
     println!("Point: [9, 2]");
     println!("Nearest neighbor: {}", point);
@@ -170,25 +426,28 @@ pub fn main() {
     // randomly generated 3D
     let n_random = 1000u;
     let make_random_point = || Point {
-        coords: Vec::from_fn(3, |_| (std::rand::task_rng().gen::<f32>()-0.5f32)*1000f32)
+        coords: Vec::from_fn(3, |_| (::std::rand::task_rng().gen::<f32>()-0.5f32)*1000f32)
     };
-    let mut random_points: Vec<Point> = Vec::from_fn(n_random, |_| make_random_point());
+    let random_points: Vec<Point<f32>> = Vec::from_fn(n_random, |_| make_random_point());
 
     let start_cons_time = get_time();
-    let random_tree = KDTreeNode::new(random_points.as_mut_slice(), 0);
+    let random_tree = KDTree::from_points(3, random_points);
     let end_cons_time = get_time();
-    println!("1,000 3d points (Construction time: {}ms)", 
+    println!("1,000 3d points (Construction time: {}ms)",
              ((end_cons_time.sec - start_cons_time.sec)*1000) as f32 +
              ((end_cons_time.nsec - start_cons_time.nsec) as f32)/1000000f32);
 
     let random_target = make_random_point();
 
-    let (point, n_visited) = random_tree.find_nearest_neighbor(&random_target);
+    let (point, n_visited) = random_tree.find_nearest_neighbor(&random_target).unwrap();
     println!("Point: {}", random_target);
     println!("Nearest neighbor: {}", point);
     println!("Distance: {}", (point - random_target).norm_sq().sqrt());
     println!("Nodes visited: {}", n_visited);
-    
+
+    let (points, n_visited) = random_tree.within_radius(&random_target, 100f32);
+    println!("{} points within a distance of 100 (nodes visited: {})", points.len(), n_visited);
+
     // benchmark search time
     let n_searches = 1000u;
     let random_targets = Vec::from_fn(n_searches, |_| make_random_point());
@@ -196,19 +455,19 @@ pub fn main() {
     let start_search_time = get_time();
     let mut total_n_visited = 0u;
     for target in random_targets.iter() {
-        let (_, n_visited) = random_tree.find_nearest_neighbor(target);
+        let (_, n_visited) = random_tree.find_nearest_neighbor(target).unwrap();
         total_n_visited += n_visited;
     }
     let end_search_time = get_time();
-    println!("Visited an average of {} nodes on {} searches in {} ms", 
+    println!("Visited an average of {} nodes on {} searches in {} ms",
              total_n_visited as f32 / n_searches as f32,
              n_searches,
              ((end_search_time.sec - start_search_time.sec)*1000) as f32 +
              ((end_search_time.nsec - start_search_time.nsec) as f32)/1000000f32);
 }
 
-fn quickselect_by<T: Clone>(arr: &mut [T], position: uint, cmp: |a: &T, b: &T| -> Ordering) -> T {
-    let mut pivot_index = std::rand::task_rng().gen_range(0, arr.len());
+pub fn quickselect_by<T: Clone>(arr: &mut [T], position: uint, cmp: |a: &T, b: &T| -> Ordering) -> T {
+    let mut pivot_index = ::std::rand::task_rng().gen_range(0, arr.len());
     // Need to wrap in another closure or we get ownership complaints.
     // Tried using an unboxed closure to get around this but couldn't get it to work.
     pivot_index = partition_by(arr, pivot_index, |a: &T, b: &T| cmp(a, b));
@@ -222,7 +481,7 @@ fn quickselect_by<T: Clone>(arr: &mut [T], position: uint, cmp: |a: &T, b: &T| -
     }
 }
 
-fn partition_by<T>(arr: &mut [T], pivot_index: uint, cmp: |a: &T, b: &T| -> Ordering) -> uint {
+pub fn partition_by<T>(arr: &mut [T], pivot_index: uint, cmp: |a: &T, b: &T| -> Ordering) -> uint {
     let array_len = arr.len();
     arr.swap(pivot_index, array_len-1);
     let mut store_index = 0u;
@@ -238,11 +497,25 @@ fn partition_by<T>(arr: &mut [T], pivot_index: uint, cmp: |a: &T, b: &T| -> Orde
 
 #[cfg(test)]
 mod test {
-    use super::{Point, KDTreeNode};
+    use super::{Point, KDTree};
+
+    // 3d points with coordinates from 0 to 100, spread deterministically
+    fn points(n: int) -> Vec<Point<int>> {
+        range(0, n).map(|i| Point { coords: vec![i * 37 % 101, i * 53 % 97, i * 11 % 89] })
+                   .collect()
+    }
+
+    // The squared distances of the k points nearest to target, found by brute force
+    fn brute_force_nearest(points: &[Point<int>], target: &Point<int>, k: uint) -> Vec<int> {
+        let mut dists: Vec<int> = points.iter().map(|p| p.dist_sq(target)).collect();
+        dists.sort();
+        dists.truncate(k);
+        dists
+    }
 
     #[test]
     fn wp() {
-        let mut wp_points: Vec<Point> = [
+        let wp_points: Vec<Point<f32>> = [
             [2f32, 3f32],
             [5f32, 4f32],
             [9f32, 6f32],
@@ -250,10 +523,119 @@ mod test {
             [8f32, 1f32],
             [7f32, 2f32]
         ].iter().map(|x| Point { coords: x.to_vec() }).collect();
-        let wp_tree = KDTreeNode::new(wp_points.as_mut_slice(), 0);
+        let wp_tree = KDTree::from_points(2, wp_points);
 
         let wp_target = Point { coords: vec![9f32, 2f32] };
-        let (point, _) = wp_tree.find_nearest_neighbor(&wp_target);
+        let (point, _) = wp_tree.find_nearest_neighbor(&wp_target).unwrap();
         assert_eq!(*point, Point { coords: vec![8f32, 1f32] });
     }
+
+    #[test]
+    fn k_nearest() {
+        let points = points(500);
+        let tree = KDTree::from_points(3, points.clone());
+        for target in [vec![50, 50, 50], vec![0, 0, 0], vec![101, 3, 77]].iter() {
+            let target = Point { coords: target.clone() };
+            let (found, n_visited) = tree.nearest(&target, 5);
+            let dists: Vec<int> = found.iter().map(|&(_, d)| d).collect();
+            assert_eq!(dists, brute_force_nearest(points.as_slice(), &target, 5));
+            assert!(n_visited < points.len());
+        }
+        let (found, _) = tree.nearest(&Point { coords: vec![1, 2, 3] }, 0);
+        assert!(found.is_empty());
+        let (found, _) = tree.nearest(&Point { coords: vec![1, 2, 3] }, 1000);
+        assert_eq!(found.len(), 500);
+    }
+
+    #[test]
+    fn range_queries() {
+        let points = points(500);
+        let tree = KDTree::from_points(3, points.clone());
+
+        let target = Point { coords: vec![40, 60, 20] };
+        let (mut found, _) = tree.within_radius(&target, 20);
+        let mut expected: Vec<&Point<int>> = points.iter().filter(|p| p.dist_sq(&target) <= 400)
+                                                   .collect();
+        found.sort_by(|a, b| a.coords.cmp(&b.coords));
+        expected.sort_by(|a, b| a.coords.cmp(&b.coords));
+        assert_eq!(found, expected);
+
+        let (min, max) = (Point { coords: vec![10, 20, 30] }, Point { coords: vec![30, 50, 60] });
+        let (mut found, n_visited) = tree.in_box(&min, &max);
+        let mut expected: Vec<&Point<int>> = points.iter().filter(|p| {
+            range(0, 3).all(|i| min.coords[i] <= p.coords[i] && p.coords[i] <= max.coords[i])
+        }).collect();
+        found.sort_by(|a, b| a.coords.cmp(&b.coords));
+        expected.sort_by(|a, b| a.coords.cmp(&b.coords));
+        assert_eq!(found, expected);
+        assert!(n_visited < points.len());
+    }
+
+    #[test]
+    fn unsigned_coordinates() {
+        let points: Vec<Point<uint>> = range(0u, 10).map(|i| Point { coords: vec![i, 9 - i] })
+                                                    .collect();
+        let tree = KDTree::from_points(2, points);
+        let (found, _) = tree.nearest(&Point { coords: vec![3, 3] }, 2);
+        assert_eq!(found.iter().map(|&(_, d)| d).collect::<Vec<uint>>(), vec![5, 5]);
+    }
+
+    #[test]
+    fn insert_and_remove() {
+        let points = points(300);
+        let mut tree = KDTree::new(3);
+        for point in points.iter() {
+            tree.insert(point.clone());
+        }
+        assert_eq!(tree.len(), 300);
+
+        let (removed, kept) = points.as_slice().split_at(150);
+        for point in removed.iter() {
+            assert!(tree.remove(point));
+        }
+        assert!(!tree.remove(&removed[0]));
+        assert_eq!(tree.len(), 150);
+
+        let target = Point { coords: vec![50, 50, 50] };
+        let (found, _) = tree.nearest(&target, 10);
+        let dists: Vec<int> = found.iter().map(|&(_, d)| d).collect();
+        assert_eq!(dists, brute_force_nearest(kept, &target, 10));
+        let (found, _) = tree.within_radius(&target, 200);
+        assert_eq!(found.len(), kept.len());
+
+        for point in kept.iter() {
+            assert!(tree.remove(point));
+        }
+        assert!(tree.is_empty());
+        assert!(tree.find_nearest_neighbor(&target).is_none());
+    }
+
+    #[test]
+    fn rebalancing() {
+        // Points inserted in order would make a linked list out of an unbalanced tree
+        let mut tree = KDTree::new(1);
+        for i in range(0i, 1000) {
+            tree.insert(Point { coords: vec![i] });
+        }
+        assert!(tree.depth() < 40);
+        let (found, n_visited) = tree.nearest(&Point { coords: vec![500] }, 1);
+        let (nearest, _) = found[0];
+        assert_eq!(*nearest, Point { coords: vec![500] });
+        assert!(n_visited < 50);
+    }
+
+    #[test]
+    #[should_fail]
+    fn insert_nan() {
+        let mut tree = KDTree::new(2);
+        tree.insert(Point { coords: vec![1.0f64, 2.0] });
+        tree.insert(Point { coords: vec![0.0f64 / 0.0, 2.0] });
+    }
+
+    #[test]
+    #[should_fail]
+    fn from_points_nan() {
+        let points = vec![Point { coords: vec![1.0f64] }, Point { coords: vec![0.0f64 / 0.0] }];
+        KDTree::from_points(1, points);
+    }
 }
@@ -1,13 +1,16 @@
 // http://rosettacode.org/wiki/Knapsack_problem/0-1
 
 use std::cmp::max;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
 // This struct is used to store our items that we want in our knap-sack.
 //
 // Show is for displaying the fields.
 // allow(dead_code) removes a dead code warning when the name of the struct is not
 // used (as in this case happens in the tests)
-struct Want<'a> {
+pub struct Want<'a> {
     #[allow(dead_code)]
     name: &'a str,
     weight: uint,
@@ -16,7 +19,7 @@ struct Want<'a> {
 
 // Global, immutable allocation of our items. This is so we can reference
 // this in multiple functions.
-const ITEMS: &'static [Want<'static>] = &[
+pub const ITEMS: &'static [Want<'static>] = &[
     Want {name: "map",                    weight: 9,   value: 150},
     Want {name: "compass",                weight: 13,  value: 35},
     Want {name: "water",                  weight: 153, value: 200},
@@ -44,7 +47,7 @@ const ITEMS: &'static [Want<'static>] = &[
 // This is a bottom-up dynamic programming solution to the 0-1 knap-sack problem.
 //      maximize value
 //      subject to weights <= max_weight
-fn knap_01_dp<'a>(xs: &[Want<'a>], max_weight: uint) -> Vec<Want<'a>> {
+pub fn knap_01_dp<'a>(xs: &[Want<'a>], max_weight: uint) -> Vec<Want<'a>> {
 
     // Save this value, so we don't have to make repeated calls.
     let xs_len = xs.len();
@@ -123,23 +126,27 @@ fn knap_01_dp<'a>(xs: &[Want<'a>], max_weight: uint) -> Vec<Want<'a>> {
 }
 
 #[cfg(not(test))]
-fn main () {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the items of the best knapsack and their totals to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let xs = knap_01_dp(ITEMS, 400);
 
     // Print the items. We have to reverse the order because we solved the
     // problem backward.
     for i in xs.iter().rev() {
-        println!("Item: {}, Weight: {}, Value: {}", i.name, i.weight, i.value);
+        try!(writeln!(out, "Item: {}, Weight: {}, Value: {}", i.name, i.weight, i.value));
     }
 
     // Print the sum of weights.
     let weights = xs.iter().fold(0, |a, &b| a + b.weight);
-    println!("Total Weight: {}", weights);
+    try!(writeln!(out, "Total Weight: {}", weights));
 
     // Print the sum of the values.
     let values = xs.iter().fold(0, |a, &b| a + b.value);
-    println!("Total Value: {}", values);
-
+    writeln!(out, "Total Value: {}", values)
 }
 
 #[test]
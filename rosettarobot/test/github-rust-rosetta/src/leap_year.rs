@@ -1,15 +1,25 @@
 // http://rosettacode.org/wiki/Leap_year
 
-fn is_leap_year(year: int) -> bool {
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
+
+pub fn is_leap_year(year: int) -> bool {
     year % 4 == 0 && (year % 100 != 0 || year % 400 == 0)
 }
 
 #[cfg(not(test))]
-fn main () {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes whether a few years are leap years to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     for &year in [1900, 1995, 1996, 1999, 2000, 2001].iter() {
-        println!("{} {} a leap year", year,
-                 if is_leap_year(year) { "is" } else { "is not" });
+        try!(writeln!(out, "{} {} a leap year", year,
+                      if is_leap_year(year) { "is" } else { "is not" }));
     }
+    Ok(())
 }
 
 #[test]
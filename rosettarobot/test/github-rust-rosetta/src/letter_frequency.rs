@@ -7,7 +7,7 @@ use std::io::BufferedReader;
 use std::collections::HashMap;
 use std::collections::hashmap::{Occupied, Vacant};
 
-fn count_chars<T: Iterator<char>>(mut chars: T) -> HashMap<char, uint> {
+pub fn count_chars<T: Iterator<char>>(mut chars: T) -> HashMap<char, uint> {
     let mut map: HashMap<char, uint> = HashMap::new();
     for letter in chars {
         match map.entry(letter) {
@@ -19,7 +19,7 @@ fn count_chars<T: Iterator<char>>(mut chars: T) -> HashMap<char, uint> {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
     let file = File::open(&Path::new("resources/unixdict.txt"));
     let mut reader = BufferedReader::new(file);
 
@@ -1,59 +1,572 @@
-// Dummy main library
-// It also contains a test module, which checks if all source files are covered by `Cargo.toml`
+// The rust-rosetta library.
+// Every task is exposed as a public module, so its functions can be used from other crates, e.g.
+// `rosetta::lzw::compress`.  A module is named after its source file, with dashes replaced by
+// underscores and a leading underscore if the name starts with a digit (`24_game.rs` becomes
+// `rosetta::_24_game`).  The `rosetta` binary lists the tasks and runs any of them by name.
+// The `registry` module describes every task (Rosetta Code URL, documentation, tests).
+// The `lisp` module evaluates the S-expressions of the `s_expressions` task.
+// The `digest` module puts the hashes of the MD5, SHA-1 and SHA-256 tasks behind a common trait,
+// with HMAC and PBKDF2.
+// The `codec` module does the same for the compression of the run-length encoding, LZW and Huffman
+// coding tasks.  Tasks are also compiled as standalone programs, so they can't depend on these
+// modules: the modules wrap the functions of the tasks instead.
+// The `png` module writes the images of the `bitmap` task as PNG files, and reads them back.
+// It also contains a test module, which checks that the repository is consistent (every source
+// file is a task listed in `Cargo.toml`, with a Rosetta Code URL header and tests).
 
-#![feature(phase, slicing_syntax)]
+#![feature(globs, if_let, macro_rules, overloaded_calls, phase, slicing_syntax, tuple_indexing,
+           unboxed_closures, unsafe_destructor)]
 
+extern crate arena;
+extern crate core;
+extern crate libc;
+extern crate num;
 extern crate regex;
+extern crate serialize;
+extern crate sync;
+extern crate test;
+extern crate time;
+#[phase(plugin)] extern crate factorial_plugin;
 #[phase(plugin)] extern crate regex_macros;
 
-#[allow(dead_code)]
-#[cfg(not(test))]
-fn main() { }
+pub use registry::Task;
+
+// Declares the table of tasks: the `Cargo.toml` name of each task, its module and its source file.
+macro_rules! tasks(
+    ($($name:tt => $module:ident in $path:tt),+) => (
+        /// The names of all tasks, in the order they appear in `Cargo.toml`.
+        pub static TASKS: &'static [&'static str] = &[$($name),+];
+
+        /// The name, source path and source code of every task, in the same order as `TASKS`.
+        pub static TASK_SOURCES: &'static [(&'static str, &'static str, &'static str)] =
+            &[$(($name, concat!("src/", $path), include_str!($path))),+];
+
+        /// Returns the `main` function of the task called `name`, if there is one.
+        #[cfg(not(test))]
+        pub fn find_task(name: &str) -> Option<fn()> {
+            match name {
+                $($name => Some($module::main as fn()),)+
+                _ => None
+            }
+        }
+    )
+)
+
+pub mod codec;
+pub mod digest;
+pub mod lisp;
+pub mod png;
+pub mod registry;
+
+#[path = "100_doors.rs"] pub mod _100_doors;
+#[path = "100_doors_unoptimized.rs"] pub mod _100_doors_unoptimized;
+#[path = "24_game.rs"] pub mod _24_game;
+#[path = "24_game_rpn.rs"] pub mod _24_game_rpn;
+#[path = "24_game_solve.rs"] pub mod _24_game_solve;
+#[path = "99_bottles_of_beer.rs"] pub mod _99_bottles_of_beer;
+#[path = "9_billion_names_of_God_the_integer.rs"] pub mod _9_billion_names_of_God_the_integer;
+#[path = "a_plus_b.rs"] pub mod a_plus_b;
+#[path = "abc_problem.rs"] pub mod abc_problem;
+#[path = "accumulator_factory.rs"] pub mod accumulator_factory;
+#[path = "ackermann_function.rs"] pub mod ackermann_function;
+#[path = "active_object.rs"] pub mod active_object;
+#[path = "aks_test_for_primes.rs"] pub mod aks_test_for_primes;
+#[path = "align_columns.rs"] pub mod align_columns;
+#[path = "almost_prime.rs"] pub mod almost_prime;
+#[path = "anagrams.rs"] pub mod anagrams;
+#[path = "arena_storage_pool.rs"] pub mod arena_storage_pool;
+#[path = "arithmetic_integers.rs"] pub mod arithmetic_integers;
+#[path = "arithmetic_mean.rs"] pub mod arithmetic_mean;
+#[path = "arithmetic_rational.rs"] pub mod arithmetic_rational;
+#[path = "arrays.rs"] pub mod arrays;
+#[path = "assertions.rs"] pub mod assertions;
+#[path = "atomic_updates.rs"] pub mod atomic_updates;
+#[path = "averages_mean_angle.rs"] pub mod averages_mean_angle;
+#[path = "balanced_brackets.rs"] pub mod balanced_brackets;
+#[path = "benford.rs"] pub mod benford;
+#[path = "binary_digits.rs"] pub mod binary_digits;
+#[path = "binary_search.rs"] pub mod binary_search;
+#[path = "binomial_coefficients.rs"] pub mod binomial_coefficients;
+#[path = "bitmap.rs"] pub mod bitmap;
+#[path = "bitmap_bezier_curves_cubic.rs"] pub mod bitmap_bezier_curves_cubic;
+#[path = "bitmap_bezier_curves_quadratic.rs"] pub mod bitmap_bezier_curves_quadratic;
+#[path = "bitmap_bresenhams_line_algorithm.rs"] pub mod bitmap_bresenhams_line_algorithm;
+#[path = "bitmap_flood_fill.rs"] pub mod bitmap_flood_fill;
+#[path = "bitmap_histogram.rs"] pub mod bitmap_histogram;
+#[path = "bitmap_midpoint_circle_algorithm.rs"] pub mod bitmap_midpoint_circle_algorithm;
+#[path = "bitwise_operations.rs"] pub mod bitwise_operations;
+#[path = "bubble_sort.rs"] pub mod bubble_sort;
+#[path = "call_foreign_function.rs"] pub mod call_foreign_function;
+#[path = "callback_to_array.rs"] pub mod callback_to_array;
+#[path = "check_file.rs"] pub mod check_file;
+#[path = "checkpoint_synchronization.rs"] pub mod checkpoint_synchronization;
+#[path = "closest-pair.rs"] pub mod closest_pair;
+#[path = "closures-value_capture.rs"] pub mod closures_value_capture;
+#[path = "comma_quibbling.rs"] pub mod comma_quibbling;
+#[path = "compile_time_calculation.rs"] pub mod compile_time_calculation;
+#[path = "complex.rs"] pub mod complex;
+#[path = "concurrent_computing.rs"] pub mod concurrent_computing;
+#[path = "conways_game_of_life.rs"] pub mod conways_game_of_life;
+#[path = "count_in_octal.rs"] pub mod count_in_octal;
+#[path = "crc_32.rs"] pub mod crc_32;
+#[path = "create_file.rs"] pub mod create_file;
+#[path = "dijkstras_algorithm.rs"] pub mod dijkstras_algorithm;
+#[path = "dot_product.rs"] pub mod dot_product;
+#[path = "echo_server.rs"] pub mod echo_server;
+#[path = "elementary_cellular_automaton.rs"] pub mod elementary_cellular_automaton;
+#[path = "empty.rs"] pub mod empty;
+#[path = "entropy.rs"] pub mod entropy;
+#[path = "equilibrium_index.rs"] pub mod equilibrium_index;
+#[path = "events.rs"] pub mod events;
+#[path = "factor_int.rs"] pub mod factor_int;
+#[path = "factorial.rs"] pub mod factorial;
+#[path = "fast_fourier_transform.rs"] pub mod fast_fourier_transform;
+#[path = "fasta.rs"] pub mod fasta;
+#[path = "fibonacci.rs"] pub mod fibonacci;
+#[path = "fibonacci_word.rs"] pub mod fibonacci_word;
+#[path = "filesize.rs"] pub mod filesize;
+#[path = "four_bit_adder.rs"] pub mod four_bit_adder;
+#[path = "function_composition.rs"] pub mod function_composition;
+#[path = "function_def.rs"] pub mod function_def;
+#[path = "gray_code.rs"] pub mod gray_code;
+#[path = "greater_element_list.rs"] pub mod greater_element_list;
+#[path = "guess_number.rs"] pub mod guess_number;
+#[path = "hailstone.rs"] pub mod hailstone;
+#[path = "hamming_numbers.rs"] pub mod hamming_numbers;
+#[path = "hamming_numbers_alt.rs"] pub mod hamming_numbers_alt;
+#[path = "handle_a_signal.rs"] pub mod handle_a_signal;
+#[path = "happy_numbers.rs"] pub mod happy_numbers;
+#[path = "harshad_or_niven_series.rs"] pub mod harshad_or_niven_series;
+#[path = "hofstadter_q.rs"] pub mod hofstadter_q;
+#[path = "horners_rule.rs"] pub mod horners_rule;
+#[path = "hough_transform.rs"] pub mod hough_transform;
+#[path = "http.rs"] pub mod http;
+#[path = "huffman_coding.rs"] pub mod huffman_coding;
+#[path = "iban.rs"] pub mod iban;
+#[path = "image_convolution.rs"] pub mod image_convolution;
+#[path = "infinity.rs"] pub mod infinity;
+#[path = "input_is_terminal.rs"] pub mod input_is_terminal;
+#[path = "input_loop.rs"] pub mod input_loop;
+#[path = "integer_sequence.rs"] pub mod integer_sequence;
+#[path = "isaac.rs"] pub mod isaac;
+#[path = "json.rs"] pub mod json;
+#[path = "k-d-tree.rs"] pub mod k_d_tree;
+#[path = "knapsack_0-1.rs"] pub mod knapsack_0_1;
+#[path = "leap_year.rs"] pub mod leap_year;
+#[path = "letter_frequency.rs"] pub mod letter_frequency;
+#[path = "linear_congruential_generator.rs"] pub mod linear_congruential_generator;
+#[path = "look-and-say_sequence.rs"] pub mod look_and_say_sequence;
+#[path = "loops-for.rs"] pub mod loops_for;
+#[path = "loops-foreach.rs"] pub mod loops_foreach;
+#[path = "loops-infinite.rs"] pub mod loops_infinite;
+#[path = "loops-n-plus-one-half.rs"] pub mod loops_n_plus_one_half;
+#[path = "loops-while.rs"] pub mod loops_while;
+#[path = "luhn_test.rs"] pub mod luhn_test;
+#[path = "lzw.rs"] pub mod lzw;
+#[path = "mandelbrot_set.rs"] pub mod mandelbrot_set;
+#[path = "markov_algorithm.rs"] pub mod markov_algorithm;
+#[path = "md5-implementation.rs"] pub mod md5_implementation;
+#[path = "median_filter.rs"] pub mod median_filter;
+#[path = "merge-sort.rs"] pub mod merge_sort;
+#[path = "metered_concurrency.rs"] pub mod metered_concurrency;
+#[path = "modular_exponentiation.rs"] pub mod modular_exponentiation;
+#[path = "mutual_recursion.rs"] pub mod mutual_recursion;
+#[path = "n_queens.rs"] pub mod n_queens;
+#[path = "output_is_terminal.rs"] pub mod output_is_terminal;
+#[path = "palindrome.rs"] pub mod palindrome;
+#[path = "parallel_calculations.rs"] pub mod parallel_calculations;
+#[path = "perfect_numbers.rs"] pub mod perfect_numbers;
+#[path = "pernicious_numbers.rs"] pub mod pernicious_numbers;
+#[path = "population_count.rs"] pub mod population_count;
+#[path = "power_set.rs"] pub mod power_set;
+#[path = "primality_trial_div.rs"] pub mod primality_trial_div;
+#[path = "prime_decomposition.rs"] pub mod prime_decomposition;
+#[path = "pythagorean_triples.rs"] pub mod pythagorean_triples;
+#[path = "quick_sort.rs"] pub mod quick_sort;
+#[path = "range_expansion.rs"] pub mod range_expansion;
+#[path = "read_file_line.rs"] pub mod read_file_line;
+#[path = "read_file_specific_line.rs"] pub mod read_file_specific_line;
+#[path = "recursion_depth.rs"] pub mod recursion_depth;
+#[path = "rename_a_file.rs"] pub mod rename_a_file;
+#[path = "repeat_str.rs"] pub mod repeat_str;
+#[path = "reverse_words_str.rs"] pub mod reverse_words_str;
+#[path = "roots_of_a_function.rs"] pub mod roots_of_a_function;
+#[path = "roots_of_unity.rs"] pub mod roots_of_unity;
+#[path = "rot13.rs"] pub mod rot13;
+#[path = "run_length_encoding.rs"] pub mod run_length_encoding;
+#[path = "s_expressions.rs"] pub mod s_expressions;
+#[path = "self-describing_numbers.rs"] pub mod self_describing_numbers;
+#[path = "sequence_of_non-squares.rs"] pub mod sequence_of_non_squares;
+#[path = "set.rs"] pub mod set;
+#[path = "sha1.rs"] pub mod sha1;
+#[path = "sha256.rs"] pub mod sha256;
+#[path = "short_circuit_evaluation.rs"] pub mod short_circuit_evaluation;
+#[path = "sierpinski_triangle.rs"] pub mod sierpinski_triangle;
+#[path = "sieve_eratosthenes.rs"] pub mod sieve_eratosthenes;
+#[path = "sort_int.rs"] pub mod sort_int;
+#[path = "stack.rs"] pub mod stack;
+#[path = "stderr.rs"] pub mod stderr;
+#[path = "string_concatenation.rs"] pub mod string_concatenation;
+#[path = "string_interpolation.rs"] pub mod string_interpolation;
+#[path = "string_matching.rs"] pub mod string_matching;
+#[path = "strip_comments_from_a_string.rs"] pub mod strip_comments_from_a_string;
+#[path = "swap.rs"] pub mod swap;
+#[path = "synchronous_concurrency.rs"] pub mod synchronous_concurrency;
+#[path = "system_time.rs"] pub mod system_time;
+#[path = "taxicab_numbers.rs"] pub mod taxicab_numbers;
+#[path = "towers_of_hanoi.rs"] pub mod towers_of_hanoi;
+#[path = "webserver.rs"] pub mod webserver;
+#[path = "word_wrap.rs"] pub mod word_wrap;
+#[path = "write_ppm.rs"] pub mod write_ppm;
+#[path = "xiaolin_wus_line_algorithm.rs"] pub mod xiaolin_wus_line_algorithm;
+#[path = "zig-zag_matrix.rs"] pub mod zig_zag_matrix;
+
+tasks!(
+    "100_doors" => _100_doors in "100_doors.rs",
+    "100_doors_unoptimized" => _100_doors_unoptimized in "100_doors_unoptimized.rs",
+    "24_game" => _24_game in "24_game.rs",
+    "24_game_rpn" => _24_game_rpn in "24_game_rpn.rs",
+    "24_game_solve" => _24_game_solve in "24_game_solve.rs",
+    "99_bottles_of_beer" => _99_bottles_of_beer in "99_bottles_of_beer.rs",
+    "9_billion_names_of_God_the_integer" => _9_billion_names_of_God_the_integer in "9_billion_names_of_God_the_integer.rs",
+    "a_plus_b" => a_plus_b in "a_plus_b.rs",
+    "abc_problem" => abc_problem in "abc_problem.rs",
+    "accumulator_factory" => accumulator_factory in "accumulator_factory.rs",
+    "ackermann_function" => ackermann_function in "ackermann_function.rs",
+    "active_object" => active_object in "active_object.rs",
+    "aks_test_for_primes" => aks_test_for_primes in "aks_test_for_primes.rs",
+    "align_columns" => align_columns in "align_columns.rs",
+    "almost_prime" => almost_prime in "almost_prime.rs",
+    "anagrams" => anagrams in "anagrams.rs",
+    "arena_storage_pool" => arena_storage_pool in "arena_storage_pool.rs",
+    "arithmetic_integers" => arithmetic_integers in "arithmetic_integers.rs",
+    "arithmetic_mean" => arithmetic_mean in "arithmetic_mean.rs",
+    "arithmetic_rational" => arithmetic_rational in "arithmetic_rational.rs",
+    "arrays" => arrays in "arrays.rs",
+    "assertions" => assertions in "assertions.rs",
+    "atomic_updates" => atomic_updates in "atomic_updates.rs",
+    "averages_mean_angle" => averages_mean_angle in "averages_mean_angle.rs",
+    "balanced_brackets" => balanced_brackets in "balanced_brackets.rs",
+    "benford" => benford in "benford.rs",
+    "binary_digits" => binary_digits in "binary_digits.rs",
+    "binary_search" => binary_search in "binary_search.rs",
+    "binomial_coefficients" => binomial_coefficients in "binomial_coefficients.rs",
+    "bitmap" => bitmap in "bitmap.rs",
+    "bitmap_bezier_curves_cubic" => bitmap_bezier_curves_cubic in "bitmap_bezier_curves_cubic.rs",
+    "bitmap_bezier_curves_quadratic" => bitmap_bezier_curves_quadratic in "bitmap_bezier_curves_quadratic.rs",
+    "bitmap_bresenhams_line_algorithm" => bitmap_bresenhams_line_algorithm in "bitmap_bresenhams_line_algorithm.rs",
+    "bitmap_flood_fill" => bitmap_flood_fill in "bitmap_flood_fill.rs",
+    "bitmap_histogram" => bitmap_histogram in "bitmap_histogram.rs",
+    "bitmap_midpoint_circle_algorithm" => bitmap_midpoint_circle_algorithm in "bitmap_midpoint_circle_algorithm.rs",
+    "bitwise_operations" => bitwise_operations in "bitwise_operations.rs",
+    "bubble_sort" => bubble_sort in "bubble_sort.rs",
+    "call_foreign_function" => call_foreign_function in "call_foreign_function.rs",
+    "callback_to_array" => callback_to_array in "callback_to_array.rs",
+    "check_file" => check_file in "check_file.rs",
+    "checkpoint_synchronization" => checkpoint_synchronization in "checkpoint_synchronization.rs",
+    "closest-pair" => closest_pair in "closest-pair.rs",
+    "closures-value_capture" => closures_value_capture in "closures-value_capture.rs",
+    "comma_quibbling" => comma_quibbling in "comma_quibbling.rs",
+    "compile_time_calculation" => compile_time_calculation in "compile_time_calculation.rs",
+    "complex" => complex in "complex.rs",
+    "concurrent_computing" => concurrent_computing in "concurrent_computing.rs",
+    "conways_game_of_life" => conways_game_of_life in "conways_game_of_life.rs",
+    "count_in_octal" => count_in_octal in "count_in_octal.rs",
+    "crc_32" => crc_32 in "crc_32.rs",
+    "create_file" => create_file in "create_file.rs",
+    "dijkstras_algorithm" => dijkstras_algorithm in "dijkstras_algorithm.rs",
+    "dot_product" => dot_product in "dot_product.rs",
+    "echo_server" => echo_server in "echo_server.rs",
+    "elementary_cellular_automaton" => elementary_cellular_automaton in "elementary_cellular_automaton.rs",
+    "empty" => empty in "empty.rs",
+    "entropy" => entropy in "entropy.rs",
+    "equilibrium_index" => equilibrium_index in "equilibrium_index.rs",
+    "events" => events in "events.rs",
+    "factor_int" => factor_int in "factor_int.rs",
+    "factorial" => factorial in "factorial.rs",
+    "fast_fourier_transform" => fast_fourier_transform in "fast_fourier_transform.rs",
+    "fasta" => fasta in "fasta.rs",
+    "fibonacci" => fibonacci in "fibonacci.rs",
+    "fibonacci_word" => fibonacci_word in "fibonacci_word.rs",
+    "filesize" => filesize in "filesize.rs",
+    "four_bit_adder" => four_bit_adder in "four_bit_adder.rs",
+    "function_composition" => function_composition in "function_composition.rs",
+    "function_def" => function_def in "function_def.rs",
+    "gray_code" => gray_code in "gray_code.rs",
+    "greater_element_list" => greater_element_list in "greater_element_list.rs",
+    "guess_number" => guess_number in "guess_number.rs",
+    "hailstone" => hailstone in "hailstone.rs",
+    "hamming_numbers" => hamming_numbers in "hamming_numbers.rs",
+    "hamming_numbers_alt" => hamming_numbers_alt in "hamming_numbers_alt.rs",
+    "handle_a_signal" => handle_a_signal in "handle_a_signal.rs",
+    "happy_numbers" => happy_numbers in "happy_numbers.rs",
+    "harshad_or_niven_series" => harshad_or_niven_series in "harshad_or_niven_series.rs",
+    "hofstadter_q" => hofstadter_q in "hofstadter_q.rs",
+    "horners_rule" => horners_rule in "horners_rule.rs",
+    "hough_transform" => hough_transform in "hough_transform.rs",
+    "http" => http in "http.rs",
+    "huffman_coding" => huffman_coding in "huffman_coding.rs",
+    "iban" => iban in "iban.rs",
+    "image_convolution" => image_convolution in "image_convolution.rs",
+    "infinity" => infinity in "infinity.rs",
+    "input_is_terminal" => input_is_terminal in "input_is_terminal.rs",
+    "input_loop" => input_loop in "input_loop.rs",
+    "integer_sequence" => integer_sequence in "integer_sequence.rs",
+    "isaac" => isaac in "isaac.rs",
+    "json" => json in "json.rs",
+    "k-d-tree" => k_d_tree in "k-d-tree.rs",
+    "knapsack_0-1" => knapsack_0_1 in "knapsack_0-1.rs",
+    "leap_year" => leap_year in "leap_year.rs",
+    "letter_frequency" => letter_frequency in "letter_frequency.rs",
+    "linear_congruential_generator" => linear_congruential_generator in "linear_congruential_generator.rs",
+    "look-and-say_sequence" => look_and_say_sequence in "look-and-say_sequence.rs",
+    "loops-for" => loops_for in "loops-for.rs",
+    "loops-foreach" => loops_foreach in "loops-foreach.rs",
+    "loops-infinite" => loops_infinite in "loops-infinite.rs",
+    "loops-n-plus-one-half" => loops_n_plus_one_half in "loops-n-plus-one-half.rs",
+    "loops-while" => loops_while in "loops-while.rs",
+    "luhn_test" => luhn_test in "luhn_test.rs",
+    "lzw" => lzw in "lzw.rs",
+    "mandelbrot_set" => mandelbrot_set in "mandelbrot_set.rs",
+    "markov_algorithm" => markov_algorithm in "markov_algorithm.rs",
+    "md5-implementation" => md5_implementation in "md5-implementation.rs",
+    "median_filter" => median_filter in "median_filter.rs",
+    "merge-sort" => merge_sort in "merge-sort.rs",
+    "metered_concurrency" => metered_concurrency in "metered_concurrency.rs",
+    "modular_exponentiation" => modular_exponentiation in "modular_exponentiation.rs",
+    "mutual_recursion" => mutual_recursion in "mutual_recursion.rs",
+    "n_queens" => n_queens in "n_queens.rs",
+    "output_is_terminal" => output_is_terminal in "output_is_terminal.rs",
+    "palindrome" => palindrome in "palindrome.rs",
+    "parallel_calculations" => parallel_calculations in "parallel_calculations.rs",
+    "perfect_numbers" => perfect_numbers in "perfect_numbers.rs",
+    "pernicious_numbers" => pernicious_numbers in "pernicious_numbers.rs",
+    "population_count" => population_count in "population_count.rs",
+    "power_set" => power_set in "power_set.rs",
+    "primality_trial_div" => primality_trial_div in "primality_trial_div.rs",
+    "prime_decomposition" => prime_decomposition in "prime_decomposition.rs",
+    "pythagorean_triples" => pythagorean_triples in "pythagorean_triples.rs",
+    "quick_sort" => quick_sort in "quick_sort.rs",
+    "range_expansion" => range_expansion in "range_expansion.rs",
+    "read_file_line" => read_file_line in "read_file_line.rs",
+    "read_file_specific_line" => read_file_specific_line in "read_file_specific_line.rs",
+    "recursion_depth" => recursion_depth in "recursion_depth.rs",
+    "rename_a_file" => rename_a_file in "rename_a_file.rs",
+    "repeat_str" => repeat_str in "repeat_str.rs",
+    "reverse_words_str" => reverse_words_str in "reverse_words_str.rs",
+    "roots_of_a_function" => roots_of_a_function in "roots_of_a_function.rs",
+    "roots_of_unity" => roots_of_unity in "roots_of_unity.rs",
+    "rot13" => rot13 in "rot13.rs",
+    "run_length_encoding" => run_length_encoding in "run_length_encoding.rs",
+    "s_expressions" => s_expressions in "s_expressions.rs",
+    "self-describing_numbers" => self_describing_numbers in "self-describing_numbers.rs",
+    "sequence_of_non-squares" => sequence_of_non_squares in "sequence_of_non-squares.rs",
+    "set" => set in "set.rs",
+    "sha1" => sha1 in "sha1.rs",
+    "sha256" => sha256 in "sha256.rs",
+    "short_circuit_evaluation" => short_circuit_evaluation in "short_circuit_evaluation.rs",
+    "sierpinski_triangle" => sierpinski_triangle in "sierpinski_triangle.rs",
+    "sieve_eratosthenes" => sieve_eratosthenes in "sieve_eratosthenes.rs",
+    "sort_int" => sort_int in "sort_int.rs",
+    "stack" => stack in "stack.rs",
+    "stderr" => stderr in "stderr.rs",
+    "string_concatenation" => string_concatenation in "string_concatenation.rs",
+    "string_interpolation" => string_interpolation in "string_interpolation.rs",
+    "string_matching" => string_matching in "string_matching.rs",
+    "strip_comments_from_a_string" => strip_comments_from_a_string in "strip_comments_from_a_string.rs",
+    "swap" => swap in "swap.rs",
+    "synchronous_concurrency" => synchronous_concurrency in "synchronous_concurrency.rs",
+    "system_time" => system_time in "system_time.rs",
+    "taxicab_numbers" => taxicab_numbers in "taxicab_numbers.rs",
+    "towers_of_hanoi" => towers_of_hanoi in "towers_of_hanoi.rs",
+    "webserver" => webserver in "webserver.rs",
+    "word_wrap" => word_wrap in "word_wrap.rs",
+    "write_ppm" => write_ppm in "write_ppm.rs",
+    "xiaolin_wus_line_algorithm" => xiaolin_wus_line_algorithm in "xiaolin_wus_line_algorithm.rs",
+    "zig-zag_matrix" => zig_zag_matrix in "zig-zag_matrix.rs"
+)
 
 #[cfg(test)]
-mod test {
+mod tests {
     use std::collections::HashSet;
     use std::io::BufferedReader;
     use std::io::fs::{mod, File};
 
-    // A test to check if all source files are covered by `Cargo.toml`
+    use registry;
+
+    // A `[lib]` or `[[bin]]` entry of `Cargo.toml`, along with the line numbers of its keys
+    struct TomlEntry {
+        section: String,
+        line: uint,
+        name: Option<(uint, String)>,
+        path: Option<(uint, String)>,
+        test: bool
+    }
+
+    impl TomlEntry {
+        // Returns the filename of the entry's source file, if it is a task directly under `src`
+        fn task_file(&self) -> Option<String> {
+            match self.path {
+                Some((_, ref path)) if self.section[] == "[[bin]]" => {
+                    let path = Path::new(path[]);
+                    if path.dirname_str() == Some("src") {
+                        path.filename_str().map(|f| f.to_string())
+                    } else {
+                        None
+                    }
+                }
+                _ => None
+            }
+        }
+    }
+
+    // A test to check that the repository is consistent: every source file is a task listed in
+    // `Cargo.toml` under its own name and in lexicographic order, starts with a link to its Rosetta
+    // Code page and has tests, and every resource is used by some task.
+    // All the problems are reported at once, as `file:line: message`.
     #[test]
-    fn check_sources_covered() {
-        let sources = get_source_files();
-        let bins = get_toml_paths();
-        let not_covered = get_not_covered(&sources, &bins);
+    fn check_repository() {
+        let entries = get_toml_entries();
+        let mut errors = Vec::new();
+
+        errors.extend(check_sources_covered(&entries).into_iter());
+        errors.extend(check_rosetta_urls().into_iter());
+        errors.extend(check_bin_names(&entries).into_iter());
+        errors.extend(check_order(&entries).into_iter());
+        errors.extend(check_resources_used().into_iter());
+        errors.extend(check_tests(&entries).into_iter());
 
-        if not_covered.len() > 0 {
-            println!("Error, the following source files are not covered by Cargo.toml:");
+        if errors.len() > 0 {
+            println!("Error, the repository is not consistent:");
 
-            for source in not_covered.iter() {
-                println!("{}", source);
+            for error in errors.iter() {
+                println!("{}", error);
             }
 
-            fail!("Please add the previous source files to Cargo.toml");
+            fail!("Please fix the {} previous problem(s)", errors.len());
         }
     }
 
+    // Every source file must be covered by `Cargo.toml`
+    fn check_sources_covered(entries: &Vec<TomlEntry>) -> Vec<String> {
+        let paths: HashSet<String> = entries.iter().filter_map(|e| e.path.as_ref()).map(|&(_, ref p)| {
+            Path::new(p[]).filename_str().unwrap().to_string()
+        }).collect();
+        get_source_files().into_iter().filter(|s| !paths.contains(s))
+                          .map(|s| format!("src/{}: not covered by Cargo.toml", s))
+                          .collect()
+    }
+
+    // Every task must start with a comment linking to its Rosetta Code page
+    fn check_rosetta_urls() -> Vec<String> {
+        get_source_files().into_iter().filter(|s| s[] != "lib.rs").filter_map(|s| {
+            let source = read_source(s[]);
+            let header_len = source[].lines().take_while(|l| l.starts_with("//")).count();
+            match registry::rosetta_url(source[]) {
+                Some((line, _)) if line <= header_len => None,
+                _ => Some(format!("src/{}:1: missing Rosetta Code URL header \
+                                   (`// http://rosettacode.org/wiki/...`)", s))
+            }
+        }).collect()
+    }
+
+    // The name of every `[[bin]]` must be the stem of its source file
+    fn check_bin_names(entries: &Vec<TomlEntry>) -> Vec<String> {
+        entries.iter().filter(|e| e.section[] == "[[bin]]").filter_map(|e| {
+            match (&e.name, &e.path) {
+                (&Some((line, ref name)), &Some((_, ref path))) => {
+                    let stem = Path::new(path[]).filestem_str().unwrap().to_string();
+                    if *name != stem {
+                        Some(format!("Cargo.toml:{}: `{}` should be named `{}` after its source file",
+                                     line, name, stem))
+                    } else {
+                        None
+                    }
+                }
+                _ => Some(format!("Cargo.toml:{}: `[[bin]]` needs both a name and a path", e.line))
+            }
+        }).collect()
+    }
+
+    // The tasks must be listed in lexicographic order
+    fn check_order(entries: &Vec<TomlEntry>) -> Vec<String> {
+        let tasks: Vec<&(uint, String)> = entries.iter().filter(|e| e.task_file().is_some())
+                                                 .filter_map(|e| e.name.as_ref())
+                                                 .collect();
+        tasks.as_slice().windows(2).filter(|pair| pair[0].1 > pair[1].1).map(|pair| {
+            format!("Cargo.toml:{}: `{}` should come before `{}`", pair[1].0, pair[1].1, pair[0].1)
+        }).collect()
+    }
+
+    // Every file in `src/resources` must be used by some task
+    fn check_resources_used() -> Vec<String> {
+        let sources: Vec<String> = get_source_files().iter().map(|s| read_source(s[])).collect();
+        let resources = fs::readdir(&Path::new("./src/resources")).unwrap();
+        resources.iter().map(|p| p.filename_str().unwrap().to_string())
+                 .filter(|r| !sources.iter().any(|s| s[].contains(r[])))
+                 .map(|r| format!("src/resources/{}: not used by any task", r))
+                 .collect()
+    }
+
+    // Every task must have at least one `#[test]`, unless it is marked with `test = false`
+    // (and then it must not have any)
+    fn check_tests(entries: &Vec<TomlEntry>) -> Vec<String> {
+        entries.iter().filter_map(|e| e.task_file().map(|f| (e, f))).filter_map(|(e, file)| {
+            let has_tests = !registry::test_names(read_source(file[])[]).is_empty();
+            if !has_tests && e.test {
+                Some(format!("src/{}:1: no #[test] (add one, or `test = false` in Cargo.toml)", file))
+            } else if has_tests && !e.test {
+                Some(format!("Cargo.toml:{}: `{}` has tests, but is marked with `test = false`",
+                             e.line, file))
+            } else {
+                None
+            }
+        }).collect()
+    }
+
     // Returns the names of the source files in the `src` directory
-    fn get_source_files() -> HashSet<String> {
+    fn get_source_files() -> Vec<String> {
         let paths = fs::readdir(&Path::new("./src")).unwrap();
-        paths.iter().map(|p| p.filename_str().unwrap().to_string())
-                    .filter(|s| s[].ends_with(".rs")).collect()
+        let mut files: Vec<String> = paths.iter().map(|p| p.filename_str().unwrap().to_string())
+                                          .filter(|s| s[].ends_with(".rs")).collect();
+        files.sort();
+        files
     }
 
-    // Returns the paths of the source files referenced in Cargo.toml
-    fn get_toml_paths() -> HashSet<String> {
+    // Returns the contents of the source file `src/<filename>`
+    fn read_source(filename: &str) -> String {
+        File::open(&Path::new("./src").join(filename)).read_to_string().unwrap()
+    }
+
+    // Returns the `[lib]` and `[[bin]]` entries of Cargo.toml
+    fn get_toml_entries() -> Vec<TomlEntry> {
         let c_toml = File::open(&Path::new("./Cargo.toml")).unwrap();
         let mut reader = BufferedReader::new(c_toml);
-        let regex = regex!("path = \"(.*)\"");
-        reader.lines().filter_map(|l| {
-            let l = l.unwrap();
-            regex.captures(l[]).map(|c| Path::new(c.at(1)).filename_str().unwrap().to_string())
-        }).collect()
-    }
+        let section = regex!(r"^\[\[?[a-z._-]+\]\]?$");
+        let key = regex!(r#"^(name|path) = "(.*)"$"#);
+        let mut entries: Vec<TomlEntry> = Vec::new();
+        let mut in_entry = false;
 
-    // Returns the filenames of the source files which are not covered by Cargo.toml
-    fn get_not_covered<'a>(sources: &'a HashSet<String>, paths: &'a HashSet<String>) -> HashSet<&'a String> {
-        sources.difference(paths).collect()
+        for (i, l) in reader.lines().enumerate() {
+            let l = l.unwrap();
+            let l = l[].trim();
+            let line = i + 1;
+            if section.is_match(l) {
+                in_entry = l == "[lib]" || l == "[[bin]]";
+                if in_entry {
+                    entries.push(TomlEntry { section: l.to_string(), line: line, name: None,
+                                             path: None, test: true });
+                }
+            } else if in_entry {
+                let entry = entries.last_mut().unwrap();
+                match key.captures(l) {
+                    Some(c) if c.at(1) == "name" => entry.name = Some((line, c.at(2).to_string())),
+                    Some(c) => entry.path = Some((line, c.at(2).to_string())),
+                    None if l == "test = false" => entry.test = false,
+                    None => {}
+                }
+            }
+        }
+        entries
     }
 }
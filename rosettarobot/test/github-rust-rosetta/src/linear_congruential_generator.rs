@@ -1,12 +1,16 @@
 // Implements http://rosettacode.org/wiki/Linear_congruential_generator
 
-trait LinearCongruentialGenerator {
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
+
+pub trait LinearCongruentialGenerator {
     fn new(seed: u32) -> Self;
     fn seed(&mut self, seed: u32);
     fn next(&mut self) -> u32;
 }
 
-struct BSDLinearCongruentialGenerator {
+pub struct BSDLinearCongruentialGenerator {
     state: u32
 }
 
@@ -25,7 +29,7 @@ impl LinearCongruentialGenerator for BSDLinearCongruentialGenerator {
     }
 }
 
-struct MSLinearCongruentialGenerator {
+pub struct MSLinearCongruentialGenerator {
     state: u32
 }
 
@@ -45,19 +49,25 @@ impl LinearCongruentialGenerator for MSLinearCongruentialGenerator {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the first ten numbers of both generators to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let mut bsd: BSDLinearCongruentialGenerator = LinearCongruentialGenerator::new(0);
     let mut ms: MSLinearCongruentialGenerator = LinearCongruentialGenerator::new(0);
     let names = ["BSD", "Microsoft"];
     let mut lcgs: [&mut LinearCongruentialGenerator, ..2] = [&mut bsd, &mut ms];
     for (name, lcg) in names.iter().zip(lcgs.iter_mut()) {
-        println!("{}", name)
+        try!(writeln!(out, "{}", name));
         for _ in range(0i, 10) {
             let next: u32 = lcg.next();
-            println!("{}", next);
+            try!(writeln!(out, "{}", next));
         }
-        println!("");
+        try!(writeln!(out, ""));
     }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -0,0 +1,642 @@
+// A small Lisp evaluator on top of the S-expression parser of `s_expressions`.
+//
+// Parsed SExps borrow from their ParseContext, so they are first converted into owned Values,
+// which are both the code and the data of the language.  The language is a small subset of
+// Scheme:
+//
+// - numbers (f64), strings, symbols and lists, plus `true` and `false`
+// - special forms: `quote`, `if`, `define`, `lambda`, `let` and `begin`
+// - primitives: `+ - * /`, `= < > <= >=`, `not`, `list`, `car`, `cdr`, `cons` and `null?`
+//
+// Only `false` and the empty list are false.  Every problem (unbound symbol, wrong number of
+// arguments, calls nested deeper than MAX_DEPTH, ...) is reported as an Error instead of a task
+// failure.
+//
+// A procedure holds the frame it was defined in, so it can still be called after the evaluation
+// which returned it.  That frame usually holds the procedure too (a recursive function is defined
+// in the frame it captures), so the heap of the interpreter keeps track of the captured frames
+// and empties the ones still alive when the interpreter is dropped, which breaks the cycles.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::{Rc, Weak};
+
+use s_expressions;
+use s_expressions::{ParseContext, SExp};
+
+// The deepest nesting of procedure calls, so that a runaway recursion returns an Error instead of
+// overflowing the stack.
+pub const MAX_DEPTH: uint = 1000;
+
+// A Lisp value.  Lists are used both for data and for code.
+pub enum Value {
+    Num(f64),
+    Str(String),
+    Sym(String),
+    Bool(bool),
+    List(Vec<Value>),
+    Builtin(&'static str, fn(&[Value]) -> Result<Value, Error>), // Primitive procedure
+    Lambda(Rc<Procedure>), // Procedure defined with `lambda`
+}
+
+// A procedure defined with `lambda`, along with the environment it was defined in.
+pub struct Procedure {
+    params: Vec<String>,
+    body: Vec<Value>,
+    env: Env,
+}
+
+// Errors that can be returned by the evaluator.
+#[deriving(PartialEq)]
+pub enum Error {
+    ParseError(s_expressions::Error), // The source isn't a valid S-expression
+    UnboundSymbol(String), // A symbol without a value
+    NotAProcedure(String), // The first element of a list that was called
+    ArityMismatch(String, uint, uint), // Procedure, expected and actual number of arguments
+    TypeError(String), // An argument of the wrong type
+    DivisionByZero,
+    BadSyntax(String), // A special form that isn't used correctly
+    RecursionTooDeep, // More than MAX_DEPTH nested procedure calls
+}
+
+// An environment: a frame of variables and the environment it extends.
+#[deriving(Clone)]
+pub struct Env {
+    frame: Rc<RefCell<Frame>>,
+    heap: Weak<Heap>,
+}
+
+struct Frame {
+    vars: HashMap<String, Value>,
+    parent: Option<Env>,
+}
+
+// The frames captured by procedures, by address.
+struct Heap {
+    frames: RefCell<HashMap<uint, Weak<RefCell<Frame>>>>,
+}
+
+impl Clone for Value {
+    fn clone(&self) -> Value {
+        match *self {
+            Num(n) => Num(n),
+            Str(ref s) => Str(s.clone()),
+            Sym(ref s) => Sym(s.clone()),
+            Bool(b) => Bool(b),
+            List(ref l) => List(l.clone()),
+            Builtin(name, f) => Builtin(name, f),
+            Lambda(ref p) => Lambda(p.clone()),
+        }
+    }
+}
+
+impl fmt::Show for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Num(n) => write!(f, "{}", n),
+            Str(ref s) => write!(f, "\"{}\"", s),
+            Sym(ref s) => write!(f, "{}", s),
+            Bool(b) => write!(f, "{}", b),
+            List(ref l) => {
+                try!(write!(f, "("));
+                for (i, v) in l.iter().enumerate() {
+                    if i > 0 {
+                        try!(write!(f, " "));
+                    }
+                    try!(write!(f, "{}", v));
+                }
+                write!(f, ")")
+            },
+            Builtin(name, _) => write!(f, "#<builtin {}>", name),
+            Lambda(_) => write!(f, "#<lambda>"),
+        }
+    }
+}
+
+impl fmt::Show for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseError(ref e) => write!(f, "parse error: {}", e),
+            UnboundSymbol(ref s) => write!(f, "unbound symbol: {}", s),
+            NotAProcedure(ref s) => write!(f, "not a procedure: {}", s),
+            ArityMismatch(ref name, expected, actual) =>
+                write!(f, "{} expects {} argument(s), got {}", name, expected, actual),
+            TypeError(ref s) => write!(f, "type error: {}", s),
+            DivisionByZero => write!(f, "division by zero"),
+            BadSyntax(ref s) => write!(f, "bad syntax: {}", s),
+            RecursionTooDeep => write!(f, "recursion deeper than {} calls", MAX_DEPTH),
+        }
+    }
+}
+
+impl Value {
+    // Converts a parsed SExp into an owned Value.
+    pub fn from_sexp(sexp: &SExp) -> Value {
+        match *sexp {
+            s_expressions::F64(n) => Num(n),
+            s_expressions::Str(s) => Str(s.to_string()),
+            s_expressions::Sym(s) => Sym(s.to_string()),
+            // Only the canonical form has atoms which aren't UTF-8
+            s_expressions::Bytes(b) => Str(String::from_utf8_lossy(b).to_string()),
+            s_expressions::List(l) => List(l.iter().map(|s| Value::from_sexp(s)).collect()),
+        }
+    }
+
+    // Everything except `false` and the empty list is true.
+    pub fn is_true(&self) -> bool {
+        match *self {
+            Bool(false) => false,
+            List(ref l) => !l.is_empty(),
+            _ => true
+        }
+    }
+}
+
+impl Env {
+    // Creates an empty environment, whose captured frames are tracked by `heap`.
+    fn new(heap: &Rc<Heap>) -> Env {
+        let frame = Frame { vars: HashMap::new(), parent: None };
+        Env { frame: Rc::new(RefCell::new(frame)), heap: heap.downgrade() }
+    }
+
+    // Creates an empty environment extending `self`.
+    pub fn extend(&self) -> Env {
+        let frame = Frame { vars: HashMap::new(), parent: Some(self.clone()) };
+        Env { frame: Rc::new(RefCell::new(frame)), heap: self.heap.clone() }
+    }
+
+    // Looks up the value of `name`, starting with the innermost frame.
+    pub fn lookup(&self, name: &str) -> Option<Value> {
+        let frame = self.frame.borrow();
+        match frame.vars.find_equiv(&name) {
+            Some(value) => Some(value.clone()),
+            None => match frame.parent {
+                Some(ref parent) => parent.lookup(name),
+                None => None
+            }
+        }
+    }
+
+    // Binds `name` to `value` in the innermost frame.
+    pub fn define(&self, name: &str, value: Value) {
+        self.frame.borrow_mut().vars.insert(name.to_string(), value);
+    }
+}
+
+// Holds the global environment, with the primitives already defined, and the heap of the frames
+// captured by procedures.
+pub struct Interpreter {
+    global: Env,
+    heap: Rc<Heap>,
+}
+
+impl Interpreter {
+    pub fn new() -> Interpreter {
+        let heap = Rc::new(Heap { frames: RefCell::new(HashMap::new()) });
+        let global = Env::new(&heap);
+        global.define("true", Bool(true));
+        global.define("false", Bool(false));
+        for &(name, f) in PRIMITIVES.iter() {
+            global.define(name, Builtin(name, f));
+        }
+        Interpreter { global: global, heap: heap }
+    }
+
+    // Evaluates a parsed SExp in the global environment.  The procedures of the result can be
+    // called for as long as the interpreter is alive.
+    pub fn eval(&mut self, sexp: &SExp) -> Result<Value, Error> {
+        let result = eval(&Value::from_sexp(sexp), &self.global, 0);
+        // Forget the captured frames which were dropped
+        let mut frames = self.heap.frames.borrow_mut();
+        let dropped: Vec<uint> = frames.iter()
+                                       .filter(|&(_, frame)| frame.upgrade().is_none())
+                                       .map(|(&address, _)| address)
+                                       .collect();
+        for address in dropped.iter() {
+            frames.remove(address);
+        }
+        result
+    }
+
+    // Parses a single expression and evaluates it in the global environment.
+    pub fn eval_str(&mut self, source: &str) -> Result<Value, Error> {
+        let ref mut ctx = ParseContext::new(source).with_symbols();
+        let sexp = try!(SExp::parse(ctx).map_err(|e| ParseError(e)));
+        self.eval(&sexp)
+    }
+
+    // Evaluates a sequence of expressions, returning the value of the last one.
+    pub fn eval_program(&mut self, source: &str) -> Result<Value, Error> {
+        self.eval_str(format!("(begin {})", source)[])
+    }
+}
+
+// Empties the captured frames, which drops the procedures they hold and the frames those refer to.
+impl Drop for Interpreter {
+    fn drop(&mut self) {
+        for frame in self.heap.frames.borrow().values() {
+            if let Some(frame) = frame.upgrade() {
+                frame.borrow_mut().vars.clear();
+            }
+        }
+    }
+}
+
+fn frame_address(frame: &Rc<RefCell<Frame>>) -> uint {
+    &**frame as *const RefCell<Frame> as uint
+}
+
+// Evaluates `value` in `env`, inside `depth` procedure calls.
+pub fn eval(value: &Value, env: &Env, depth: uint) -> Result<Value, Error> {
+    match *value {
+        Sym(ref name) => env.lookup(name[]).ok_or(UnboundSymbol(name.clone())),
+        List(ref list) if !list.is_empty() => {
+            match list[0] {
+                Sym(ref form) => match form[] {
+                    "quote" => eval_quote(list[]),
+                    "if" => eval_if(list[], env, depth),
+                    "define" => eval_define(list[], env, depth),
+                    "lambda" => eval_lambda(list[], env),
+                    "let" => eval_let(list[], env, depth),
+                    "begin" => eval_body(list.slice_from(1), env, depth),
+                    _ => eval_call(list[], env, depth)
+                },
+                _ => eval_call(list[], env, depth)
+            }
+        },
+        // Numbers, strings, booleans, procedures and the empty list evaluate to themselves.
+        _ => Ok(value.clone())
+    }
+}
+
+// Calls `f` with the (already evaluated) `args`, inside `depth` procedure calls.
+pub fn apply(f: &Value, args: &[Value], depth: uint) -> Result<Value, Error> {
+    match *f {
+        Builtin(_, builtin) => builtin(args),
+        Lambda(ref procedure) => {
+            if procedure.params.len() != args.len() {
+                return Err(ArityMismatch(f.to_string(), procedure.params.len(), args.len()));
+            }
+            if depth >= MAX_DEPTH {
+                return Err(RecursionTooDeep);
+            }
+            let env = procedure.env.extend();
+            for (param, arg) in procedure.params.iter().zip(args.iter()) {
+                env.define(param[], arg.clone());
+            }
+            eval_body(procedure.body[], &env, depth + 1)
+        },
+        _ => Err(NotAProcedure(f.to_string()))
+    }
+}
+
+// Evaluates every expression of `body`, returning the value of the last one (or the empty list).
+fn eval_body(body: &[Value], env: &Env, depth: uint) -> Result<Value, Error> {
+    let mut result = List(Vec::new());
+    for expr in body.iter() {
+        result = try!(eval(expr, env, depth));
+    }
+    Ok(result)
+}
+
+fn eval_call(list: &[Value], env: &Env, depth: uint) -> Result<Value, Error> {
+    let f = try!(eval(&list[0], env, depth));
+    let mut args = Vec::with_capacity(list.len() - 1);
+    for arg in list.slice_from(1).iter() {
+        args.push(try!(eval(arg, env, depth)));
+    }
+    apply(&f, args[], depth)
+}
+
+// (quote <datum>)
+fn eval_quote(list: &[Value]) -> Result<Value, Error> {
+    match list {
+        [_, ref datum] => Ok(datum.clone()),
+        _ => Err(BadSyntax("(quote <datum>)".to_string()))
+    }
+}
+
+// (if <test> <consequent> [<alternative>])
+fn eval_if(list: &[Value], env: &Env, depth: uint) -> Result<Value, Error> {
+    let (test, consequent, alternative) = match list {
+        [_, ref test, ref consequent] => (test, consequent, None),
+        [_, ref test, ref consequent, ref alternative] => (test, consequent, Some(alternative)),
+        _ => return Err(BadSyntax("(if <test> <consequent> [<alternative>])".to_string()))
+    };
+    if try!(eval(test, env, depth)).is_true() {
+        eval(consequent, env, depth)
+    } else {
+        match alternative {
+            Some(alternative) => eval(alternative, env, depth),
+            None => Ok(List(Vec::new()))
+        }
+    }
+}
+
+// (define <name> <expr>) or (define (<name> <param>...) <body>...)
+fn eval_define(list: &[Value], env: &Env, depth: uint) -> Result<Value, Error> {
+    match list {
+        [_, Sym(ref name), ref expr] => {
+            let value = try!(eval(expr, env, depth));
+            env.define(name[], value);
+            Ok(Sym(name.clone()))
+        },
+        [_, List(ref signature), ..] if list.len() > 2 && !signature.is_empty() => {
+            let name = match signature[0] {
+                Sym(ref name) => name.clone(),
+                _ => return Err(BadSyntax("(define (<name> <param>...) <body>...)".to_string()))
+            };
+            let procedure = try!(make_procedure(signature.slice_from(1), list.slice_from(2), env));
+            env.define(name[], procedure);
+            Ok(Sym(name))
+        },
+        _ => Err(BadSyntax("(define <name> <expr>)".to_string()))
+    }
+}
+
+// (lambda (<param>...) <body>...)
+fn eval_lambda(list: &[Value], env: &Env) -> Result<Value, Error> {
+    match list {
+        [_, List(ref params), ..] if list.len() > 2 => {
+            make_procedure(params[], list.slice_from(2), env)
+        },
+        _ => Err(BadSyntax("(lambda (<param>...) <body>...)".to_string()))
+    }
+}
+
+// (let ((<name> <expr>)...) <body>...)
+fn eval_let(list: &[Value], env: &Env, depth: uint) -> Result<Value, Error> {
+    let syntax = || BadSyntax("(let ((<name> <expr>)...) <body>...)".to_string());
+    let bindings = match list {
+        [_, List(ref bindings), ..] if list.len() > 2 => bindings,
+        _ => return Err(syntax())
+    };
+    // The values are computed in the outer environment, as in Scheme.
+    let inner = env.extend();
+    for binding in bindings.iter() {
+        match *binding {
+            List(ref pair) => match pair[] {
+                [Sym(ref name), ref expr] => inner.define(name[], try!(eval(expr, env, depth))),
+                _ => return Err(syntax())
+            },
+            _ => return Err(syntax())
+        }
+    }
+    eval_body(list.slice_from(2), &inner, depth)
+}
+
+fn make_procedure(params: &[Value], body: &[Value], env: &Env) -> Result<Value, Error> {
+    let mut names = Vec::with_capacity(params.len());
+    for param in params.iter() {
+        match *param {
+            Sym(ref name) => names.push(name.clone()),
+            _ => return Err(BadSyntax(format!("parameter {} is not a symbol", param)))
+        }
+    }
+    // The heap empties the frame if it's still alive when the interpreter is dropped
+    if let Some(heap) = env.heap.upgrade() {
+        heap.frames.borrow_mut().insert(frame_address(&env.frame), env.frame.downgrade());
+    }
+    Ok(Lambda(Rc::new(Procedure { params: names, body: body.to_vec(), env: env.clone() })))
+}
+
+// The primitive procedures of the global environment.
+static PRIMITIVES: &'static [(&'static str, fn(&[Value]) -> Result<Value, Error>)] = &[
+    ("+", add), ("-", sub), ("*", mul), ("/", div),
+    ("=", num_eq), ("<", lt), (">", gt), ("<=", le), (">=", ge),
+    ("not", not), ("list", list), ("car", car), ("cdr", cdr), ("cons", cons), ("null?", null),
+];
+
+fn numbers(name: &str, args: &[Value]) -> Result<Vec<f64>, Error> {
+    let mut numbers = Vec::with_capacity(args.len());
+    for arg in args.iter() {
+        match *arg {
+            Num(n) => numbers.push(n),
+            _ => return Err(TypeError(format!("{} expects numbers, got {}", name, arg)))
+        }
+    }
+    Ok(numbers)
+}
+
+fn check_arity(name: &str, args: &[Value], expected: uint) -> Result<(), Error> {
+    if args.len() == expected {
+        Ok(())
+    } else {
+        Err(ArityMismatch(name.to_string(), expected, args.len()))
+    }
+}
+
+fn add(args: &[Value]) -> Result<Value, Error> {
+    Ok(Num(try!(numbers("+", args)).iter().fold(0.0, |acc, &n| acc + n)))
+}
+
+fn mul(args: &[Value]) -> Result<Value, Error> {
+    Ok(Num(try!(numbers("*", args)).iter().fold(1.0, |acc, &n| acc * n)))
+}
+
+// (- x) is the opposite of x, (- x y z) is x - y - z
+fn sub(args: &[Value]) -> Result<Value, Error> {
+    let nums = try!(numbers("-", args));
+    match nums[] {
+        [] => Err(ArityMismatch("-".to_string(), 1, 0)),
+        [x] => Ok(Num(-x)),
+        [x, rest..] => Ok(Num(rest.iter().fold(x, |acc, &n| acc - n)))
+    }
+}
+
+// (/ x) is the inverse of x, (/ x y z) is x / y / z
+fn div(args: &[Value]) -> Result<Value, Error> {
+    let nums = try!(numbers("/", args));
+    let (first, divisors) = match nums[] {
+        [] => return Err(ArityMismatch("/".to_string(), 1, 0)),
+        [_] => (1.0, nums[]),
+        [x, rest..] => (x, rest)
+    };
+    if divisors.iter().any(|&n| n == 0.0) {
+        return Err(DivisionByZero);
+    }
+    Ok(Num(divisors.iter().fold(first, |acc, &n| acc / n)))
+}
+
+// Checks that `cmp` holds for every pair of consecutive arguments
+fn compare(name: &str, args: &[Value], cmp: |f64, f64| -> bool) -> Result<Value, Error> {
+    let nums = try!(numbers(name, args));
+    Ok(Bool(nums[].windows(2).all(|pair| cmp(pair[0], pair[1]))))
+}
+
+fn num_eq(args: &[Value]) -> Result<Value, Error> { compare("=", args, |a, b| a == b) }
+fn lt(args: &[Value]) -> Result<Value, Error> { compare("<", args, |a, b| a < b) }
+fn gt(args: &[Value]) -> Result<Value, Error> { compare(">", args, |a, b| a > b) }
+fn le(args: &[Value]) -> Result<Value, Error> { compare("<=", args, |a, b| a <= b) }
+fn ge(args: &[Value]) -> Result<Value, Error> { compare(">=", args, |a, b| a >= b) }
+
+fn not(args: &[Value]) -> Result<Value, Error> {
+    try!(check_arity("not", args, 1));
+    Ok(Bool(!args[0].is_true()))
+}
+
+fn list(args: &[Value]) -> Result<Value, Error> {
+    Ok(List(args.to_vec()))
+}
+
+fn car(args: &[Value]) -> Result<Value, Error> {
+    try!(check_arity("car", args, 1));
+    match args[0] {
+        List(ref l) if !l.is_empty() => Ok(l[0].clone()),
+        ref v => Err(TypeError(format!("car expects a non-empty list, got {}", v)))
+    }
+}
+
+fn cdr(args: &[Value]) -> Result<Value, Error> {
+    try!(check_arity("cdr", args, 1));
+    match args[0] {
+        List(ref l) if !l.is_empty() => Ok(List(l.slice_from(1).to_vec())),
+        ref v => Err(TypeError(format!("cdr expects a non-empty list, got {}", v)))
+    }
+}
+
+fn cons(args: &[Value]) -> Result<Value, Error> {
+    try!(check_arity("cons", args, 2));
+    match args[1] {
+        List(ref l) => {
+            let mut result = vec![args[0].clone()];
+            result.push_all(l[]);
+            Ok(List(result))
+        },
+        ref v => Err(TypeError(format!("cons expects a list as second argument, got {}", v)))
+    }
+}
+
+fn null(args: &[Value]) -> Result<Value, Error> {
+    try!(check_arity("null?", args, 1));
+    match args[0] {
+        List(ref l) => Ok(Bool(l.is_empty())),
+        _ => Ok(Bool(false))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Interpreter, Error, ArityMismatch, BadSyntax, DivisionByZero, Lambda,
+                NotAProcedure, Num, ParseError, RecursionTooDeep, TypeError, UnboundSymbol,
+                apply};
+    use s_expressions::UnexpectedEOF;
+    use std::task::TaskBuilder;
+
+    // Evaluates a program and returns its printed value
+    fn run(source: &str) -> Result<String, Error> {
+        Interpreter::new().eval_program(source).map(|v| v.to_string())
+    }
+
+    fn ok(source: &str) -> String {
+        run(source).unwrap()
+    }
+
+    #[test]
+    fn atoms() {
+        assert_eq!(ok("42")[], "42");
+        assert_eq!(ok("\"hello world\"")[], "\"hello world\"");
+        assert_eq!(ok("true")[], "true");
+        assert_eq!(ok("()")[], "()");
+        assert_eq!(ok("(quote (a \"b\" 3))")[], "(a \"b\" 3)");
+    }
+
+    #[test]
+    fn arithmetic() {
+        assert_eq!(ok("(+ 1 2 3)")[], "6");
+        assert_eq!(ok("(- 10 4 1)")[], "5");
+        assert_eq!(ok("(- 3)")[], "-3");
+        assert_eq!(ok("(* 2 (+ 1 2))")[], "6");
+        assert_eq!(ok("(/ 12 4)")[], "3");
+        assert_eq!(ok("(< 1 2 3)")[], "true");
+        assert_eq!(ok("(>= 1 2)")[], "false");
+    }
+
+    #[test]
+    fn lists() {
+        assert_eq!(ok("(list 1 2 3)")[], "(1 2 3)");
+        assert_eq!(ok("(car (list 1 2 3))")[], "1");
+        assert_eq!(ok("(cdr (list 1 2 3))")[], "(2 3)");
+        assert_eq!(ok("(cons 0 (list 1 2))")[], "(0 1 2)");
+        assert_eq!(ok("(null? (cdr (list 1)))")[], "true");
+    }
+
+    #[test]
+    fn special_forms() {
+        assert_eq!(ok("(if (< 1 2) \"yes\" \"no\")")[], "\"yes\"");
+        assert_eq!(ok("(if () 1)")[], "()");
+        assert_eq!(ok("(define x 5) (* x x)")[], "25");
+        assert_eq!(ok("(let ((x 2) (y 3)) (+ x y))")[], "5");
+        assert_eq!(ok("((lambda (x y) (- x y)) 7 2)")[], "5");
+    }
+
+    #[test]
+    fn closures_and_recursion() {
+        assert_eq!(ok("(define (fact n) (if (= n 0) 1 (* n (fact (- n 1))))) (fact 10)")[],
+                   "3628800");
+        assert_eq!(ok("(define (adder n) (lambda (x) (+ x n)))
+                       (define add3 (adder 3))
+                       (add3 4)")[], "7");
+        // let doesn't leak its bindings
+        assert_eq!(run("(let ((y 1)) y) y"), Err(UnboundSymbol("y".to_string())));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(run("(/ 1 0)"), Err(DivisionByZero));
+        assert_eq!(run("(foo 1)"), Err(UnboundSymbol("foo".to_string())));
+        assert_eq!(run("(1 2)"), Err(NotAProcedure("1".to_string())));
+        assert_eq!(run("(car 1 2)"), Err(ArityMismatch("car".to_string(), 1, 2)));
+        assert_eq!(run("((lambda (x) x))"), Err(ArityMismatch("#<lambda>".to_string(), 1, 0)));
+        assert!(match run("(+ 1 \"a\")") { Err(TypeError(_)) => true, _ => false });
+        assert!(match run("(if)") { Err(BadSyntax(_)) => true, _ => false });
+        assert_eq!(run("(+ 1"), Err(ParseError(UnexpectedEOF)));
+    }
+
+    #[test]
+    fn recursion_depth() {
+        // Every call takes several frames of the stack, more than the default of a test task
+        let result = TaskBuilder::new().stack_size(64 * 1024 * 1024).try(proc() {
+            // A deep recursion is fine, an infinite one is an error and not a stack overflow
+            assert_eq!(ok("(define (count n) (if (= n 0) 0 (+ 1 (count (- n 1)))))
+                           (count 900)")[], "900");
+            assert_eq!(run("(define (forever n) (+ 1 (forever n))) (forever 0)"),
+                       Err(RecursionTooDeep));
+            // The interpreter can still be used afterwards
+            let mut interpreter = Interpreter::new();
+            assert!(interpreter.eval_program("(define (forever) (forever)) (forever)").is_err());
+            assert_eq!(interpreter.eval_str("(+ 1 2)").unwrap().to_string()[], "3");
+        });
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn escaping_procedures() {
+        let mut interpreter = Interpreter::new();
+        // A procedure returned by an evaluation can still be called after the next one
+        let adder = interpreter.eval_program("(define (adder n) (lambda (x) (+ x n))) (adder 3)")
+                               .unwrap();
+        let args = [Num(4.0)];
+        assert_eq!(apply(&adder, args[], 0).unwrap().to_string()[], "7");
+        interpreter.eval_str("(define adder 0)").unwrap();
+        assert_eq!(apply(&adder, args[], 0).unwrap().to_string()[], "7");
+    }
+
+    #[test]
+    fn frames_are_dropped() {
+        let mut interpreter = Interpreter::new();
+        // The frame of (make) holds the recursive procedure `count`, which captured it
+        interpreter.eval_program("(define (make) (define (count n) (if (= n 0) 0 (count (- n 1))))
+                                                 count)
+                                  (define count (make))
+                                  (count 3)").unwrap();
+        let frame = match interpreter.global.lookup("count") {
+            Some(Lambda(ref procedure)) => procedure.env.frame.downgrade(),
+            _ => fail!("count isn't a procedure")
+        };
+        assert!(frame.upgrade().is_some());
+        drop(interpreter);
+        assert!(frame.upgrade().is_none());
+    }
+}
@@ -1,20 +1,30 @@
 // http://rosettacode.org/wiki/Look-and-say_sequence
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 use run_length_encoding::encode;
 
 mod run_length_encoding;
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the first 20 terms of the sequence to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let mut s = look_and_say("1");
     for _ in range(0u,20) {
-        println!("{}", s.as_slice());
+        try!(writeln!(out, "{}", s.as_slice()));
         s = look_and_say(s.as_slice());
     }
+    Ok(())
 }
 
-fn look_and_say(value: &str) -> String {
+pub fn look_and_say(value: &str) -> String {
     if value.chars().any(|c| !c.is_digit()) { fail!("this task requires all digits"); }
-    encode(value)
+    // The digits are escaped by the encoding, but can't be mistaken for counts here
+    encode(value).replace("\\", "")
 }
 
 #[test]
@@ -1,13 +1,20 @@
 // Implements http://rosettacode.org/wiki/Loops/For
 
 
+use std::io::{mod, IoResult};
 use std::iter;
 
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes a triangle of stars to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     for i in iter::range_inclusive(1u, 5) {
         for _ in iter::range_inclusive(1u, i) {
-            print!("*")
+            try!(write!(out, "*"))
         }
-        println!("")
+        try!(writeln!(out, ""))
     }
+    Ok(())
 }
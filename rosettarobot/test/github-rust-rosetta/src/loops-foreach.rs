@@ -2,7 +2,7 @@
 
 use std::collections::HashMap;
 
-fn main() {
+pub fn main() {
     // Iterate through the characters of a string
     let s = "hello, world!";
     for i in s.chars() {
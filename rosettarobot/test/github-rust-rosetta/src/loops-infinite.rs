@@ -1,6 +1,6 @@
 // Implements http://rosettacode.org/wiki/Loops/Infinite
 
-fn main() {
+pub fn main() {
     loop {
         println!("spam");
     }
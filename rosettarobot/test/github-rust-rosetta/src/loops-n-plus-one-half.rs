@@ -1,13 +1,20 @@
 // Implements http://rosettacode.org/wiki/Loops/N_plus_one_half
 
+use std::io::{mod, IoResult};
 use std::iter;
 
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the numbers from 1 to 10, separated by commas, to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     for i in iter::range_inclusive(1u,10) {
-        print!("{}", i);
+        try!(write!(out, "{}", i));
         if i == 10 {
             break;
         }
-        print!(", ");
+        try!(write!(out, ", "));
     }
+    Ok(())
 }
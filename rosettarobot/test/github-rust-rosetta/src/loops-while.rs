@@ -1,10 +1,17 @@
 // Implements http://rosettacode.org/wiki/Loops/While
 
+use std::io::{mod, IoResult};
 
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the halvings of 1024 down to 1 to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let mut i = 1024u;
     while i > 0 {
-        println!("{}", i);
+        try!(writeln!(out, "{}", i));
         i /= 2;
     }
+    Ok(())
 }
@@ -1,12 +1,16 @@
 // Implements http://rosettacode.org/wiki/Luhn_test_of_credit_card_numbers
 
-enum LuhnState {
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
+
+pub enum LuhnState {
     Even,
     Odd,
 }
 
-fn digits(n: u64) -> std::iter::Unfold<'static, u64, u64> {
-    std::iter::Unfold::new(n, |state| {
+pub fn digits(n: u64) -> ::std::iter::Unfold<'static, u64, u64> {
+    ::std::iter::Unfold::new(n, |state| {
         match *state {
             0 => None,
             n => {
@@ -18,7 +22,7 @@ fn digits(n: u64) -> std::iter::Unfold<'static, u64, u64> {
     })
 }
 
-fn luhn_test(n: u64) -> bool {
+pub fn luhn_test(n: u64) -> bool {
     let odd_even = [Odd, Even];
     let mut numbers = digits(n).zip(odd_even.iter().cycle().map(|&s|s));
     let sum = numbers.fold(0u64, |s,n| {
@@ -31,15 +35,21 @@ fn luhn_test(n: u64) -> bool {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes whether a few numbers pass the test to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let nos = [49927398716, 49927398717, 1234567812345678, 1234567812345670];
     for n in nos.iter() {
         if luhn_test(*n) {
-            println!("{} passes.", n);
+            try!(writeln!(out, "{} passes.", n));
         } else {
-            println!("{} fails.", n);
+            try!(writeln!(out, "{} fails.", n));
         }
     }
+    Ok(())
 }
 
 #[test]
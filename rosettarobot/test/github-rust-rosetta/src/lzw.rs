@@ -1,9 +1,17 @@
 // Implements http://rosettacode.org/wiki/LZW_compression
+//
+// `compress` and `decompress` are the task itself, with codes as plain numbers.  `compress_bytes`
+// and `decompress_bytes` pack the codes into bytes with a variable width, like the LZW of Unix
+// `compress` (the .Z format) and of GIF images.
 
+use std::cmp;
 use std::collections::hashmap::HashMap;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
 // Compress using LZW
-fn compress(original_str: &str) -> Vec<int> {
+pub fn compress(original_str: &str) -> Vec<int> {
    let original = original_str.as_bytes();
    let mut dict_size = 256;
    let mut dictionary = HashMap::new();
@@ -37,7 +45,7 @@ fn compress(original_str: &str) -> Vec<int> {
 }
 
 // Decompress using LZW
-fn decompress(compressed: &Vec<int>) -> String {
+pub fn decompress(compressed: &Vec<int>) -> String {
    let mut dict_size = 256;
    let mut dictionary = HashMap::new();
 
@@ -65,19 +73,387 @@ fn decompress(compressed: &Vec<int>) -> String {
    String::from_utf8(result).unwrap()
 }
 
+// The smallest and largest widths of the packed codes, in bits
+pub const MIN_BITS: uint = 9;
+pub const MAX_BITS: uint = 16;
+
+// The codes after the 256 bytes: CLEAR empties the dictionary, END marks the end of the data
+// (there is none in the .Z format, which stops with the data)
+pub const CLEAR: uint = 256;
+pub const END: uint = 257;
+
+// The first bytes of a .Z file, followed by a byte with the maximum width in its lowest 5 bits
+pub const MAGIC: [u8, ..2] = [0x1f, 0x9d];
+
+// The flag of the third byte of a .Z file telling that CLEAR is used.  Without it, the first new
+// string gets the code 256.
+pub const BLOCK_MODE: u8 = 0x80;
+
+// Once the dictionary is full, the compression ratio is checked every CHECK_GAP bytes of input,
+// and the dictionary cleared when it gets worse (as `compress` does)
+const CHECK_GAP: uint = 10000;
+
+// How codes are packed into bytes
+#[deriving(Clone, PartialEq, Show)]
+pub enum Flavor {
+    // Most significant bit first, ends with END
+    Msb,
+    // Least significant bit first, starts with CLEAR and ends with END.  This is the data of a GIF
+    // image with 8-bit pixels, before it is split into sub-blocks.
+    Gif,
+    // The .Z format of Unix `compress`: a 3-byte header, then codes least significant bit first,
+    // in groups of 8 codes of the same width
+    UnixCompress
+}
+
+// The settings of `compress_bytes` and `decompress_bytes`
+#[deriving(Clone, PartialEq, Show)]
+pub struct Config {
+    flavor: Flavor,
+    max_bits: uint
+}
+
+impl Config {
+    // Codes up to 12 bits for GIF, which doesn't allow more, and 16 bits otherwise
+    pub fn new(flavor: Flavor) -> Config {
+        let max_bits = if flavor == Gif { 12 } else { MAX_BITS };
+        Config { flavor: flavor, max_bits: max_bits }
+    }
+
+    // Sets the largest width of the codes.  The .Z format stores it in its header, so it doesn't
+    // matter for decompressing.
+    pub fn max_bits(self, max_bits: uint) -> Config {
+        assert!(MIN_BITS <= max_bits && max_bits <= MAX_BITS,
+                "the width of codes must be between {} and {} bits", MIN_BITS, MAX_BITS);
+        Config { max_bits: max_bits, ..self }
+    }
+
+    // The widest code, which is one bit wider than `max_bits` for 9 bits in the .Z format, since
+    // `compress` only checks against its maximum when the width grows
+    fn max_width(&self) -> uint {
+        match self.flavor {
+            UnixCompress => cmp::max(self.max_bits, MIN_BITS + 1),
+            _ => self.max_bits
+        }
+    }
+}
+
+// Compresses `data` with codes packed into bytes
+pub fn compress_bytes(data: &[u8], config: &Config) -> Vec<u8> {
+    let (header, first) = match config.flavor {
+        UnixCompress => (vec![MAGIC[0], MAGIC[1], BLOCK_MODE | config.max_bits as u8], CLEAR + 1),
+        _ => (Vec::new(), END + 1)
+    };
+    let limit = 1u << config.max_bits;
+    let mut writer = CodeWriter::new(header, config.flavor != Msb, config.flavor == UnixCompress);
+    let mut widths = CodeWidth::new(first, limit, config.max_width());
+    if config.flavor == Gif {
+        writer.write(CLEAR, widths.width());
+        writer.end_group();
+    }
+
+    // The code of each string in the dictionary, from the code of the string without its last byte
+    // and that byte
+    let mut dictionary: HashMap<(uint, u8), uint> = HashMap::new();
+    let (mut checkpoint, mut ratio) = (CHECK_GAP, 0u64);
+    let mut prefix = None;
+    for (i, &byte) in data.iter().enumerate() {
+        let code = match prefix {
+            None => {
+                prefix = Some(byte as uint);
+                continue;
+            }
+            Some(code) => code
+        };
+        match dictionary.find(&(code, byte)) {
+            Some(&longer) => {
+                prefix = Some(longer);
+                continue;
+            }
+            None => ()
+        }
+
+        writer.write(code, widths.width());
+        widths.advance();
+        let next = first + dictionary.len();
+        if next < limit {
+            dictionary.insert((code, byte), next);
+        } else if i >= checkpoint {
+            checkpoint = i + CHECK_GAP;
+            let new_ratio = i as u64 * 256 / writer.bytes.len() as u64;
+            if new_ratio > ratio {
+                ratio = new_ratio;
+            } else {
+                ratio = 0;
+                writer.write(CLEAR, widths.width());
+                writer.end_group();
+                widths.clear();
+                dictionary.clear();
+            }
+        }
+        prefix = Some(byte as uint);
+    }
+
+    match prefix {
+        Some(code) => {
+            writer.write(code, widths.width());
+            widths.advance();
+        }
+        None => ()
+    }
+    if config.flavor != UnixCompress {
+        writer.write(END, widths.width());
+    }
+    writer.bytes
+}
+
+// Decompresses the output of `compress_bytes`
+pub fn decompress_bytes(compressed: &[u8], config: &Config) -> Result<Vec<u8>, String> {
+    let (config, header_len, clear, end, first) = match config.flavor {
+        UnixCompress => {
+            if compressed.len() < 3 || compressed.slice_to(2) != MAGIC.as_slice() {
+                return Err("not in the .Z format".to_string());
+            }
+            let max_bits = (compressed[2] & 0x1f) as uint;
+            if max_bits < MIN_BITS || max_bits > MAX_BITS {
+                return Err(format!("unsupported code width of {} bits", max_bits));
+            }
+            let block_mode = compressed[2] & BLOCK_MODE != 0;
+            (config.clone().max_bits(max_bits), 3, if block_mode { Some(CLEAR) } else { None },
+             None, if block_mode { CLEAR + 1 } else { CLEAR })
+        }
+        _ => (config.clone(), 0, Some(CLEAR), Some(END), END + 1)
+    };
+    let limit = 1u << config.max_bits;
+    let mut reader = CodeReader::new(compressed, header_len * 8, config.flavor != Msb,
+                                     config.flavor == UnixCompress);
+    let mut widths = CodeWidth::new(first, limit, config.max_width());
+
+    // Every string of the dictionary is the string of a shorter code and one more byte.  The
+    // bytes are found backwards, so they go through `stack`.
+    let mut prefixes = Vec::from_elem(limit, 0u);
+    let mut suffixes = Vec::from_elem(limit, 0u8);
+    let mut stack = Vec::new();
+    let mut output = Vec::new();
+    let mut previous = None;
+    loop {
+        let position = reader.position();
+        let code = match reader.read(widths.width()) {
+            Some(code) => code,
+            None if end.is_none() => return Ok(output),
+            None => return Err("truncated data".to_string())
+        };
+        if Some(code) == clear {
+            reader.end_group();
+            widths.clear();
+            previous = None;
+            continue;
+        }
+        if Some(code) == end {
+            return Ok(output);
+        }
+
+        let next = widths.next();
+        match previous {
+            None if code < 256 => output.push(code as u8),
+            // A known string, or the one this code defines: the previous string and its first byte
+            Some(previous) if (code < next && (code < 256 || code >= first)) ||
+                              (code == next && next < limit) => {
+                let start = output.len();
+                let mut string = if code == next { previous } else { code };
+                while string >= 256 {
+                    stack.push(suffixes[string]);
+                    string = prefixes[string];
+                }
+                stack.push(string as u8);
+                output.extend(stack.iter().rev().map(|&byte| byte));
+                stack.clear();
+                let first_byte = output[start];
+                if code == next {
+                    output.push(first_byte);
+                }
+                if next < limit {
+                    prefixes[next] = previous;
+                    suffixes[next] = first_byte;
+                }
+            }
+            _ => return Err(format!("invalid code {} at bit {}", code, position))
+        }
+        widths.advance();
+        previous = Some(code);
+    }
+}
+
+// Follows the width of the codes, which the compressor and the decompressor update in the same
+// way: it is the width needed for the next code the decompressor adds to its dictionary.
+struct CodeWidth {
+    next: uint,
+    after_clear: bool, // The first code after a clear doesn't add a string
+    first: uint,
+    limit: uint,
+    max_width: uint
+}
+
+impl CodeWidth {
+    fn new(first: uint, limit: uint, max_width: uint) -> CodeWidth {
+        CodeWidth { next: first, after_clear: true, first: first, limit: limit,
+                    max_width: max_width }
+    }
+
+    fn width(&self) -> uint {
+        let mut width = MIN_BITS;
+        while width < self.max_width && self.next >= 1 << width {
+            width += 1;
+        }
+        width
+    }
+
+    // The next code of the dictionary of the decompressor
+    fn next(&self) -> uint {
+        self.next
+    }
+
+    // Goes past a code of the data
+    fn advance(&mut self) {
+        if self.after_clear {
+            self.after_clear = false;
+        } else if self.next < self.limit {
+            self.next += 1;
+        }
+    }
+
+    fn clear(&mut self) {
+        self.next = self.first;
+        self.after_clear = true;
+    }
+}
+
+// Packs codes into bytes.  In the .Z format, the codes come in groups of 8 codes (as many bytes as
+// the width) which end when the width changes or after a CLEAR, and the last group is padded.
+struct CodeWriter {
+    bytes: Vec<u8>,
+    position: uint, // In bits
+    lsb_first: bool,
+    groups: bool,
+    width: uint, // Of the current group
+    group_start: uint
+}
+
+impl CodeWriter {
+    // Codes are written after `bytes`
+    fn new(bytes: Vec<u8>, lsb_first: bool, groups: bool) -> CodeWriter {
+        let position = bytes.len() * 8;
+        CodeWriter { bytes: bytes, position: position, lsb_first: lsb_first, groups: groups,
+                     width: MIN_BITS, group_start: position }
+    }
+
+    fn write_bit(&mut self, bit: bool) {
+        let shift = self.position % 8;
+        if shift == 0 {
+            self.bytes.push(0);
+        }
+        if bit {
+            let mask = if self.lsb_first { 1 << shift } else { 0x80 >> shift };
+            *self.bytes.last_mut().unwrap() |= mask;
+        }
+        self.position += 1;
+    }
+
+    fn write(&mut self, code: uint, width: uint) {
+        if width != self.width {
+            self.end_group();
+            self.width = width;
+        }
+        for i in range(0, width) {
+            let shift = if self.lsb_first { i } else { width - 1 - i };
+            self.write_bit((code >> shift) & 1 == 1);
+        }
+    }
+
+    fn end_group(&mut self) {
+        if self.groups {
+            while (self.position - self.group_start) % (self.width * 8) != 0 {
+                self.write_bit(false);
+            }
+        }
+        self.group_start = self.position;
+    }
+}
+
+// Unpacks the codes written by CodeWriter
+struct CodeReader<'a> {
+    bytes: &'a [u8],
+    position: uint, // In bits
+    lsb_first: bool,
+    groups: bool,
+    width: uint, // Of the current group
+    group_start: uint
+}
+
+impl<'a> CodeReader<'a> {
+    // Codes are read from the bit `position` of `bytes`
+    fn new(bytes: &'a [u8], position: uint, lsb_first: bool, groups: bool) -> CodeReader<'a> {
+        CodeReader { bytes: bytes, position: position, lsb_first: lsb_first, groups: groups,
+                     width: MIN_BITS, group_start: position }
+    }
+
+    fn position(&self) -> uint {
+        self.position
+    }
+
+    // Returns None if there aren't `width` bits left
+    fn read(&mut self, width: uint) -> Option<uint> {
+        if width != self.width {
+            self.end_group();
+            self.width = width;
+        }
+        if self.position + width > self.bytes.len() * 8 {
+            return None;
+        }
+        let mut code = 0u;
+        for i in range(0, width) {
+            let offset = self.position % 8;
+            let shift = if self.lsb_first { offset } else { 7 - offset };
+            let bit = (self.bytes[self.position / 8] >> shift) as uint & 1;
+            code |= if self.lsb_first { bit << i } else { bit << (width - 1 - i) };
+            self.position += 1;
+        }
+        Some(code)
+    }
+
+    fn end_group(&mut self) {
+        if self.groups {
+            let used = (self.position - self.group_start) % (self.width * 8);
+            if used != 0 {
+                self.position += self.width * 8 - used;
+            }
+        }
+        self.group_start = self.position;
+    }
+}
+
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes a string compressed, decompressed and packed in the .Z format to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     // Show original
     let original = "TOBEORNOTTOBEORTOBEORNOT";
-    println!("Original: {}", original);
+    try!(writeln!(out, "Original: {}", original));
 
     // Show compressed
     let compressed = compress(original);
-    println!("Compressed: {}", compressed);
+    try!(writeln!(out, "Compressed: {}", compressed));
 
     // Show decompressed
     let decompressed = decompress(&compressed);
-    println!("Decompressed: {}", decompressed);
+    try!(writeln!(out, "Decompressed: {}", decompressed));
+
+    // Show the same codes packed in the .Z format
+    let packed = compress_bytes(original.as_bytes(), &Config::new(UnixCompress));
+    writeln!(out, "Packed: {} ({} bytes)", packed, packed.len())
 }
 
 #[test]
@@ -93,3 +469,73 @@ fn test_example() {
     assert_eq!(compress(original).as_slice(), [84i, 79, 66, 69, 79, 82, 78, 79, 84,
                                                 256, 258, 260, 265, 259, 261, 263].as_slice());
 }
+
+#[test]
+fn test_packed_example() {
+    let original = b"TOBEORNOTTOBEORTOBEORNOT";
+    let expected = [
+        (UnixCompress, vec![31u8, 157, 144, 84, 158, 8, 41, 242, 68, 138, 147, 39, 84, 2, 14, 44,
+                            168, 144, 160, 65, 132]),
+        (Msb, vec![42u8, 19, 200, 68, 82, 121, 72, 156, 79, 42, 64, 160, 144, 104, 92, 22, 15, 9,
+                   128, 128]),
+        (Gif, vec![0u8, 169, 60, 17, 82, 228, 137, 20, 39, 79, 168, 8, 36, 104, 112, 97, 193, 131,
+                   9, 3, 2])];
+    for &(ref flavor, ref packed) in expected.iter() {
+        let config = Config::new(flavor.clone());
+        assert_eq!(compress_bytes(original, &config), *packed);
+        assert_eq!(decompress_bytes(packed.as_slice(), &config).unwrap().as_slice(),
+                   original.as_slice());
+    }
+}
+
+#[test]
+fn test_packed_coherence() {
+    // Text, then bytes which don't compress, so that the dictionary fills up and gets cleared
+    let text = include_bytes!("resources/unixdict.txt");
+    let mut data = text.to_vec();
+    let mut seed = 1u32;
+    for _ in range(0u, 100000) {
+        seed = seed * 1103515245 + 12345;
+        data.push((seed >> 16) as u8);
+    }
+    data.push_all(text);
+
+    for flavor in [Msb, Gif, UnixCompress].iter() {
+        for &max_bits in [MIN_BITS, 12, MAX_BITS].iter() {
+            let config = Config::new(flavor.clone()).max_bits(max_bits);
+            for input in [data.as_slice(), b"", b"a"].iter() {
+                let packed = compress_bytes(*input, &config);
+                assert_eq!(decompress_bytes(packed.as_slice(), &config).unwrap().as_slice(),
+                           *input);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_unix_compress_header() {
+    let config = Config::new(UnixCompress);
+    assert_eq!(compress_bytes(b"", &config.clone().max_bits(12)), vec![0x1fu8, 0x9d, 0x8c]);
+
+    // The width in the header is used, and without block mode, 256 is a string and not CLEAR
+    let packed = [0x1fu8, 0x9d, 0x09, 0x61, 0xc4, 0x00];
+    assert_eq!(decompress_bytes(&packed, &config), Ok(b"ab".to_vec()));
+
+    assert_eq!(decompress_bytes(b"\x1f\x8b\x08", &config), Err("not in the .Z format".to_string()));
+    assert_eq!(decompress_bytes(&[0x1fu8, 0x9d, 0x91], &config),
+               Err("unsupported code width of 17 bits".to_string()));
+}
+
+#[test]
+fn test_packed_errors() {
+    let config = Config::new(Msb);
+    let packed = compress_bytes(b"TOBEORNOT", &config);
+    assert_eq!(decompress_bytes(packed.slice_to(packed.len() - 2), &config),
+               Err("truncated data".to_string()));
+
+    // The first code must be a byte, and the next one at most the code being defined (258)
+    assert_eq!(decompress_bytes(&[0x96u8, 0x00], &config),
+               Err("invalid code 300 at bit 0".to_string()));
+    assert_eq!(decompress_bytes(&[0x30u8, 0xc0, 0xc0], &config),
+               Err("invalid code 259 at bit 9".to_string()));
+}
@@ -0,0 +1,258 @@
+// Implements http://rosettacode.org/wiki/Mandelbrot_set
+//
+// Also renders the Julia sets of http://rosettacode.org/wiki/Julia_set, with the same escape time
+// algorithm.  The rows of the image are shared between tasks, each of them computing every n-th
+// row, so the image doesn't depend on the number of tasks.  The points which escape are colored by
+// a palette, with the fractional iteration count of the smooth coloring algorithm.
+extern crate num;
+extern crate time;
+
+use std::f64::consts::LN_2;
+use std::iter::range_step;
+use std::os;
+use num::complex::Complex;
+use bitmap::{Color, Image};
+mod bitmap;
+
+// The squared radius past which a point escapes, large enough for a smooth coloring
+const ESCAPE_NORM_SQR: f64 = 65536.0;
+
+#[deriving(Clone, PartialEq, Show)]
+pub enum Fractal {
+    Mandelbrot,
+    // The Julia set of z * z + c, for the constant c
+    Julia(Complex<f64>)
+}
+
+// Colors which repeat every `period` iterations, with the colors in between interpolated
+#[deriving(Clone, PartialEq, Show)]
+pub struct Palette {
+    colors: Vec<Color>,
+    period: f64,
+    inside: Color
+}
+
+impl Palette {
+    // `inside` is the color of the points which don't escape
+    pub fn new(colors: Vec<Color>, period: f64, inside: Color) -> Palette {
+        assert!(!colors.is_empty(), "a palette needs colors");
+        assert!(period > 0.0, "the period must be positive");
+        Palette { colors: colors, period: period, inside: inside }
+    }
+
+    // From dark blue to white and orange, repeated every 32 iterations, and black inside
+    pub fn classic() -> Palette {
+        let colors = [(0, 7, 100), (32, 107, 203), (237, 255, 255), (255, 170, 0), (0, 2, 0)];
+        Palette::new(colors.iter().map(|&(red, green, blue)| {
+            Color { red: red, green: green, blue: blue }
+        }).collect(), 32.0, Color { red: 0, green: 0, blue: 0 })
+    }
+
+    // From black to white over `period` iterations, and white inside
+    pub fn grayscale(period: f64) -> Palette {
+        let (black, white) = (Color { red: 0, green: 0, blue: 0 },
+                              Color { red: 255, green: 255, blue: 255 });
+        Palette::new(vec![black, white], period, white)
+    }
+
+    // The color after `iterations`, or the inside color for None
+    pub fn color(&self, iterations: Option<f64>) -> Color {
+        let iterations = match iterations {
+            Some(iterations) => iterations,
+            None => return self.inside
+        };
+        let position = (iterations / self.period).fract() * self.colors.len() as f64;
+        let index = position.floor() as uint;
+        let (from, to) = (self.colors[index], self.colors[(index + 1) % self.colors.len()]);
+        let t = position - index as f64;
+        let mix = |from: u8, to: u8| (from as f64 + (to as f64 - from as f64) * t).round() as u8;
+        Color { red: mix(from.red, to.red), green: mix(from.green, to.green),
+                blue: mix(from.blue, to.blue) }
+    }
+}
+
+#[deriving(Clone, PartialEq, Show)]
+pub struct Renderer {
+    fractal: Fractal,
+    center: Complex<f64>,
+    width: f64,
+    max_iterations: uint,
+    smooth: bool,
+    palette: Palette,
+    tasks: uint
+}
+
+impl Renderer {
+    // The whole set, with 256 iterations, smooth colors, and a task per CPU
+    pub fn new(fractal: Fractal) -> Renderer {
+        let center = match fractal {
+            Mandelbrot => Complex::new(-0.5, 0.0),
+            Julia(_) => Complex::new(0.0, 0.0)
+        };
+        Renderer { fractal: fractal, center: center, width: 3.0, max_iterations: 256,
+                   smooth: true, palette: Palette::classic(), tasks: os::num_cpus() }
+    }
+
+    // Sets the center of the image in the complex plane, and its width.  The height follows from
+    // the size of the image, with square pixels.
+    pub fn viewport(self, center: Complex<f64>, width: f64) -> Renderer {
+        assert!(width > 0.0, "the viewport must have a width");
+        Renderer { center: center, width: width, ..self }
+    }
+
+    pub fn max_iterations(self, max_iterations: uint) -> Renderer {
+        Renderer { max_iterations: max_iterations, ..self }
+    }
+
+    // Sets whether the colors are interpolated between the iterations
+    pub fn smooth(self, smooth: bool) -> Renderer {
+        Renderer { smooth: smooth, ..self }
+    }
+
+    pub fn palette(self, palette: Palette) -> Renderer {
+        Renderer { palette: palette, ..self }
+    }
+
+    // Sets the number of tasks which compute the rows
+    pub fn tasks(self, tasks: uint) -> Renderer {
+        assert!(tasks > 0, "at least one task computes the rows");
+        Renderer { tasks: tasks, ..self }
+    }
+
+    // The number of iterations before the point escapes, fractional for smooth colors, or None if
+    // it doesn't within `max_iterations`
+    pub fn escape_time(&self, point: Complex<f64>) -> Option<f64> {
+        let (mut z, c) = match self.fractal {
+            Mandelbrot => (Complex::new(0.0, 0.0), point),
+            Julia(c) => (point, c)
+        };
+        for n in range(0, self.max_iterations) {
+            z = z * z + c;
+            if z.norm_sqr() > ESCAPE_NORM_SQR {
+                if !self.smooth {
+                    return Some(n as f64);
+                }
+                let log_modulus = z.norm_sqr().ln() / 2.0;
+                let iterations = n as f64 + 1.0 - (log_modulus / LN_2).ln() / LN_2;
+                return Some(iterations.max(0.0));
+            }
+        }
+        None
+    }
+
+    // The colors of a row of an image of `width` x `height` pixels
+    fn render_row(&self, y: uint, width: uint, height: uint) -> Vec<Color> {
+        let scale = self.width / width as f64;
+        let im = self.center.im - (y as f64 + 0.5 - height as f64 / 2.0) * scale;
+        range(0, width).map(|x| {
+            let re = self.center.re + (x as f64 + 0.5 - width as f64 / 2.0) * scale;
+            self.palette.color(self.escape_time(Complex::new(re, im)))
+        }).collect()
+    }
+
+    pub fn render(&self, width: uint, height: uint) -> Image {
+        let (sender, receiver) = channel();
+        for task in range(0, self.tasks) {
+            let (renderer, sender) = (self.clone(), sender.clone());
+            spawn(proc() {
+                for y in range_step(task, height, renderer.tasks) {
+                    sender.send((y, renderer.render_row(y, width, height)));
+                }
+            });
+        }
+
+        let mut image = Image::new(width, height);
+        for _ in range(0, height) {
+            let (y, row) = receiver.recv();
+            for (x, &color) in row.iter().enumerate() {
+                image[(x, y)] = color;
+            }
+        }
+        image
+    }
+}
+
+// The FNV-1a hash of the channels of an image, to compare images
+pub fn checksum(image: &Image) -> u64 {
+    let mut hash = 0xcbf29ce484222325u64;
+    for color in image.data.iter() {
+        for &channel in [color.red, color.green, color.blue].iter() {
+            hash = (hash ^ channel as u64) * 0x100000001b3;
+        }
+    }
+    hash
+}
+
+#[cfg(not(test))]
+pub fn main() {
+    let renderers = [("mandelbrot.ppm", Renderer::new(Mandelbrot)),
+                     ("julia.ppm", Renderer::new(Julia(Complex::new(-0.8, 0.156))))];
+    for &(filename, ref renderer) in renderers.iter() {
+        let start = time::precise_time_ns();
+        let image = renderer.render(800, 600);
+        let ms = (time::precise_time_ns() - start) / 1000000;
+        println!("{}: {} ms with {} tasks, checksum {:016x}",
+                 filename, ms, renderer.tasks, checksum(&image));
+        match image.write_ppm(filename) {
+            Ok(()) => (),
+            Err(e) => println!("Failed to write {}: {}", filename, e)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitmap::Color;
+    use num::complex::Complex;
+    use super::{Julia, Mandelbrot, Palette, Renderer, checksum};
+
+    #[test]
+    fn escape_time() {
+        let renderer = Renderer::new(Mandelbrot).smooth(false);
+        assert_eq!(renderer.escape_time(Complex::new(0.0, 0.0)), None);
+        assert_eq!(renderer.escape_time(Complex::new(-1.0, 0.0)), None);
+        assert_eq!(renderer.escape_time(Complex::new(1.0, 0.0)), Some(4.0));
+        // Less than the iterations, since the escape radius is large
+        let smooth = renderer.smooth(true).escape_time(Complex::new(1.0, 0.0)).unwrap();
+        assert!((smooth - 1.76688).abs() < 1e-5);
+
+        let julia = Renderer::new(Julia(Complex::new(0.0, 0.0))).smooth(false);
+        assert_eq!(julia.escape_time(Complex::new(0.5, 0.5)), None);
+        assert_eq!(julia.escape_time(Complex::new(2.0, 0.0)), Some(3.0));
+    }
+
+    #[test]
+    fn palette() {
+        let palette = Palette::grayscale(10.0);
+        assert_eq!(palette.color(None), Color { red: 255, green: 255, blue: 255 });
+        assert_eq!(palette.color(Some(0.0)), Color { red: 0, green: 0, blue: 0 });
+        assert_eq!(palette.color(Some(2.5)), Color { red: 128, green: 128, blue: 128 });
+        assert_eq!(palette.color(Some(7.5)), Color { red: 128, green: 128, blue: 128 });
+        assert_eq!(palette.color(Some(12.5)), Color { red: 128, green: 128, blue: 128 });
+    }
+
+    #[test]
+    fn viewport() {
+        // The pixels at -1, 0 and 1, white inside and black outside
+        let (black, white) = (Color { red: 0, green: 0, blue: 0 },
+                              Color { red: 255, green: 255, blue: 255 });
+        let renderer = Renderer::new(Mandelbrot).viewport(Complex::new(0.0, 0.0), 3.0)
+                                                .palette(Palette::new(vec![black], 1.0, white));
+        let image = renderer.render(3, 1);
+        assert_eq!(image.to_ascii().as_slice(), "..#\n");
+    }
+
+    #[test]
+    fn checksums() {
+        // The same image with any number of tasks
+        let mandelbrot = Renderer::new(Mandelbrot).max_iterations(100);
+        for &tasks in [1u, 3, 8].iter() {
+            let image = mandelbrot.clone().tasks(tasks).render(48, 32);
+            assert_eq!(checksum(&image), 0xc4f6ef5a279c98bd);
+        }
+        let julia = Renderer::new(Julia(Complex::new(-0.8, 0.156)))
+                        .viewport(Complex::new(0.1, 0.2), 1.5).tasks(4);
+        assert_eq!(checksum(&julia.render(40, 30)), 0xc8c3843ed63fccc7);
+        assert_eq!(checksum(&julia.smooth(false).render(40, 30)), 0x28caa22ca1969d2e);
+    }
+}
@@ -1,20 +1,33 @@
 // Solution for http://rosettacode.org/wiki/Execute_a_Markov_algorithm
+//
+// Besides applying an algorithm to a string, `MarkovAlgorithm::execute` yields the steps one by
+// one, and can stop a runaway algorithm with a budget on the number of steps or the length of the
+// string.  Algorithms are deterministic, so a string seen twice means that the algorithm would
+// loop forever: this is reported as an error as well.  Loops are found with Brent's algorithm,
+// which only keeps one string besides the input and the current one: the error may come some
+// steps after the string repeats, but it tells where the loop starts.
+
+use std::fmt;
+use std::mem;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
 // Individual markov rule
-struct MarkovRule {
+pub struct MarkovRule {
     pattern: String,
     replacement: String,
     stop: bool
 }
 
 impl MarkovRule {
-    fn new(pattern: String, replacement: String, stop: bool) -> MarkovRule {
+    pub fn new(pattern: String, replacement: String, stop: bool) -> MarkovRule {
         MarkovRule {pattern: pattern, replacement: replacement, stop: stop}
     }
 }
 
 // The complete markov algorithm
-struct MarkovAlgorithm {
+pub struct MarkovAlgorithm {
     rules: Vec<MarkovRule>
 }
 
@@ -22,9 +35,10 @@ impl MarkovAlgorithm {
     // Parse an algorithm description to build a markov algorithm
     pub fn from_str(s: &str) -> Result<MarkovAlgorithm, String> {
         let mut rules: Vec<MarkovRule> = vec!();
-        for line in s.lines()
+        for (line_number, line) in s.lines()
             .map(|l| l.trim()) // Ignore whitespace before and after
-            .filter(|l| l.char_len() > 0 && l.char_at(0) != '#') { // Ignore comments
+            .enumerate()
+            .filter(|&(_, l)| l.char_len() > 0 && l.char_at(0) != '#') { // Ignore comments
 
             // check for -> (must be preceded by whitespace)
             // invalid ruleset if absent
@@ -33,7 +47,7 @@ impl MarkovAlgorithm {
             match arrow_pos {
                 None => {
                     // Ruleset is invalid
-                    return Err(format!("Invalid rule \"{}\"", line));
+                    return Err(format!("Invalid rule \"{}\" on line {}", line, line_number + 1));
                 }
                 Some(arrow) => {
                     // extract pattern (trim trailing whitespace)
@@ -60,54 +74,194 @@ impl MarkovAlgorithm {
         Ok(rule_set)
     }
 
-    // Transform a text string by applying the markov algorithm
-    pub fn apply(&self, input: &str) -> String {
+    // Transform a text string by applying the markov algorithm, fails if it doesn't terminate
+    pub fn apply(&self, input: &str) -> Result<String, ExecError> {
+        self.execute(input).run()
+    }
+
+    // Starts applying the markov algorithm to `input`, one step at a time
+    pub fn execute<'a>(&'a self, input: &str) -> Execution<'a> {
+        Execution {
+            rules: self.rules.as_slice(),
+            input: input.to_string(),
+            state: input.to_string(),
+            steps: 0,
+            checkpoint: input.to_string(),
+            checkpoint_step: 0,
+            power: 1,
+            max_steps: None,
+            max_length: None,
+            done: false
+        }
+    }
+}
+
+// A rule applied to the string
+#[deriving(Clone, PartialEq, Show)]
+pub struct Step {
+    pub rule: uint, // Index of the rule, comments not counted
+    pub position: uint, // Byte offset of the replaced pattern
+    pub before: String,
+    pub after: String
+}
+
+// Why an execution stopped before the algorithm terminated, with the number of steps applied
+#[deriving(Clone, PartialEq)]
+pub enum ExecError {
+    StepLimit(uint),
+    LengthLimit(uint),
+    // The string after the second step was already seen after the first one (0 is the input)
+    Loop(uint, uint)
+}
+
+impl fmt::Show for ExecError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            StepLimit(steps) => write!(f, "no result after {} steps", steps),
+            LengthLimit(steps) => write!(f, "the string grew too long after {} steps", steps),
+            Loop(0, repeat) => write!(f, "step {} goes back to the input, the algorithm never \
+                                          terminates", repeat),
+            Loop(first, repeat) => write!(f, "step {} goes back to the string after step {}, \
+                                              the algorithm never terminates", repeat, first)
+        }
+    }
+}
+
+// The execution of a markov algorithm, which yields every step
+pub struct Execution<'a> {
+    rules: &'a [MarkovRule],
+    input: String,
+    state: String,
+    steps: uint,
+    // The string after `checkpoint_step` steps, which the following strings are compared to.
+    // It moves forward after `power` steps, and `power` doubles, until it is in the loop if any
+    checkpoint: String,
+    checkpoint_step: uint,
+    power: uint,
+    max_steps: Option<uint>,
+    max_length: Option<uint>,
+    done: bool
+}
+
+impl<'a> Execution<'a> {
+    // Fails once `max_steps` rules were applied and another one could be
+    pub fn max_steps(mut self, max_steps: uint) -> Execution<'a> {
+        self.max_steps = Some(max_steps);
+        self
+    }
+
+    // Fails once the string is longer than `max_length` characters
+    pub fn max_length(mut self, max_length: uint) -> Execution<'a> {
+        self.max_length = Some(max_length);
+        self
+    }
 
-        // get a writable version of the input to work with
-        let mut state = input.to_string();
+    // The string after the steps applied so far
+    pub fn state(&self) -> &str {
+        self.state.as_slice()
+    }
 
-        // Don't allow input to be used after this
-        drop(input);
+    pub fn steps(&self) -> uint {
+        self.steps
+    }
 
-        // loop while operations are possible
+    // Applies the remaining steps and returns the final string
+    pub fn run(mut self) -> Result<String, ExecError> {
         loop {
-            // find the first rule that is applicable
-            // (pattern string is in state)
-            let possible_rule = self.rules.iter().find(|rule|{
-                state.as_slice().find_str(rule.pattern.as_slice()).is_some()
-            });
+            match self.next() {
+                Some(Ok(_)) => (),
+                Some(Err(e)) => return Err(e),
+                None => return Ok(self.state)
+            }
+        }
+    }
 
-            match possible_rule {
-                // stop if no rule found
-                None => { break; }
-                Some(rule) => {
-                    // replace the first instance (only) of the pattern
-                    // Note: cannot use str::replace as that replaces all instances
+    fn fail(&mut self, error: ExecError) -> Option<Result<Step, ExecError>> {
+        self.done = true;
+        Some(Err(error))
+    }
 
-                    // unwrap is safe here as the code for finding a rule
-                    // already established that the pattern is present
-                    let pos = state.as_slice().find_str(rule.pattern.as_slice()).unwrap();
-                    let width = rule.pattern.len();
+    // The first step of a loop of `length` steps, i.e. the first n such that the strings after n
+    // and n + length steps are the same, by applying the algorithm to the input again
+    fn loop_start(&self, length: uint) -> uint {
+        let next = |state: &str| match apply_first(self.rules, state) {
+            Some((_, _, after)) => after,
+            None => unreachable!()
+        };
+        let mut ahead = self.input.clone();
+        for _ in range(0, length) {
+            ahead = next(ahead.as_slice());
+        }
+        let mut behind = self.input.clone();
+        let mut start = 0;
+        while behind != ahead {
+            behind = next(behind.as_slice());
+            ahead = next(ahead.as_slice());
+            start += 1;
+        }
+        start
+    }
+}
 
-                    // string parts
-                    let left = state.as_slice().slice_to(pos).to_string();
-                    let right = state.as_slice().slice_from(pos + width).to_string();
+// Finds the first rule that is applicable to `state`, and returns its index, the position of its
+// pattern and the string after replacing the first instance (only) of the pattern
+// Note: cannot use str::replace as that replaces all instances
+fn apply_first(rules: &[MarkovRule], state: &str) -> Option<(uint, uint, String)> {
+    rules.iter().enumerate().filter_map(|(index, rule)| {
+        state.find_str(rule.pattern.as_slice()).map(|pos| {
+            let after = format!("{}{}{}", state.slice_to(pos), rule.replacement,
+                                state.slice_from(pos + rule.pattern.len()));
+            (index, pos, after)
+        })
+    }).next()
+}
 
-                    // construct new string
-                    state = format!("{}{}{}", left, rule.replacement, right);
+impl<'a> Iterator<Result<Step, ExecError>> for Execution<'a> {
+    fn next(&mut self) -> Option<Result<Step, ExecError>> {
+        if self.done {
+            return None;
+        }
+        let steps = self.steps;
+        if self.max_length.map_or(false, |max| self.state.as_slice().char_len() > max) {
+            return self.fail(LengthLimit(steps));
+        }
 
-                    // stop if required
-                    if rule.stop { break; }
-                }
+        let (index, pos, after) = match apply_first(self.rules, self.state.as_slice()) {
+            Some(found) => found,
+            None => {
+                // stop if no rule found
+                self.done = true;
+                return None;
             }
+        };
+
+        if steps > self.checkpoint_step && self.state == self.checkpoint {
+            let length = steps - self.checkpoint_step;
+            let first = self.loop_start(length);
+            return self.fail(Loop(first, first + length));
+        }
+        if self.max_steps.map_or(false, |max| steps >= max) {
+            return self.fail(StepLimit(steps));
+        }
+        if steps - self.checkpoint_step == self.power {
+            self.checkpoint = self.state.clone();
+            self.checkpoint_step = steps;
+            self.power *= 2;
         }
 
-        state
+        let before = mem::replace(&mut self.state, after);
+        self.steps += 1;
+
+        // stop if required
+        if self.rules[index].stop {
+            self.done = true;
+        }
+        Some(Ok(Step { rule: index, position: pos, before: before, after: self.state.clone() }))
     }
 }
 
 // A Rosetta Code sample
-struct RCSample<'a> {
+pub struct RCSample<'a> {
     ruleset: &'a str,
     input: &'a str,
     expected_result: &'a str
@@ -115,7 +269,7 @@ struct RCSample<'a> {
 
 // Sample markow algorithms from rosetta code
 // The extra whitespaces are trimmed when MarkovAlgorithm::from_str is called
-fn get_samples<'a>() -> [RCSample<'a>, ..5] {
+pub fn get_samples<'a>() -> [RCSample<'a>, ..5] {
     [
         RCSample {
             ruleset:
@@ -218,17 +372,26 @@ fn get_samples<'a>() -> [RCSample<'a>, ..5] {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the result of each sample algorithm to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     for (index, sample) in get_samples().iter().enumerate() {
         match MarkovAlgorithm::from_str(sample.ruleset) {
             Ok(algorithm) => {
-                println!("Sample {}", (index + 1));
-                println!("Output: {}", algorithm.apply(sample.input));
-                println!("Expected result: {}", sample.expected_result);
+                try!(writeln!(out, "Sample {}", (index + 1)));
+                match algorithm.apply(sample.input) {
+                    Ok(output) => try!(writeln!(out, "Output: {}", output)),
+                    Err(e) => try!(writeln!(out, "Error: {}", e))
+                }
+                try!(writeln!(out, "Expected result: {}", sample.expected_result));
             }
-            Err(message) => println!("{}", message)
+            Err(message) => try!(writeln!(out, "{}", message))
         }
     }
+    Ok(())
 }
 
 #[test]
@@ -236,8 +399,78 @@ fn test_samples() {
     for sample in get_samples().iter() {
         match MarkovAlgorithm::from_str(sample.ruleset) {
             Ok(algorithm) => assert_eq!(sample.expected_result,
-                                        algorithm.apply(sample.input).as_slice()),
+                                        algorithm.apply(sample.input).unwrap().as_slice()),
             Err(message) => fail!("{}", message)
         }
     }
 }
+
+#[test]
+fn test_parse_error() {
+    let error = MarkovAlgorithm::from_str("A -> apple\n# comment\nB bag").err().unwrap();
+    assert_eq!(error.as_slice(), "Invalid rule \"B bag\" on line 3");
+}
+
+#[test]
+fn test_trace() {
+    let samples = get_samples();
+    let sample = &samples[0];
+    let algorithm = MarkovAlgorithm::from_str(sample.ruleset).unwrap();
+    let steps: Vec<Step> = algorithm.execute(sample.input).map(|step| step.unwrap()).collect();
+    assert_eq!(steps.iter().map(|step| step.rule).collect::<Vec<uint>>(), vec![0, 1, 2, 3, 4]);
+    assert_eq!(steps[0], Step {
+        rule: 0,
+        position: 16,
+        before: "I bought a B of As from T S.".to_string(),
+        after: "I bought a B of apples from T S.".to_string()
+    });
+    assert_eq!(steps[4].after.as_slice(), sample.expected_result);
+    for pair in steps.as_slice().windows(2) {
+        assert_eq!(pair[0].after, pair[1].before);
+    }
+
+    // A terminating rule is the last step
+    let algorithm = MarkovAlgorithm::from_str("a -> .b\nb -> c").unwrap();
+    let steps: Vec<Step> = algorithm.execute("aa").map(|step| step.unwrap()).collect();
+    assert_eq!(steps.len(), 1);
+    assert_eq!(steps[0].after.as_slice(), "ba");
+}
+
+#[test]
+fn test_limits() {
+    let samples = get_samples();
+    let sample = &samples[3];
+    let algorithm = MarkovAlgorithm::from_str(sample.ruleset).unwrap();
+    let mut execution = algorithm.execute(sample.input).max_steps(10);
+    assert_eq!(execution.by_ref().filter(|step| step.is_ok()).count(), 10);
+    assert_eq!(execution.steps(), 10);
+    assert!(execution.next().is_none());
+    assert_eq!(algorithm.execute(sample.input).max_steps(10).run(), Err(StepLimit(10)));
+
+    // The limit isn't reached by an algorithm which terminates within it
+    let algorithm = MarkovAlgorithm::from_str("a -> b").unwrap();
+    assert_eq!(algorithm.execute("aaa").max_steps(3).run(), Ok("bbb".to_string()));
+
+    let algorithm = MarkovAlgorithm::from_str("a -> aa").unwrap();
+    assert_eq!(algorithm.execute("a").max_length(10).run(), Err(LengthLimit(10)));
+    let mut execution = algorithm.execute("a").max_length(10);
+    for _ in execution.by_ref() {}
+    assert_eq!(execution.state(), "aaaaaaaaaaa");
+}
+
+#[test]
+fn test_loop_detection() {
+    let algorithm = MarkovAlgorithm::from_str("a -> b\nb -> a").unwrap();
+    assert_eq!(algorithm.apply("a"), Err(Loop(0, 2)));
+
+    let algorithm = MarkovAlgorithm::from_str("x -> \nab -> ba\nba -> ab").unwrap();
+    assert_eq!(algorithm.apply("xab"), Err(Loop(1, 3)));
+    let error = algorithm.apply("xab").err().unwrap();
+    assert_eq!(format!("{}", error).as_slice(),
+               "step 3 goes back to the string after step 1, the algorithm never terminates");
+
+    // The loop is found after it repeats, but reported from its first step
+    let mut execution = algorithm.execute("xxxxxab");
+    assert_eq!(execution.by_ref().last(), Some(Err(Loop(5, 7))));
+    assert!(execution.steps() >= 7);
+}
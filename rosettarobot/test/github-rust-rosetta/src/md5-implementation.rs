@@ -3,11 +3,20 @@
  * Ported from C - Simple MD5 implementation
 * on Wikipedia https://en.wikipedia.org/wiki/MD5
 */
-use std::iter::range_step;
+use std::cmp;
 use std::fmt::{Show, Formatter, Result};
+use std::slice::bytes::copy_memory;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the digests of the test suite of RFC 1321 to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let inputs=
     [b"a",
     b"abc",
@@ -17,12 +26,21 @@ fn main() {
     b"12345678901234567890123456789012345678901234567890123456789012345678901234567890"];
 
     for &input in inputs.iter() {
-        println!("{}", md5(input));
+        try!(writeln!(out, "{}", md5(input)));
     }
+    Ok(())
 }
 
+// The size of a MD5 hash in bytes
+pub const SIZE: uint = 16;
+
+// The size of the blocks the message is processed in, in bytes
+pub const CHUNK: uint = 64;
+
+pub const INIT: [u32,..4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+
 // Constants are the integer part of the sines of integers (in radians) * 2^32.
-const K:[u32,..64] = [
+pub const K:[u32,..64] = [
 0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee ,
 0xf57c0faf, 0x4787c62a, 0xa8304613, 0xfd469501 ,
 0x698098d8, 0x8b44f7af, 0xffff5bb1, 0x895cd7be ,
@@ -41,13 +59,13 @@ const K:[u32,..64] = [
 0xf7537e82, 0xbd3af235, 0x2ad7d2bb, 0xeb86d391 ];
 
 // R specifies the per-round shift amounts
-const R:[u32,..64] = [7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22,
+pub const R:[u32,..64] = [7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22,
                       5,  9, 14, 20, 5,  9, 14, 20, 5,  9, 14, 20, 5,  9, 14, 20,
                       4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23,
                       6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21];
 
 // "newtype" for [u8,..16] to specify it's a MD5 hash
-struct MD5([u8,..16]);
+pub struct MD5(pub [u8,..SIZE]);
 impl Show for MD5 {
     fn fmt(&self, f: &mut Formatter) -> Result {
         let MD5(md5)=*self;
@@ -60,11 +78,11 @@ impl Show for MD5 {
 
 // leftrotate function definition
 #[inline]
-fn left_rotate(x: u32, c: u32) -> u32 {
+pub fn left_rotate(x: u32, c: u32) -> u32 {
     (x << c as uint) | (x >> (32 - c) as uint)
 }
 
-fn to_bytes(val: u64) -> [u8,..8]
+pub fn to_bytes(val: u64) -> [u8,..8]
 {
     let mut tmp:[u8,..8] = [0u8,..8];
     for i in range (0u, 8) {
@@ -73,50 +91,99 @@ fn to_bytes(val: u64) -> [u8,..8]
     tmp
 }
 
-fn md5(initial_msg: &[u8]) -> MD5
+pub fn md5(initial_msg: &[u8]) -> MD5
 {
-    let initial_len=initial_msg.len() as u64;
-
-    // These vars will contain the hash
-    let mut h:[u32,..4]=[0x67452301u32, 0xefcdab89, 0x98badcfe, 0x10325476];
-
-     //Pre-processing:
-    //append "1" bit to message
-    //append "0" bits until message length in bits ≡ 448 (mod 512)
-    //append length mod (2^64) to message
-    let mut new_len=initial_len;
-    while new_len % (512/8) != 448/8 {
-        new_len+=1;
+    let mut md5 = Md5::new();
+    md5.update(initial_msg);
+    MD5(md5.finalize())
+}
+
+// The partial evaluation of a MD5 hash, the message can be given in several parts
+pub struct Md5 {
+    h: [u32,..4],
+    buffer: [u8,..CHUNK], // The start of an incomplete chunk
+    buffered: uint,
+    len: u64
+}
+
+impl Md5 {
+    pub fn new() -> Md5 {
+        Md5 { h: INIT, buffer: [0u8,..CHUNK], buffered: 0, len: 0 }
     }
 
-    let mut msg = initial_msg.to_vec();
-    msg.push(0x80u8); // append the "1" bit; most significant bit is "first"
+    pub fn reset(&mut self) {
+        *self = Md5::new();
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.len += data.len() as u64;
+        let mut data = data;
 
-    for _ in range (initial_len + 1, new_len) {
-        msg.push(0); // append "0" bits
+        // Complete the chunk started by a previous update first
+        if self.buffered > 0 {
+            let n = cmp::min(data.len(), CHUNK - self.buffered);
+            copy_memory(self.buffer.slice_mut(self.buffered, self.buffered + n),
+                        data.slice_to(n));
+            self.buffered += n;
+            data = data.slice_from(n);
+            if self.buffered < CHUNK {
+                return;
+            }
+            let chunk = self.buffer;
+            self.process_chunk(chunk.as_slice());
+            self.buffered = 0;
+        }
+
+        while data.len() >= CHUNK {
+            self.process_chunk(data.slice_to(CHUNK));
+            data = data.slice_from(CHUNK);
+        }
+        copy_memory(self.buffer.slice_mut(0, data.len()), data);
+        self.buffered = data.len();
     }
 
-    // append the len in bits at the end of the buffer.
-    msg.push_all(to_bytes(initial_len << 3).as_slice());
+    // Returns the hash of the message, and starts a new one
+    pub fn finalize(&mut self) -> [u8,..SIZE] {
+        let bit_len = self.len << 3;
+
+        //Pre-processing:
+        //append "1" bit to message
+        //append "0" bits until message length in bits ≡ 448 (mod 512)
+        //append length mod (2^64) to message
+        let mut padding = [0u8,..CHUNK];
+        padding[0] = 0x80u8; // most significant bit is "first"
+        let padding_len = if self.buffered < 56 { 56 - self.buffered } else { 120 - self.buffered };
+        self.update(padding.slice_to(padding_len));
+        self.update(to_bytes(bit_len).as_slice());
+        assert_eq!(self.buffered, 0);
 
-    assert_eq!(msg.len() % 64, 0);
+        //var char digest[16] := h0 append h1 append h2 append h3 //(Output is in little-endian)
+        let mut digest = [0u8,..SIZE];
+        for (i, s) in self.h.iter().enumerate() {
+            digest[i*4] = (*s ) as u8;
+            digest[i*4+1] = (*s >> 8) as u8;
+            digest[i*4+2] = (*s >> 16) as u8;
+            digest[i*4+3] = (*s >> 24) as u8;
+        }
+        self.reset();
+        digest
+    }
 
-    let mut w:[u32,..16] = [0u32,..16];
-    // Process the message in successive 512-bit chunks:
-    //for each 512-bit chunk of message:
-    for offset in range_step(0u64, new_len, (512/8)) {
+    // Process the message in successive 512-bit chunks
+    fn process_chunk(&mut self, chunk: &[u8]) {
         // break chunk into sixteen 32-bit words w[j], 0 ≤ j ≤ 15
-        for i in range(0u32, 16) {
-            let j = i as uint * 4 + offset as uint;
-            w[i as uint] =
-                    (msg[j]   as u32)      |
-                    (msg[j+1] as u32) <<8  |
-                    (msg[j+2] as u32) <<16 |
-                     msg[j+3] as u32  <<24;
+        let mut w:[u32,..16] = [0u32,..16];
+        for i in range(0u, 16) {
+            let j = i * 4;
+            w[i] =
+                    (chunk[j]   as u32)      |
+                    (chunk[j+1] as u32) <<8  |
+                    (chunk[j+2] as u32) <<16 |
+                     chunk[j+3] as u32  <<24;
         }
 
         // Initialize hash value for this chunk:
-        let (mut a, mut b, mut c, mut d) = (h[0], h[1], h[2], h[3]);
+        let (mut a, mut b, mut c, mut d) = (self.h[0], self.h[1], self.h[2], self.h[3]);
 
         // Main loop:
         for ind in range(0u, 64) {
@@ -139,22 +206,11 @@ fn md5(initial_msg: &[u8]) -> MD5
         }
 
         // Add this chunk's hash to result so far:
-        h[0] += a;
-        h[1] += b;
-        h[2] += c;
-        h[3] += d;
-    }
-    drop(msg); // cleanup, msg is freed
-
-    //var char digest[16] := h0 append h1 append h2 append h3 //(Output is in little-endian)
-    let mut digest = [0u8,..16];
-    for (i, s) in h.iter().enumerate() {
-        digest[i*4] = (*s ) as u8;
-        digest[i*4+1] = (*s >> 8) as u8;
-        digest[i*4+2] = (*s >> 16) as u8;
-        digest[i*4+3] = (*s >> 24) as u8;
+        self.h[0] += a;
+        self.h[1] += b;
+        self.h[2] += c;
+        self.h[3] += d;
     }
-    MD5(digest)
 }
 
 #[test]
@@ -188,3 +244,23 @@ fn known_hashes() {
         assert_eq!(format!("{}", m), o.to_string());
     }
 }
+
+#[test]
+fn incremental_updates() {
+    let message = [b'a', ..1000];
+    let whole = md5(message.as_slice());
+
+    // Parts of all sizes, across the chunk boundaries
+    let mut hash = Md5::new();
+    let mut start = 0;
+    for size in range(0u, 45) {
+        hash.update(message.slice(start, start + size));
+        start += size;
+    }
+    hash.update(message.slice_from(start));
+    assert_eq!(format!("{}", MD5(hash.finalize())), format!("{}", whole));
+
+    // finalize starts a new hash
+    hash.update(b"abc");
+    assert_eq!(format!("{}", MD5(hash.finalize())), "900150983cd24fb0d6963f7d28e17f72".to_string());
+}
@@ -0,0 +1,99 @@
+// Implements http://rosettacode.org/wiki/Median_filter
+//
+// Each channel of a pixel is replaced by the median of that channel in a square window around the
+// pixel, which removes isolated noisy pixels while keeping the edges sharp.
+use bitmap::{Color, Edges, Image};
+mod bitmap;
+
+pub trait MedianFilter {
+    // Filters with a window of `window` x `window` pixels, where `window` is odd
+    fn median_filter(&self, window: uint, edges: Edges) -> Self;
+}
+
+impl MedianFilter for Image {
+    fn median_filter(&self, window: uint, edges: Edges) -> Image {
+        assert!(window % 2 == 1, "the window must have a middle pixel");
+        let radius = (window / 2) as int;
+        let mut result = Image::new(self.width, self.height);
+        let (mut reds, mut greens, mut blues) = (Vec::new(), Vec::new(), Vec::new());
+        for y in range(0, self.height as int) {
+            for x in range(0, self.width as int) {
+                reds.clear();
+                greens.clear();
+                blues.clear();
+                for dy in range(-radius, radius + 1) {
+                    for dx in range(-radius, radius + 1) {
+                        let color = self.get_extended(x + dx, y + dy, edges.clone());
+                        reds.push(color.red);
+                        greens.push(color.green);
+                        blues.push(color.blue);
+                    }
+                }
+                reds.sort();
+                greens.sort();
+                blues.sort();
+                let middle = reds.len() / 2;
+                result.set(x, y, Color { red: reds[middle], green: greens[middle],
+                                         blue: blues[middle] });
+            }
+        }
+        result
+    }
+}
+
+#[cfg(not(test))]
+pub fn main() {
+    use bitmap::{Extend, Pnm};
+
+    let path = Path::new("../src/resources/filters_input.ppm");
+    let image = match Pnm::open(&path) {
+        Ok(pnm) => pnm.to_image(),
+        Err(e) => fail!("failed to read {}: {}", path.display(), e)
+    };
+    match image.median_filter(3, Extend).write_ppm("median.ppm") {
+        Ok(()) => println!("Wrote median.ppm"),
+        Err(e) => println!("Failed to write median.ppm: {}", e)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitmap::{Color, Extend, Image, Mirror, Pnm};
+    use super::MedianFilter;
+
+    #[test]
+    fn golden_file() {
+        let image = Pnm::parse(include_bytes!("resources/filters_input.ppm")).unwrap().to_image();
+        let median = Pnm::parse(include_bytes!("resources/filters_median.ppm")).unwrap();
+        assert_eq!(image.median_filter(3, Extend).data, median.to_image().data);
+        assert_eq!(image.median_filter(1, Mirror).data, image.data);
+
+        let wide = image.median_filter(5, Mirror);
+        assert_eq!(wide.data.slice_to(4).to_vec(),
+                   vec![Color { red: 32, green: 96, blue: 128 },
+                        Color { red: 32, green: 96, blue: 128 },
+                        Color { red: 128, green: 96, blue: 40 },
+                        Color { red: 160, green: 96, blue: 40 }]);
+    }
+
+    #[test]
+    fn noise() {
+        // A white pixel on black disappears, a white square only loses its corner at (3, 3)
+        let mut image = Image::new(6, 6);
+        let white = Color { red: 255, green: 255, blue: 255 };
+        image.set(1, 1, white);
+        for y in range(3, 6) {
+            for x in range(3, 6) {
+                image.set(x, y, white);
+            }
+        }
+        assert_eq!(image.median_filter(3, Extend).to_ascii().as_slice(),
+                   "######\n######\n######\n####..\n###...\n###...\n");
+    }
+
+    #[test]
+    #[should_fail]
+    fn even_window() {
+        Image::new(2, 2).median_filter(2, Extend);
+    }
+}
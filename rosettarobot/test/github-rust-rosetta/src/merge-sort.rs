@@ -2,8 +2,11 @@
 
 // This is an idiomatic-but-slow implementation. A more efficient implementation
 // would use `unsafe` to avoid allocating so many temporary vectors.
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
-fn merge_sort<E: PartialOrd + Clone>(arr: &[E]) -> Vec<E> {
+pub fn merge_sort<E: PartialOrd + Clone>(arr: &[E]) -> Vec<E> {
     if arr.len() <= 1 {
         return arr.to_vec();
     }
@@ -13,7 +16,7 @@ fn merge_sort<E: PartialOrd + Clone>(arr: &[E]) -> Vec<E> {
     merge(left.as_slice(), right.as_slice())
 }
 
-fn merge<E: PartialOrd + Clone>(left: &[E], right: &[E]) -> Vec<E> {
+pub fn merge<E: PartialOrd + Clone>(left: &[E], right: &[E]) -> Vec<E> {
     let mut merged = Vec::with_capacity(left.len() + right.len());
     let mut i = 0;
     let mut j = 0;
@@ -39,9 +42,14 @@ fn merge<E: PartialOrd + Clone>(left: &[E], right: &[E]) -> Vec<E> {
 
 #[cfg(not(test))]
 pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes a sorted array to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let arr = [1i, 9, 3, 2, 1003, 23, -123, 7];
     let sorted = merge_sort(&arr);
-    println!("{}", sorted);
+    writeln!(out, "{}", sorted)
 }
 
 #[cfg(test)]
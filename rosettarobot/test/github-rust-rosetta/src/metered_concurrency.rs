@@ -62,7 +62,7 @@ impl<'a> Drop for CountingSemaphoreGuard<'a> {
     }
 }
 
-fn metered(duration: Duration) {
+pub fn metered(duration: Duration) {
     static MAX_COUNT: uint = 4; // Total available resources
     static NUM_WORKERS: u8 = 10; // Number of workers contending for the resources
     let backoff = Duration::milliseconds(1); // Linear backoff time
@@ -106,7 +106,7 @@ fn test_metered_concurrency() {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
     // Hold each resource for 2 seconds per worker
     metered(Duration::seconds(2));
 }
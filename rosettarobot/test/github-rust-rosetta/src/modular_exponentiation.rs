@@ -1,11 +1,14 @@
 // http://rosettacode.org/wiki/Modular_exponentiation
 extern crate core;
 extern crate num;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 use core::num::One;
 use num::bigint::BigUint;
 use num::integer::Integer;
 
-fn mod_exp<T: Integer>(mut a: T, mut b: T, m: T) -> T {
+pub fn mod_exp<T: Integer>(mut a: T, mut b: T, m: T) -> T {
     let one: T = One::one();
     let two = one + one;
 
@@ -22,7 +25,12 @@ fn mod_exp<T: Integer>(mut a: T, mut b: T, m: T) -> T {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the last 40 digits of a^b to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     use core::num::pow;
     use std::from_str::FromStr;
 
@@ -31,7 +39,7 @@ fn main() {
     let a: BigUint = FromStr::from_str(a_str).unwrap();
     let b: BigUint = FromStr::from_str(b_str).unwrap();
     let m: BigUint = pow(FromPrimitive::from_int(10).unwrap(), 40);
-    println!("{}", mod_exp(a, b, m));
+    writeln!(out, "{}", mod_exp(a, b, m))
 }
 
 #[test]
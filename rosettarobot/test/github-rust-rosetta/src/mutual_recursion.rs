@@ -1,13 +1,17 @@
 // Implements http://rosettacode.org/wiki/Mutual_recursion
 
-fn f(n: uint) -> uint {
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
+
+pub fn f(n: uint) -> uint {
     match n {
         0 => 1,
         _ => n - m(f(n - 1))
     }
 }
 
-fn m(n: uint) -> uint {
+pub fn m(n: uint) -> uint {
     match n {
         0 => 0,
         _ => n - f(m(n - 1))
@@ -15,16 +19,21 @@ fn m(n: uint) -> uint {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the first 20 values of both sequences to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     for i in range(0u, 20).map(f) {
-        print!("{} ", i);
+        try!(write!(out, "{} ", i));
     }
-    println!("")
+    try!(writeln!(out, ""));
 
     for i in range(0u, 20).map(m) {
-        print!("{} ", i);
+        try!(write!(out, "{} ", i));
     }
-    println!("")
+    writeln!(out, "")
 }
 
 #[test]
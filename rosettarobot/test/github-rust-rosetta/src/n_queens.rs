@@ -9,7 +9,7 @@ use std::iter::AdditiveIterator;
 use test::Bencher;
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
     for num in range(0i32, 16) {
         println!("Sequential: {}: {}", num, n_queens(num));
     }
@@ -28,7 +28,7 @@ fn main() {
 
 // Solves n-queens using a depth-first, backtracking solution.
 // Returns the number of solutions for a given n.
-fn n_queens(n: i32) -> uint {
+pub fn n_queens(n: i32) -> uint {
     // Pass off to our helper function.
     return n_queens_helper((1 << n as uint) -1, 0, 0, 0);
 }
@@ -52,7 +52,7 @@ fn n_queens(n: i32) -> uint {
 //
 // This implementation is optimized for speed and memory by using
 // integers and bit shifting instead of arrays for storing the conflicts.
-fn n_queens_helper(all_ones: i32, left_diags: i32, columns: i32, right_diags: i32) -> uint {
+pub fn n_queens_helper(all_ones: i32, left_diags: i32, columns: i32, right_diags: i32) -> uint {
     // all_ones is a special value that simply has all 1s in the first n positions
     // and 0s elsewhere. We can use it to clear out areas that we don't care about.
 
@@ -108,7 +108,7 @@ fn n_queens_helper(all_ones: i32, left_diags: i32, columns: i32, right_diags: i3
 //
 // This is much slower for smaller numbers (under 16~17) but outperforms
 // the sequential algorithm after that.
-fn semi_parallel_n_queens(n: i32) -> uint {
+pub fn semi_parallel_n_queens(n: i32) -> uint {
     let all_ones = (1 << n as uint) - 1;
     let (columns, left_diags, right_diags) = (0, 0, 0);
 
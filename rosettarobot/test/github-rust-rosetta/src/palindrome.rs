@@ -1,7 +1,11 @@
 // Implements http://rosettacode.org/wiki/Palindrome_detection
 
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
+
 // Returns true if the string is a palindrome
-fn palindrome(string: &str) -> bool {
+pub fn palindrome(string: &str) -> bool {
     // The first part of the string
     let forward = string.chars().take(string.len() / 2);
 
@@ -18,11 +22,17 @@ fn palindrome(string: &str) -> bool {
 
 // Demonstration code
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes whether a few strings are palindromes to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let test_strings = ["nope", "eevee", "lalala", "rust", "lalalal"];
     for &string in test_strings.iter() {
-        println!("{}: {}", string, palindrome(string));
+        try!(writeln!(out, "{}: {}", string, palindrome(string)));
     }
+    Ok(())
 }
 
 #[test]
@@ -12,7 +12,7 @@ use prime_decomposition::factor;
 mod prime_decomposition;
 
 // Returns the minimal prime factor of a number
-fn min_factor(x: uint) -> uint {
+pub fn min_factor(x: uint) -> uint {
     // factor returns a sorted vector, so we just take the first element
     factor(x)[0]
 }
@@ -31,13 +31,13 @@ fn largest_min_factor_chan(numbers: &[uint]) -> uint {
 
     // Receive them and keep the largest one
     numbers.iter().fold(0u, |max, _| {
-        std::cmp::max(receiver.recv(), max)
+        ::std::cmp::max(receiver.recv(), max)
     })
 }
 
 // Returns the largest minimal factor of the numbers in a slice
 // The function is implemented using the Future struct
-fn largest_min_factor_fut(numbers: &[uint]) -> uint {
+pub fn largest_min_factor_fut(numbers: &[uint]) -> uint {
     // We will save the future values of the minimal factor in the results vec
     let mut results = Vec::from_fn(numbers.len(), |i| {
         let number = numbers[i];
@@ -49,7 +49,7 @@ fn largest_min_factor_fut(numbers: &[uint]) -> uint {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
     // Numbers to be factorized
     let numbers = &[1122725u,
                    1125827,
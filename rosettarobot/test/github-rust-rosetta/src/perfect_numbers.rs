@@ -1,15 +1,24 @@
 // Implements http://rosettacode.org/wiki/Perfect_numbers
 use std::iter::{range_inclusive, AdditiveIterator};
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
-fn perfect_number(n: uint) -> bool {
+pub fn perfect_number(n: uint) -> bool {
   range_inclusive(1, n / 2).filter(|&i| n % i == 0).sum() == n
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+  run(&mut io::stdout()).unwrap();
+}
+
+// Writes the perfect numbers below 10000 to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
   for n in range(2, 10_000u).filter(|&n| perfect_number(n)) {
-    println!("{}", n);
+    try!(writeln!(out, "{}", n));
   }
+  Ok(())
 }
 
 #[test]
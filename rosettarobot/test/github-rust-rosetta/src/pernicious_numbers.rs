@@ -1,24 +1,33 @@
 // http://rosettacode.org/wiki/Pernicious_numbers
 use std::iter::{count, Filter, Counter};
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 use aks_test_for_primes::is_prime;
 mod aks_test_for_primes;
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the first 25 pernicious numbers, and those from 888888877 to 888888887, to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     for i in pernicious().take(25) {
-        print!("{} ", i);
+        try!(write!(out, "{} ", i));
     }
-    println!("");
+    try!(writeln!(out, ""));
     for i in range(888_888_877u64, 888_888_888).filter(|&i| is_pernicious(i)) {
-        print!("{} ", i);
+        try!(write!(out, "{} ", i));
     }
+    Ok(())
 }
 
-fn pernicious<'a>() -> Filter<'a, u64, Counter<u64>> {
+pub fn pernicious<'a>() -> Filter<'a, u64, Counter<u64>> {
     count(0u64, 1).filter(|&i| is_pernicious(i))
 }
 
-fn is_pernicious(n: u64) -> bool {
+pub fn is_pernicious(n: u64) -> bool {
     is_prime(n.count_ones())
 }
 
@@ -0,0 +1,783 @@
+// Writes the bitmaps of the `bitmap` task as PNG images (RFC 2083), and reads them back, with the
+// checksum of the `crc_32` task for the chunks.
+//
+// The images are 8-bit grayscale or RGB, not interlaced, and their rows are not filtered.  The
+// zlib stream (RFC 1950) has either stored blocks, or a single block compressed with LZ77 and the
+// fixed Huffman codes of deflate (RFC 1951).  The decoder reads those images, with any filter and
+// any number of IDAT chunks, but not the dynamic Huffman codes of most other encoders.
+
+use std::cmp;
+use std::io;
+use std::io::{File, IoResult};
+
+use bitmap::{Color, Image, ImageGray8};
+use crc_32;
+
+// The first bytes of every PNG file
+pub const SIGNATURE: [u8, ..8] = [137, 80, 78, 71, 13, 10, 26, 10];
+
+#[deriving(Clone, PartialEq, Show)]
+pub enum ColorType {
+    Gray,
+    Rgb
+}
+
+impl ColorType {
+    // The number of samples of a pixel
+    pub fn channels(&self) -> uint {
+        match *self {
+            Gray => 1,
+            Rgb => 3
+        }
+    }
+
+    // The value of the color type in the header
+    fn code(&self) -> u8 {
+        match *self {
+            Gray => 0,
+            Rgb => 2
+        }
+    }
+}
+
+// How the image data is compressed
+#[deriving(Clone, PartialEq, Show)]
+pub enum Compression {
+    Stored,
+    FixedHuffman
+}
+
+// A PNG image: the samples of every pixel, row by row
+#[deriving(Clone, PartialEq, Show)]
+pub struct Png {
+    pub width: uint,
+    pub height: uint,
+    pub color_type: ColorType,
+    pub samples: Vec<u8>
+}
+
+#[deriving(PartialEq, Show)]
+pub enum PngError {
+    NotPng, // The data doesn't start with the signature
+    Truncated,
+    BadChecksum(String), // The type of the chunk, or "zlib" for the checksum of the image data
+    Unsupported(String),
+    Malformed(String),
+    IoError(io::IoError)
+}
+
+impl Png {
+    pub fn from_image(image: &Image) -> Png {
+        let mut samples = Vec::with_capacity(image.data.len() * 3);
+        for color in image.data.iter() {
+            samples.push_all(&[color.red, color.green, color.blue]);
+        }
+        Png { width: image.width, height: image.height, color_type: Rgb, samples: samples }
+    }
+
+    pub fn from_gray(image: &ImageGray8) -> Png {
+        Png { width: image.width, height: image.height, color_type: Gray,
+              samples: image.data.clone() }
+    }
+
+    pub fn to_image(&self) -> Image {
+        let data = match self.color_type {
+            Gray => self.samples.iter().map(|&v| Color { red: v, green: v, blue: v }).collect(),
+            Rgb => self.samples.chunks(3).map(|c| Color { red: c[0], green: c[1], blue: c[2] })
+                                         .collect()
+        };
+        Image { width: self.width, height: self.height, data: data }
+    }
+
+    // Converts to grayscale, with the luminance of the colors of an RGB image
+    pub fn to_gray(&self) -> ImageGray8 {
+        match self.color_type {
+            Gray => ImageGray8 { width: self.width, height: self.height,
+                                 data: self.samples.clone() },
+            Rgb => self.to_image().to_gray()
+        }
+    }
+
+    pub fn open(path: &Path) -> Result<Png, PngError> {
+        match File::open(path).read_to_end() {
+            Ok(data) => Png::decode(data.as_slice()),
+            Err(e) => Err(IoError(e))
+        }
+    }
+
+    pub fn save(&self, path: &Path, compression: Compression) -> IoResult<()> {
+        let mut file = try!(File::create(path));
+        file.write(self.encode(compression).as_slice())
+    }
+
+    // The PNG file, which can't be empty
+    pub fn encode(&self, compression: Compression) -> Vec<u8> {
+        assert!(self.width > 0 && self.height > 0, "a PNG image must have pixels");
+        let row_len = self.width * self.color_type.channels();
+        assert_eq!(self.samples.len(), row_len * self.height);
+
+        let mut png = SIGNATURE.to_vec();
+        let mut header = Vec::new();
+        push_u32(&mut header, self.width as u32);
+        push_u32(&mut header, self.height as u32);
+        // 8 bits per sample, deflate, adaptive filtering and no interlacing
+        header.push_all(&[8, self.color_type.code(), 0, 0, 0]);
+        write_chunk(&mut png, b"IHDR", header.as_slice());
+
+        // Every row starts with its filter, here none
+        let mut raw = Vec::with_capacity((row_len + 1) * self.height);
+        for row in self.samples.chunks(row_len) {
+            raw.push(0);
+            raw.push_all(row);
+        }
+        write_chunk(&mut png, b"IDAT", zlib_compress(raw.as_slice(), compression).as_slice());
+        write_chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    pub fn decode(data: &[u8]) -> Result<Png, PngError> {
+        if data.len() < SIGNATURE.len() || data.slice_to(SIGNATURE.len()) != SIGNATURE.as_slice() {
+            return Err(NotPng);
+        }
+        let mut position = SIGNATURE.len();
+        let mut header = None;
+        let mut compressed = Vec::new();
+        loop {
+            // The length, the type, the data and the checksum of the type and the data
+            if data.len() < position + 12 {
+                return Err(Truncated);
+            }
+            let length = read_u32(data.slice_from(position)) as uint;
+            if data.len() - position - 12 < length {
+                return Err(Truncated);
+            }
+            let kind = data.slice(position + 4, position + 8);
+            let chunk = data.slice(position + 8, position + 8 + length);
+            if crc_32::crc(data.slice(position + 4, position + 8 + length)) !=
+               read_u32(data.slice_from(position + 8 + length)) {
+                return Err(BadChecksum(chunk_name(kind)));
+            }
+            position += 12 + length;
+
+            if kind == b"IHDR" {
+                if header.is_some() || chunk.len() != 13 {
+                    return Err(Malformed("invalid IHDR chunk".to_string()));
+                }
+                header = Some(try!(parse_header(chunk)));
+            } else if header.is_none() {
+                return Err(Malformed("the first chunk must be IHDR".to_string()));
+            } else if kind == b"IDAT" {
+                compressed.push_all(chunk);
+            } else if kind == b"IEND" {
+                break;
+            } else if kind[0] & 0x20 == 0 {
+                // The chunks with an uppercase first letter can't be skipped
+                return Err(Unsupported(format!("{} chunks", chunk_name(kind))));
+            }
+        }
+
+        let (width, height, color_type) = header.unwrap();
+        let raw = try!(zlib_decompress(compressed.as_slice()));
+        let samples = try!(unfilter(raw.as_slice(), width * color_type.channels(),
+                                    color_type.channels()));
+        if samples.len() != width * color_type.channels() * height {
+            return Err(Malformed(format!("{} rows of pixels instead of {}",
+                                         raw.len() / (width * color_type.channels() + 1),
+                                         height)));
+        }
+        Ok(Png { width: width, height: height, color_type: color_type, samples: samples })
+    }
+}
+
+// Writes PNG files like `Image::write_ppm` writes PPM files, with the fixed Huffman codes
+pub trait PngWritable {
+    fn write_png(&self, filename: &str) -> IoResult<()>;
+}
+
+impl PngWritable for Image {
+    fn write_png(&self, filename: &str) -> IoResult<()> {
+        Png::from_image(self).save(&Path::new(filename), FixedHuffman)
+    }
+}
+
+impl PngWritable for ImageGray8 {
+    fn write_png(&self, filename: &str) -> IoResult<()> {
+        Png::from_gray(self).save(&Path::new(filename), FixedHuffman)
+    }
+}
+
+fn push_u32(output: &mut Vec<u8>, value: u32) {
+    output.push_all(&[(value >> 24) as u8, (value >> 16) as u8, (value >> 8) as u8, value as u8]);
+}
+
+fn read_u32(data: &[u8]) -> u32 {
+    data.iter().take(4).fold(0, |value, &byte| value << 8 | byte as u32)
+}
+
+fn chunk_name(kind: &[u8]) -> String {
+    kind.iter().map(|&c| c as char).collect()
+}
+
+// Appends a chunk, whose checksum covers its type and its data
+fn write_chunk(png: &mut Vec<u8>, kind: &[u8], data: &[u8]) {
+    push_u32(png, data.len() as u32);
+    let start = png.len();
+    png.push_all(kind);
+    png.push_all(data);
+    let crc = crc_32::crc(png.slice_from(start));
+    push_u32(png, crc);
+}
+
+// The width, the height and the color type of an IHDR chunk
+fn parse_header(chunk: &[u8]) -> Result<(uint, uint, ColorType), PngError> {
+    let (width, height) = (read_u32(chunk) as uint, read_u32(chunk.slice_from(4)) as uint);
+    let color_type = match chunk[9] {
+        0 => Gray,
+        2 => Rgb,
+        n => return Err(Unsupported(format!("the color type {}", n)))
+    };
+    if width == 0 || height == 0 {
+        Err(Malformed(format!("an empty image of {}x{} pixels", width, height)))
+    } else if width.checked_mul(&height).and_then(|n| n.checked_mul(&4)).is_none() {
+        Err(Malformed(format!("an image of {}x{} pixels is too large", width, height)))
+    } else if chunk[8] != 8 {
+        Err(Unsupported(format!("{} bits per sample", chunk[8])))
+    } else if chunk[10] != 0 || chunk[11] != 0 {
+        Err(Malformed("unknown compression or filter method".to_string()))
+    } else if chunk[12] != 0 {
+        Err(Unsupported("interlacing".to_string()))
+    } else {
+        Ok((width, height, color_type))
+    }
+}
+
+// Undoes the filter of every row, which predicts each byte from the bytes of the same channel on
+// the left, above, and above on the left
+fn unfilter(raw: &[u8], row_len: uint, channels: uint) -> Result<Vec<u8>, PngError> {
+    if raw.len() % (row_len + 1) != 0 {
+        return Err(Malformed(format!("{} bytes of image data for rows of {} bytes", raw.len(),
+                                     row_len + 1)));
+    }
+    let mut samples: Vec<u8> = Vec::with_capacity(raw.len());
+    for (y, row) in raw.chunks(row_len + 1).enumerate() {
+        let start = y * row_len;
+        for i in range(0, row_len) {
+            let left = if i >= channels { samples[start + i - channels] } else { 0 };
+            let up = if y > 0 { samples[start + i - row_len] } else { 0 };
+            let up_left = if i >= channels && y > 0 {
+                samples[start + i - row_len - channels]
+            } else {
+                0
+            };
+            let prediction = match row[0] {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((left as uint + up as uint) / 2) as u8,
+                4 => paeth(left, up, up_left),
+                n => return Err(Malformed(format!("unknown filter {} in row {}", n, y)))
+            };
+            samples.push(row[1 + i] + prediction);
+        }
+    }
+    Ok(samples)
+}
+
+// The neighbor closest to left + up - up_left
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let estimate = left as int + up as int - up_left as int;
+    let distance = |value: u8| (estimate - value as int).abs();
+    if distance(left) <= distance(up) && distance(left) <= distance(up_left) {
+        left
+    } else if distance(up) <= distance(up_left) {
+        up
+    } else {
+        up_left
+    }
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data.iter() {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    b << 16 | a
+}
+
+// The deflate parameters
+const MAX_STORED: uint = 65535;
+const WINDOW_SIZE: uint = 32768;
+const MIN_MATCH: uint = 3;
+const MAX_MATCH: uint = 258;
+const END_OF_BLOCK: uint = 256;
+// The bases and the numbers of extra bits of the length symbols from 257, and of the distances
+const LENGTH_BASES: [uint, ..29] = [3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35,
+                                    43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258];
+const LENGTH_EXTRA: [uint, ..29] = [0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4,
+                                    4, 4, 4, 5, 5, 5, 5, 0];
+const DISTANCE_BASES: [uint, ..30] = [1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193,
+                                      257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145,
+                                      8193, 12289, 16385, 24577];
+const DISTANCE_EXTRA: [uint, ..30] = [0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8,
+                                      9, 9, 10, 10, 11, 11, 12, 12, 13, 13];
+// The positions are chained by a hash of their first 3 bytes
+const HASH_BITS: uint = 15;
+const MAX_CHAIN: uint = 64;
+
+// A zlib stream with a window of 32K and no preset dictionary
+fn zlib_compress(data: &[u8], compression: Compression) -> Vec<u8> {
+    let mut stream = vec![0x78, 0x01];
+    match compression {
+        Stored => deflate_stored(data, &mut stream),
+        FixedHuffman => deflate_fixed(data, &mut stream)
+    }
+    push_u32(&mut stream, adler32(data));
+    stream
+}
+
+// Blocks of at most 65535 bytes, each with its length and the complement of its length
+fn deflate_stored(data: &[u8], output: &mut Vec<u8>) {
+    let mut start = 0;
+    loop {
+        let end = cmp::min(start + MAX_STORED, data.len());
+        let length = (end - start) as u16;
+        // The last block flag and the block type 0, padded to the byte
+        output.push(if end == data.len() { 1 } else { 0 });
+        output.push_all(&[length as u8, (length >> 8) as u8, !length as u8, (!length >> 8) as u8]);
+        output.push_all(data.slice(start, end));
+        if end == data.len() {
+            break;
+        }
+        start = end;
+    }
+}
+
+// A single block, with the longest match among the last positions with the same hash
+fn deflate_fixed(data: &[u8], output: &mut Vec<u8>) {
+    let mut writer = BitWriter { output: output, bits: 0, count: 0 };
+    // The last block flag and the block type 1
+    writer.write(1, 1);
+    writer.write(1, 2);
+    let mut matcher = Matcher::new(data);
+    let mut i = 0;
+    while i < data.len() {
+        match matcher.longest_match(i) {
+            Some((length, distance)) => {
+                let code = range(0, LENGTH_BASES.len()).rev()
+                                                      .find(|&c| LENGTH_BASES[c] <= length)
+                                                      .unwrap();
+                writer.write_fixed_symbol(257 + code);
+                writer.write((length - LENGTH_BASES[code]) as u32, LENGTH_EXTRA[code]);
+                let code = range(0, DISTANCE_BASES.len()).rev()
+                                                        .find(|&c| DISTANCE_BASES[c] <= distance)
+                                                        .unwrap();
+                writer.write_code(code as u32, 5);
+                writer.write((distance - DISTANCE_BASES[code]) as u32, DISTANCE_EXTRA[code]);
+                for j in range(i, i + length) {
+                    matcher.insert(j);
+                }
+                i += length;
+            }
+            None => {
+                writer.write_fixed_symbol(data[i] as uint);
+                matcher.insert(i);
+                i += 1;
+            }
+        }
+    }
+    writer.write_fixed_symbol(END_OF_BLOCK);
+    writer.flush();
+}
+
+// Finds the previous occurrences of the bytes at a position
+struct Matcher<'a> {
+    data: &'a [u8],
+    // The last position of each hash
+    heads: Vec<Option<uint>>,
+    // The previous position with the same hash as each position
+    previous: Vec<Option<uint>>
+}
+
+impl<'a> Matcher<'a> {
+    fn new(data: &'a [u8]) -> Matcher<'a> {
+        Matcher { data: data, heads: Vec::from_elem(1 << HASH_BITS, None),
+                  previous: Vec::from_elem(data.len(), None) }
+    }
+
+    fn hash(&self, i: uint) -> uint {
+        ((self.data[i] as uint << 10) ^ (self.data[i + 1] as uint << 5) ^ self.data[i + 2] as uint)
+            & ((1 << HASH_BITS) - 1)
+    }
+
+    fn insert(&mut self, i: uint) {
+        if i + MIN_MATCH <= self.data.len() {
+            let hash = self.hash(i);
+            self.previous[i] = self.heads[hash];
+            self.heads[hash] = Some(i);
+        }
+    }
+
+    // The length and the distance of the longest match, the closest one between equal matches
+    fn longest_match(&self, i: uint) -> Option<(uint, uint)> {
+        if i + MIN_MATCH > self.data.len() {
+            return None;
+        }
+        let max_length = cmp::min(MAX_MATCH, self.data.len() - i);
+        let (mut best_length, mut best_distance) = (0, 0);
+        let (mut candidate, mut chain) = (self.heads[self.hash(i)], MAX_CHAIN);
+        loop {
+            let j = match candidate {
+                Some(j) if i - j <= WINDOW_SIZE && chain > 0 => j,
+                _ => break
+            };
+            let length = range(0, max_length).take_while(|&k| self.data[i + k] == self.data[j + k])
+                                             .count();
+            if length > best_length {
+                best_length = length;
+                best_distance = i - j;
+            }
+            if length == max_length {
+                break;
+            }
+            candidate = self.previous[j];
+            chain -= 1;
+        }
+        if best_length >= MIN_MATCH { Some((best_length, best_distance)) } else { None }
+    }
+}
+
+struct BitWriter<'a> {
+    output: &'a mut Vec<u8>,
+    bits: u32,
+    count: uint
+}
+
+impl<'a> BitWriter<'a> {
+    // Writes the `count` low bits of `value`, the least significant first
+    fn write(&mut self, value: u32, count: uint) {
+        self.bits |= value << self.count;
+        self.count += count;
+        while self.count >= 8 {
+            self.output.push(self.bits as u8);
+            self.bits >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    // Writes a Huffman code, the most significant bit first
+    fn write_code(&mut self, code: u32, length: uint) {
+        let reversed = range(0, length).fold(0, |reversed, i| reversed << 1 | (code >> i & 1));
+        self.write(reversed, length);
+    }
+
+    // Writes a literal, a length, or the end of the block with the fixed Huffman code
+    fn write_fixed_symbol(&mut self, symbol: uint) {
+        if symbol < 144 {
+            self.write_code(0x30 + symbol as u32, 8);
+        } else if symbol < 256 {
+            self.write_code(0x190 + (symbol - 144) as u32, 9);
+        } else if symbol < 280 {
+            self.write_code((symbol - 256) as u32, 7);
+        } else {
+            self.write_code(0xc0 + (symbol - 280) as u32, 8);
+        }
+    }
+
+    // Pads the last byte
+    fn flush(&mut self) {
+        if self.count > 0 {
+            self.output.push(self.bits as u8);
+            self.bits = 0;
+            self.count = 0;
+        }
+    }
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: uint,
+    bits: u32,
+    count: uint
+}
+
+impl<'a> BitReader<'a> {
+    // Reads `count` bits, the least significant first
+    fn read(&mut self, count: uint) -> Result<u32, PngError> {
+        while self.count < count {
+            if self.position >= self.data.len() {
+                return Err(Truncated);
+            }
+            self.bits |= (self.data[self.position] as u32) << self.count;
+            self.position += 1;
+            self.count += 8;
+        }
+        let value = self.bits & ((1 << count) - 1);
+        self.bits >>= count;
+        self.count -= count;
+        Ok(value)
+    }
+
+    // Skips the rest of the current byte
+    fn align(&mut self) {
+        self.bits = 0;
+        self.count = 0;
+    }
+}
+
+// A canonical Huffman code, given by the length of the code of each symbol
+struct Huffman {
+    // The number of codes of each length
+    counts: Vec<uint>,
+    // The symbols, by code
+    symbols: Vec<uint>
+}
+
+impl Huffman {
+    fn new(lengths: &[uint]) -> Huffman {
+        let max_length = lengths.iter().map(|&l| l).max().unwrap_or(0);
+        let mut counts = Vec::from_elem(max_length + 1, 0u);
+        for &length in lengths.iter().filter(|&&l| l > 0) {
+            counts[length] += 1;
+        }
+        // The first code of each length, in `symbols`
+        let mut offsets = Vec::from_elem(max_length + 1, 0u);
+        for length in range(1, max_length) {
+            offsets[length + 1] = offsets[length] + counts[length];
+        }
+        let mut symbols = Vec::from_elem(lengths.len(), 0u);
+        for (symbol, &length) in lengths.iter().enumerate().filter(|&(_, &l)| l > 0) {
+            symbols[offsets[length]] = symbol;
+            offsets[length] += 1;
+        }
+        Huffman { counts: counts, symbols: symbols }
+    }
+
+    // The codes of the literals and lengths, and of the distances, of the blocks of type 1
+    fn fixed() -> (Huffman, Huffman) {
+        let literals = Vec::from_fn(288, |s| {
+            if s < 144 { 8 } else if s < 256 { 9 } else if s < 280 { 7 } else { 8 }
+        });
+        (Huffman::new(literals.as_slice()), Huffman::new(Vec::from_elem(30, 5).as_slice()))
+    }
+
+    // Reads a code a bit at a time, until it is the code of a symbol
+    fn decode(&self, reader: &mut BitReader) -> Result<uint, PngError> {
+        let (mut code, mut first, mut index) = (0, 0, 0);
+        for length in range(1, self.counts.len()) {
+            code |= try!(reader.read(1)) as uint;
+            let count = self.counts[length];
+            if code < first + count {
+                return Ok(self.symbols[index + code - first]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(Malformed("invalid Huffman code".to_string()))
+    }
+}
+
+fn zlib_decompress(data: &[u8]) -> Result<Vec<u8>, PngError> {
+    if data.len() < 2 {
+        return Err(Truncated);
+    }
+    let (method, flags) = (data[0], data[1]);
+    if method & 0x0f != 8 || method >> 4 > 7 || (method as uint * 256 + flags as uint) % 31 != 0 {
+        return Err(Malformed("invalid zlib header".to_string()));
+    } else if flags & 0x20 != 0 {
+        return Err(Unsupported("preset dictionaries".to_string()));
+    }
+
+    let mut reader = BitReader { data: data, position: 2, bits: 0, count: 0 };
+    let mut output = Vec::new();
+    loop {
+        let last = try!(reader.read(1)) == 1;
+        match try!(reader.read(2)) {
+            0 => {
+                reader.align();
+                let length = try!(reader.read(16));
+                if try!(reader.read(16)) != !length & 0xffff {
+                    return Err(Malformed("invalid length of a stored block".to_string()));
+                }
+                let start = reader.position;
+                if data.len() - start < length as uint {
+                    return Err(Truncated);
+                }
+                output.push_all(data.slice(start, start + length as uint));
+                reader.position += length as uint;
+            }
+            1 => try!(inflate_fixed(&mut reader, &mut output)),
+            2 => return Err(Unsupported("dynamic Huffman codes".to_string())),
+            _ => return Err(Malformed("invalid block type".to_string()))
+        }
+        if last {
+            break;
+        }
+    }
+
+    reader.align();
+    if data.len() - reader.position < 4 {
+        return Err(Truncated);
+    }
+    if read_u32(data.slice_from(reader.position)) != adler32(output.as_slice()) {
+        return Err(BadChecksum("zlib".to_string()));
+    }
+    Ok(output)
+}
+
+// Decompresses a block of type 1 at the end of `output`, whose bytes can be repeated
+fn inflate_fixed(reader: &mut BitReader, output: &mut Vec<u8>) -> Result<(), PngError> {
+    let (literals, distances) = Huffman::fixed();
+    loop {
+        let symbol = try!(literals.decode(reader));
+        if symbol < END_OF_BLOCK {
+            output.push(symbol as u8);
+            continue;
+        } else if symbol == END_OF_BLOCK {
+            return Ok(());
+        }
+
+        let code = symbol - 257;
+        if code >= LENGTH_BASES.len() {
+            return Err(Malformed(format!("invalid length symbol {}", symbol)));
+        }
+        let length = LENGTH_BASES[code] + try!(reader.read(LENGTH_EXTRA[code])) as uint;
+        let code = try!(distances.decode(reader));
+        let distance = DISTANCE_BASES[code] + try!(reader.read(DISTANCE_EXTRA[code])) as uint;
+        if distance > output.len() {
+            return Err(Malformed(format!("distance {} before the start of the data", distance)));
+        }
+        let start = output.len() - distance;
+        for i in range(start, start + length) {
+            let byte = (*output)[i];
+            output.push(byte);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bitmap::{Color, ImageGray8, Pnm};
+    use super::{BadChecksum, FixedHuffman, Gray, Malformed, NotPng, Png, Rgb, Stored, Truncated};
+    use super::{Unsupported, SIGNATURE, write_chunk};
+
+    // Written by zlib, with a row for each filter and two IDAT chunks after a tEXt chunk
+    static FILTERS: &'static [u8] = &[
+        137, 80, 78, 71, 13, 10, 26, 10, 0, 0, 0, 13, 73, 72, 68, 82, 0, 0, 0, 5, 0, 0, 0, 5, 8, 2,
+        0, 0, 0, 2, 13, 177, 178, 0, 0, 0, 15, 116, 69, 88, 116, 67, 111, 109, 109, 101, 110, 116,
+        0, 102, 105, 108, 116, 101, 114, 115, 234, 157, 225, 59, 0, 0, 0, 38, 73, 68, 65, 84, 120,
+        218, 99, 96, 96, 96, 176, 97, 102, 168, 96, 99, 216, 194, 201, 240, 129, 135, 129, 145, 221,
+        8, 200, 23, 129, 35, 38, 32, 159, 221, 72, 132, 221, 72, 131, 221, 200, 134, 145, 138, 68,
+        173, 0, 0, 0, 38, 73, 68, 65, 84, 221, 40, 128, 153, 47, 133, 65, 73, 90, 67, 73, 218, 72,
+        73, 218, 70, 73, 218, 141, 5, 36, 207, 44, 194, 206, 172, 193, 206, 108, 195, 206, 28, 0, 0,
+        136, 96, 9, 86, 36, 177, 185, 128, 0, 0, 0, 0, 73, 69, 78, 68, 174, 66, 96, 130];
+
+    // A PNG file of an image, with its IDAT chunk replaced by `idat`
+    fn with_idat(png: &Png, idat: &[u8]) -> Vec<u8> {
+        let encoded = png.encode(Stored);
+        let mut result = encoded.slice_to(SIGNATURE.len() + 25).to_vec();
+        write_chunk(&mut result, b"IDAT", idat);
+        write_chunk(&mut result, b"IEND", &[]);
+        result
+    }
+
+    #[test]
+    fn encoding() {
+        // The first checked with zlib, the second with the description of stored blocks
+        let colors = Png { width: 2, height: 2, color_type: Rgb,
+                           samples: vec![255, 0, 0, 0, 255, 0, 0, 0, 255, 255, 255, 255] };
+        assert_eq!(colors.encode(FixedHuffman).as_slice(),
+                   [137u8, 80, 78, 71, 13, 10, 26, 10, 0, 0, 0, 13, 73, 72, 68, 82, 0, 0, 0, 2, 0,
+                    0, 0, 2, 8, 2, 0, 0, 0, 253, 212, 154, 115, 0, 0, 0, 17, 73, 68, 65, 84, 120,
+                    1, 99, 248, 207, 192, 0, 198, 12, 255, 129, 0, 0, 31, 238, 5, 251, 89, 236,
+                    21, 221, 0, 0, 0, 0, 73, 69, 78, 68, 174, 66, 96, 130].as_slice());
+
+        let pixel = Png { width: 1, height: 1, color_type: Gray, samples: vec![7] };
+        assert_eq!(pixel.encode(Stored).as_slice(),
+                   [137u8, 80, 78, 71, 13, 10, 26, 10, 0, 0, 0, 13, 73, 72, 68, 82, 0, 0, 0, 1, 0,
+                    0, 0, 1, 8, 0, 0, 0, 0, 58, 126, 155, 85, 0, 0, 0, 13, 73, 68, 65, 84, 120,
+                    1, 1, 2, 0, 253, 255, 0, 7, 0, 9, 0, 8, 185, 172, 134, 135, 0, 0, 0, 0, 73,
+                    69, 78, 68, 174, 66, 96, 130].as_slice());
+    }
+
+    #[test]
+    fn round_trips() {
+        let pentagon = Pnm::parse(include_bytes!("../resources/Pentagon.pgm")).unwrap().to_gray();
+        let filters = Pnm::parse(include_bytes!("../resources/filters_input.ppm")).unwrap();
+        // Several stored blocks
+        let data = Vec::from_fn(300 * 300, |i| (i % 300 * (i / 300) % 251) as u8);
+        let large = ImageGray8 { width: 300, height: 300, data: data };
+        let images = [Png::from_gray(&pentagon), Png::from_image(&filters.to_image()),
+                      Png::from_gray(&large)];
+        for png in images.iter() {
+            for compression in [Stored, FixedHuffman].iter() {
+                let encoded = png.encode(compression.clone());
+                assert_eq!(&Png::decode(encoded.as_slice()).unwrap(), png);
+            }
+        }
+        assert_eq!(images[0].encode(Stored).len(), 77113);
+        assert_eq!(images[0].encode(FixedHuffman).len(), 3575);
+        assert_eq!(images[2].encode(Stored).len(), 90373);
+        assert_eq!(images[2].encode(FixedHuffman).len(), 79934);
+        assert_eq!(images[1].to_image().data, filters.to_image().data);
+        assert_eq!(images[0].to_gray().data, pentagon.data);
+    }
+
+    #[test]
+    fn filters() {
+        let png = Png::decode(FILTERS).unwrap();
+        assert_eq!((png.width, png.height, png.color_type), (5, 5, Rgb));
+        let image = png.to_image();
+        for y in range(0, 5) {
+            for x in range(0, 5) {
+                let color = Color { red: ((x * 60 + y * 7) % 256) as u8,
+                                    green: ((y * 50 + x * 3) % 256) as u8,
+                                    blue: ((x * y * 20) % 256) as u8 };
+                assert_eq!(image[(x, y)], color);
+            }
+        }
+    }
+
+    #[test]
+    fn errors() {
+        let pixel = Png { width: 1, height: 1, color_type: Gray, samples: vec![7] };
+        let encoded = pixel.encode(FixedHuffman);
+        assert_eq!(Png::decode(b"GIF89a"), Err(NotPng));
+        assert_eq!(Png::decode(encoded.slice_to(encoded.len() - 1)), Err(Truncated));
+
+        let mut corrupted = encoded.clone();
+        corrupted[SIGNATURE.len() + 25 + 9] ^= 1;
+        assert_eq!(Png::decode(corrupted.as_slice()), Err(BadChecksum("IDAT".to_string())));
+
+        let mut header = encoded.slice_to(SIGNATURE.len()).to_vec();
+        write_chunk(&mut header, b"IHDR", &[0, 0, 0, 1, 0, 0, 0, 1, 8, 0, 0, 0, 1]);
+        write_chunk(&mut header, b"IEND", &[]);
+        assert_eq!(Png::decode(header.as_slice()), Err(Unsupported("interlacing".to_string())));
+
+        let mut unknown = encoded.slice_to(SIGNATURE.len() + 25).to_vec();
+        write_chunk(&mut unknown, b"PLTE", &[0, 0, 0]);
+        assert_eq!(Png::decode(unknown.as_slice()),
+                   Err(Unsupported("PLTE chunks".to_string())));
+
+        let zlib = [0x78, 0x01, 0x63, 0x60, 0x07, 0x00, 0x00, 0x09, 0x00, 0x08];
+        assert_eq!(Png::decode(with_idat(&pixel, &zlib).as_slice()).unwrap(), pixel);
+        let bad_adler = [0x78, 0x01, 0x63, 0x60, 0x07, 0x00, 0x00, 0x09, 0x00, 0x09];
+        assert_eq!(Png::decode(with_idat(&pixel, &bad_adler).as_slice()),
+                   Err(BadChecksum("zlib".to_string())));
+        let dynamic = [0x78, 0x01, 0x05, 0x00];
+        assert_eq!(Png::decode(with_idat(&pixel, &dynamic).as_slice()),
+                   Err(Unsupported("dynamic Huffman codes".to_string())));
+        // A stored block of the filter 5 and a sample
+        let bad_filter = [0x78, 0x01, 0x01, 0x02, 0x00, 0xfd, 0xff, 0x05, 0x07, 0x00, 0x13, 0x00,
+                          0x0d];
+        assert_eq!(Png::decode(with_idat(&pixel, &bad_filter).as_slice()),
+                   Err(Malformed("unknown filter 5 in row 0".to_string())));
+    }
+
+    #[test]
+    #[should_fail]
+    fn empty() {
+        Png { width: 0, height: 3, color_type: Gray, samples: vec![] }.encode(Stored);
+    }
+}
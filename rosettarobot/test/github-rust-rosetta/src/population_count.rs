@@ -1,36 +1,46 @@
 // http://rosettacode.org/wiki/Population_count
 use std::iter::{count, Filter, Counter, Map};
 use std::num;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
 #[cfg(not(test))]
-fn main() {
-    fn print_30<T: Iterator<uint>>(it: T) {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the population counts of the first 30 powers of 3, and the first 30 evil and odious
+// numbers, to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
+    fn write_30<W: Writer, T: Iterator<uint>>(out: &mut W, it: T) -> IoResult<()> {
         for i in it.take(30) {
-            print!("{} ", i);
+            try!(write!(out, "{} ", i));
         }
+        Ok(())
     }
 
-    println!("Pow. of 3");
-    print_30(pow_3());
+    try!(writeln!(out, "Pow. of 3"));
+    try!(write_30(out, pow_3()));
 
-    println!("\nEvil");
-    print_30(evil());
+    try!(writeln!(out, "\nEvil"));
+    try!(write_30(out, evil()));
 
-    println!("\nOdious");
-    print_30(odious());
+    try!(writeln!(out, "\nOdious"));
+    write_30(out, odious())
 }
 
-fn even_ones(i: &uint) -> bool { i.count_ones() % 2 == 0 }
+pub fn even_ones(i: &uint) -> bool { i.count_ones() % 2 == 0 }
 
-fn odious<'a>() -> Filter<'a, uint, Counter<uint>> {
+pub fn odious<'a>() -> Filter<'a, uint, Counter<uint>> {
     count(0u, 1).filter(|i| !even_ones(i))
 }
 
-fn evil<'a>() -> Filter<'a, uint, Counter<uint>> {
+pub fn evil<'a>() -> Filter<'a, uint, Counter<uint>> {
     count(0u, 1).filter(even_ones)
 }
 
-fn pow_3<'a>() -> Map<'a, uint, uint, Counter<uint>> {
+pub fn pow_3<'a>() -> Map<'a, uint, uint, Counter<uint>> {
     count(0u, 1).map(|i| num::pow(3u32, i).count_ones())
 }
 
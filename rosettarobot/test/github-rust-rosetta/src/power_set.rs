@@ -3,12 +3,15 @@
 
 use std::vec::Vec;
 use std::slice::Items;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
 // If set == {}
 //   return {{}}
 // else if set == {a} U rest
 //   return power_set(rest) U ({a} U each set in power_set(rest))
-fn power_set<'a, T: Clone + 'a>(items: &mut Items<'a,T>) -> Vec<Vec<T>> {
+pub fn power_set<'a, T: Clone + 'a>(items: &mut Items<'a,T>) -> Vec<Vec<T>> {
     let mut power = Vec::new();
     match items.next() {
         None       => power.push(Vec::new()),
@@ -39,13 +42,18 @@ fn test() {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the power set of {1, 2, 3, 4} to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let mut set = Vec::<int>::new();
     set.push(1);
     set.push(2);
     set.push(3);
     set.push(4);
     let power = power_set(&mut set.iter());
-    println!("Set      : {}", set);
-    println!("Power Set: {}", power);
+    try!(writeln!(out, "Set      : {}", set));
+    writeln!(out, "Power Set: {}", power)
 }
@@ -1,8 +1,11 @@
 //Implements http://rosettacode.org/wiki/Primality_by_Trial_Division
 
 use std::iter::range_step;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
-fn is_prime(number: int) -> bool {
+pub fn is_prime(number: int) -> bool {
     if number % 2 == 0 && number != 2 {
         return false;
     }
@@ -14,9 +17,15 @@ fn is_prime(number: int) -> bool {
 }
 
 #[cfg(not(test))]
-fn main() {
-    println!("{:b}", is_prime(15485863)); // The 1 000 000th prime.
-    println!("{:b}", is_prime(62773913)); // The product of the 1000th and 1001st primes.
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes whether two numbers are prime to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
+    try!(writeln!(out, "{:b}", is_prime(15485863))); // The 1 000 000th prime.
+    try!(writeln!(out, "{:b}", is_prime(62773913))); // The product of the 1000th and 1001st primes.
+    Ok(())
 }
 
 #[test]
@@ -1,5 +1,9 @@
 // Implements http://rosettacode.org/wiki/Prime_decomposition
 
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
+
 // We need this to be public because it is used from another file
 pub fn factor(mut nb: uint) -> Vec<uint> {
     let mut result = vec!();
@@ -31,14 +35,20 @@ pub fn factor(mut nb: uint) -> Vec<uint> {
 }
 
 // Needed so parallel_calculations compiles cleanly, because it
-// uses this code as a library
+// uses this code as a library, here and on `run`
 #[allow(dead_code)]
 #[cfg(not(test))]
-fn main() {
-    println!("Factors of 5: {}", factor(5));
-    println!("Factors of 15: {}", factor(15));
-    println!("Factors of 16: {}", factor(16));
-    println!("Factors of 10287: {}", factor(10287));
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the prime factors of a few numbers to `out`
+#[allow(dead_code)]
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
+    try!(writeln!(out, "Factors of 5: {}", factor(5)));
+    try!(writeln!(out, "Factors of 15: {}", factor(15)));
+    try!(writeln!(out, "Factors of 16: {}", factor(16)));
+    writeln!(out, "Factors of 10287: {}", factor(10287))
 }
 
 #[test]
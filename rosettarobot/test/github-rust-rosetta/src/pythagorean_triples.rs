@@ -1,12 +1,15 @@
 // http://rosettacode.org/wiki/Pythagorean_triples
 use std::collections::DList;
 use std::num::pow;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
 /// Count the number of Pythagorean triples whose sum are below the specified limit (inclusive).
 /// Does a BFS over the tree of primitive Pythagorean triples (see [0]), and uses the fact that
 /// each child has a bigger sum than its parent.
 /// [0]: http://en.wikipedia.org/wiki/Tree_of_Pythagorean_triples
-fn count_pythagorean_triples(below: u64) -> (u64, u64) {
+pub fn count_pythagorean_triples(below: u64) -> (u64, u64) {
     let mut tot_cnt = 0;
     let mut prim_cnt = 0;
     let mut queue = DList::new();
@@ -44,12 +47,18 @@ fn count_pythagorean_triples(below: u64) -> (u64, u64) {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the number of triples with a perimeter up to each power of 10 to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     for n in range(1, 9) {
         let (tot, prim) = count_pythagorean_triples(pow(10, n));
-        println!("Up to 10^{}: {:>10u} triples {:>10u} primitives",
-                 n, tot, prim);
+        try!(writeln!(out, "Up to 10^{}: {:>10u} triples {:>10u} primitives",
+                           n, tot, prim));
     }
+    Ok(())
 }
 
 #[test]
@@ -1,12 +1,15 @@
 //Implements http://rosettacode.org/wiki/Sorting_algorithms/Quicksort
 
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 // Used by the tests
 #[cfg(test)]
 use std::rand::{task_rng, Rng};
 
 // We use in place quick sort
 // For details see http://en.wikipedia.org/wiki/Quicksort#In-place_version
-fn quick_sort<T: Ord>(v: &mut[T]) {
+pub fn quick_sort<T: Ord>(v: &mut[T]) {
     let len = v.len();
     if len < 2 {
         return;
@@ -24,7 +27,7 @@ fn quick_sort<T: Ord>(v: &mut[T]) {
 // Reorders the slice with values lower than the pivot at the left side,
 // and values bigger than it at the right side.
 // Also returns the store index.
-fn partition<T: Ord>(v: &mut [T]) -> uint {
+pub fn partition<T: Ord>(v: &mut [T]) -> uint {
     let len = v.len();
     let pivot_index = len / 2;
 
@@ -43,20 +46,25 @@ fn partition<T: Ord>(v: &mut [T]) -> uint {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes some numbers and strings before and after sorting them to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     // Sort numbers
     let mut numbers = [4i, 65, 2, -31, 0, 99, 2, 83, 782, 1];
-    println!("Before: {}", numbers.as_slice());
+    try!(writeln!(out, "Before: {}", numbers.as_slice()));
 
     quick_sort(numbers);
-    println!("After: {}", numbers.as_slice());
+    try!(writeln!(out, "After: {}", numbers.as_slice()));
 
     // Sort strings
     let mut strings = ["beach", "hotel", "airplane", "car", "house", "art"];
-    println!("Before: {}", strings.as_slice());
+    try!(writeln!(out, "Before: {}", strings.as_slice()));
 
     quick_sort(strings);
-    println!("After: {}", strings.as_slice());
+    writeln!(out, "After: {}", strings.as_slice())
 }
 
 #[cfg(test)]
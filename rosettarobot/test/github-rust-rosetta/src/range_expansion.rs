@@ -6,15 +6,23 @@ extern crate regex;
 #[phase(plugin)] extern crate regex_macros;
 
 use std::iter::range_inclusive;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the expansion of the example range to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let range = "-6,-3-1,3-5,7-11,14,15,17-20";
-    println!("Expanded range: {}", expand_range(range));
+    writeln!(out, "Expanded range: {}", expand_range(range))
 }
 
 // Expand a string containing numbers and ranges, into a vector of numbers
-fn expand_range(range: &str) -> Vec<int> {
+pub fn expand_range(range: &str) -> Vec<int> {
     let mut result = vec![];
 
     for item in range.split(',') {
@@ -25,7 +33,7 @@ fn expand_range(range: &str) -> Vec<int> {
 }
 
 // Expand a single element, which can be a number or a range.
-fn expand_item(item: &str) -> Vec<int> {
+pub fn expand_item(item: &str) -> Vec<int> {
     // Handle the case of a single number
     for cap in regex!(r"^(-?\d+)$").captures_iter(item) {
         return vec![from_str(cap.at(0)).unwrap()]
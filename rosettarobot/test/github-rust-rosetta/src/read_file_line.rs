@@ -4,7 +4,7 @@ use std::io::fs::File;
 use std::io::BufferedReader;
 use std::os::args;
 
-fn main() {
+pub fn main() {
     let filename = match args().len() {
         1 => fail!("You must enter a filename to read line by line"),
         _ => args()[1].clone()
@@ -4,7 +4,7 @@ use std::io::fs::File;
 use std::io::BufferedReader;
 use std::os::args;
 
-fn main() {
+pub fn main() {
     match args().len() {
         2 => fail!("You must enter a filename to read line by line, and a line number"),
         1 => fail!("You must enter a line number"),
@@ -1,10 +1,10 @@
 // Implements http://rosettacode.org/wiki/Find_limit_of_recursion
 
-fn recursion(n: int) {
+pub fn recursion(n: int) {
     println!("deep: {:d}", n);
     recursion(n + 1);
 }
 
-fn main() {
+pub fn main() {
     recursion(0);
 }
@@ -0,0 +1,176 @@
+// Metadata about every task, gathered from its source code.
+//
+// The upload robot needs the Rosetta Code URL and the documentation of each task.  Instead of
+// scraping every source file itself, it can read the whole registry as JSON (`rosetta json`).
+
+use serialize::json;
+
+// The prefix shared by the URLs of all Rosetta Code tasks.
+static ROSETTA_URL_PREFIX: &'static str = "http://rosettacode.org/wiki/";
+
+#[deriving(Clone, Encodable, PartialEq, Show)]
+pub struct Task {
+    // The name of the task in `Cargo.toml`, as expected by `rosetta run`
+    pub name: String,
+    // The Rosetta Code page of the task, taken from the comment at the top of the source file
+    pub url: Option<String>,
+    // The path of the source file, relative to the root of the repository
+    pub path: String,
+    // The comment at the top of the source file, without the line holding the Rosetta Code URL
+    pub doc: String,
+    // The names of the `#[test]` functions of the task
+    pub tests: Vec<String>
+}
+
+impl Task {
+    pub fn from_source(name: &str, path: &str, source: &str) -> Task {
+        Task {
+            name: name.to_string(),
+            url: rosetta_url(source).map(|(_, url)| url),
+            path: path.to_string(),
+            doc: module_doc(source),
+            tests: test_names(source).into_iter().map(|(_, name)| name).collect()
+        }
+    }
+}
+
+// Returns every task of the library, in the order they appear in `Cargo.toml`
+pub fn tasks() -> Vec<Task> {
+    ::TASK_SOURCES.iter().map(|&(name, path, source)| Task::from_source(name, path, source))
+                         .collect()
+}
+
+// Returns the task called `name`, if there is one
+pub fn find(name: &str) -> Option<Task> {
+    ::TASK_SOURCES.iter().find(|&&(n, _, _)| n == name)
+                         .map(|&(name, path, source)| Task::from_source(name, path, source))
+}
+
+// Returns the whole registry as a JSON array of tasks
+pub fn to_json() -> String {
+    json::encode(&tasks())
+}
+
+// Returns the (1-based) line number and the Rosetta Code URL of the first comment line of
+// `source` which contains one, the same way the upload robot does.
+pub fn rosetta_url(source: &str) -> Option<(uint, String)> {
+    for (i, line) in source.lines().enumerate() {
+        let comment = match line.find_str("//") {
+            Some(start) => line.slice_from(start),
+            None => continue
+        };
+        if let Some(start) = comment.find_str(ROSETTA_URL_PREFIX) {
+            let url = comment.slice_from(start).words().next().unwrap();
+            return Some((i + 1, url.to_string()));
+        }
+    }
+    None
+}
+
+// Returns the comment block at the top of `source`, without the comment markers and without the
+// line holding the Rosetta Code URL
+pub fn module_doc(source: &str) -> String {
+    let lines: Vec<&str> = source.lines()
+        .map(|l| l.trim_right())
+        .take_while(|l| l.starts_with("//"))
+        .filter(|l| !l.contains(ROSETTA_URL_PREFIX))
+        .map(|l| {
+            let text = l.trim_left_chars('/').trim_left_chars('!');
+            if text.starts_with(" ") { text.slice_from(1) } else { text }
+        })
+        .collect();
+    lines.connect("\n").as_slice().trim().to_string()
+}
+
+// Returns the (1-based) line number and the name of every `#[test]` function in `source`
+pub fn test_names(source: &str) -> Vec<(uint, String)> {
+    let lines: Vec<&str> = source.lines().map(|l| l.trim()).collect();
+    let mut tests = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        if !line.starts_with("#[test]") {
+            continue;
+        }
+        // The function follows the attribute, possibly after more attributes
+        let fn_line = lines.slice_from(i + 1).iter().enumerate()
+                           .find(|&(_, l)| !l.starts_with("#[") && !l.is_empty());
+        if let Some((offset, l)) = fn_line {
+            let decl = if l.starts_with("pub ") { l.slice_from(4) } else { *l };
+            if decl.starts_with("fn ") {
+                let name = decl.slice_from(3).split(|c: char| c == '(' || c == '<')
+                               .next().unwrap().trim();
+                tests.push((i + offset + 2, name.to_string()));
+            }
+        }
+    }
+    tests
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Task, find, module_doc, rosetta_url, test_names, tasks, to_json};
+
+    static SOURCE: &'static str = "// Implement data structures for a Huffman encoding tree:
+//   http://rosettacode.org/wiki/Huffman_coding
+//
+// Second paragraph.
+
+// Not part of the documentation
+fn main() {}
+
+#[test]
+fn first() {}
+
+#[test]
+#[should_fail]
+fn second() {}
+
+#[bench]
+fn not_a_test() {}
+";
+
+    #[test]
+    fn url() {
+        assert_eq!(rosetta_url(SOURCE),
+                   Some((2, "http://rosettacode.org/wiki/Huffman_coding".to_string())));
+        assert_eq!(rosetta_url("fn main() {}"), None);
+    }
+
+    #[test]
+    fn doc() {
+        assert_eq!(module_doc(SOURCE).as_slice(),
+                   "Implement data structures for a Huffman encoding tree:\n\nSecond paragraph.");
+        assert_eq!(module_doc("//! Crate docs\nfn main() {}").as_slice(), "Crate docs");
+    }
+
+    #[test]
+    fn tests() {
+        assert_eq!(test_names(SOURCE),
+                   vec![(10, "first".to_string()), (14, "second".to_string())]);
+    }
+
+    #[test]
+    fn from_source() {
+        let task = Task::from_source("huffman_coding", "src/huffman_coding.rs", SOURCE);
+        assert_eq!(task.name.as_slice(), "huffman_coding");
+        assert_eq!(task.url, Some("http://rosettacode.org/wiki/Huffman_coding".to_string()));
+        assert_eq!(task.path.as_slice(), "src/huffman_coding.rs");
+        assert_eq!(task.tests, vec!["first".to_string(), "second".to_string()]);
+    }
+
+    #[test]
+    fn registry() {
+        let hailstone = find("hailstone").unwrap();
+        assert_eq!(hailstone.url, Some("http://rosettacode.org/wiki/Hailstone_sequence".to_string()));
+        assert_eq!(hailstone.path.as_slice(), "src/hailstone.rs");
+        assert_eq!(hailstone.tests, vec!["test_27".to_string(), "test_biggest".to_string()]);
+        assert!(find("no_such_task").is_none());
+        assert_eq!(tasks().len(), ::TASKS.len());
+    }
+
+    #[test]
+    fn json() {
+        let json = to_json();
+        assert!(json.as_slice().starts_with("[{\"name\":\"100_doors\""));
+        assert!(json.as_slice().contains("\"url\":\"http://rosettacode.org/wiki/Hailstone_sequence\""));
+    }
+}
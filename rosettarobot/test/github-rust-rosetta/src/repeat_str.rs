@@ -1,8 +1,17 @@
 // http://rosettacode.org/wiki/Repeat_a_string
 
 #[cfg(not(test))]
-fn main() {
-    println!("{}", "ha".repeat(5).as_slice());
+use std::io;
+use std::io::IoResult;
+
+#[cfg(not(test))]
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes a repeated string to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
+    writeln!(out, "{}", "ha".repeat(5).as_slice())
 }
 
 #[test]
@@ -1,15 +1,24 @@
 // Implements http://rosettacode.org/wiki/Reverse_words_in_a_string
 
-fn rev_words(line: &str) -> String {
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
+
+pub fn rev_words(line: &str) -> String {
     line.words().rev().collect::<Vec<&str>>().connect(" ")
 }
 
-fn rev_words_on_lines(text: &str) -> String {
+pub fn rev_words_on_lines(text: &str) -> String {
      text.lines().map(rev_words).collect::<Vec<String>>().connect("\n")
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the example text with its words reversed to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let text = "---------- Ice and Fire ------------
 
 fire, in end will world the say Some
@@ -21,7 +30,7 @@ fire. favor who those with hold I
 
 Frost Robert -----------------------";
 
-    println!("{}", rev_words_on_lines(text));
+    writeln!(out, "{}", rev_words_on_lines(text))
 }
 
 #[test]
@@ -1,8 +1,12 @@
 // http://rosettacode.org/wiki/Roots_of_a_function
 
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
+
 // Note: We cannot use `range_step` here because Floats don't implement
 // the `CheckedAdd` trait.
-fn find_roots<T: Copy + PartialOrd + Signed>(f: |T| -> T, start: T, stop: T,
+pub fn find_roots<T: Copy + PartialOrd + Signed>(f: |T| -> T, start: T, stop: T,
                                              step: T, epsilon: T) -> Vec<T> {
     let mut ret = vec![];
     let mut current = start;
@@ -27,9 +31,14 @@ fn test_find_roots() {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the roots of a cubic polynomial to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let roots = find_roots(|x: f64| x*x*x - 3.0*x*x + 2.0*x,
                            -1.0, 3.0, 0.0001, 0.00000001);
 
-    println!("roots of f(x) = x^3 - 3x^2 + 2x are: {}", roots);
+    writeln!(out, "roots of f(x) = x^3 - 3x^2 + 2x are: {}", roots)
 }
@@ -2,17 +2,26 @@
 extern crate num;
 use num::complex::{Complex, Complex32};
 use std::f32::consts;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the cube roots of unity to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let degree = 3u;
 
     for root in roots_of_unity(degree).iter() {
-        println!("{}", root);
+        try!(writeln!(out, "{}", root));
     }
+    Ok(())
 }
 
-fn roots_of_unity(degree: uint) -> Vec<Complex32> {
+pub fn roots_of_unity(degree: uint) -> Vec<Complex32> {
     range(0, degree).map(|el|
         Complex::<f32>::from_polar(&1f32, &(2f32 * consts::PI * (el as f32) / (degree as f32))))
         .collect::<Vec<Complex32>>()
@@ -1,6 +1,10 @@
 // Implements http://rosettacode.org/wiki/Rot-13
 
-fn rot13 (string: &str) -> String {
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
+
+pub fn rot13 (string: &str) -> String {
     fn rot13u8 (c: char) -> char {
         let d = c as u8;
         match c {
@@ -16,11 +20,16 @@ fn rot13 (string: &str) -> String {
 }
 
 #[cfg(not(test))]
-fn main () {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes a string and its encoding to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let string = "Do you love apples?";
 
-    println!("Original: {}", string);
-    println!("Encoded: {}", rot13(string));
+    try!(writeln!(out, "Original: {}", string));
+    writeln!(out, "Encoded: {}", rot13(string))
 }
 
 #[test]
@@ -1,19 +1,44 @@
 // http://rosettacode.org/wiki/Run-length_encoding
-const INPUT: &'static str = "WWWWWWWWWWWWBWWWWWWWWWWWWBBBWWWWWWWWWWWWWWWWWWWWWWWWBWWWWWWWWWWWWWW";
+//
+// `encode` and `decode` are the task.  `encode_bytes` and `decode_bytes` work on arbitrary bytes,
+// in one of the formats of `Format`, and `encode_stream` and `decode_stream` do the same between
+// a `Reader` and a `Writer`.
+
+use std::cmp;
+use std::fmt;
+#[cfg(not(test))]
+use std::io;
+use std::io::{EndOfFile, InvalidInput, IoError, IoResult};
+
+pub const INPUT: &'static str = "WWWWWWWWWWWWBWWWWWWWWWWWWBBBWWWWWWWWWWWWWWWWWWWWWWWWBWWWWWWWWWWWWWW";
 
 // Needed so look-and-say_sequence compiles cleanly, because it
-// uses this code as a library
+// uses this code as a library, here and on `run`
 #[allow(dead_code)]
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the example string encoded and decoded back to `out`
+#[allow(dead_code)]
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let enc = encode(INPUT);
-    println!("encoded {}", enc);
+    try!(writeln!(out, "encoded {}", enc));
 
     let dec = decode(enc.as_slice());
-    println!("decoded {}", dec.unwrap());
+    writeln!(out, "decoded {}", dec.unwrap())
 }
 
+// Digits and backslashes are escaped with a backslash, so that `decode` doesn't take them for a
+// count: "111AA" is encoded as `3\12A`.
 pub fn encode(value: &str) -> String {
+    fn push_run(ret: &mut String, count: uint, chr: char) {
+        ret.push_str(count.to_string().as_slice());
+        if chr.is_digit() || chr == '\\' { ret.push('\\') }
+        ret.push(chr);
+    }
+
     let mut ret = String::new();
     let mut chars = value.chars();
 
@@ -23,40 +48,494 @@ pub fn encode(value: &str) -> String {
     for chr in chars {
         if cur == Some(chr) { count += 1 }
         else {
-                ret.push_str(count.to_string().as_slice());
-                ret.push(cur.unwrap());
+                push_run(&mut ret, count, cur.unwrap());
                 count=1u;
                 cur=Some(chr);
         }
     }
-    ret.push_str(count.to_string().as_slice());
-    ret.push(cur.unwrap());
+    push_run(&mut ret, count, cur.unwrap());
     ret
 }
 
-pub fn decode(value: &str) -> Result<String, String> {
+// Decodes the output of `encode`.  A character after a backslash is never part of a count, so
+// strings with digits can be encoded as well: `3\12A` decodes to "111AA".
+pub fn decode(value: &str) -> Result<String, DecodeError> {
     let mut result = String::new();
-    if value.is_empty() { return Ok(result) }
-
+    let mut count = None;
+    let mut escaped = false;
     let mut start = 0;
 
     for (i, c) in value.char_indices() {
-        if c.is_digit() { continue }
-        if i==start { return Err(format!("expected digit, found {}", c)) }
+        if !escaped {
+            if c.is_digit() {
+                let digit = c.to_digit(10).unwrap();
+                if count.is_none() {
+                    start = i;
+                }
+                let n = count.unwrap_or(0u);
+                count = n.checked_mul(&10).and_then(|n| n.checked_add(&digit));
+                if count.is_none() {
+                    return Err(DecodeError { position: start, kind: InvalidCount });
+                }
+                continue;
+            }
+            if count.is_none() {
+                return Err(DecodeError { position: i, kind: ExpectedCount });
+            }
+            if c == '\\' {
+                escaped = true;
+                continue;
+            }
+        }
+        match count {
+            Some(0) => return Err(DecodeError { position: start, kind: InvalidCount }),
+            Some(n) => result.push_str(String::from_char(n, c).as_slice()),
+            None => unreachable!()
+        }
+        count = None;
+        escaped = false;
+    }
+    if count.is_some() {
+        return Err(DecodeError { position: value.len(), kind: UnexpectedEnd });
+    }
+    Ok(result)
+}
 
-        let ret_s = value.slice(start, i);
-        let ret : uint = from_str(ret_s).unwrap();
+// The formats of `encode_bytes`
+#[deriving(Clone, PartialEq, Show)]
+pub enum Format {
+    // The format of `encode`: every run is its length in decimal, then the byte, with a backslash
+    // before the bytes which are digits or backslashes
+    Text,
+    // Apple PackBits: a header byte n, then n + 1 bytes copied as they are if n is between 0 and
+    // 127, or one byte repeated 1 - n times if n is between -127 and -1 (as an i8).  -128 is
+    // skipped.
+    PackBits,
+    // Bytes are copied as they are, except for runs of 4 bytes or more, and for the escape byte:
+    // they are written as the escape byte, the length of the run (1 to 255) and the repeated byte.
+    Escape(u8)
+}
 
-        let repeated = String::from_char(ret, c);
-        start = i + 1;
+impl Format {
+    // The shortest run encoded as a run rather than as literal bytes
+    fn min_run(&self, byte: u8) -> uint {
+        match *self {
+            Text => 1,
+            PackBits => 3,
+            Escape(escape) if byte == escape => 1,
+            Escape(_) => 4
+        }
+    }
 
-        result.push_str(repeated.as_slice());
+    // The longest run which fits in one run.  Decoders don't have to hold a whole run in memory,
+    // see `Decoder::write_run`
+    fn max_run(&self) -> uint {
+        match *self {
+            Text => ::std::uint::MAX,
+            PackBits => 128,
+            Escape(_) => 255
+        }
+    }
+}
+
+#[deriving(Clone, PartialEq, Show)]
+pub enum DecodeErrorKind {
+    ExpectedCount,
+    InvalidCount,
+    UnexpectedEnd
+}
+
+// An error of decoding, at the byte `position` of the encoded data
+#[deriving(Clone, PartialEq)]
+pub struct DecodeError {
+    pub position: uint,
+    pub kind: DecodeErrorKind
+}
+
+impl fmt::Show for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let description = match self.kind {
+            ExpectedCount => "expected a count",
+            InvalidCount => "invalid count",
+            UnexpectedEnd => "unexpected end of data"
+        };
+        write!(f, "{} at byte {}", description, self.position)
+    }
+}
+
+pub fn encode_bytes(data: &[u8], format: Format) -> Vec<u8> {
+    let mut encoder = Encoder::new(format);
+    let mut output = Vec::new();
+    for &byte in data.iter() {
+        encoder.push(byte, &mut output);
+    }
+    encoder.finish(&mut output);
+    output
+}
+
+pub fn decode_bytes(data: &[u8], format: Format) -> Result<Vec<u8>, DecodeError> {
+    let mut decoder = Decoder::new(format);
+    let mut output = Vec::new();
+    for &byte in data.iter() {
+        try!(decoder.push(byte, &mut output));
+        decoder.write_run(&mut output, ::std::uint::MAX);
+    }
+    try!(decoder.finish());
+    Ok(output)
+}
+
+// The size of the blocks read by the streaming functions
+const BLOCK_SIZE: uint = 64 * 1024;
+
+// Encodes everything `input` has to read
+pub fn encode_stream<R: Reader, W: Writer>(input: &mut R, output: &mut W, format: Format)
+                                           -> IoResult<()> {
+    let mut encoder = Encoder::new(format);
+    let mut block = Vec::from_elem(BLOCK_SIZE, 0u8);
+    let mut encoded = Vec::new();
+    loop {
+        let n = match input.read(block.as_mut_slice()) {
+            Ok(n) => n,
+            Err(IoError { kind: EndOfFile, .. }) => break,
+            Err(e) => return Err(e)
+        };
+        for &byte in block.slice_to(n).iter() {
+            encoder.push(byte, &mut encoded);
+        }
+        try!(output.write(encoded.as_slice()));
+        encoded.clear();
+    }
+    encoder.finish(&mut encoded);
+    output.write(encoded.as_slice())
+}
+
+// Decodes everything `input` has to read.  Invalid data gives an `InvalidInput` error, once the
+// output before it is written.
+pub fn decode_stream<R: Reader, W: Writer>(input: &mut R, output: &mut W, format: Format)
+                                           -> IoResult<()> {
+    fn invalid(error: DecodeError) -> IoError {
+        IoError {
+            kind: InvalidInput,
+            desc: "invalid run-length encoded data",
+            detail: Some(error.to_string())
+        }
+    }
+
+    let mut decoder = Decoder::new(format);
+    let mut block = Vec::from_elem(BLOCK_SIZE, 0u8);
+    let mut decoded = Vec::new();
+    loop {
+        let n = match input.read(block.as_mut_slice()) {
+            Ok(n) => n,
+            Err(IoError { kind: EndOfFile, .. }) => break,
+            Err(e) => return Err(e)
+        };
+        for &byte in block.slice_to(n).iter() {
+            match decoder.push(byte, &mut decoded) {
+                Ok(()) => (),
+                Err(e) => {
+                    try!(output.write(decoded.as_slice()));
+                    return Err(invalid(e));
+                }
+            }
+            // Runs are written a block at a time, however long they are
+            loop {
+                decoder.write_run(&mut decoded, BLOCK_SIZE);
+                if decoded.len() < BLOCK_SIZE {
+                    break;
+                }
+                try!(output.write(decoded.as_slice()));
+                decoded.clear();
+            }
+        }
+    }
+    try!(output.write(decoded.as_slice()));
+    decoder.finish().map_err(invalid)
+}
+
+// Encodes bytes given one at a time.  The current run is only written once it ends, and literal
+// bytes when a run ends or their packet is full.
+struct Encoder {
+    format: Format,
+    run: Option<(u8, uint)>, // The repeated byte and the length of the run
+    literals: Vec<u8>
+}
+
+impl Encoder {
+    fn new(format: Format) -> Encoder {
+        Encoder { format: format, run: None, literals: Vec::new() }
+    }
+
+    fn push(&mut self, byte: u8, output: &mut Vec<u8>) {
+        let run = self.run;
+        match run {
+            Some((repeated, length)) if repeated == byte && length < self.format.max_run() => {
+                self.run = Some((repeated, length + 1));
+            }
+            _ => {
+                self.end_run(output);
+                self.run = Some((byte, 1));
+            }
+        }
+    }
+
+    fn finish(&mut self, output: &mut Vec<u8>) {
+        self.end_run(output);
+        self.write_literals(output);
+    }
+
+    fn end_run(&mut self, output: &mut Vec<u8>) {
+        let (byte, length) = match self.run.take() {
+            Some(run) => run,
+            None => return
+        };
+        if length < self.format.min_run(byte) {
+            for _ in range(0, length) {
+                self.literals.push(byte);
+                if self.format == PackBits && self.literals.len() == 128 {
+                    self.write_literals(output);
+                }
+            }
+            return;
+        }
+
+        self.write_literals(output);
+        match self.format {
+            Text => {
+                output.push_all(length.to_string().as_bytes());
+                if (byte as char).is_digit() || byte == b'\\' {
+                    output.push(b'\\');
+                }
+                output.push(byte);
+            }
+            PackBits => output.push_all(&[(1 - length as int) as u8, byte]),
+            Escape(escape) => output.push_all(&[escape, length as u8, byte])
+        }
+    }
+
+    fn write_literals(&mut self, output: &mut Vec<u8>) {
+        if self.literals.is_empty() {
+            return;
+        }
+        if self.format == PackBits {
+            output.push((self.literals.len() - 1) as u8);
+        }
+        output.push_all(self.literals.as_slice());
+        self.literals.clear();
+    }
+}
+
+// The part of an encoded run a decoder expects next
+#[deriving(Clone, Show)]
+enum DecoderState {
+    Start,
+    // Text
+    Count(uint, uint), // The count so far and the position of its first digit
+    EscapedByte(uint, uint),
+    // PackBits
+    Literals(uint), // The number of literal bytes left
+    RepeatedByte(uint, uint), // The length of the run and the position of its header
+    // Escape
+    EscapeCount(uint), // The position of the escape byte
+    EscapedRun(uint, uint)
+}
+
+// Decodes bytes given one at a time.  Literal bytes are written as they come, but a run is only
+// written by `write_run`, as much of it as the output can take.
+struct Decoder {
+    format: Format,
+    state: DecoderState,
+    position: uint,
+    run: Option<(u8, uint)> // The repeated byte and how many times it is still to be written
+}
+
+impl Decoder {
+    fn new(format: Format) -> Decoder {
+        Decoder { format: format, state: Start, position: 0, run: None }
+    }
+
+    // Writes the run decoded last, or its beginning so that `output` holds at most `limit` bytes
+    fn write_run(&mut self, output: &mut Vec<u8>, limit: uint) {
+        let (byte, length) = match self.run {
+            Some(run) => run,
+            None => return
+        };
+        let n = cmp::min(length, limit - cmp::min(limit, output.len()));
+        output.grow(n, byte);
+        self.run = if n < length { Some((byte, length - n)) } else { None };
+    }
+
+    fn push(&mut self, byte: u8, output: &mut Vec<u8>) -> Result<(), DecodeError> {
+        let position = self.position;
+        self.position += 1;
+        fn error(kind: DecodeErrorKind, position: uint) -> Result<(), DecodeError> {
+            Err(DecodeError { position: position, kind: kind })
+        }
+
+        self.state = match (self.format.clone(), self.state.clone()) {
+            // Text
+            (Text, Start) | (Text, Count(..)) if (byte as char).is_digit() => {
+                let (count, start) = match self.state {
+                    Count(count, start) => (count, start),
+                    _ => (0, position)
+                };
+                let digit = (byte - b'0') as uint;
+                match count.checked_mul(&10).and_then(|n| n.checked_add(&digit)) {
+                    Some(count) => Count(count, start),
+                    None => return error(InvalidCount, start)
+                }
+            }
+            (Text, Start) => return error(ExpectedCount, position),
+            (Text, Count(0, start)) => return error(InvalidCount, start),
+            (Text, Count(count, start)) if byte == b'\\' => EscapedByte(count, start),
+            (Text, Count(count, _)) | (Text, EscapedByte(count, _)) => {
+                self.run = Some((byte, count));
+                Start
+            }
+
+            // PackBits
+            (PackBits, Start) => match byte as i8 {
+                -128 => Start,
+                n if n < 0 => RepeatedByte((1 - n as int) as uint, position),
+                n => Literals(n as uint + 1)
+            },
+            (PackBits, Literals(left)) => {
+                output.push(byte);
+                if left > 1 { Literals(left - 1) } else { Start }
+            }
+            (PackBits, RepeatedByte(length, _)) | (Escape(_), EscapedRun(length, _)) => {
+                self.run = Some((byte, length));
+                Start
+            }
+
+            // Escape
+            (Escape(escape), Start) if byte == escape => EscapeCount(position),
+            (Escape(_), Start) => {
+                output.push(byte);
+                Start
+            }
+            (Escape(_), EscapeCount(start)) if byte == 0 => return error(InvalidCount, start),
+            (Escape(_), EscapeCount(start)) => EscapedRun(byte as uint, start),
+
+            (format, state) => fail!("invalid state {} for {}", state, format)
+        };
+        Ok(())
+    }
+
+    // Checks that the data didn't stop in the middle of a run
+    fn finish(&self) -> Result<(), DecodeError> {
+        match self.state {
+            Start => Ok(()),
+            _ => Err(DecodeError { position: self.position, kind: UnexpectedEnd })
+        }
     }
-    Ok(result)
 }
 
 #[test]
 fn test_encode_decode() {
     assert_eq!(decode(encode(INPUT).as_slice()).unwrap(), INPUT.to_string());
-    assert_eq!(decode("a"), Err("expected digit, found a".to_string()));
+    assert_eq!(decode("a"), Err(DecodeError { position: 0, kind: ExpectedCount }));
+
+    // Digits and backslashes are escaped
+    assert_eq!(encode("111AA"), "3\\12A".to_string());
+    for s in ["111", "a\\\\b", "12\\3\\\\40", "\\"].iter() {
+        assert_eq!(decode(encode(*s).as_slice()), Ok(s.to_string()));
+    }
+}
+
+#[test]
+fn test_decode_errors() {
+    assert_eq!(decode("3\\12A"), Ok("111AA".to_string()));
+    assert_eq!(decode("12W3"), Err(DecodeError { position: 4, kind: UnexpectedEnd }));
+    assert_eq!(decode("12W3\\"), Err(DecodeError { position: 5, kind: UnexpectedEnd }));
+    assert_eq!(decode("2W0B"), Err(DecodeError { position: 2, kind: InvalidCount }));
+    assert_eq!(decode("99999999999999999999999W"),
+               Err(DecodeError { position: 0, kind: InvalidCount }));
+    assert_eq!(decode("3W\\B").unwrap_err().to_string(), "expected a count at byte 2".to_string());
+
+    assert_eq!(decode_bytes(b"12W3", Text), Err(DecodeError { position: 4, kind: UnexpectedEnd }));
+    assert_eq!(decode_bytes(&[0x02, 0x80, 0x00], PackBits),
+               Err(DecodeError { position: 3, kind: UnexpectedEnd }));
+    assert_eq!(decode_bytes(&[0x2a, 0xff, 0x00, 0x2a], Escape(0xff)),
+               Err(DecodeError { position: 1, kind: InvalidCount }));
+}
+
+#[test]
+fn test_formats() {
+    assert_eq!(encode_bytes(b"WWW12\\", Text), b"3W1\\11\\21\\\\".to_vec());
+
+    // The example of Apple's documentation
+    let data = [0xaau8, 0xaa, 0xaa, 0x80, 0x00, 0x2a, 0xaa, 0xaa, 0xaa, 0xaa, 0x80, 0x00, 0x2a,
+                0x22, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa, 0xaa];
+    let packed = [0xfeu8, 0xaa, 0x02, 0x80, 0x00, 0x2a, 0xfd, 0xaa, 0x03, 0x80, 0x00, 0x2a, 0x22,
+                  0xf7, 0xaa];
+    assert_eq!(encode_bytes(&data, PackBits).as_slice(), packed.as_slice());
+    assert_eq!(decode_bytes(&packed, PackBits).unwrap().as_slice(), data.as_slice());
+    // -128 is a no-op
+    assert_eq!(decode_bytes(&[0x80, 0x00, 0x2a], PackBits), Ok(vec![0x2a]));
+
+    assert_eq!(encode_bytes(&[1, 1, 1, 1, 2, 2, 2, 0xff, 3], Escape(0xff)),
+               vec![0xff, 4, 1, 2, 2, 2, 0xff, 1, 0xff, 3]);
+}
+
+#[test]
+fn test_bytes_coherence() {
+    // Runs longer than the longest encoded run, and more literal bytes than fit in one packet
+    let mut data = Vec::new();
+    for i in range(0u, 300) {
+        data.push(i as u8);
+        data.grow(i % 5, b'\\');
+        data.grow(if i % 100 == 0 { 600 } else { 0 }, b'7');
+    }
+    for format in [Text, PackBits, Escape(0), Escape(b'\\')].iter() {
+        let encoded = encode_bytes(data.as_slice(), format.clone());
+        assert_eq!(decode_bytes(encoded.as_slice(), format.clone()), Ok(data.clone()));
+        assert_eq!(encode_bytes(b"", format.clone()), vec![]);
+    }
+}
+
+#[test]
+fn test_streams() {
+    use std::io::{MemReader, MemWriter};
+
+    // More than a block, in both directions
+    let mut data = Vec::new();
+    for i in range(0u, 200000) {
+        data.push((i / 1000) as u8);
+    }
+    data.grow(200000, 0);
+    let mut encoded = MemWriter::new();
+    encode_stream(&mut MemReader::new(data.clone()), &mut encoded, PackBits).unwrap();
+    assert_eq!(encoded.get_ref(), encode_bytes(data.as_slice(), PackBits).as_slice());
+
+    let mut decoded = MemWriter::new();
+    decode_stream(&mut MemReader::new(encoded.unwrap()), &mut decoded, PackBits).unwrap();
+    assert_eq!(decoded.unwrap(), data);
+
+    // The output before the error is written
+    let mut decoded = MemWriter::new();
+    let error = decode_stream(&mut MemReader::new(b"2A1".to_vec()), &mut decoded, Text);
+    assert_eq!(error.unwrap_err().kind, InvalidInput);
+    assert_eq!(decoded.unwrap(), b"AA".to_vec());
+}
+
+#[test]
+fn test_stream_long_run() {
+    use std::io::MemReader;
+
+    // Counts the bytes written, and the longest write
+    struct Counter { total: uint, longest: uint }
+    impl Writer for Counter {
+        fn write(&mut self, buf: &[u8]) -> IoResult<()> {
+            self.total += buf.len();
+            self.longest = cmp::max(self.longest, buf.len());
+            Ok(())
+        }
+    }
+
+    let mut counter = Counter { total: 0, longest: 0 };
+    let encoded = b"1B100000000A2C".to_vec();
+    decode_stream(&mut MemReader::new(encoded), &mut counter, Text).unwrap();
+    assert_eq!(counter.total, 100000003);
+    assert!(counter.longest <= BLOCK_SIZE);
 }
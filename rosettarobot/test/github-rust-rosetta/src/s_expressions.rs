@@ -11,76 +11,115 @@
 // It supports the ability to read individual atoms, not just lists, although whether this is
 // useful is questionable.
 //
-// Caveats: Does not support symbols vs. non-symbols (it wouldn't be hard, but it would greatly
-// complicate setting up our test structure since we'd have to force it to go through functions
-// that checked to make sure Symbols couldn't have spaces, or slow down our parser by checking for
-// this information each time, which is obnoxious).  Does not support string escaping, because the
-// decoding technique doesn't allocate extra space for strings.  Does support numbers, but only
-// float types (supporting more types is possible but would complicate the code significantly).
+// SExps can also be written and read in Rivest's canonical form, where every atom is prefixed by
+// its length in bytes ("(4:data3:abc)"), and in its base64 transport form
+// ("{KDQ6ZGF0YTM6YWJjKQ==}").  Both are bytes rather than text: an atom can hold any bytes, and
+// one which isn't UTF-8 is read as Bytes.
+// The canonical encoding of a SExp is unique, so it can be hashed or signed.  Quoted strings are
+// plain atoms; symbols and floats carry a display hint ("[6:symbol]4:data", "[5:float]3:4.5") so
+// that they decode back to the same SExp.
+//
+// SExpReader reads a whole stream of SExps from any Reader, one top-level SExp at a time, for
+// inputs too big to be read into a single string.
+//
+// Bare literals are strings, like quoted ones, unless the ParseContext is made `with_symbols`:
+// then they are symbols, so that SExps can be evaluated (see the `lisp` module).  Symbols are
+// written back without quotes, so it's up to whoever builds a Sym by hand not to put spaces or
+// parentheses in it.
+//
+// Caveats: Does not support string escaping, because the decoding technique doesn't allocate extra
+// space for strings.  Does support numbers, but only float types (supporting more types is
+// possible but would complicate the code significantly).
 //
 #![feature(slicing_syntax)]
 
 extern crate arena;
+extern crate serialize;
 extern crate test;
 
 use arena::TypedArena;
+use serialize::base64::{FromBase64, ToBase64, STANDARD};
 
 use std::io;
 use std::num;
+use std::str;
 use std::string;
 
 #[deriving(PartialEq,Show)]
-// The actual SExp structure.  Supports f64s, lists, and string literals.  Note that it takes
-// everything by reference, rather than owning it--this is mostly done just so we can allocate
-// SExps statically (since we don't have to call Vec).  It does complicate the code a bit,
+// The actual SExp structure.  Supports f64s, lists, string literals and symbols.  Note that it
+// takes everything by reference, rather than owning it--this is mostly done just so we can
+// allocate SExps statically (since we don't have to call Vec).  It does complicate the code a bit,
 // requiring us to have a ParseContext that holds an arena where lists are actually allocated.
-enum SExp<'a> {
+pub enum SExp<'a> {
     F64(f64), // Float literal: 0.5
     List(&'a [SExp<'a>]), // List of SExps: ( a b c)
     Str(&'a str), // Plain old string literal: "abc"
+    Sym(&'a str), // Symbol: abc
+    Bytes(&'a [u8]), // Atom of the canonical form which isn't UTF-8: 2:\xff\xfe
 }
 
 // Errors that can be thrown by the parser.
 #[deriving(PartialEq,Show)]
-enum Error {
+pub enum Error {
     NoReprForFloat, // If the float is NaN, Infinity, etc.
     UnterminatedStringLiteral, // Missing an end double quote during string parsing
     IoError(io::IoError), // Some other kind of I/O error
     IncorrectCloseDelimiter, // ) appeared where it shouldn't (usually as the first token)
     UnexpectedEOF, // Usually means a missing ), but could also mean there were no tokens at all.
     ExpectedEOF, // More tokens after the list is finished, or after a literal if there is no list.
+    MalformedCanonical, // Bad length prefix or display hint in the canonical form
+    MalformedTransport, // Missing braces or bad base64 in the transport form
+    NoReprForBytes, // If Bytes are written in text form
+    NotUtf8, // If bytes which aren't UTF-8 are parsed as text
 }
 
+// Display hints of the atoms that aren't quoted strings, in the canonical form.
+static SYMBOL_HINT: &'static str = "symbol";
+static FLOAT_HINT: &'static str = "float";
+
 // Tokens returned from the token stream.
 #[deriving(PartialEq)]
-enum Token<'a> {
+pub enum Token<'a> {
     ListStart, // Left parenthesis
     ListEnd, // Right parenthesis
-    Literal(SExp<'a>), // String or float literal, quotes removed.
+    Literal(SExp<'a>), // String, symbol or float literal, quotes removed.
     EOF, // Stream is out of tokens.
 }
 
 // An iterator over a string that yields a stream of Tokens.
 // Implementation note: it probably seems weird to store first, rest, AND string, since they should
 // all be derivable from string.  But see below.
-struct Tokens<'a> {
+pub struct Tokens<'a> {
     string: &'a str, // The part of the string that still needs to be parsed
     first: Option<char>, // The first character to parse
     rest: &'a str, // The rest of the string after the first character
+    len: uint, // The length of the whole string
+    start: uint, // Byte offset of the last token (or of the end of the string)
+    symbols: bool, // Whether bare literals which aren't floats are symbols rather than strings
 }
 
 impl<'a> Tokens<'a> {
     // Initialize a token stream for a given string.
-    fn new(string: &str) -> Tokens {
+    pub fn new(string: &str) -> Tokens {
         let (ch, s) = string.slice_shift_char();
-        Tokens { string: string, first: ch, rest: s }
+        Tokens { string: string, first: ch, rest: s, len: string.len(), start: 0, symbols: false }
+    }
+
+    // The byte offset where the last token returned by next() (or its error) starts.
+    pub fn offset(&self) -> uint {
+        self.start
+    }
+
+    // Like next(), but an error comes with the offset where it was detected.
+    fn next_at(&mut self) -> Result<Token<'a>, (Error, uint)> {
+        self.next().map_err(|e| (e, self.start))
     }
 
     // Utility function to update information in the iterator.  It might not be performant to keep
     // rest cached, but there are times where we don't know exactly what string is (at least, not
     // in a way that we can *safely* reconstruct it without allocating), so we keep both here.
     // With some unsafe code we could probably get rid of one of them (and maybe first, too).
-    fn update(&mut self, string: &'a str) {
+    pub fn update(&mut self, string: &'a str) {
         self.string = string;
         let (ch, s) = string.slice_shift_char();
         self.first = ch;
@@ -88,8 +127,13 @@ impl<'a> Tokens<'a> {
     }
 
     // This is where the lexing happens.  Note that it does not handle string escaping.
-    fn next(&mut self) -> Result<Token<'a>, Error> {
+    pub fn next(&mut self) -> Result<Token<'a>, Error> {
         loop {
+            // self.string may be stale (see below), but first and rest never are
+            self.start = self.len - match self.first {
+                Some(c) => c.len_utf8_bytes() + self.rest.len(),
+                None => 0
+            };
             match self.first {
                 // List start
                 Some('(') => {
@@ -149,7 +193,10 @@ impl<'a> Tokens<'a> {
                         Some(_) => self.first = end_ch,
                         None => self.update(self.rest)
                     }
-                    return Ok(Literal(parse_literal(str)));
+                    return Ok(Literal(match parse_literal(str) {
+                        Str(s) if self.symbols => Sym(s),
+                        literal => literal
+                    }));
                 }
                 None => return Ok(EOF)
             }
@@ -158,15 +205,15 @@ impl<'a> Tokens<'a> {
 }
 
 // Convenience method to turn I/O errors into SExp Errors, inspired by the JSON encoder.
-fn from_io_result<T>(res: io::IoResult<T>) -> Result<T, Error> {
+pub fn from_io_result<T>(res: io::IoResult<T>) -> Result<T, Error> {
     res.map_err( |err| IoError(err) )
 }
 
 // This is not the most efficient way to do this, because we end up going over numeric literals
 // twice, but it avoids having to write our own number parsing logic.
-fn parse_literal(literal: &str) -> SExp {
+pub fn parse_literal(literal: &str) -> SExp {
     match literal.bytes().next() {
-        Some(b'0'...b'9') | Some(b'-') => match num::from_str_radix(literal, 10) {
+        Some(b'0'...b'9') | Some(b'-') => match parse_float(literal) {
             Some(f) => F64(f),
             None => Str(literal)
         },
@@ -174,28 +221,111 @@ fn parse_literal(literal: &str) -> SExp {
     }
 }
 
+// The text of a float, in both forms, or None for NaN and infinities.  Floats too small for the 6
+// decimals of "{}", such as subnormals, are written with an exponent: "4.940656e-324".  Floats
+// which 6 decimals don't give back (0.1 + 0.2 reads back as 0.3) are written exactly instead, as
+// a hexadecimal mantissa and a binary exponent: "0x13333333333334p-54".
+pub fn float_repr(f: f64) -> Option<String> {
+    let decimal = match f.classify() {
+        num::FPNaN | num::FPInfinite => return None,
+        num::FPZero => format!("{}", f),
+        _ if f.abs() >= 1e-6 => format!("{}", f),
+        _ => {
+            // "{:e}" divides by 10^exp, which underflows for subnormals, so scale them up first
+            let exp = f.abs().log10().floor() as i32;
+            format!("{}e{}", f * 1e300 / 10f64.powi(exp + 300), exp)
+        }
+    };
+    // Compared bit for bit, so that -0 isn't written as 0
+    let (mantissa, exp, sign) = f.integer_decode();
+    match parse_float(decimal[]) {
+        Some(g) if g.integer_decode() == (mantissa, exp, sign) => Some(decimal),
+        _ => Some(format!("{}0x{:x}p{}", if sign < 0 { "-" } else { "" }, mantissa, exp))
+    }
+}
+
+// Parse a float written by float_repr (or without the exponent), or None if it isn't one.
+pub fn parse_float(s: &str) -> Option<f64> {
+    let (sign, unsigned) = if s.starts_with("-") { (-1f64, s[1..]) } else { (1f64, s) };
+    if unsigned.starts_with("0x") {
+        return parse_hex_float(unsigned[2..]).map(|f| sign * f);
+    }
+    let (mantissa, exp) = match s.find('e') {
+        Some(i) => match from_str::<i32>(s[i + 1..]) {
+            Some(exp) => (s[..i], exp),
+            None => return None
+        },
+        None => (s, 0)
+    };
+    let mantissa: f64 = match num::from_str_radix(mantissa, 10) {
+        Some(mantissa) => mantissa,
+        None => return None
+    };
+    // Same as in float_repr(), 10^exp alone would underflow for subnormals
+    let f = if exp < -300 {
+        mantissa * 10f64.powi(exp + 300) * 1e-300
+    } else {
+        mantissa * 10f64.powi(exp)
+    };
+    if f.is_finite() { Some(f) } else { None }
+}
+
+// Parse the hexadecimal mantissa and binary exponent of a float written exactly by float_repr,
+// such as "13333333333334p-54".  The mantissa fits in a f64, so scaling it by 2^exp is exact.
+fn parse_hex_float(s: &str) -> Option<f64> {
+    let (mantissa, exp) = match s.find('p') {
+        Some(i) => (num::from_str_radix::<u64>(s[..i], 16), from_str::<i32>(s[i + 1..])),
+        None => return None
+    };
+    let f = match (mantissa, exp) {
+        // 2^exp alone would underflow for subnormals
+        (Some(m), Some(exp)) if m < 1 << 53 && exp < -1000 => {
+            m as f64 * 2f64.powi(exp + 1000) * 2f64.powi(-1000)
+        },
+        (Some(m), Some(exp)) if m < 1 << 53 => m as f64 * 2f64.powi(exp),
+        _ => return None
+    };
+    if f.is_finite() { Some(f) } else { None }
+}
+
 // Parse context, holds information required by the parser (and owns any allocations it makes)
-struct ParseContext<'a> {
-    string: &'a str, // The string being parsed.  Not required, but convenient.
+pub struct ParseContext<'a> {
+    bytes: &'a [u8], // The bytes being parsed, for the canonical form.
+    string: Option<&'a str>, // The same bytes, if they are UTF-8, for the text form.
     arena: Option<TypedArena<Vec<SExp<'a>>>>, // Arena holding any allocations made by the parser.
     stack: Vec<Vec<SExp<'a>>>, // Stored in the parse context so it can be reused once allocated.
+    symbols: bool, // Whether bare literals are parsed as symbols
 }
 
 impl<'a> ParseContext<'a> {
     // Create a new parse context from a given string
-    fn new(string: &'a str) -> ParseContext<'a> {
-        ParseContext { string: string, arena: None, stack: Vec::new() }
+    pub fn new(string: &'a str) -> ParseContext<'a> {
+        let bytes = string.as_bytes();
+        ParseContext { bytes: bytes, string: Some(string), arena: None, stack: Vec::new(),
+                       symbols: false }
+    }
+
+    // Create a new parse context from given bytes, such as the canonical form
+    pub fn from_bytes(bytes: &'a [u8]) -> ParseContext<'a> {
+        ParseContext { bytes: bytes, string: str::from_utf8(bytes), arena: None, stack: Vec::new(),
+                       symbols: false }
+    }
+
+    // Parse bare literals of the text form as symbols rather than strings
+    pub fn with_symbols(mut self) -> ParseContext<'a> {
+        self.symbols = true;
+        self
     }
 }
 
 impl<'a> SExp<'a> {
     // Serialize a SExp.
-    fn encode<T: io::Writer>(&self, writer: &mut T) -> Result<(), Error> {
+    pub fn encode<T: io::Writer>(&self, writer: &mut T) -> Result<(), Error> {
         match *self {
-            F64(f) => match f.classify() {
-                // We don't want to identify NaN, Infinity, etc. as floats.
-                num::FPNormal | num::FPZero => from_io_result(write!(writer, "{}", f)),
-                _ => Err(NoReprForFloat)
+            // We don't want to identify NaN, Infinity, etc. as floats.
+            F64(f) => match float_repr(f) {
+                Some(repr) => from_io_result(writer.write_str(repr[])),
+                None => Err(NoReprForFloat)
             },
             List(ref l) => {
                 // Writing a list is very straightforward--write a left parenthesis, then
@@ -217,11 +347,19 @@ impl<'a> SExp<'a> {
                 from_io_result(writer.write_char(')'))
             },
             Str(s) => from_io_result(write!(writer, "\"{}\"", s)),
+            Sym(s) => from_io_result(write!(writer, "{}", s)),
+            Bytes(_) => Err(NoReprForBytes),
         }
     }
 
     // Deserialize a SExp.
-    fn parse(ctx: &'a mut ParseContext<'a>) -> Result<SExp<'a>, Error> {
+    pub fn parse(ctx: &'a mut ParseContext<'a>) -> Result<SExp<'a>, Error> {
+        SExp::parse_with_offset(ctx).map_err(|(e, _)| e)
+    }
+
+    // Like parse(), but an error comes with the byte offset in the context's string where it was
+    // detected, so that callers can point at it.
+    pub fn parse_with_offset(ctx: &'a mut ParseContext<'a>) -> Result<SExp<'a>, (Error, uint)> {
         ctx.arena = Some(TypedArena::new());
         // Hopefully this unreachable! gets optimized out, because it should literally be
         // unreachable.
@@ -229,25 +367,34 @@ impl<'a> SExp<'a> {
             Some(ref mut arena) => arena,
             None => unreachable!()
         };
-        let ParseContext {string, ref mut stack, .. } = *ctx;
+        let ParseContext {string, ref mut stack, symbols, .. } = *ctx;
+        let string = match string {
+            Some(string) => string,
+            None => return Err((NotUtf8, 0))
+        };
         // Make sure the stack is cleared--we keep it in the context to avoid unnecessary
         // reallocation between parses (if you need to remember old parse information for a new
         // list, you can pass in a new context).
         stack.clear();
         let mut tokens = Tokens::new(string);
+        tokens.symbols = symbols;
         // First, we check the very first token to see if we're parsing a full list.  It
         // simplifies parsing a lot in the subsequent code if we can assume that.
-        let next = tokens.next();
+        let next = tokens.next_at();
         let mut list = match try!(next) {
             ListStart => Vec::new(),
-            Literal(s) => return if try!(tokens.next()) == EOF { Ok(s) } else { Err(ExpectedEOF) },
-            ListEnd => return Err(IncorrectCloseDelimiter),
-            EOF => return Err(UnexpectedEOF),
+            Literal(s) => return if try!(tokens.next_at()) == EOF {
+                Ok(s)
+            } else {
+                Err((ExpectedEOF, tokens.offset()))
+            },
+            ListEnd => return Err((IncorrectCloseDelimiter, tokens.offset())),
+            EOF => return Err((UnexpectedEOF, tokens.offset())),
         };
 
         // We know we're in a list if we got this far.
         loop {
-            let tok = tokens.next();
+            let tok = tokens.next_at();
             match try!(tok) {
                 ListStart => {
                     // We push the previous context onto our stack when we start reading a new list.
@@ -265,19 +412,19 @@ impl<'a> SExp<'a> {
                     },
                     // There was nothing on the stack, so we're at the end of the topmost list.
                     // The check to make sure there are no more tokens is required for correctness.
-                    None => return match try!(tokens.next()) {
+                    None => return match try!(tokens.next_at()) {
                         EOF => Ok(List(arena.alloc(list)[])),
-                        _ => Err(ExpectedEOF),
+                        _ => Err((ExpectedEOF, tokens.offset())),
                     }
                 },
                 // We encountered an EOF before the list ended--that's an error.
-                EOF => return Err(UnexpectedEOF),
+                EOF => return Err((UnexpectedEOF, tokens.offset())),
             }
         }
     }
 
     // Convenience method for the common case where you just want to encode a SExp as a String.
-    fn buffer_encode(&self) -> Result<String, Error> {
+    pub fn buffer_encode(&self) -> Result<String, Error> {
         let mut m = io::MemWriter::new();
         try!(self.encode(&mut m));
         // Because encode() only ever writes valid UTF-8, we can safely skip the secondary check we
@@ -285,29 +432,384 @@ impl<'a> SExp<'a> {
         // buffer was already UTF-8, we'd want to call container_as_str() here.
         unsafe { Ok(string::raw::from_utf8(m.unwrap())) }
     }
+
+    // Serialize a SExp in canonical form.
+    pub fn encode_canonical<T: io::Writer>(&self, writer: &mut T) -> Result<(), Error> {
+        match *self {
+            F64(f) => match float_repr(f) {
+                Some(repr) => encode_atom(writer, Some(FLOAT_HINT), repr.as_bytes()),
+                None => Err(NoReprForFloat)
+            },
+            // No spaces at all in the canonical form, so this is simpler than in encode().
+            List(ref l) => {
+                try!(from_io_result(writer.write_char('(')));
+                for sexp in l.iter() {
+                    try!(sexp.encode_canonical(writer));
+                }
+                from_io_result(writer.write_char(')'))
+            },
+            Str(s) => encode_atom(writer, None, s.as_bytes()),
+            Sym(s) => encode_atom(writer, Some(SYMBOL_HINT), s.as_bytes()),
+            Bytes(b) => encode_atom(writer, None, b),
+        }
+    }
+
+    // Convenience method to encode a SExp in canonical form as bytes.
+    pub fn buffer_encode_canonical(&self) -> Result<Vec<u8>, Error> {
+        let mut m = io::MemWriter::new();
+        try!(self.encode_canonical(&mut m));
+        Ok(m.unwrap())
+    }
+
+    // Encode a SExp in transport form (its canonical form in base64, between braces).
+    pub fn buffer_encode_transport(&self) -> Result<String, Error> {
+        let canonical = try!(self.buffer_encode_canonical());
+        Ok(format!("{{{}}}", canonical[].to_base64(STANDARD)))
+    }
+
+    // Deserialize a SExp in canonical form.  Like parse(), it allocates lists in the context's
+    // arena, and atoms borrow from the context's bytes.
+    pub fn parse_canonical(ctx: &'a mut ParseContext<'a>) -> Result<SExp<'a>, Error> {
+        ctx.arena = Some(TypedArena::new());
+        let arena = match ctx.arena {
+            Some(ref mut arena) => arena,
+            None => unreachable!()
+        };
+        let ParseContext {bytes, ref mut stack, .. } = *ctx;
+        stack.clear();
+        // As in parse(), a lone atom is handled up front so the loop only deals with lists.
+        let mut list = match bytes.get(0) {
+            Some(&b'(') => Vec::new(),
+            _ => {
+                let (atom, end) = try!(parse_canonical_atom(bytes, 0));
+                return if end == bytes.len() { Ok(atom) } else { Err(ExpectedEOF) }
+            }
+        };
+        let mut pos = 1;
+        loop {
+            match bytes.get(pos) {
+                Some(&b'(') => {
+                    stack.push(list);
+                    list = Vec::new();
+                    pos += 1;
+                },
+                Some(&b')') => {
+                    pos += 1;
+                    match stack.pop() {
+                        Some(mut l) => {
+                            l.push(List(arena.alloc(list)[]));
+                            list = l;
+                        },
+                        None => return if pos == bytes.len() {
+                            Ok(List(arena.alloc(list)[]))
+                        } else {
+                            Err(ExpectedEOF)
+                        }
+                    }
+                },
+                Some(_) => {
+                    let (atom, end) = try!(parse_canonical_atom(bytes, pos));
+                    list.push(atom);
+                    pos = end;
+                },
+                None => return Err(UnexpectedEOF),
+            }
+        }
+    }
+}
+
+// Write an atom, and its display hint if it has one, in canonical form.
+fn encode_atom<T: io::Writer>(writer: &mut T, hint: Option<&str>, atom: &[u8])
+                              -> Result<(), Error> {
+    match hint {
+        Some(hint) => try!(from_io_result(write!(writer, "[{}:{}]", hint.len(), hint))),
+        None => ()
+    }
+    try!(from_io_result(write!(writer, "{}:", atom.len())));
+    from_io_result(writer.write(atom))
+}
+
+// Read a canonical atom, with its optional display hint, starting at byte pos of bytes.  Returns
+// the atom and the position of the byte after it.
+fn parse_canonical_atom<'a>(bytes: &'a [u8], pos: uint) -> Result<(SExp<'a>, uint), Error> {
+    let (hint, pos) = match bytes.get(pos) {
+        Some(&b'[') => {
+            let (hint, end) = try!(parse_canonical_string(bytes, pos + 1));
+            match bytes.get(end) {
+                Some(&b']') => (Some(hint), end + 1),
+                Some(_) => return Err(MalformedCanonical),
+                None => return Err(UnexpectedEOF)
+            }
+        },
+        _ => (None, pos)
+    };
+    let (atom, end) = try!(parse_canonical_string(bytes, pos));
+    // Only plain atoms can be any bytes, symbols and floats are text
+    let text = str::from_utf8(atom);
+    let sexp = match (hint, text) {
+        (None, Some(text)) => Str(text),
+        (None, None) => Bytes(atom),
+        (Some(hint), Some(text)) if hint == SYMBOL_HINT.as_bytes() => Sym(text),
+        (Some(hint), Some(text)) if hint == FLOAT_HINT.as_bytes() => match parse_float(text) {
+            Some(f) => F64(f),
+            None => return Err(MalformedCanonical)
+        },
+        (Some(_), _) => return Err(MalformedCanonical)
+    };
+    Ok((sexp, end))
 }
 
-const SEXP_STRUCT: SExp<'static> = List([
+// Read a length-prefixed byte string ("3:abc") starting at byte pos of bytes.  Returns the byte
+// string and the position of the byte after it.
+fn parse_canonical_string<'a>(bytes: &'a [u8], pos: uint) -> Result<(&'a [u8], uint), Error> {
+    let mut end = pos;
+    let mut len = 0u;
+    while end < bytes.len() && bytes[end] >= b'0' && bytes[end] <= b'9' {
+        let digit = (bytes[end] - b'0') as uint;
+        len = match len.checked_mul(&10).and_then(|l| l.checked_add(&digit)) {
+            Some(len) => len,
+            None => return Err(MalformedCanonical)
+        };
+        end += 1;
+    }
+    // The length must be there, and leading zeros would make the encoding ambiguous.
+    if end == bytes.len() {
+        return Err(UnexpectedEOF)
+    }
+    if end == pos || bytes[end] != b':' || (bytes[pos] == b'0' && end > pos + 1) {
+        return Err(MalformedCanonical)
+    }
+    let start = end + 1;
+    if len > bytes.len() - start {
+        return Err(UnexpectedEOF)
+    }
+    Ok((bytes[start..start + len], start + len))
+}
+
+// Decode the transport form of a SExp into its canonical form, which can then be parsed with
+// parse_canonical().  Whitespace is allowed around the braces and anywhere inside them.
+pub fn transport_to_canonical(transport: &[u8]) -> Result<Vec<u8>, Error> {
+    let base64: Vec<u8> = transport.iter().map(|&b| b)
+                                   .filter(|&b| !(b as char).is_whitespace())
+                                   .collect();
+    if base64.len() < 2 || base64[0] != b'{' || base64[base64.len() - 1] != b'}' {
+        return Err(MalformedTransport)
+    }
+    base64[1..base64.len() - 1].from_base64().map_err(|_| MalformedTransport)
+}
+
+// An error from a SExpReader, with the (1-based) line and column where it was detected.  For an
+// unterminated string or an unclosed list, that's where the string or list started.
+#[deriving(PartialEq,Show)]
+pub struct ReadError {
+    pub error: Error,
+    pub line: uint,
+    pub column: uint,
+}
+
+// Reads a stream of top-level SExps (such as a log file with one list per entry) from any Reader,
+// one at a time, so that the whole stream never has to be in memory.  The text of the current
+// SExp is copied into a buffer, which is then handed to the usual parser; an SExp can therefore be
+// split across any number of reads from the underlying Reader.
+pub struct SExpReader<R> {
+    reader: io::BufferedReader<R>,
+    peeked: Option<(char, uint, uint)>, // A character that was read but belongs to the next SExp
+    line: uint, // Position of the next character of the underlying Reader
+    column: uint,
+    buffer: String, // Text of the current SExp.  Reused between SExps.
+}
+
+impl<R: io::Reader> SExpReader<R> {
+    // Create a reader for the SExps of a given Reader
+    pub fn new(reader: R) -> SExpReader<R> {
+        SExpReader {
+            reader: io::BufferedReader::new(reader),
+            peeked: None,
+            line: 1,
+            column: 1,
+            buffer: String::new(),
+        }
+    }
+
+    // Read the next SExp and pass it to f, returning what f returns, or None if the stream is out
+    // of SExps.  The SExp borrows from the reader's buffer, which is why it can't just be
+    // returned.
+    pub fn read_with<T>(&mut self, f: |SExp| -> T) -> Result<Option<T>, ReadError> {
+        let (line, column) = match try!(self.fill_buffer()) {
+            Some(position) => position,
+            None => return Ok(None)
+        };
+        let ref mut ctx = ParseContext::new(self.buffer[]);
+        match SExp::parse_with_offset(ctx) {
+            Ok(sexp) => Ok(Some(f(sexp))),
+            Err((e, offset)) => {
+                // The buffer holds every character of the SExp, so the error is as many lines and
+                // columns further as there are before its offset
+                let (mut line, mut column) = (line, column);
+                for ch in self.buffer[..offset].chars() {
+                    if ch == '\n' {
+                        line += 1;
+                        column = 1;
+                    } else {
+                        column += 1;
+                    }
+                }
+                Err(ReadError { error: e, line: line, column: column })
+            }
+        }
+    }
+
+    // Read the text of the next SExp, or None if the stream is out of SExps.
+    pub fn read_text(&mut self) -> Result<Option<String>, ReadError> {
+        match try!(self.fill_buffer()) {
+            Some(_) => Ok(Some(self.buffer.clone())),
+            None => Ok(None)
+        }
+    }
+
+    // Copy the text of the next SExp into the buffer and return the position where it starts.
+    fn fill_buffer(&mut self) -> Result<Option<(uint, uint)>, ReadError> {
+        self.buffer.clear();
+        // Skip whitespace between SExps
+        let first;
+        loop {
+            match try!(self.next_char()) {
+                Some((c, _, _)) if c.is_whitespace() => (),
+                next => { first = next; break }
+            }
+        }
+        let (c, line, column) = match first {
+            Some(first) => first,
+            None => return Ok(None)
+        };
+        match c {
+            '(' => try!(self.read_list(line, column)),
+            ')' => return Err(ReadError {
+                error: IncorrectCloseDelimiter,
+                line: line,
+                column: column
+            }),
+            '"' => try!(self.read_string(line, column)),
+            _ => try!(self.read_literal(c)),
+        }
+        Ok(Some((line, column)))
+    }
+
+    // Read a list whose left parenthesis, at the given position, has already been read.
+    fn read_list(&mut self, line: uint, column: uint) -> Result<(), ReadError> {
+        self.buffer.push('(');
+        // Positions of the lists that are still open, so we can tell where the unclosed one starts
+        let mut open = vec![(line, column)];
+        // Like in Tokens, a quote only starts a string at the start of a token, not inside a
+        // literal
+        let mut in_literal = false;
+        loop {
+            match try!(self.next_char()) {
+                Some(('"', l, c)) if !in_literal => try!(self.read_string(l, c)),
+                Some((ch, l, c)) => {
+                    self.buffer.push(ch);
+                    in_literal = !(ch.is_whitespace() || ch == '(' || ch == ')');
+                    match ch {
+                        '(' => open.push((l, c)),
+                        ')' => {
+                            open.pop();
+                            if open.is_empty() { return Ok(()) }
+                        },
+                        _ => (),
+                    }
+                },
+                None => {
+                    let (l, c) = *open.last().unwrap();
+                    return Err(ReadError { error: UnexpectedEOF, line: l, column: c })
+                }
+            }
+        }
+    }
+
+    // Read a quoted string whose opening quote, at the given position, has already been read.
+    fn read_string(&mut self, line: uint, column: uint) -> Result<(), ReadError> {
+        self.buffer.push('"');
+        loop {
+            match try!(self.next_char()) {
+                Some((ch, _, _)) => {
+                    self.buffer.push(ch);
+                    if ch == '"' { return Ok(()) }
+                },
+                None => return Err(ReadError {
+                    error: UnterminatedStringLiteral,
+                    line: line,
+                    column: column
+                })
+            }
+        }
+    }
+
+    // Read a top-level literal starting with first.  Like in Tokens, it ends at whitespace or at a
+    // parenthesis, which is left for the next SExp.
+    fn read_literal(&mut self, first: char) -> Result<(), ReadError> {
+        self.buffer.push(first);
+        loop {
+            match try!(self.next_char()) {
+                Some((ch, _, _)) if ch.is_whitespace() => return Ok(()),
+                Some(next @ ('(', _, _)) | Some(next @ (')', _, _)) => {
+                    self.peeked = Some(next);
+                    return Ok(())
+                },
+                Some((ch, _, _)) => self.buffer.push(ch),
+                None => return Ok(())
+            }
+        }
+    }
+
+    // Read the next character and its position, or None at the end of the stream.
+    fn next_char(&mut self) -> Result<Option<(char, uint, uint)>, ReadError> {
+        match self.peeked.take() {
+            Some(peeked) => return Ok(Some(peeked)),
+            None => ()
+        }
+        match self.reader.read_char() {
+            Ok(ch) => {
+                let position = (ch, self.line, self.column);
+                if ch == '\n' {
+                    self.line += 1;
+                    self.column = 1;
+                } else {
+                    self.column += 1;
+                }
+                Ok(Some(position))
+            },
+            Err(ref e) if e.kind == io::EndOfFile => Ok(None),
+            Err(e) => Err(ReadError { error: IoError(e), line: self.line, column: self.column })
+        }
+    }
+}
+
+pub const SEXP_STRUCT: SExp<'static> = List([
     List([Str("data"), Str("quoted data"), F64(123.), F64(4.5)]),
     List([Str("data"), List([Str("!@#"), List([F64(4.5)]), Str("(more"), Str("data)")])]),
 ]);
 
-fn try_encode() -> Result<String, Error> {
+pub fn try_encode() -> Result<String, Error> {
     SEXP_STRUCT.buffer_encode()
 }
 
-const SEXP_STRING_IN: &'static str = r#"((data "quoted data" 123 4.5)
+pub const SEXP_STRING_IN: &'static str = r#"((data "quoted data" 123 4.5)
 (data (!@# (4.5) "(more" "data)")))"#;
 
-fn try_decode<'a>(ctx: &'a mut ParseContext<'a>) -> Result<SExp<'a>, Error> {
+pub fn try_decode<'a>(ctx: &'a mut ParseContext<'a>) -> Result<SExp<'a>, Error> {
     SExp::parse(ctx)
 }
 
 #[cfg(not(test))]
-fn main() {
-    println!("{}", try_encode());
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the example structure encoded, and the example string decoded, to `out`
+pub fn run<W: Writer>(out: &mut W) -> io::IoResult<()> {
+    try!(writeln!(out, "{}", try_encode()));
     let ref mut ctx = ParseContext::new(SEXP_STRING_IN);
-    println!("{}", try_decode(ctx));
+    writeln!(out, "{}", try_decode(ctx))
 }
 
 #[bench]
@@ -339,3 +841,238 @@ fn test_sexp_decode() {
     let ref mut ctx = ParseContext::new(SEXP_STRING_IN);
     assert_eq!(Ok(SEXP_STRUCT), try_decode(ctx));
 }
+
+#[test]
+fn test_sexp_symbols() {
+    // Bare literals are strings, unless the context parses them as symbols
+    let ref mut ctx = ParseContext::new(r#"(data "data" 1)"#);
+    assert_eq!(Ok(List([Str("data"), Str("data"), F64(1.)])), SExp::parse(ctx));
+    let ref mut ctx = ParseContext::new(r#"(data "data" 1)"#).with_symbols();
+    let sexp = SExp::parse(ctx);
+    assert_eq!(Ok(List([Sym("data"), Str("data"), F64(1.)])), sexp);
+    assert_eq!(Ok(r#"(data "data" 1)"#.to_string()), sexp.unwrap().buffer_encode());
+}
+
+#[test]
+fn test_sexp_canonical() {
+    const SEXP_CANONICAL: &'static str = "((4:data11:quoted data[5:float]3:123[5:float]3:4.5)\
+(4:data(3:!@#([5:float]3:4.5)5:(more5:data))))";
+    assert_eq!(Ok(SEXP_CANONICAL.as_bytes()),
+               SEXP_STRUCT.buffer_encode_canonical().as_ref().map( |b| b[]));
+    let ref mut ctx = ParseContext::new(SEXP_CANONICAL);
+    assert_eq!(Ok(SEXP_STRUCT), SExp::parse_canonical(ctx));
+
+    // Symbols carry a display hint
+    let symbols = List([Sym("data"), Str("data")]);
+    assert_eq!(Ok(b"([6:symbol]4:data4:data)".to_vec()), symbols.buffer_encode_canonical());
+    let ref mut ctx = ParseContext::new("([6:symbol]4:data4:data)");
+    assert_eq!(Ok(symbols), SExp::parse_canonical(ctx));
+}
+
+#[test]
+fn test_sexp_canonical_atoms() {
+    // Lengths are in bytes, not characters
+    assert_eq!(Ok(b"2:\xc3\xa9".to_vec()), Str("\u00e9").buffer_encode_canonical());
+    assert_eq!(Ok(b"0:".to_vec()), Str("").buffer_encode_canonical());
+    let ref mut ctx = ParseContext::new("(3:abc()0:)");
+    assert_eq!(Ok(List([Str("abc"), List([]), Str("")])), SExp::parse_canonical(ctx));
+}
+
+#[test]
+fn test_sexp_canonical_bytes() {
+    // Atoms which aren't UTF-8 are Bytes, and they can't be written as text
+    const CANONICAL: &'static [u8] = b"(2:\xff\x001:\xc3[6:symbol]1:a)";
+    let ref mut ctx = ParseContext::from_bytes(CANONICAL);
+    let sexp = SExp::parse_canonical(ctx);
+    assert_eq!(Ok(List([Bytes(b"\xff\x00"), Bytes(b"\xc3"), Sym("a")])), sexp);
+    let sexp = sexp.unwrap();
+    assert_eq!(Ok(CANONICAL.to_vec()), sexp.buffer_encode_canonical());
+    assert_eq!(Err(NoReprForBytes), sexp.buffer_encode());
+
+    let canonical = transport_to_canonical(sexp.buffer_encode_transport().unwrap().as_bytes());
+    assert_eq!(Ok(CANONICAL.to_vec()), canonical);
+
+    // Symbols and floats are still text
+    fn parse(b: &[u8]) -> Result<(), Error> {
+        let ref mut ctx = ParseContext::from_bytes(b);
+        SExp::parse_canonical(ctx).map(|_| ())
+    }
+    assert_eq!(Err(MalformedCanonical), parse(b"[6:symbol]1:\xff"));
+    assert_eq!(Err(MalformedCanonical), parse(b"[5:float]1:\xff"));
+
+    // The text form can't be parsed from bytes which aren't UTF-8
+    let ref mut ctx = ParseContext::from_bytes(b"(a \xff)");
+    assert_eq!(Err(NotUtf8), SExp::parse(ctx));
+}
+
+#[test]
+fn test_sexp_subnormal_floats() {
+    // Floats too small for 6 decimals are written with an exponent, in both forms
+    let tiny = 5e-324f64;
+    assert!(tiny.classify() == num::FPSubnormal);
+    assert_eq!(Ok("(4.940656e-324 -1e-7 0.5)".to_string()),
+               List([F64(tiny), F64(-1e-7), F64(0.5)]).buffer_encode());
+    assert_eq!(Ok(b"[5:float]13:4.940656e-324".to_vec()), F64(tiny).buffer_encode_canonical());
+
+    let ref mut ctx = ParseContext::new("4.940656e-324");
+    assert_eq!(Ok(F64(tiny)), SExp::parse(ctx));
+    let ref mut ctx = ParseContext::new("[5:float]13:4.940656e-324");
+    assert_eq!(Ok(F64(tiny)), SExp::parse_canonical(ctx));
+    let ref mut ctx = ParseContext::new("(1e-7 2.5e-310 1e999)");
+    assert_eq!(Ok(List([F64(1e-7), F64(2.5e-310), Str("1e999")])), SExp::parse(ctx));
+}
+
+#[test]
+fn test_sexp_float_round_trip() {
+    fn bits(sexp: Result<SExp, Error>) -> (u64, i16, i8) {
+        match sexp {
+            Ok(F64(f)) => f.integer_decode(),
+            other => fail!("{} isn't a float", other)
+        }
+    }
+    assert_eq!(Ok("0x13333333333334p-54".to_string()), F64(0.1 + 0.2).buffer_encode());
+    // Every float reads back bit for bit, from both forms
+    let floats = [0.1 + 0.2, 1e-7 + 1e-20, -1.0 / 3.0, 4.5, -0.0, 5e-324, 1.7976931348623157e308];
+    for &f in floats.iter() {
+        let text = F64(f).buffer_encode().unwrap();
+        let ref mut ctx = ParseContext::new(text[]);
+        assert_eq!(bits(SExp::parse(ctx)), f.integer_decode());
+        let canonical = F64(f).buffer_encode_canonical().unwrap();
+        let ref mut ctx = ParseContext::from_bytes(canonical[]);
+        assert_eq!(bits(SExp::parse_canonical(ctx)), f.integer_decode());
+    }
+}
+
+#[test]
+fn test_sexp_canonical_errors() {
+    fn parse(s: &str) -> Result<(), Error> {
+        let ref mut ctx = ParseContext::new(s);
+        SExp::parse_canonical(ctx).map(|_| ())
+    }
+    assert_eq!(Err(UnexpectedEOF), parse("(3:ab"));
+    assert_eq!(Err(UnexpectedEOF), parse("(3:abc"));
+    assert_eq!(Err(ExpectedEOF), parse("3:abc)"));
+    assert_eq!(Err(MalformedCanonical), parse("03:abc"));
+    assert_eq!(Err(MalformedCanonical), parse("(abc)"));
+    assert_eq!(Err(MalformedCanonical), parse("[4:text]3:abc"));
+    assert_eq!(Err(MalformedCanonical), parse("[5:float]3:abc"));
+    // Half of a character is an atom of its own, followed by the other half
+    assert_eq!(Err(ExpectedEOF), parse("1:\u00e9"));
+}
+
+#[test]
+fn test_sexp_transport() {
+    assert_eq!(Ok("{KDM6YWJjKQ==}".to_string()), List([Str("abc")]).buffer_encode_transport());
+    assert_eq!(Ok(b"(3:abc)".to_vec()), transport_to_canonical(b" {KDM6\n YWJj KQ==}\n"));
+    assert_eq!(Err(MalformedTransport), transport_to_canonical(b"KDM6YWJjKQ=="));
+    assert_eq!(Err(MalformedTransport), transport_to_canonical(b"{KDM6*YWJjKQ==}"));
+
+    let transport = SEXP_STRUCT.buffer_encode_transport().unwrap();
+    let canonical = transport_to_canonical(transport.as_bytes()).unwrap();
+    let ref mut ctx = ParseContext::from_bytes(canonical[]);
+    assert_eq!(Ok(SEXP_STRUCT), SExp::parse_canonical(ctx));
+}
+
+// A Reader that hands out its data a few bytes at a time, to split SExps (and even characters)
+// across reads.
+#[cfg(test)]
+struct ChunkedReader {
+    data: Vec<u8>,
+    pos: uint,
+    chunk: uint,
+}
+
+#[cfg(test)]
+impl io::Reader for ChunkedReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::IoResult<uint> {
+        if self.pos == self.data.len() {
+            return Err(io::standard_error(io::EndOfFile))
+        }
+        let n = ::std::cmp::min(::std::cmp::min(self.chunk, buf.len()), self.data.len() - self.pos);
+        buf.clone_from_slice(self.data[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+#[cfg(test)]
+fn read_all<R: io::Reader>(reader: &mut SExpReader<R>) -> Result<Vec<String>, ReadError> {
+    let mut sexps = Vec::new();
+    loop {
+        match try!(reader.read_with(|sexp| sexp.buffer_encode().unwrap())) {
+            Some(s) => sexps.push(s),
+            None => return Ok(sexps)
+        }
+    }
+}
+
+#[test]
+fn test_reader_stream() {
+    let input = "(log 1 \"début\")\n(log 2 (nested \"a ( b\"))  atom\"x\"(last)\n\n";
+    // Bare literals are strings, so they are written back quoted
+    let expected = vec!["(\"log\" 1 \"début\")", "(\"log\" 2 (\"nested\" \"a ( b\"))",
+                        "\"atom\"x\"\"", "(\"last\")"];
+    // Every chunk size splits the input differently, including inside the multi-byte 'é'
+    for chunk in range(1u, 8) {
+        let mut reader = SExpReader::new(ChunkedReader {
+            data: input.as_bytes().to_vec(), pos: 0, chunk: chunk
+        });
+        let sexps = read_all(&mut reader).unwrap();
+        assert_eq!(sexps.iter().map(|s| s[]).collect::<Vec<&str>>(), expected);
+    }
+}
+
+#[test]
+fn test_reader_text() {
+    let mut reader = SExpReader::new(io::MemReader::new(SEXP_STRING_IN.as_bytes().to_vec()));
+    assert_eq!(reader.read_text(), Ok(Some(SEXP_STRING_IN.to_string())));
+    assert_eq!(reader.read_text(), Ok(None));
+}
+
+#[test]
+fn test_reader_errors() {
+    fn error(input: &str) -> ReadError {
+        let mut reader = SExpReader::new(io::MemReader::new(input.as_bytes().to_vec()));
+        read_all(&mut reader).unwrap_err()
+    }
+    assert_eq!(error("(a)\n(b \"never\nends)"),
+               ReadError { error: UnterminatedStringLiteral, line: 2, column: 4 });
+    assert_eq!(error("(a)\n (b (c) (d"),
+               ReadError { error: UnexpectedEOF, line: 2, column: 9 });
+    assert_eq!(error("(a b))"),
+               ReadError { error: IncorrectCloseDelimiter, line: 1, column: 6 });
+}
+
+#[test]
+fn test_reader_matches_parser() {
+    fn parse(input: &str) -> Result<String, (Error, uint)> {
+        let ref mut ctx = ParseContext::new(input);
+        SExp::parse_with_offset(ctx).map(|sexp| sexp.buffer_encode().unwrap())
+    }
+    // A quote inside a literal doesn't start a string, for the reader as for the parser
+    let inputs = ["(a\"b)\n c\")", "(x \"a\"\"b\"c\"d\")", "(a\"b c\")", "(a\"(b\" c)"];
+    for &input in inputs.iter() {
+        let mut reader = SExpReader::new(io::MemReader::new(input.as_bytes().to_vec()));
+        let first = reader.read_with(|sexp| sexp.buffer_encode().unwrap());
+        match parse(input) {
+            Ok(sexp) => assert_eq!(first, Ok(Some(sexp))),
+            // The reader stops where the parser found the start of the next SExp
+            Err((ExpectedEOF, offset)) => {
+                assert_eq!(first, Ok(Some(parse(input[..offset]).unwrap())))
+            },
+            Err((e, _)) => assert_eq!(first.unwrap_err().error, e),
+        }
+    }
+}
+
+#[test]
+fn test_parse_offsets() {
+    fn offset(s: &str) -> (Error, uint) {
+        let ref mut ctx = ParseContext::new(s);
+        SExp::parse_with_offset(ctx).unwrap_err()
+    }
+    assert_eq!(offset("(a) b"), (ExpectedEOF, 4));
+    assert_eq!(offset("(a \"b)"), (UnterminatedStringLiteral, 3));
+    assert_eq!(offset(" )"), (IncorrectCloseDelimiter, 1));
+    assert_eq!(offset("(\u00e9 (b)"), (UnexpectedEOF, 7));
+}
@@ -1,6 +1,6 @@
 // http://rosettacode.org/wiki/Self-describing_numbers
 
-fn is_self_describing(mut n: u64) -> bool {
+pub fn is_self_describing(mut n: u64) -> bool {
 
     // Compute the length of the number (the number of digits)
     let mut tmp = n;
@@ -29,7 +29,7 @@ fn is_self_describing(mut n: u64) -> bool {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
     // Print out all self-describing numbers below 10^8
     for i in range(0, 100_000_000) {
         if is_self_describing(i) {
@@ -1,15 +1,24 @@
 // http://rosettacode.org/wiki/Sequence_of_non-squares
 use std::iter::range_inclusive;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
 // the formula that should produce no perfect squares
-fn non_sq(n: u64) -> u64 { (n + ( 0.5 + (n as f64).sqrt()) as u64) }
+pub fn non_sq(n: u64) -> u64 { (n + ( 0.5 + (n as f64).sqrt()) as u64) }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the first 22 non-squares to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     // print the first 22 non squares
     for n in range_inclusive(1, 22).map(non_sq) {
-        println!("{}", n);
+        try!(writeln!(out, "{}", n));
     }
+    Ok(())
 }
 
 #[test]
@@ -2,7 +2,7 @@
 
 use std::collections::HashSet;
 
-fn main() {
+pub fn main() {
     // The first set contains integers from 0 to 7
     let set1 = range(0i, 7).collect::<HashSet<int>>();
 
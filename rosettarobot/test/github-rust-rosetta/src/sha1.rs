@@ -2,38 +2,47 @@
 // straight port from golang crypto/sha1
 // library implementation
 
+use std::cmp;
+#[cfg(not(test))]
+use std::io;
 use std::io::IoResult;
 use std::slice::bytes::copy_memory;
 
 // The size of a SHA1 checksum in bytes.
-const SIZE: uint = 20;
+pub const SIZE: uint = 20;
 
 // The blocksize of SHA1 in bytes.
-const CHUNK:uint = 64;
-const INIT:[u32,..5] = [0x67452301,0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+pub const CHUNK:uint = 64;
+pub const INIT:[u32,..5] = [0x67452301,0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
 
 #[cfg(not(test))]
-fn main() {
-    let mut d = Digest::new();
-    d.write(b"The quick brown fox jumps over the lazy dog").unwrap();
-    let sha1=d.sha1();
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the bytes of the checksum of the example string to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
+    let mut d = Sha1::new();
+    d.update(b"The quick brown fox jumps over the lazy dog");
+    let sha1=d.finalize();
 
     for h in sha1.iter() {
-        print!("{:x} ", *h);
+        try!(write!(out, "{:x} ", *h));
     }
- }
+    Ok(())
+}
 
-// digest represents the partial evaluation of a checksum.
-struct Digest {
+// Sha1 represents the partial evaluation of a checksum.
+pub struct Sha1 {
     h:      [u32, ..5],
     x:      [u8, ..CHUNK],
     nx:     uint,
     len:    u64
 }
 
-impl Digest {
-    fn new() -> Digest {
-        Digest {
+impl Sha1 {
+    pub fn new() -> Sha1 {
+        Sha1 {
             h:  INIT,
             x:  [0u8, ..CHUNK],
             nx: 0u,
@@ -41,7 +50,41 @@ impl Digest {
         }
     }
 
-    fn sha1(&mut self) -> [u8,..SIZE] {
+    pub fn reset(&mut self) {
+        *self = Sha1::new();
+    }
+
+    pub fn update(&mut self, buf: &[u8]) {
+        let mut buf_m = buf;
+
+        self.len += buf_m.len() as u64;
+
+        if self.nx > 0 {
+            let n = cmp::min(buf_m.len(), CHUNK - self.nx);
+            copy_memory(self.x.slice_mut(self.nx, self.nx + n), buf_m.slice_to(n));
+            self.nx += n;
+            if self.nx == CHUNK {
+                let x = self.x.as_slice();
+                self.h=self.process_block(x);
+                self.nx = 0;
+            }
+            buf_m = buf_m.slice_from(n);
+        }
+        if buf_m.len() >= CHUNK {
+            let n = buf_m.len() &!(CHUNK - 1);
+            self.h=self.process_block(buf_m.slice_to(n));
+            buf_m = buf_m.slice_from(n);
+        }
+        let ln=buf_m.len();
+        if ln > 0 {
+            assert!(self.x.len() >= ln);
+            copy_memory(self.x.slice_mut(0, ln), buf_m);
+            self.nx = ln;
+        }
+    }
+
+    // Returns the checksum of the data written so far, and starts a new one
+    pub fn finalize(&mut self) -> [u8,..SIZE] {
         let mut len = self.len;
         // Padding.  Add a 1 bit and 0 bits until 56 bytes mod 64.
         let mut tmp : [u8,..64] = [0u8,..64];
@@ -49,9 +92,9 @@ impl Digest {
 
         let m:uint=(len%64u64) as uint;
         if m < 56 {
-            self.write(tmp.slice(0u, 56-m)).unwrap();
+            self.update(tmp.slice(0u, 56-m));
         } else {
-            self.write(tmp.slice(0u, 64+56-m)).unwrap();
+            self.update(tmp.slice(0u, 64+56-m));
         }
 
         // Length in bits (=lengh in bytes*8=shift 3 bits to the right).
@@ -59,7 +102,7 @@ impl Digest {
         for i in range (0u, 8) {
             tmp[i] = (len >> (56u - 8*i)) as u8;
         }
-        self.write(tmp.slice(0,8)).unwrap();
+        self.update(tmp.slice(0,8));
 
         assert!(self.nx == 0);
 
@@ -70,10 +113,11 @@ impl Digest {
             digest[i*4+2] = (*s >> 8) as u8;
             digest[i*4+3] = *s as u8;
         }
+        self.reset();
         digest
     }
 
-    fn process_block(&self, data:&[u8]) ->  [u32, ..5]{
+    pub fn process_block(&self, data:&[u8]) ->  [u32, ..5]{
         let k:[u32,..4] = [0x5A827999, 0x6ED9EBA1, 0x8F1BBCDC, 0xCA62C1D6];
 
         #[inline]
@@ -149,41 +193,10 @@ impl Digest {
     }
 }
 
-impl Writer for Digest {
+impl Writer for Sha1 {
     #[inline]
     fn write(&mut self, buf: &[u8]) -> IoResult<()> {
-        let mut buf_m = buf;
-
-        self.len += buf_m.len() as u64;
-
-        if self.nx > 0 {
-            let mut n = buf_m.len();
-            if n > CHUNK - self.nx {
-                n = CHUNK - self.nx;
-            }
-            for i in range(0,n) {
-                self.x[self.nx + i] = *buf_m.get(i).unwrap();
-            }
-            self.nx += n;
-            if self.nx == CHUNK {
-                let x = self.x.as_slice();
-                self.h=self.process_block(x);
-                self.nx = 0;
-            }
-            buf_m = buf_m.slice_from(n);
-        }
-        if buf_m.len() >= CHUNK {
-            let n = buf_m.len() &!(CHUNK - 1);
-            let x = self.x.slice_from(n);
-            self.h=self.process_block(x);
-            buf_m = buf_m.slice_from(n);
-        }
-        let ln=buf_m.len();
-        if ln > 0 {
-            assert!(self.x.len() >= ln);
-            copy_memory(self.x, buf_m);
-            self.nx = ln;
-        }
+        self.update(buf);
         Ok(())
     }
 }
@@ -214,10 +227,29 @@ fn known_sha1s() {
         let i = i.as_slice();
         let o = o.as_slice();
 
-        let mut d = Digest::new();
+        let mut d = Sha1::new();
         d.write_str(i).unwrap();
-        let sha1=d.sha1();
+        let sha1=d.finalize();
 
         assert_eq!(sha1.as_slice(), o);
     }
 }
+
+#[test]
+fn long_messages() {
+    // A million "a", written in parts which don't line up with the blocks
+    let part = [b'a', ..1000];
+    let mut d = Sha1::new();
+    for i in range(0u, 1000) {
+        let (first, second) = (part.slice_to(i), part.slice_from(i));
+        d.update(first);
+        d.update(second);
+    }
+    let expected = [0x34u8, 0xaa, 0x97, 0x3c, 0xd4, 0xc4, 0xda, 0xa4, 0xf6, 0x1e,
+                    0xeb, 0x2b, 0xdb, 0xad, 0x27, 0x31, 0x65, 0x34, 0x01, 0x6f];
+    assert_eq!(d.finalize().as_slice(), expected.as_slice());
+
+    // finalize starts a new checksum
+    d.update(b"The quick brown fox jumps over the lazy dog");
+    assert_eq!(d.finalize()[0], 0x2f);
+}
@@ -1,20 +1,160 @@
 // Implements http://rosettacode.org/wiki/SHA-256
+// following FIPS 180-4, with the same structure as the SHA-1 task
 
-// note that for now the rustc::util::sha2::Sha256 docs state:
-// This implementation is not intended for external use or for any use where security is
-// important.
-extern crate rustc;
-use rustc::util::sha2::{Sha256, Digest};
+extern crate serialize;
 
+use serialize::hex::ToHex;
+use std::cmp;
+use std::slice::bytes::copy_memory;
 #[cfg(not(test))]
-fn main() {
-    println!("{}", sha_256("Rosetta code"));
+use std::io;
+use std::io::IoResult;
+
+// The size of a SHA-256 hash in bytes
+pub const SIZE: uint = 32;
+
+// The blocksize of SHA-256 in bytes
+pub const CHUNK: uint = 64;
+
+pub const INIT: [u32,..8] = [0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a,
+                             0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19];
+
+// The first 32 bits of the fractional parts of the cube roots of the first 64 primes
+pub const K: [u32,..64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2];
+
+#[cfg(not(test))]
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the hash of the example string to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
+    writeln!(out, "{}", sha_256("Rosetta code"))
 }
 
-fn sha_256(input: &str) -> String {
-    let mut sh = box Sha256::new();
-    sh.input_str(input);
-    sh.result_str()
+pub fn sha_256(input: &str) -> String {
+    let mut sh = Sha256::new();
+    sh.update(input.as_bytes());
+    sh.finalize().as_slice().to_hex()
+}
+
+// The partial evaluation of a SHA-256 hash, the message can be given in several parts
+pub struct Sha256 {
+    h: [u32,..8],
+    buffer: [u8,..CHUNK], // The start of an incomplete block
+    buffered: uint,
+    len: u64
+}
+
+impl Sha256 {
+    pub fn new() -> Sha256 {
+        Sha256 { h: INIT, buffer: [0u8,..CHUNK], buffered: 0, len: 0 }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Sha256::new();
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.len += data.len() as u64;
+        let mut data = data;
+
+        // Complete the block started by a previous update first
+        if self.buffered > 0 {
+            let n = cmp::min(data.len(), CHUNK - self.buffered);
+            copy_memory(self.buffer.slice_mut(self.buffered, self.buffered + n),
+                        data.slice_to(n));
+            self.buffered += n;
+            data = data.slice_from(n);
+            if self.buffered < CHUNK {
+                return;
+            }
+            let block = self.buffer;
+            self.process_block(block.as_slice());
+            self.buffered = 0;
+        }
+
+        while data.len() >= CHUNK {
+            self.process_block(data.slice_to(CHUNK));
+            data = data.slice_from(CHUNK);
+        }
+        copy_memory(self.buffer.slice_mut(0, data.len()), data);
+        self.buffered = data.len();
+    }
+
+    // Returns the hash of the message, and starts a new one
+    pub fn finalize(&mut self) -> [u8,..SIZE] {
+        let bit_len = self.len << 3;
+
+        // Padding.  Add a 1 bit and 0 bits until 56 bytes mod 64, then the length in bits.
+        let mut padding = [0u8,..CHUNK];
+        padding[0] = 0x80;
+        let padding_len = if self.buffered < 56 { 56 - self.buffered } else { 120 - self.buffered };
+        self.update(padding.slice_to(padding_len));
+        let mut len = [0u8,..8];
+        for i in range(0u, 8) {
+            len[i] = (bit_len >> (56 - 8*i)) as u8;
+        }
+        self.update(len.as_slice());
+        assert_eq!(self.buffered, 0);
+
+        let mut digest = [0u8,..SIZE];
+        for (i, s) in self.h.iter().enumerate() {
+            digest[i*4] = (*s >> 24) as u8;
+            digest[i*4+1] = (*s >> 16) as u8;
+            digest[i*4+2] = (*s >> 8) as u8;
+            digest[i*4+3] = *s as u8;
+        }
+        self.reset();
+        digest
+    }
+
+    fn process_block(&mut self, block: &[u8]) {
+        #[inline]
+        fn rotr(x: u32, n: uint) -> u32 {
+            (x >> n) | (x << (32 - n))
+        }
+
+        // The message schedule
+        let mut w = [0u32,..64];
+        for i in range(0u, 16) {
+            let j = i * 4;
+            w[i] = (block[j] as u32)<<24 |
+                   (block[j+1] as u32)<<16 |
+                   (block[j+2] as u32)<<8 |
+                    block[j+3] as u32;
+        }
+        for i in range(16u, 64) {
+            let s0 = rotr(w[i-15], 7) ^ rotr(w[i-15], 18) ^ (w[i-15] >> 3);
+            let s1 = rotr(w[i-2], 17) ^ rotr(w[i-2], 19) ^ (w[i-2] >> 10);
+            w[i] = w[i-16] + s0 + w[i-7] + s1;
+        }
+
+        let (mut a, mut b, mut c, mut d) = (self.h[0], self.h[1], self.h[2], self.h[3]);
+        let (mut e, mut f, mut g, mut h) = (self.h[4], self.h[5], self.h[6], self.h[7]);
+        for i in range(0u, 64) {
+            let s1 = rotr(e, 6) ^ rotr(e, 11) ^ rotr(e, 25);
+            let ch = (e & f) ^ ((!e) & g);
+            let t1 = h + s1 + ch + K[i] + w[i];
+            let s0 = rotr(a, 2) ^ rotr(a, 13) ^ rotr(a, 22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0 + maj;
+            h = g; g = f; f = e; e = d + t1;
+            d = c; c = b; b = a; a = t1 + t2;
+        }
+
+        for (x, y) in self.h.iter_mut().zip([a, b, c, d, e, f, g, h].iter()) {
+            *x += *y;
+        }
+    }
 }
 
 #[test]
@@ -23,3 +163,31 @@ fn test_rosetta() {
     "764faf5c61ac315f1497f9dfa542713965b785e5cc2f707d6468d7d1124cdfcf"
     .to_string());
 }
+
+#[test]
+fn test_fips_vectors() {
+    assert_eq!(sha_256(""),
+    "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855".to_string());
+    assert_eq!(sha_256("abc"),
+    "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad".to_string());
+    // Two blocks, since the padding doesn't fit in the first one
+    assert_eq!(sha_256("abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+    "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1".to_string());
+}
+
+#[test]
+fn test_incremental_updates() {
+    // A million "a", in parts which don't line up with the blocks
+    let part = [b'a', ..1000];
+    let mut sh = Sha256::new();
+    for i in range(0u, 1000) {
+        sh.update(part.slice_to(i));
+        sh.update(part.slice_from(i));
+    }
+    assert_eq!(sh.finalize().as_slice().to_hex(),
+    "cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0".to_string());
+
+    // finalize starts a new hash
+    sh.update(b"Rosetta code");
+    assert_eq!(sh.finalize()[0], 0x76);
+}
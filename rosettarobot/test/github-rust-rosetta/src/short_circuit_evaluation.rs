@@ -1,22 +1,31 @@
 // Implements http://rosettacode.org/wiki/Short-circuit_evaluation
+use std::io::{mod, IoResult};
 
-fn a(x: bool) -> bool {
-    println!("Inside function a");
-    x
+pub fn a<W: Writer>(out: &mut W, x: bool) -> IoResult<bool> {
+    try!(writeln!(out, "Inside function a"));
+    Ok(x)
 }
 
-fn b(x: bool) -> bool {
-    println!("Inside function b");
-    x
+pub fn b<W: Writer>(out: &mut W, x: bool) -> IoResult<bool> {
+    try!(writeln!(out, "Inside function b"));
+    Ok(x)
 }
 
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the calls made to evaluate each conjunction and disjunction to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let booleans = [true, false];
 
     for &i in booleans.iter() {
         for &j in booleans.iter() {
-            println!("{} and {} is {}", i, j, a(i) && b(j));
-            println!("{} or {} is {}", i, j, a(i) || b(j));
+            let and = try!(a(out, i)) && try!(b(out, j));
+            try!(writeln!(out, "{} and {} is {}", i, j, and));
+            let or = try!(a(out, i)) || try!(b(out, j));
+            try!(writeln!(out, "{} or {} is {}", i, j, or));
         }
     }
+    Ok(())
 }
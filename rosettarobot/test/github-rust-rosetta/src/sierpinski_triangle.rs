@@ -1,28 +1,30 @@
 // http://rosettacode.org/wiki/Sierpinski_triangle
+//
+// The triangle is drawn by the elementary cellular automaton of rule 90, where each cell becomes
+// the exclusive or of its neighbors: from a single live cell, its generations are Pascal's
+// triangle (mod 2).
 
-use std::iter::range_step;
+use std::io::{mod, IoResult};
+use elementary_cellular_automaton::{Automaton, Dead, to_text};
+// With the path of the file, so that its own `mod bitmap` is found next to it
+#[allow(dead_code)]
+#[path = "elementary_cellular_automaton.rs"]
+mod elementary_cellular_automaton;
 
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the triangle of order 4 to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let order = 4u;
     let height = 1u << order;
-    let mut state = Vec::from_elem(height + 1, true);
-
-    // Compute the triangle line-by-line by viewing it as Pascal's triangle (mod 2)
-    for i in range(0u, height) {
-        for _ in range(0u, height - i - 1) {
-            print!(" ");
-        }
 
-        for j in range(0u, i + 1) {
-            print!(" {}", if state[j] { "*" } else { " " });
-        }
-
-        // Compute the next line
-        for j in range_step(i as int, 0, -1) {
-            *state.get_mut(j as uint) ^= state[(j - 1) as uint];
-        }
-
-        print!("\n");
+    // Wide enough for the last generation not to reach the boundary
+    let automaton = Automaton::single_cell(90, Dead, 2 * height);
+    let generations: Vec<Vec<bool>> = automaton.take(height).collect();
+    for line in to_text(generations.as_slice(), '*', ' ').as_slice().lines() {
+        try!(write!(out, "{}\n", line.trim_right()));
     }
+    Ok(())
 }
-
@@ -1,13 +1,16 @@
 // Implements http://rosettacode.org/wiki/Sieve_of_Eratosthenes
 
 use std::iter::{range_inclusive, range_step};
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
-fn int_sqrt(n: uint) -> uint {
+pub fn int_sqrt(n: uint) -> uint {
     (n as f64).sqrt() as uint
 }
 
 // Return the prime numbers up to limit
-fn simple_sieve(limit: uint) -> Vec<uint> {
+pub fn simple_sieve(limit: uint) -> Vec<uint> {
     if limit < 2 {
         return vec!();
     }
@@ -26,8 +29,13 @@ fn simple_sieve(limit: uint) -> Vec<uint> {
 }
 
 #[cfg(not(test))]
-fn main() {
-    println!("{}", simple_sieve(100))
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the primes up to 100 to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
+    writeln!(out, "{}", simple_sieve(100))
 }
 
 #[test]
@@ -1,12 +1,21 @@
 // Implements http://rosettacode.org/wiki/Sort_an_integer_array
 
 #[cfg(not(test))]
-fn main() {
+use std::io;
+use std::io::IoResult;
+
+#[cfg(not(test))]
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes a sorted vector to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let mut a = vec!(9u, 8, 7, 6, 5, 4, 3, 2, 1, 0);
 
     // Merge sort in place, allocates ~2*n memory
     a.sort();
-    println!("{}", a);
+    writeln!(out, "{}", a)
 }
 
 #[test]
@@ -1,39 +1,48 @@
 // Implements http://rosettacode.org/wiki/Stack
 
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
+
 #[deriving(Show)]
-struct Stack<T> {
+pub struct Stack<T> {
     // We use a vector because of simplicity
     vec: Vec<T>
 }
 
 impl<T> Stack<T> {
-    fn new() -> Stack<T> {
+    pub fn new() -> Stack<T> {
         Stack { vec: Vec::new() }
     }
 
     // Adds an element at the top of the stack
-    fn push(&mut self, elem: T) {
+    pub fn push(&mut self, elem: T) {
         self.vec.push(elem);
     }
 
     // Removes and returns the element at the top of the stack
-    fn pop(&mut self) -> Option<T> {
+    pub fn pop(&mut self) -> Option<T> {
         self.vec.pop()
     }
 
     // Returns a reference of the element at the top of the stack
-    fn peek(&self) -> Option<&T> {
+    pub fn peek(&self) -> Option<&T> {
         self.vec.last()
     }
 
     // Returns true if the stack is empty
-    fn empty(&self) -> bool {
+    pub fn empty(&self) -> bool {
         self.vec.len() == 0
     }
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the top of a stack, the popped element and whether it is empty to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let mut stack = Stack::new();
 
     // Fill the stack
@@ -42,13 +51,13 @@ fn main() {
     stack.push(9);
 
     // Show the element at the top
-    println!("{}", stack.peek().unwrap());
+    try!(writeln!(out, "{}", stack.peek().unwrap()));
     // Show the element we popped
-    println!("{}", stack.pop().unwrap());
+    try!(writeln!(out, "{}", stack.pop().unwrap()));
     if stack.empty() {
-        println!("The stack is empty.")
+        writeln!(out, "The stack is empty.")
     } else {
-        println!("The stack is not empty.")
+        writeln!(out, "The stack is not empty.")
     }
 }
 
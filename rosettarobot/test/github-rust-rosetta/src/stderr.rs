@@ -3,7 +3,7 @@
 
 use std::io;
 
-fn main() {
+pub fn main() {
     let mut stderr = io::stderr();
     stderr.write(b"Goodbye, World!\n");
 }
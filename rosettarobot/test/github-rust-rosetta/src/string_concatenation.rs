@@ -1,6 +1,10 @@
 // http://rosettacode.org/wiki/String_concatenation
 
-fn add_world(mut x: String) -> String {
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
+
+pub fn add_world(mut x: String) -> String {
     // world is a &'a[u8]
     let world = " world";
     x.push_str(world);
@@ -8,12 +12,17 @@ fn add_world(mut x: String) -> String {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the concatenated string to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     // The call to_string() turns a &[u8] into a Vec<u8>.
     // This is done because Vecs are growable but slices aren't.
     let hello = "hello".to_string();
     let hello_world = add_world(hello);
-    println!("{}", hello_world);
+    writeln!(out, "{}", hello_world)
 }
 
 #[test]
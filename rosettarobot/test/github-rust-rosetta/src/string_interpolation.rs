@@ -1,9 +1,18 @@
 // Implements http://rosettacode.org/wiki/String_interpolation
 
 #[cfg(not(test))]
-fn main() {
+use std::io;
+use std::io::IoResult;
+
+#[cfg(not(test))]
+pub fn main() {
+  run(&mut io::stdout()).unwrap();
+}
+
+// Writes the interpolated string to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
   let original = "Mary had a X lamb";
   let little = "little";
   let replaced = original.replace("X",little);
-  println!("{}",replaced);
+  writeln!(out, "{}",replaced)
 }
@@ -1,6 +1,10 @@
 // Implements http://rosettacode.org/wiki/String_matching
 
-fn match_string(container: &str, target: &str) -> (bool, bool, bool) {
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
+
+pub fn match_string(container: &str, target: &str) -> (bool, bool, bool) {
   let starts = container.starts_with(target);
   let ends = container.ends_with(target);
   let contains = starts || ends || container.contains(target);
@@ -8,27 +12,32 @@ fn match_string(container: &str, target: &str) -> (bool, bool, bool) {
   (starts, contains, ends)
 }
 
-#[cfg(not(test))]
-fn print_info(container: &str, target: &str) {
-  println!(r#"Matching "{}" in the string "{}""#, target, container);
+pub fn print_info<W: Writer>(out: &mut W, container: &str, target: &str) -> IoResult<()> {
+  try!(writeln!(out, r#"Matching "{}" in the string "{}""#, target, container));
   let (starts, contains, ends) = match_string(container,target);
 
   if starts {
-    println!(r#""{}" starts with "{}""#, container, target);
+    try!(writeln!(out, r#""{}" starts with "{}""#, container, target));
   }
   if contains {
-    println!(r#""{}" contains "{}""#, container, target);
+    try!(writeln!(out, r#""{}" contains "{}""#, container, target));
   }
   if ends {
-    println!(r#""{}" ends with "{}""#, container, target);
+    try!(writeln!(out, r#""{}" ends with "{}""#, container, target));
   }
+  Ok(())
 }
 
 #[cfg(not(test))]
-fn main() {
-  print_info("abcd", "ab");
-  print_info("abcd", "bc");
-  print_info("abcd", "cd");
+pub fn main() {
+  run(&mut io::stdout()).unwrap();
+}
+
+// Writes where a few strings match in "abcd" to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
+  try!(print_info(out, "abcd", "ab"));
+  try!(print_info(out, "abcd", "bc"));
+  print_info(out, "abcd", "cd")
 }
 
 #[test]
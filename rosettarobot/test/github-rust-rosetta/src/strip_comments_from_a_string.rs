@@ -1,6 +1,10 @@
 // http://rosettacode.org/wiki/Strip_comments_from_a_string
 
-fn strip_comments(str: &str) -> &str {
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
+
+pub fn strip_comments(str: &str) -> &str {
     let markers = ['#', ';'];
     str.find(markers.as_slice()).map_or(str, |i| str.slice_to(i)).trim()
 }
@@ -18,12 +22,18 @@ fn test_strip_comments() {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes a few strings with their comments stripped to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let inputs = ["apples, pears # and bananas",
                   "apples, pears ; and bananas",
                   "  apples, pears "];
 
     for &input in inputs.iter() {
-        println!("Input: {}\nStripped: {}", input, strip_comments(input))
+        try!(writeln!(out, "Input: {}\nStripped: {}", input, strip_comments(input)));
     }
+    Ok(())
 }
@@ -1,11 +1,17 @@
 // Implements http://rosettacode.org/wiki/Generic_swap
+use std::io::{mod, IoResult};
 use std::mem::swap;
 
-fn main() {
-  println!("Same type:");
+pub fn main() {
+  run(&mut io::stdout()).unwrap();
+}
+
+// Writes two strings before and after swapping them to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
+  try!(writeln!(out, "Same type:"));
   let mut thing_one = "The First String";
   let mut thing_two = "The Second String";
-  println!("Thing 1: {}, Thing 2: {}", thing_one, thing_two);
+  try!(writeln!(out, "Thing 1: {}, Thing 2: {}", thing_one, thing_two));
   swap(&mut thing_one, &mut thing_two);
-  println!("Thing 1: {}, Thing 2: {}", thing_one, thing_two);
+  writeln!(out, "Thing 1: {}, Thing 2: {}", thing_one, thing_two)
 }
@@ -9,14 +9,14 @@ use std::io::File;
 use std::io::BufferedReader;
 use std::comm::{channel, Sender, Receiver};
 
-const FILENAME: &'static str = "resources/input.txt";
+pub const FILENAME: &'static str = "resources/input.txt";
 
-enum Message {
+pub enum Message {
     Line(String),
     End
 }
 
-fn printer(i_snd: Sender<int>, msg_rcv: Receiver<Message>) {
+pub fn printer(i_snd: Sender<int>, msg_rcv: Receiver<Message>) {
     let mut count = 0;
     loop {
         match msg_rcv.recv() {
@@ -30,7 +30,7 @@ fn printer(i_snd: Sender<int>, msg_rcv: Receiver<Message>) {
     i_snd.send(count);
 }
 
-fn reader(msg_snd: Sender<Message>, i_rcv: Receiver<int>) {
+pub fn reader(msg_snd: Sender<Message>, i_rcv: Receiver<int>) {
     let mut file = BufferedReader::new(File::open(&Path::new(FILENAME)));
     for line in file.lines() {
         msg_snd.send(Line(line.unwrap()));
@@ -39,7 +39,7 @@ fn reader(msg_snd: Sender<Message>, i_rcv: Receiver<int>) {
     println!("Total Lines: {}", i_rcv.recv());
 }
 
-fn main() {
+pub fn main() {
     let (msg_snd, msg_rcv) = channel();
     let (i_snd, i_rcv) = channel();
 
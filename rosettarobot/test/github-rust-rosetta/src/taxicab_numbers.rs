@@ -1,18 +1,21 @@
 // http://rosettacode.org/wiki/Taxicab_numbers
 use std::collections::PriorityQueue;
 use std::num::pow;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
 /// A type to represent a pair-sum of cubes.
 /// value = a^3 + b^3
 #[deriving(PartialEq, Eq)]
-struct SumCubes {
+pub struct SumCubes {
     a: u64,
     b: u64,
     value: u64,
 }
 
 impl SumCubes {
-    fn new(a: u64, b: u64) -> SumCubes {
+    pub fn new(a: u64, b: u64) -> SumCubes {
         SumCubes{ value: pow(a, 3) + pow(b, 3), a: a, b: b }
     }
 }
@@ -31,14 +34,14 @@ impl Ord for SumCubes {
 }
 
 /// An iterator through all Taxicab numbers
-struct TaxicabNumbers {
+pub struct TaxicabNumbers {
     // Use a priority queue to iterate through sums
     // of cubes efficiently in increasing order
     pq: PriorityQueue<SumCubes>,
 }
 
 impl TaxicabNumbers {
-    fn new() -> TaxicabNumbers {
+    pub fn new() -> TaxicabNumbers {
         let mut res = TaxicabNumbers{ pq: PriorityQueue::new() };
         res.pq.push(SumCubes::new(1, 1)); // Start with 1^3 + 1^3
         res
@@ -77,16 +80,22 @@ impl Iterator<Vec<SumCubes>> for TaxicabNumbers {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the first 25 taxicab numbers, and the 2000th to 2006th, to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let numbers = TaxicabNumbers::new();
     for (at, ways) in numbers.take(2006).enumerate()
                              .filter(|&(at, _)| at + 1 <= 25 || at + 1 >= 2000) {
-        print!("{:>4u}:{:>10u}", at + 1, ways[0].value);
+        try!(write!(out, "{:>4u}:{:>10u}", at + 1, ways[0].value));
         for &SumCubes{ a, b, .. } in ways.iter() {
-            print!(" = {:>4u}^3 + {:>4u}^3", a, b);
+            try!(write!(out, " = {:>4u}^3 + {:>4u}^3", a, b));
         }
-        print!("\n");
+        try!(write!(out, "\n"));
     }
+    Ok(())
 }
 
 #[test]
@@ -1,13 +1,22 @@
 // http://rosettacode.org/wiki/Towers_of_Hanoi
 
-fn play(n: int, from: int, to: int, via: int) {
+use std::io::{mod, IoResult};
+
+// Writes the moves needed to bring `n` disks from pole `from` to pole `to` to `out`
+pub fn play<W: Writer>(out: &mut W, n: int, from: int, to: int, via: int) -> IoResult<()> {
     if n > 0 {
-        play(n - 1, from, via, to);
-        println!("Move disk from pole {:d} to pole {:d}", from, to);
-        play(n - 1, via, to, from);
+        try!(play(out, n - 1, from, via, to));
+        try!(writeln!(out, "Move disk from pole {:d} to pole {:d}", from, to));
+        try!(play(out, n - 1, via, to, from));
     }
+    Ok(())
+}
+
+// Writes the moves which solve the puzzle with 4 disks to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
+    play(out, 4, 1, 2, 3)
 }
 
-fn main() {
-    play(4, 1, 2, 3);
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
 }
@@ -3,7 +3,7 @@
 use std::io::net::tcp::{TcpAcceptor, TcpListener, TcpStream};
 use std::io::{Acceptor, Listener, IoResult};
 
-fn handle_client(mut stream: TcpStream) -> IoResult<()> {
+pub fn handle_client(mut stream: TcpStream) -> IoResult<()> {
     let response =
 b"HTTP/1.1 200 OK
 Content-Type: text/html;
@@ -57,7 +57,7 @@ pub fn handle_server(ip: &'static str, port: u16) -> IoResult<TcpAcceptor> {
 }
 
 #[cfg(not(test))]
-fn main() {
+pub fn main() {
     const HOST: &'static str = "127.0.0.1";
     const PORT: u16 = 80;
     let acceptor = handle_server(HOST, PORT).unwrap();
@@ -7,6 +7,9 @@
 
 use std::str::Words;
 use std::mem::swap;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
 
 pub struct WordWrap<'a> {
     words: Words<'a>,
@@ -15,7 +18,7 @@ pub struct WordWrap<'a> {
 }
 
 impl<'a> WordWrap<'a> {
-    fn new(text: &'a str, line_length: uint) -> WordWrap {
+    pub fn new(text: &'a str, line_length: uint) -> WordWrap {
         WordWrap {
             words : text.words(),
             line_length : line_length,
@@ -61,7 +64,12 @@ impl<'a> Iterator<String> for WordWrap<'a> {
 }
 
 #[cfg(not(test))]
-fn main () {
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the example text wrapped at 72 and 80 columns to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
     let text =
         "In olden times when wishing still helped one, there lived a king \
          whose daughters were all beautiful, but the youngest was so beautiful \
@@ -74,13 +82,13 @@ fn main () {
          ball was her favorite plaything.";
 
     for &length in [72u, 80u].iter() {
-        println!("Text wrapped at {}", length);
+        try!(writeln!(out, "Text wrapped at {}", length));
         for line in WordWrap::new(text, length) {
-            println!("{}", line);
+            try!(writeln!(out, "{}", line));
         }
-        println!("");
+        try!(writeln!(out, ""));
     }
-
+    Ok(())
 }
 
 #[test]
@@ -5,7 +5,7 @@ use bitmap::Image;
 use bitmap::Color;
 mod bitmap;
 
-trait PPMWritable {
+pub trait PPMWritable {
     fn write_ppm(&self, filename: &str) -> IoResult<()>;
 }
 
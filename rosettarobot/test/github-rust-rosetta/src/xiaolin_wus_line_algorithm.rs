@@ -0,0 +1,157 @@
+// Implements http://rosettacode.org/wiki/Xiaolin_Wu's_line_algorithm
+//
+// Every pixel near the line is blended with the color of the line in proportion to how much of it
+// the line covers.  The ends can be anywhere, even outside of the image: only the columns around
+// the part of the line inside the image are drawn.
+use std::cmp;
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
+use bitmap::{clip_line, Color, Image};
+mod bitmap;
+
+pub trait DrawAntialiasedLine {
+    fn draw_antialiased_line(&mut self, from: (f64, f64), to: (f64, f64), color: Color);
+}
+
+// The fractional part of `x`, and what is left of 1 after it
+fn fpart(x: f64) -> f64 {
+    x - x.floor()
+}
+
+fn rfpart(x: f64) -> f64 {
+    1.0 - fpart(x)
+}
+
+// Blends the pixel at (x, y) with `color`, where (x, y) are swapped for a steep line
+fn plot(image: &mut Image, steep: bool, (x, y): (int, int), color: Color, coverage: f64) {
+    let (x, y) = if steep { (y, x) } else { (x, y) };
+    let old = match image.get(x, y) {
+        Some(old) => old,
+        None => return
+    };
+    let mix = |old: u8, new: u8| {
+        (old as f64 * (1.0 - coverage) + new as f64 * coverage).round() as u8
+    };
+    image.set(x, y, Color {
+        red: mix(old.red, color.red),
+        green: mix(old.green, color.green),
+        blue: mix(old.blue, color.blue)
+    });
+}
+
+impl DrawAntialiasedLine for Image {
+    fn draw_antialiased_line(&mut self, (x0, y0): (f64, f64), (x1, y1): (f64, f64), color: Color) {
+        // The line is drawn along the x axis, from left to right
+        let steep = (y1 - y0).abs() > (x1 - x0).abs();
+        let (x0, y0, x1, y1) = if steep { (y0, x0, y1, x1) } else { (x0, y0, x1, y1) };
+        let (x0, y0, x1, y1) = if x0 > x1 { (x1, y1, x0, y0) } else { (x0, y0, x1, y1) };
+        let gradient = if x1 == x0 { 1.0 } else { (y1 - y0) / (x1 - x0) };
+
+        // The ends cover their pixels according to where they are in them
+        let x_end = (x0 + 0.5).floor();
+        let y_end = y0 + gradient * (x_end - x0);
+        let x_gap = rfpart(x0 + 0.5);
+        let x_start = x_end as int;
+        plot(self, steep, (x_start, y_end.floor() as int), color, rfpart(y_end) * x_gap);
+        plot(self, steep, (x_start, y_end.floor() as int + 1), color, fpart(y_end) * x_gap);
+        let y_first = y_end + gradient;
+
+        let x_end = (x1 + 0.5).floor();
+        let y_end = y1 + gradient * (x_end - x1);
+        let x_gap = fpart(x1 + 0.5);
+        let x_stop = x_end as int;
+        plot(self, steep, (x_stop, y_end.floor() as int), color, rfpart(y_end) * x_gap);
+        plot(self, steep, (x_stop, y_end.floor() as int + 1), color, fpart(y_end) * x_gap);
+
+        // In between, the line is shared by the two pixels around it in every column, so its
+        // columns are in the image if it is less than a pixel above it
+        let (width, height) = if steep { (self.height, self.width) }
+                              else { (self.width, self.height) };
+        let clipped = clip_line((x0, y0), (x1, y1), (-1.0, -2.0), (width as f64, height as f64));
+        let ((enter_x, _), (leave_x, _)) = match clipped {
+            Some(ends) => ends,
+            None => return
+        };
+        let first = cmp::max(enter_x.floor() as int, x_start + 1);
+        let last = cmp::min(leave_x.ceil() as int + 1, x_stop);
+        for x in range(first, last) {
+            let y = y_first + gradient * (x - x_start - 1) as f64;
+            plot(self, steep, (x, y.floor() as int), color, rfpart(y));
+            plot(self, steep, (x, y.floor() as int + 1), color, fpart(y));
+        }
+    }
+}
+
+#[cfg(not(test))]
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes two lines drawn on an image to `out`, with a character per shade
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
+    let mut image = Image::new(40, 16);
+    let white = Color { red: 255, green: 255, blue: 255 };
+    image.draw_antialiased_line((1.0, 1.0), (38.0, 14.0), white);
+    image.draw_antialiased_line((2.0, 14.0), (12.5, 1.0), white);
+
+    // Darker characters for brighter pixels
+    let shades = [' ', '.', ':', '-', '=', '+', '*', '#', '%', '@'];
+    for row in image.data.chunks(image.width) {
+        let line: String = row.iter().map(|c| shades[c.red as uint * (shades.len() - 1) / 255])
+                                     .collect();
+        try!(writeln!(out, "{}", line));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use bitmap::{Color, Image};
+    use super::DrawAntialiasedLine;
+
+    fn reds(image: &Image) -> Vec<Vec<u8>> {
+        image.data.chunks(image.width).map(|row| row.iter().map(|c| c.red).collect()).collect()
+    }
+
+    #[test]
+    fn straight_lines() {
+        let white = Color { red: 255, green: 255, blue: 255 };
+        let mut image = Image::new(5, 3);
+        image.draw_antialiased_line((1.0, 1.0), (4.0, 1.0), white);
+        // The ends cover half of their pixels
+        assert_eq!(reds(&image), vec![vec![0, 0, 0, 0, 0],
+                                      vec![0, 128, 255, 255, 128],
+                                      vec![0, 0, 0, 0, 0]]);
+
+        let mut image = Image::new(3, 4);
+        image.draw_antialiased_line((1.0, 3.0), (1.0, 0.0), white);
+        assert_eq!(reds(&image), vec![vec![0, 128, 0], vec![0, 255, 0], vec![0, 255, 0],
+                                      vec![0, 128, 0]]);
+    }
+
+    #[test]
+    fn blending() {
+        let mut image = Image::new(4, 4);
+        image.fill(Color { red: 100, green: 0, blue: 200 });
+        image.draw_antialiased_line((0.0, 0.5), (3.0, 3.5), Color { red: 200, green: 0, blue: 0 });
+        // Every column is shared by two pixels
+        assert_eq!(image.get(1, 1), Some(Color { red: 150, green: 0, blue: 100 }));
+        assert_eq!(image.get(1, 2), Some(Color { red: 150, green: 0, blue: 100 }));
+        assert_eq!(image.get(1, 3), Some(Color { red: 100, green: 0, blue: 200 }));
+    }
+
+    #[test]
+    fn clipping() {
+        let mut image = Image::new(4, 2);
+        let red = Color { red: 255, green: 0, blue: 0 };
+        image.draw_antialiased_line((-5.0, 0.0), (10.0, 0.0), red);
+        image.draw_antialiased_line((-5.0, -5.0), (-1.0, -20.0), red);
+        assert_eq!(reds(&image), vec![vec![255, 255, 255, 255], vec![0, 0, 0, 0]]);
+
+        // Only the columns near the image are drawn
+        let mut image = Image::new(4, 2);
+        image.draw_antialiased_line((-1e12, 1.0), (1e12, 1.0), red);
+        assert_eq!(reds(&image), vec![vec![0, 0, 0, 0], vec![255, 255, 255, 255]]);
+    }
+}
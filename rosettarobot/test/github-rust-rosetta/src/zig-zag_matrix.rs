@@ -3,14 +3,18 @@
 // explained in the discussion page
 // http://rosettacode.org/wiki/Talk:Zig-zag_matrix
 
+#[cfg(not(test))]
+use std::io;
+use std::io::IoResult;
+
 #[deriving(Show, PartialEq, Eq)]
-struct SortIndex {
+pub struct SortIndex {
     x:  uint,
     y:  uint
 }
 
 impl SortIndex {
-    fn new(x:uint, y:uint) -> SortIndex {
+    pub fn new(x:uint, y:uint) -> SortIndex {
         SortIndex{x:x, y:y}
     }
 }
@@ -43,7 +47,7 @@ impl Ord for SortIndex {
     }
 }
 
-fn zigzag(n:uint) -> Vec<Vec<uint>> {
+pub fn zigzag(n:uint) -> Vec<Vec<uint>> {
     let mut l:Vec<SortIndex> = range(0u, n*n).map(|i| SortIndex::new(i%n,i/n)).collect();
     l.sort();
 
@@ -55,8 +59,13 @@ fn zigzag(n:uint) -> Vec<Vec<uint>> {
 }
 
 #[cfg(not(test))]
-fn main() {
-    println!("{}", zigzag(5));
+pub fn main() {
+    run(&mut io::stdout()).unwrap();
+}
+
+// Writes the zig-zag matrix of size 5 to `out`
+pub fn run<W: Writer>(out: &mut W) -> IoResult<()> {
+    writeln!(out, "{}", zigzag(5))
 }
 
 #[test]
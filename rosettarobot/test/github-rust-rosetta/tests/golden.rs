@@ -0,0 +1,196 @@
+// Golden-output tests for the tasks
+//
+// A task whose output is deterministic writes it through a `run` function taking any `Writer`
+// (its `main` just calls `run(&mut io::stdout())`).  These tests call `run` and compare what it
+// writes with the expected output checked in as `tests/golden/<task>.txt`, where `<task>` is the
+// name of the task in `Cargo.toml`.
+//
+// After an intentional change of a task's output, regenerate the expected output with
+//     BLESS=1 cargo test --test golden
+// and review the changes to `tests/golden` before committing them.
+
+#![feature(macro_rules)]
+
+extern crate rosetta;
+
+use std::io::{File, IoResult, MemWriter};
+use std::os;
+
+// Declares one test per task, named after the task's module
+macro_rules! golden_tests(
+    ($($name:tt => $module:ident),+) => ($(
+        #[test]
+        fn $module() {
+            check_golden($name, |out| rosetta::$module::run(out));
+        }
+    )+)
+)
+
+// Every task is listed, except the ones whose output isn't deterministic or needs an environment:
+// - they read from stdin: 24_game, 24_game_rpn, a_plus_b, arithmetic_integers, guess_number,
+//   input_loop
+// - their output depends on random numbers or on the time: balanced_brackets, k-d-tree,
+//   system_time
+// - they use the network: echo_server, http, webserver
+// - their output depends on how tasks are scheduled: active_object, atomic_updates,
+//   checkpoint_synchronization, concurrent_computing, events, handle_a_signal,
+//   metered_concurrency, parallel_calculations, synchronous_concurrency
+// - their output depends on the iteration order of a hash map: huffman_coding, loops-foreach, set
+// - they read or write files, or take arguments: anagrams, benford, bitmap, bitmap_histogram,
+//   check_file, conways_game_of_life, create_file, elementary_cellular_automaton, fasta,
+//   filesize, hough_transform, image_convolution, letter_frequency, mandelbrot_set,
+//   median_filter, read_file_line, read_file_specific_line, rename_a_file, write_ppm
+// - their output depends on the terminal, or goes to stderr: input_is_terminal,
+//   output_is_terminal, stderr
+// - they never stop, or overflow the stack on purpose: integer_sequence, loops-infinite,
+//   recursion_depth
+// - they take too long for a test: hamming_numbers (hamming_numbers_alt has the same output),
+//   n_queens, self-describing_numbers
+// - they don't print anything: arrays, assertions, bubble_sort, empty
+// The `rosetta`, `lisp`, `checksum` and `compress` binaries are tools rather than tasks.
+golden_tests!(
+    "100_doors" => _100_doors,
+    "100_doors_unoptimized" => _100_doors_unoptimized,
+    "24_game_solve" => _24_game_solve,
+    "99_bottles_of_beer" => _99_bottles_of_beer,
+    "9_billion_names_of_God_the_integer" => _9_billion_names_of_God_the_integer,
+    "abc_problem" => abc_problem,
+    "accumulator_factory" => accumulator_factory,
+    "ackermann_function" => ackermann_function,
+    "aks_test_for_primes" => aks_test_for_primes,
+    "align_columns" => align_columns,
+    "almost_prime" => almost_prime,
+    "arena_storage_pool" => arena_storage_pool,
+    "arithmetic_mean" => arithmetic_mean,
+    "arithmetic_rational" => arithmetic_rational,
+    "averages_mean_angle" => averages_mean_angle,
+    "binary_digits" => binary_digits,
+    "binary_search" => binary_search,
+    "binomial_coefficients" => binomial_coefficients,
+    "bitmap_bezier_curves_cubic" => bitmap_bezier_curves_cubic,
+    "bitmap_bezier_curves_quadratic" => bitmap_bezier_curves_quadratic,
+    "bitmap_bresenhams_line_algorithm" => bitmap_bresenhams_line_algorithm,
+    "bitmap_flood_fill" => bitmap_flood_fill,
+    "bitmap_midpoint_circle_algorithm" => bitmap_midpoint_circle_algorithm,
+    "bitwise_operations" => bitwise_operations,
+    "call_foreign_function" => call_foreign_function,
+    "callback_to_array" => callback_to_array,
+    "closest-pair" => closest_pair,
+    "closures-value_capture" => closures_value_capture,
+    "comma_quibbling" => comma_quibbling,
+    "compile_time_calculation" => compile_time_calculation,
+    "complex" => complex,
+    "count_in_octal" => count_in_octal,
+    "crc_32" => crc_32,
+    "dijkstras_algorithm" => dijkstras_algorithm,
+    "dot_product" => dot_product,
+    "entropy" => entropy,
+    "equilibrium_index" => equilibrium_index,
+    "factor_int" => factor_int,
+    "factorial" => factorial,
+    "fast_fourier_transform" => fast_fourier_transform,
+    "fibonacci" => fibonacci,
+    "fibonacci_word" => fibonacci_word,
+    "four_bit_adder" => four_bit_adder,
+    "function_composition" => function_composition,
+    "function_def" => function_def,
+    "gray_code" => gray_code,
+    "greater_element_list" => greater_element_list,
+    "hailstone" => hailstone,
+    "hamming_numbers_alt" => hamming_numbers_alt,
+    "happy_numbers" => happy_numbers,
+    "harshad_or_niven_series" => harshad_or_niven_series,
+    "hofstadter_q" => hofstadter_q,
+    "horners_rule" => horners_rule,
+    "iban" => iban,
+    "infinity" => infinity,
+    "isaac" => isaac,
+    "json" => json,
+    "knapsack_0-1" => knapsack_0_1,
+    "leap_year" => leap_year,
+    "linear_congruential_generator" => linear_congruential_generator,
+    "look-and-say_sequence" => look_and_say_sequence,
+    "loops-for" => loops_for,
+    "loops-n-plus-one-half" => loops_n_plus_one_half,
+    "loops-while" => loops_while,
+    "luhn_test" => luhn_test,
+    "lzw" => lzw,
+    "markov_algorithm" => markov_algorithm,
+    "md5-implementation" => md5_implementation,
+    "merge-sort" => merge_sort,
+    "modular_exponentiation" => modular_exponentiation,
+    "mutual_recursion" => mutual_recursion,
+    "palindrome" => palindrome,
+    "perfect_numbers" => perfect_numbers,
+    "pernicious_numbers" => pernicious_numbers,
+    "population_count" => population_count,
+    "power_set" => power_set,
+    "primality_trial_div" => primality_trial_div,
+    "prime_decomposition" => prime_decomposition,
+    "pythagorean_triples" => pythagorean_triples,
+    "quick_sort" => quick_sort,
+    "range_expansion" => range_expansion,
+    "repeat_str" => repeat_str,
+    "reverse_words_str" => reverse_words_str,
+    "roots_of_a_function" => roots_of_a_function,
+    "roots_of_unity" => roots_of_unity,
+    "rot13" => rot13,
+    "run_length_encoding" => run_length_encoding,
+    "s_expressions" => s_expressions,
+    "sequence_of_non-squares" => sequence_of_non_squares,
+    "sha1" => sha1,
+    "sha256" => sha256,
+    "short_circuit_evaluation" => short_circuit_evaluation,
+    "sierpinski_triangle" => sierpinski_triangle,
+    "sieve_eratosthenes" => sieve_eratosthenes,
+    "sort_int" => sort_int,
+    "stack" => stack,
+    "string_concatenation" => string_concatenation,
+    "string_interpolation" => string_interpolation,
+    "string_matching" => string_matching,
+    "strip_comments_from_a_string" => strip_comments_from_a_string,
+    "swap" => swap,
+    "taxicab_numbers" => taxicab_numbers,
+    "towers_of_hanoi" => towers_of_hanoi,
+    "word_wrap" => word_wrap,
+    "xiaolin_wus_line_algorithm" => xiaolin_wus_line_algorithm,
+    "zig-zag_matrix" => zig_zag_matrix
+)
+
+// Runs a task and compares its output with `tests/golden/<name>.txt`, or overwrites that file
+// with the output if the `BLESS` environment variable is set
+fn check_golden(name: &str, run: |&mut MemWriter| -> IoResult<()>) {
+    let mut writer = MemWriter::new();
+    run(&mut writer).unwrap();
+    let actual = String::from_utf8(writer.unwrap()).unwrap();
+    let path = Path::new(format!("tests/golden/{}.txt", name));
+
+    if os::getenv("BLESS").is_some() {
+        File::create(&path).write_str(actual[]).unwrap();
+        return;
+    }
+
+    let expected = match File::open(&path).read_to_string() {
+        Ok(expected) => expected,
+        Err(_) => fail!("{} does not exist, run the tests with BLESS=1 to create it",
+                        path.display())
+    };
+
+    if actual != expected {
+        let mut actual_lines = actual[].lines();
+        let mut expected_lines = expected[].lines();
+        let mut line = 1u;
+        loop {
+            match (expected_lines.next(), actual_lines.next()) {
+                (Some(e), Some(a)) if e == a => line += 1,
+                (e, a) => {
+                    println!("{}:{}: first difference", path.display(), line);
+                    println!("expected: {}", e.unwrap_or("<end of output>"));
+                    println!("actual:   {}", a.unwrap_or("<end of output>"));
+                    break;
+                }
+            }
+        }
+        fail!("the output of `{}` changed, run the tests with BLESS=1 if this is intended", name);
+    }
+}